@@ -0,0 +1,95 @@
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use chrono::{DateTime, Utc};
+
+/// Claude Code's session files for `working_dir` written to since `since`. Claude Code keeps
+/// a JSONL file per session in a folder named after the working directory.
+pub(super) fn claude_sessions(working_dir: &Path, since: DateTime<Utc>) -> Vec<PathBuf> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    let folder: String = working_dir
+        .to_string_lossy()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    session_files(&home.join(".claude/projects").join(folder), since)
+}
+
+/// Codex's session ("rollout") files for `working_dir` written to since `since`. Codex sorts
+/// them into date folders, and each starts with the session's working directory.
+pub(super) fn codex_sessions(working_dir: &Path, since: DateTime<Utc>) -> Vec<PathBuf> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    let mut files = Vec::new();
+    collect_session_files(&home.join(".codex/sessions"), since, &mut files);
+    files.retain(|file| {
+        json_lines(file)
+            .next()
+            .and_then(|meta| meta.pointer("/payload/cwd").and_then(|v| v.as_str()).map(PathBuf::from))
+            .is_some_and(|cwd| cwd == working_dir)
+    });
+    files
+}
+
+/// `.jsonl` files in `dir` written to since `since`
+fn session_files(dir: &Path, since: DateTime<Utc>) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+        .filter(|path| modified_since(path, since))
+        .collect()
+}
+
+/// Like `session_files`, through nested folders
+fn collect_session_files(dir: &Path, since: DateTime<Utc>, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            if modified_since(&path, since) {
+                collect_session_files(&path, since, files);
+            }
+        } else if path.extension().is_some_and(|ext| ext == "jsonl") && modified_since(&path, since) {
+            files.push(path);
+        }
+    }
+}
+
+fn modified_since(path: &Path, since: DateTime<Utc>) -> bool {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .map(|modified| modified >= SystemTime::from(since))
+        .unwrap_or(false)
+}
+
+/// The entries of a JSONL log, skipping lines that don't parse
+pub(super) fn json_lines(path: &Path) -> impl Iterator<Item = serde_json::Value> {
+    std::fs::File::open(path)
+        .ok()
+        .map(BufReader::new)
+        .into_iter()
+        .flat_map(|reader| reader.lines().map_while(|line| line.ok()))
+        .filter_map(|line| serde_json::from_str(&line).ok())
+}
+
+/// When a log entry was written, if it says
+pub(super) fn entry_time(entry: &serde_json::Value) -> Option<DateTime<Utc>> {
+    entry
+        .get("timestamp")
+        .and_then(|v| v.as_str())
+        .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+        .map(|ts| ts.with_timezone(&Utc))
+}
+
+/// Whether a log entry was written since `since`; entries without a timestamp count
+pub(super) fn entry_since(entry: &serde_json::Value, since: DateTime<Utc>) -> bool {
+    entry_time(entry).is_none_or(|ts| ts >= since)
+}
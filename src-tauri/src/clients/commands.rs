@@ -2,7 +2,10 @@ use tauri::State;
 
 use crate::error::{Error, Result};
 use crate::state::AppState;
-use super::{ClientConfig, ClientSummary};
+use super::{
+    check_health, client_id_from_name, start_install, AddClientRequest, ClientConfig, ClientHealth, ClientInstall,
+    ClientSummary, ClientType, LaunchProfile, UpdateClientRequest, BUILTIN_CLIENT_IDS,
+};
 
 #[tauri::command]
 pub async fn list_clients(
@@ -29,12 +32,203 @@ pub async fn get_client(
 pub async fn detect_installed_clients(
     state: State<'_, AppState>,
 ) -> Result<Vec<ClientSummary>> {
-    let mut clients = state.clients.write();
-    
-    for client in clients.values_mut() {
-        client.detect_installation();
-    }
-    
+    state.detect_clients(false);
+
+    let clients = state.clients.read();
+    let summaries: Vec<ClientSummary> = clients.values().map(|c| c.into()).collect();
+    Ok(summaries)
+}
+
+/// Look for every client's installation again, ignoring earlier results (e.g. after
+/// installing an agent)
+#[tauri::command]
+pub async fn refresh_clients(
+    state: State<'_, AppState>,
+) -> Result<Vec<ClientSummary>> {
+    state.detect_clients(true);
+
+    let clients = state.clients.read();
     let summaries: Vec<ClientSummary> = clients.values().map(|c| c.into()).collect();
     Ok(summaries)
 }
+
+/// Install a client's agent with the first installer this machine can run (npm, Homebrew
+/// or the agent's install script), in a PTY session the UI can show. Progress is reported
+/// through `client-install-progress` events, and the client is detected again once the
+/// installer exits.
+#[tauri::command]
+pub async fn install_client(
+    state: State<'_, AppState>,
+    client_id: String,
+) -> Result<ClientInstall> {
+    start_install(&state, &client_id)
+}
+
+/// Check that a client's agent is installed, starts and is signed in, before a session
+/// is created with it (see `check_health`)
+#[tauri::command]
+pub async fn check_client_health(
+    state: State<'_, AppState>,
+    client_id: String,
+) -> Result<ClientHealth> {
+    let client = state
+        .clients
+        .read()
+        .get(&client_id)
+        .cloned()
+        .ok_or_else(|| Error::ClientNotFound(client_id.clone()))?;
+
+    // Running the agent blocks, so keep it off the async runtime's threads
+    tokio::task::spawn_blocking(move || check_health(&client))
+        .await
+        .map_err(|e| Error::TerminalError(e.to_string()))
+}
+
+/// Refuse default args that every launch of the client would then be blocked for
+fn check_denied_flags(state: &AppState, args: &[String]) -> Result<()> {
+    match state.settings.read().find_denied_flag(args) {
+        Some(flag) => Err(Error::InvalidRequest(format!(
+            "Agent flag '{}' is blocked by the denied flags setting",
+            flag
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Refuse profiles without a name, with the same name, or using denied flags
+fn check_profiles(state: &AppState, profiles: &[LaunchProfile]) -> Result<()> {
+    for (index, profile) in profiles.iter().enumerate() {
+        if profile.name.trim().is_empty() {
+            return Err(Error::InvalidRequest("A launch profile needs a name".into()));
+        }
+        if profiles[..index].iter().any(|other| other.name == profile.name) {
+            return Err(Error::InvalidRequest(format!("There are two launch profiles named '{}'", profile.name)));
+        }
+        check_denied_flags(state, &profile.args)?;
+    }
+    Ok(())
+}
+
+/// Register an agent CLI as a client, saved so it is there on the next start
+#[tauri::command]
+pub async fn add_client(
+    state: State<'_, AppState>,
+    request: AddClientRequest,
+) -> Result<ClientConfig> {
+    let name = request.name.trim();
+    let command = request.command.trim();
+    if name.is_empty() || command.is_empty() {
+        return Err(Error::InvalidRequest("A client needs a name and a command".into()));
+    }
+    let id = client_id_from_name(request.id.as_deref().unwrap_or(name));
+    if id.is_empty() {
+        return Err(Error::InvalidRequest("The client ID must contain letters or digits".into()));
+    }
+
+    check_denied_flags(&state, &request.args)?;
+    check_profiles(&state, &request.profiles)?;
+
+    let mut client = ClientConfig {
+        id,
+        name: name.to_string(),
+        client_type: request.client_type.unwrap_or(ClientType::Custom),
+        command: command.to_string(),
+        args: request.args,
+        env: request.env,
+        description: request.description,
+        installed: false,
+        shell: request.shell,
+        terminal_env: request.terminal_env,
+        profiles: request.profiles,
+    };
+    client.detect_installation();
+
+    {
+        let mut clients = state.clients.write();
+        if clients.contains_key(&client.id) {
+            return Err(Error::InvalidRequest(format!("A client with ID '{}' already exists", client.id)));
+        }
+        state.save_client(&client)?;
+        clients.insert(client.id.clone(), client.clone());
+    }
+
+    eprintln!("[Ada] Added client {} ({})", client.id, client.command);
+    Ok(client)
+}
+
+/// Change a client, built-in ones included. New terminals use the changes; running
+/// agents keep the settings they were launched with.
+#[tauri::command]
+pub async fn update_client(
+    state: State<'_, AppState>,
+    request: UpdateClientRequest,
+) -> Result<ClientConfig> {
+    let mut clients = state.clients.write();
+    let client = clients
+        .get_mut(&request.client_id)
+        .ok_or_else(|| Error::ClientNotFound(request.client_id.clone()))?;
+
+    let mut updated = client.clone();
+    if let Some(name) = request.name {
+        updated.name = name.trim().to_string();
+    }
+    if let Some(command) = request.command {
+        updated.command = command.trim().to_string();
+    }
+    if updated.name.is_empty() || updated.command.is_empty() {
+        return Err(Error::InvalidRequest("A client needs a name and a command".into()));
+    }
+    if let Some(args) = request.args {
+        check_denied_flags(&state, &args)?;
+        updated.args = args;
+    }
+    if let Some(env) = request.env {
+        updated.env = env;
+    }
+    if let Some(description) = request.description {
+        updated.description = description;
+    }
+    if let Some(shell) = request.shell {
+        updated.shell = Some(shell);
+    }
+    if let Some(terminal_env) = request.terminal_env {
+        updated.terminal_env = terminal_env;
+    }
+    if let Some(profiles) = request.profiles {
+        check_profiles(&state, &profiles)?;
+        updated.profiles = profiles;
+    }
+    updated.detect_installation();
+
+    state.save_client(&updated)?;
+    *client = updated.clone();
+
+    Ok(updated)
+}
+
+/// Remove a client added with `add_client`. Projects using it as their default agent are
+/// left without one; its existing terminals keep running.
+#[tauri::command]
+pub async fn remove_client(
+    state: State<'_, AppState>,
+    client_id: String,
+) -> Result<()> {
+    if BUILTIN_CLIENT_IDS.contains(&client_id.as_str()) {
+        return Err(Error::InvalidRequest(format!("'{}' is a built-in client and can't be removed", client_id)));
+    }
+    if state.clients.write().remove(&client_id).is_none() {
+        return Err(Error::ClientNotFound(client_id));
+    }
+    state.delete_client_file(&client_id)?;
+
+    for project in state.projects.write().values_mut() {
+        if project.settings.default_client.as_deref() == Some(client_id.as_str()) {
+            project.settings.default_client = None;
+            project.updated_at = chrono::Utc::now();
+            let _ = state.save_project(project);
+        }
+    }
+
+    eprintln!("[Ada] Removed client {}", client_id);
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use chrono::{DateTime, Duration, Utc};
+
+use super::ClientConfig;
+
+/// How long a client's detected installation is trusted before it is checked again
+const DETECTION_TTL: Duration = Duration::hours(24);
+
+/// Installation status of clients from earlier runs, so startup doesn't have to look for
+/// every agent again (see `AppState::detect_clients`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DetectionCache {
+    #[serde(default)]
+    entries: HashMap<String, DetectionEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DetectionEntry {
+    /// The command that was looked for; changing it invalidates the entry
+    command: String,
+    installed: bool,
+    checked_at: DateTime<Utc>,
+}
+
+impl DetectionCache {
+    /// Read the cache, starting empty if it is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> crate::error::Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Set the client's `installed` from a fresh entry for its command; false if there is none
+    pub fn apply(&self, client: &mut ClientConfig, now: DateTime<Utc>) -> bool {
+        match self.entries.get(&client.id) {
+            Some(entry) if entry.command == client.command && now - entry.checked_at < DETECTION_TTL => {
+                client.installed = entry.installed;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn record(&mut self, client: &ClientConfig, now: DateTime<Utc>) {
+        self.entries.insert(
+            client.id.clone(),
+            DetectionEntry { command: client.command.clone(), installed: client.installed, checked_at: now },
+        );
+    }
+
+    /// Drop entries of clients that no longer exist
+    pub fn retain_clients<'a>(&mut self, client_ids: impl IntoIterator<Item = &'a String>) {
+        let client_ids: Vec<&String> = client_ids.into_iter().collect();
+        self.entries.retain(|id, _| client_ids.contains(&id));
+    }
+}
+
+/// Look for several clients' installations at once, each on its own thread
+pub fn detect_installations<'a>(clients: impl IntoIterator<Item = &'a mut ClientConfig>) {
+    std::thread::scope(|scope| {
+        for client in clients {
+            scope.spawn(move || client.detect_installation());
+        }
+    });
+}
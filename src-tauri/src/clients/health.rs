@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+
+use crate::terminal::pty::run_captured;
+use super::{ClientConfig, ClientType};
+
+/// How long the agent gets to print its version
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Output kept in a health report
+const MAX_OUTPUT_CHARS: usize = 2000;
+
+/// Whether the agent looks signed in, judged from its credential files and API key variables
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthStatus {
+    LoggedIn,
+    LoggedOut,
+    /// The agent keeps its credentials somewhere Ada can't look (e.g. the keychain)
+    Unknown,
+}
+
+/// Result of `check_client_health`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientHealth {
+    pub client_id: String,
+    pub checked_at: DateTime<Utc>,
+    pub installed: bool,
+    /// Where the command was found
+    pub command_path: Option<String>,
+    /// The agent ran and exited successfully
+    pub starts: bool,
+    /// First line of its `--version` (or `--help`) output
+    pub version: Option<String>,
+    pub exit_code: Option<u32>,
+    /// What the agent printed, shortened
+    pub output: String,
+    pub auth: AuthStatus,
+    /// What the auth status was judged from, e.g. `ANTHROPIC_API_KEY`
+    pub auth_source: Option<String>,
+    /// Starts and isn't known to be logged out
+    pub healthy: bool,
+    /// What's wrong, for display
+    pub problems: Vec<String>,
+}
+
+/// Check that a client's agent is installed, starts, and is signed in where that can be told.
+/// The agent is run with `--version` (then `--help`, for agents without it) in a PTY of its
+/// own, in a scratch folder, so it can't touch a project. Blocks for up to
+/// `HEALTH_CHECK_TIMEOUT` per run.
+pub fn check_health(client: &ClientConfig) -> ClientHealth {
+    let mut client = client.clone();
+    client.detect_installation();
+
+    let mut health = ClientHealth {
+        client_id: client.id.clone(),
+        checked_at: Utc::now(),
+        installed: client.installed,
+        command_path: None,
+        starts: false,
+        version: None,
+        exit_code: None,
+        output: String::new(),
+        auth: AuthStatus::Unknown,
+        auth_source: None,
+        healthy: false,
+        problems: Vec::new(),
+    };
+
+    let (auth, auth_source) = auth_status(&client);
+    health.auth = auth;
+    health.auth_source = auth_source;
+    if auth == AuthStatus::LoggedOut {
+        health.problems.push(format!("{} is not logged in", client.name));
+    }
+
+    if !client.installed {
+        health.problems.push(format!("'{}' was not found", client.command));
+        return health;
+    }
+    health.command_path = Some(client.get_command_path().to_string_lossy().to_string());
+
+    let scratch = std::env::temp_dir().join(format!("ada-health-{}", uuid::Uuid::new_v4()));
+    if let Err(e) = std::fs::create_dir_all(&scratch) {
+        health.problems.push(format!("Could not create a folder to run the agent in: {}", e));
+        return health;
+    }
+
+    let mut spawn_error = None;
+    for args in [["--version"], ["--help"]] {
+        match run_captured(&client, &args, &scratch, HEALTH_CHECK_TIMEOUT) {
+            Ok(run) => {
+                health.exit_code = run.exit_code;
+                health.starts = run.exit_code == Some(0);
+                health.version = run.output.lines().map(str::trim).find(|line| !line.is_empty()).map(String::from);
+                health.output = run.output.trim().chars().take(MAX_OUTPUT_CHARS).collect();
+                if health.starts {
+                    break;
+                }
+            }
+            Err(e) => {
+                spawn_error = Some(e);
+                break;
+            }
+        }
+    }
+    let _ = std::fs::remove_dir_all(&scratch);
+
+    if !health.starts {
+        health.problems.push(match (spawn_error, health.exit_code) {
+            (Some(e), _) => format!("{} could not be started: {}", client.name, e),
+            (None, Some(code)) => format!("{} exited with code {}", client.name, code),
+            (None, None) => format!("{} did not exit within {} seconds", client.name, HEALTH_CHECK_TIMEOUT.as_secs()),
+        });
+    }
+    health.healthy = health.starts && health.auth != AuthStatus::LoggedOut;
+    health
+}
+
+/// Look for the API key variables and credential files the agent signs in with
+fn auth_status(client: &ClientConfig) -> (AuthStatus, Option<String>) {
+    let home = dirs::home_dir().unwrap_or_default();
+    let (keys, files, unknown_if_missing): (&[&str], Vec<PathBuf>, bool) = match client.client_type {
+        ClientType::ClaudeCode => (
+            &["ANTHROPIC_API_KEY", "CLAUDE_CODE_OAUTH_TOKEN"],
+            vec![home.join(".claude/.credentials.json")],
+            // macOS keeps the login in the keychain
+            cfg!(target_os = "macos"),
+        ),
+        ClientType::Codex => (&["OPENAI_API_KEY"], vec![home.join(".codex/auth.json")], false),
+        ClientType::OpenCode => (&[], vec![home.join(".local/share/opencode/auth.json")], true),
+        ClientType::Aider => (&["OPENAI_API_KEY", "ANTHROPIC_API_KEY", "GEMINI_API_KEY"], vec![home.join(".aider.conf.yml")], true),
+        ClientType::Amp => (&["AMP_API_KEY"], vec![home.join(".local/share/amp/secrets.json")], true),
+        ClientType::Goose => (&[], vec![home.join(".config/goose/config.yaml")], false),
+        ClientType::QwenCode => (&["OPENAI_API_KEY"], vec![home.join(".qwen/oauth_creds.json")], true),
+        ClientType::Copilot => (&["GH_TOKEN", "GITHUB_TOKEN"], vec![home.join(".copilot/config.json")], true),
+        ClientType::Custom => return (AuthStatus::Unknown, None),
+    };
+
+    if let Some(key) = keys
+        .iter()
+        .find(|key| client.env.contains_key(**key) || std::env::var_os(key).is_some())
+    {
+        return (AuthStatus::LoggedIn, Some(key.to_string()));
+    }
+    if let Some(file) = files.iter().find(|file| file.exists()) {
+        return (AuthStatus::LoggedIn, Some(file.to_string_lossy().to_string()));
+    }
+    if client.client_type == ClientType::ClaudeCode && claude_oauth_account(&home) {
+        return (AuthStatus::LoggedIn, Some(home.join(".claude.json").to_string_lossy().to_string()));
+    }
+
+    if unknown_if_missing {
+        (AuthStatus::Unknown, None)
+    } else {
+        (AuthStatus::LoggedOut, None)
+    }
+}
+
+/// Claude Code records the signed-in account in `~/.claude.json`
+fn claude_oauth_account(home: &std::path::Path) -> bool {
+    std::fs::read_to_string(home.join(".claude.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .is_some_and(|config| config.get("oauthAccount").is_some_and(|account| !account.is_null()))
+}
@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::error::{Error, Result};
+use crate::state::AppState;
+use crate::terminal::pty::{agent_path, kill_pty, spawn_script};
+use crate::terminal::shell::ShellConfig;
+use crate::terminal::TerminalOutputBuffer;
+use super::{ClientType, Installer};
+
+/// Interval between checks for an installer having exited
+const INSTALL_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Output kept from an installer run
+const INSTALL_SCROLLBACK_BYTES: usize = 256 * 1024;
+
+/// An installer run started by `install_client`. Its output arrives as `terminal-output`
+/// events for `session_id`; input, resizes and cancelling (`close_terminal`) go through the
+/// terminal commands.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientInstall {
+    pub client_id: String,
+    pub session_id: String,
+    /// The installer command being run
+    pub command: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientInstallStage {
+    Started,
+    Finished,
+}
+
+/// Emitted as `client-install-progress` when an installer starts and when it exits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientInstallEvent {
+    pub client_id: String,
+    pub session_id: String,
+    pub stage: ClientInstallStage,
+    pub command: String,
+    /// Whether the installer exited successfully; None until it has finished
+    pub success: Option<bool>,
+    /// Whether the client is found after the installer ran
+    pub installed: bool,
+}
+
+/// The PTY session an installer for `client_id` runs in
+pub fn install_session_id(client_id: &str) -> String {
+    format!("install-{}", client_id)
+}
+
+/// The first of the agent's installers whose required programs are on this machine
+fn pick_installer(client_type: ClientType) -> Result<&'static Installer> {
+    let installers = client_type.installers();
+    if installers.is_empty() {
+        return Err(Error::InvalidRequest("Ada doesn't know how to install this client".to_string()));
+    }
+
+    installers
+        .iter()
+        .find(|installer| installer.requires.iter().all(|program| find_program(program).is_some()))
+        .ok_or_else(|| {
+            let options: Vec<String> = installers.iter().map(|i| i.requires.join(" and ")).collect();
+            Error::InvalidRequest(format!("Installing this client needs {}", options.join(" or ")))
+        })
+}
+
+/// Look a program up on PATH and in the usual install locations
+/// (macOS GUI apps don't inherit the shell PATH)
+fn find_program(name: &str) -> Option<PathBuf> {
+    which::which(name).ok().or_else(|| {
+        let home = dirs::home_dir()?;
+        let cwd = std::env::current_dir().ok()?;
+        which::which_in(name, Some(agent_path(&home)), cwd).ok()
+    })
+}
+
+/// Start installing a client's agent in a PTY and watch for the installer to exit, then
+/// detect the client again
+pub fn start_install(state: &AppState, client_id: &str) -> Result<ClientInstall> {
+    let client = state
+        .clients
+        .read()
+        .get(client_id)
+        .cloned()
+        .ok_or_else(|| Error::ClientNotFound(client_id.to_string()))?;
+    let installer = pick_installer(client.client_type)?;
+    let shell = ShellConfig::detect()
+        .ok_or_else(|| Error::TerminalError("No shell found to run the installer".to_string()))?;
+
+    let session_id = install_session_id(client_id);
+    let mut pty_handles = state.pty_handles.write();
+    if pty_handles.contains_key(&session_id) {
+        return Err(Error::InvalidRequest(format!("{} is already being installed", client.name)));
+    }
+
+    let output_buffer = std::sync::Arc::new(TerminalOutputBuffer::new(INSTALL_SCROLLBACK_BYTES, None));
+    let working_dir = dirs::home_dir().unwrap_or_else(std::env::temp_dir);
+    let pty_handle = spawn_script(
+        &state.app_handle,
+        &session_id,
+        &shell.path,
+        installer.command,
+        &working_dir,
+        output_buffer.clone(),
+    )?;
+    pty_handles.insert(session_id.clone(), pty_handle);
+    drop(pty_handles);
+    state.output_buffers.write().insert(session_id.clone(), output_buffer);
+
+    eprintln!("[Ada] Installing client {}: {}", client_id, installer.command);
+    let install = ClientInstall {
+        client_id: client_id.to_string(),
+        session_id,
+        command: installer.command.to_string(),
+    };
+    emit_progress(&state.app_handle, &install, ClientInstallStage::Started, None, client.installed);
+
+    let app_handle = state.app_handle.clone();
+    let watched = install.clone();
+    std::thread::spawn(move || watch_install(app_handle, watched));
+
+    Ok(install)
+}
+
+/// Wait for an installer to exit, then report whether the client can now be found
+fn watch_install(app_handle: AppHandle, install: ClientInstall) {
+    let success = loop {
+        std::thread::sleep(INSTALL_POLL_INTERVAL);
+        let Some(state) = app_handle.try_state::<AppState>() else {
+            return;
+        };
+        let pty_handles = state.pty_handles.read();
+        let Some(pty_handle) = pty_handles.get(&install.session_id) else {
+            // Closed from the UI before it finished
+            break None;
+        };
+        let exited = pty_handle.child.lock().try_wait();
+        match exited {
+            Ok(None) => continue,
+            Ok(Some(status)) => break Some(status.success()),
+            Err(_) => break None,
+        }
+    };
+
+    let Some(state) = app_handle.try_state::<AppState>() else {
+        return;
+    };
+    // The output buffer stays until the session is closed, so the UI can still show it
+    let pty_handle = state.pty_handles.write().remove(&install.session_id);
+    if let Some(pty_handle) = pty_handle {
+        kill_pty(pty_handle);
+    }
+
+    state.detect_clients(true);
+    let installed = state
+        .clients
+        .read()
+        .get(&install.client_id)
+        .is_some_and(|client| client.installed);
+    eprintln!(
+        "[Ada] Installer for client {} finished (installed: {})",
+        install.client_id, installed
+    );
+    emit_progress(&app_handle, &install, ClientInstallStage::Finished, success, installed);
+}
+
+fn emit_progress(
+    app_handle: &AppHandle,
+    install: &ClientInstall,
+    stage: ClientInstallStage,
+    success: Option<bool>,
+    installed: bool,
+) {
+    let _ = app_handle.emit(
+        "client-install-progress",
+        ClientInstallEvent {
+            client_id: install.client_id.clone(),
+            session_id: install.session_id.clone(),
+            stage,
+            command: install.command.clone(),
+            success,
+            installed,
+        },
+    );
+}
@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use super::{ClientConfig, ClientType};
+
+/// An MCP tool server registered with a project, handed to every agent launched in it
+/// that can take servers on its command line (see `apply_mcp_servers`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct McpServer {
+    pub name: String,
+    /// Program that runs a stdio server, e.g. `npx`
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Address of an HTTP server, instead of a command
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+impl McpServer {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.trim().is_empty() {
+            return Err(Error::InvalidRequest("An MCP server needs a name".into()));
+        }
+        match (&self.command, &self.url) {
+            (Some(_), None) | (None, Some(_)) => Ok(()),
+            _ => Err(Error::InvalidRequest(format!(
+                "MCP server '{}' needs either a command or a URL",
+                self.name
+            ))),
+        }
+    }
+
+    /// The server in the `mcpServers` JSON format shared by Claude Code and Copilot
+    fn to_json(&self, client_type: ClientType) -> serde_json::Value {
+        let mut server = match &self.url {
+            Some(url) => serde_json::json!({ "type": "http", "url": url }),
+            None => serde_json::json!({
+                "command": self.command,
+                "args": self.args,
+                "env": self.env,
+            }),
+        };
+        // Copilot wants the transport spelled out, and the tools the agent may use
+        if client_type == ClientType::Copilot {
+            if self.url.is_none() {
+                server["type"] = "local".into();
+            }
+            server["tools"] = serde_json::json!(["*"]);
+        }
+        server
+    }
+
+    /// The server as Codex `-c` config entries. Its variables are only named (`env_vars`),
+    /// for Codex to pass on from its own environment.
+    fn to_codex_config(&self) -> Vec<String> {
+        let table = format!("mcp_servers.{}", toml_key(&self.name));
+        let mut entries = Vec::new();
+        if let Some(url) = &self.url {
+            entries.push(format!("{}.url={}", table, toml_string(url)));
+        }
+        if let Some(command) = &self.command {
+            entries.push(format!("{}.command={}", table, toml_string(command)));
+            let args: Vec<String> = self.args.iter().map(|arg| toml_string(arg)).collect();
+            entries.push(format!("{}.args=[{}]", table, args.join(", ")));
+        }
+        if !self.env.is_empty() {
+            let names: Vec<String> = self.env.keys().map(|key| toml_string(key)).collect();
+            entries.push(format!("{}.env_vars=[{}]", table, names.join(", ")));
+        }
+        entries
+    }
+}
+
+/// Pass a project's MCP servers to the client about to be launched: written to `config_file`
+/// (readable only by the user) for Claude Code's `--mcp-config` and Copilot's
+/// `--additional-mcp-config`, and as `-c mcp_servers.*` entries to Codex, with the servers'
+/// variables in the agent's environment. Their values, usually tokens, are kept off the
+/// command line, where `ps`, invocation logs and scrollback would show them.
+/// Other agents only read MCP servers from their own config files, so they're left as they are.
+pub fn apply_mcp_servers(client: &mut ClientConfig, servers: &[McpServer], config_file: &Path) -> Result<()> {
+    if servers.is_empty() {
+        return Ok(());
+    }
+
+    match client.client_type {
+        ClientType::ClaudeCode | ClientType::Copilot => {
+            let config: serde_json::Map<String, serde_json::Value> = servers
+                .iter()
+                .map(|server| (server.name.clone(), server.to_json(client.client_type)))
+                .collect();
+            write_private(config_file, &serde_json::json!({ "mcpServers": config }).to_string())?;
+            let config_file = config_file.to_string_lossy();
+            if client.client_type == ClientType::ClaudeCode {
+                client.args.extend(["--mcp-config".to_string(), config_file.to_string()]);
+            } else {
+                // Copilot reads a file when the value starts with @
+                client.args.extend(["--additional-mcp-config".to_string(), format!("@{}", config_file)]);
+            }
+        }
+        ClientType::Codex => {
+            for server in servers {
+                client.env.extend(server.env.clone());
+            }
+            for entry in servers.iter().flat_map(McpServer::to_codex_config) {
+                client.args.extend(["-c".to_string(), entry]);
+            }
+        }
+        ClientType::OpenCode
+        | ClientType::Aider
+        | ClientType::Amp
+        | ClientType::Goose
+        | ClientType::QwenCode
+        | ClientType::Custom => {}
+    }
+    Ok(())
+}
+
+/// Write a file only the user can read
+pub fn write_private(path: &Path, content: &str) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    // A file left by an older launch may have been created with wider permissions
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(content.as_bytes())
+}
+
+/// A TOML string; JSON string escapes are valid TOML
+fn toml_string(value: &str) -> String {
+    serde_json::Value::from(value).to_string()
+}
+
+/// A TOML key, quoted unless it is a bare key
+fn toml_key(key: &str) -> String {
+    if !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        key.to_string()
+    } else {
+        toml_string(key)
+    }
+}
@@ -1,4 +1,19 @@
+mod agent_logs;
 pub mod commands;
+mod detection;
+mod health;
+mod install;
+mod mcp;
+mod overrides;
+mod transcript;
 mod types;
+mod usage;
 
+pub use detection::*;
+pub use health::*;
+pub use install::*;
+pub use mcp::*;
+pub use overrides::*;
+pub use transcript::*;
 pub use types::*;
+pub use usage::*;
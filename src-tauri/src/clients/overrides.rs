@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::{ClientConfig, ClientType};
+
+/// A project's changes to how one agent is launched, on top of the client's configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct AgentOverrides {
+    /// Model to run, passed as `--model` (as `GOOSE_MODEL` to Goose; not applied to custom
+    /// clients, or to Amp, which picks its own models)
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Extra Claude Code settings, passed as `--settings` (Claude Code only)
+    #[serde(default)]
+    pub claude_settings: Option<serde_json::Value>,
+    /// Codex config entries, passed as `-c key=value`; values are TOML (Codex only)
+    #[serde(default)]
+    pub codex_config: BTreeMap<String, String>,
+    /// Arguments added after the client's own
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// Add a project's overrides to the arguments of the client about to be launched
+pub fn apply_agent_overrides(client: &mut ClientConfig, overrides: &AgentOverrides) {
+    if let Some(model) = &overrides.model {
+        match client.client_type {
+            ClientType::Goose => {
+                client.env.insert("GOOSE_MODEL".to_string(), model.clone());
+            }
+            client_type if client_type.supports_model() => {
+                client.args.extend(["--model".to_string(), model.clone()]);
+            }
+            _ => {}
+        }
+    }
+
+    match client.client_type {
+        ClientType::ClaudeCode => {
+            if let Some(settings) = &overrides.claude_settings {
+                client.args.extend(["--settings".to_string(), settings.to_string()]);
+            }
+        }
+        ClientType::Codex => {
+            // Entries left empty (e.g. by an imported project bundle) aren't passed
+            for (key, value) in overrides.codex_config.iter().filter(|(_, value)| !value.is_empty()) {
+                client.args.extend(["-c".to_string(), format!("{}={}", key, value)]);
+            }
+        }
+        ClientType::OpenCode
+        | ClientType::Aider
+        | ClientType::Amp
+        | ClientType::Goose
+        | ClientType::QwenCode
+        | ClientType::Copilot
+        | ClientType::Custom => {}
+    }
+
+    client.args.extend(overrides.args.iter().cloned());
+}
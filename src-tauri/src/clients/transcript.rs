@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use chrono::{DateTime, Utc};
+
+use super::agent_logs::{claude_sessions, codex_sessions, entry_since, entry_time, json_lines};
+use super::ClientType;
+
+/// Longest tool input or output kept in a transcript entry
+const MAX_TOOL_TEXT_CHARS: usize = 4000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptEntryKind {
+    /// What the user asked
+    Prompt,
+    /// Text the agent wrote back
+    Message,
+    /// A tool the agent ran, with its input
+    ToolCall,
+    /// What a tool returned
+    ToolResult,
+}
+
+/// One step of an agent conversation, without terminal escape codes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    pub timestamp: Option<DateTime<Utc>>,
+    pub kind: TranscriptEntryKind,
+    /// Tool name, for tool calls
+    pub tool: Option<String>,
+    pub text: String,
+}
+
+/// The conversations of the agent sessions of `client_type` that ran in `working_dir` since
+/// `since`, oldest first, read from the agent's session logs (see `agent_token_usage`).
+/// None for agents whose logs Ada can't read.
+pub fn agent_transcript(
+    client_type: ClientType,
+    working_dir: &Path,
+    since: DateTime<Utc>,
+) -> Option<Vec<TranscriptEntry>> {
+    let (files, parse): (_, fn(&serde_json::Value, &mut Vec<TranscriptEntry>)) = match client_type {
+        ClientType::ClaudeCode => (claude_sessions(working_dir, since), claude_entries),
+        ClientType::Codex => (codex_sessions(working_dir, since), codex_entries),
+        _ => return None,
+    };
+
+    let mut entries = Vec::new();
+    for file in files {
+        for entry in json_lines(&file).filter(|entry| entry_since(entry, since)) {
+            parse(&entry, &mut entries);
+        }
+    }
+    // Sessions are read one file at a time; interleave them by time
+    entries.sort_by_key(|entry| entry.timestamp);
+    Some(entries)
+}
+
+/// Claude Code logs a line per prompt and per content block of each response. Subagent
+/// ("sidechain") conversations and messages Claude Code injects itself are left out.
+fn claude_entries(entry: &serde_json::Value, entries: &mut Vec<TranscriptEntry>) {
+    let flag = |name: &str| entry.get(name).and_then(|v| v.as_bool()).unwrap_or(false);
+    if flag("isSidechain") || flag("isMeta") {
+        return;
+    }
+    let timestamp = entry_time(entry);
+    let Some(content) = entry.pointer("/message/content") else {
+        return;
+    };
+    let is_user = entry.get("type").and_then(|v| v.as_str()) == Some("user");
+
+    if let Some(text) = content.as_str() {
+        let kind = if is_user { TranscriptEntryKind::Prompt } else { TranscriptEntryKind::Message };
+        entries.push(TranscriptEntry { timestamp, kind, tool: None, text: text.to_string() });
+        return;
+    }
+
+    for block in content.as_array().into_iter().flatten() {
+        let (kind, tool, text) = match block.get("type").and_then(|v| v.as_str()) {
+            Some("text") => (
+                if is_user { TranscriptEntryKind::Prompt } else { TranscriptEntryKind::Message },
+                None,
+                text_of(block.get("text")),
+            ),
+            Some("tool_use") => (
+                TranscriptEntryKind::ToolCall,
+                block.get("name").and_then(|v| v.as_str()).map(String::from),
+                block.get("input").map(|input| input.to_string()).unwrap_or_default(),
+            ),
+            Some("tool_result") => (TranscriptEntryKind::ToolResult, None, text_of(block.get("content"))),
+            _ => continue,
+        };
+        entries.push(TranscriptEntry { timestamp, kind, tool, text: truncate_tool_text(kind, text) });
+    }
+}
+
+/// Codex logs messages and function calls as `response_item`s. The context Codex adds to the
+/// conversation itself (environment, instructions) is sent as user messages in tags and is
+/// left out.
+fn codex_entries(entry: &serde_json::Value, entries: &mut Vec<TranscriptEntry>) {
+    if entry.get("type").and_then(|v| v.as_str()) != Some("response_item") {
+        return;
+    }
+    let timestamp = entry_time(entry);
+    let Some(payload) = entry.get("payload") else {
+        return;
+    };
+
+    let (kind, tool, text) = match payload.get("type").and_then(|v| v.as_str()) {
+        Some("message") => {
+            let kind = match payload.get("role").and_then(|v| v.as_str()) {
+                Some("user") => TranscriptEntryKind::Prompt,
+                Some("assistant") => TranscriptEntryKind::Message,
+                _ => return,
+            };
+            let text = text_of(payload.get("content"));
+            if kind == TranscriptEntryKind::Prompt && text.trim_start().starts_with('<') {
+                return;
+            }
+            (kind, None, text)
+        }
+        Some("function_call") => (
+            TranscriptEntryKind::ToolCall,
+            payload.get("name").and_then(|v| v.as_str()).map(String::from),
+            text_of(payload.get("arguments")),
+        ),
+        Some("function_call_output") => (TranscriptEntryKind::ToolResult, None, text_of(payload.get("output"))),
+        _ => return,
+    };
+    entries.push(TranscriptEntry { timestamp, kind, tool, text: truncate_tool_text(kind, text) });
+}
+
+/// Text of a string, or of an array of content blocks with `text` fields
+fn text_of(value: Option<&serde_json::Value>) -> String {
+    match value {
+        Some(serde_json::Value::String(text)) => text.clone(),
+        Some(serde_json::Value::Array(blocks)) => blocks
+            .iter()
+            .filter_map(|block| block.get("text").and_then(|v| v.as_str()).or_else(|| block.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Some(serde_json::Value::Object(object)) => object
+            .get("output")
+            .or_else(|| object.get("text"))
+            .and_then(|v| v.as_str())
+            .map(String::from)
+            .unwrap_or_else(|| serde_json::Value::Object(object.clone()).to_string()),
+        _ => String::new(),
+    }
+}
+
+/// Tool inputs and outputs (file contents, command output) can be huge; prompts and messages
+/// are kept whole
+fn truncate_tool_text(kind: TranscriptEntryKind, mut text: String) -> String {
+    if matches!(kind, TranscriptEntryKind::ToolCall | TranscriptEntryKind::ToolResult) {
+        if let Some((cut, _)) = text.char_indices().nth(MAX_TOOL_TEXT_CHARS) {
+            text.truncate(cut);
+            text.push_str("\n[truncated]");
+        }
+    }
+    text
+}
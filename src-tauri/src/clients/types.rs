@@ -2,12 +2,27 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+use crate::error::{Error, Result};
+use crate::terminal::shell::ShellSetting;
+use crate::terminal::TerminalEnv;
+
+/// Clients Ada ships with (see `AppState::init_default_clients`); they can be changed
+/// but not removed
+pub const BUILTIN_CLIENT_IDS: [&str; 8] = [
+    "claude-code", "opencode", "codex", "aider", "amp", "goose", "qwen-code", "copilot",
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "snake_case")]
 pub enum ClientType {
     ClaudeCode,
     OpenCode,
     Codex,
+    Aider,
+    Amp,
+    Goose,
+    QwenCode,
+    Copilot,
     Custom,
 }
 
@@ -21,9 +36,202 @@ pub struct ClientConfig {
     pub env: HashMap<String, String>,
     pub description: String,
     pub installed: bool,
+    /// Shell to launch this client through (None = exec directly)
+    #[serde(default)]
+    pub shell: Option<ShellSetting>,
+    /// TERM, locale and color settings for this client's terminals
+    #[serde(default)]
+    pub terminal_env: TerminalEnv,
+    /// Named sets of extra args and env a terminal can launch the agent with
+    #[serde(default)]
+    pub profiles: Vec<LaunchProfile>,
+}
+
+/// A named way of launching a client, e.g. "plan" or "auto-approve", picked per terminal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaunchProfile {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Added after the client's own args
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+}
+
+impl LaunchProfile {
+    fn new(name: &str, description: &str, args: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            env: HashMap::new(),
+        }
+    }
+
+    fn with_env(mut self, key: &str, value: &str) -> Self {
+        self.env.insert(key.to_string(), value.to_string());
+        self
+    }
+}
+
+/// A way of installing an agent (see `install_client`)
+#[derive(Debug, Clone, Copy)]
+pub struct Installer {
+    /// Programs the command needs, e.g. npm
+    pub requires: &'static [&'static str],
+    /// Run through the user's shell
+    pub command: &'static str,
+}
+
+impl ClientType {
+    /// Ways of installing the agent, in order of preference
+    pub fn installers(self) -> &'static [Installer] {
+        match self {
+            ClientType::ClaudeCode => &[
+                Installer { requires: &["npm"], command: "npm install -g @anthropic-ai/claude-code" },
+                Installer { requires: &["curl", "bash"], command: "curl -fsSL https://claude.ai/install.sh | bash" },
+            ],
+            ClientType::OpenCode => &[
+                Installer { requires: &["brew"], command: "brew install sst/tap/opencode" },
+                Installer { requires: &["npm"], command: "npm install -g opencode-ai" },
+                Installer { requires: &["curl", "bash"], command: "curl -fsSL https://opencode.ai/install | bash" },
+            ],
+            ClientType::Codex => &[
+                Installer { requires: &["npm"], command: "npm install -g @openai/codex" },
+                Installer { requires: &["brew"], command: "brew install codex" },
+            ],
+            ClientType::Aider => &[
+                Installer { requires: &["curl", "sh"], command: "curl -LsSf https://aider.chat/install.sh | sh" },
+                Installer { requires: &["brew"], command: "brew install aider" },
+            ],
+            ClientType::Amp => &[
+                Installer { requires: &["npm"], command: "npm install -g @sourcegraph/amp" },
+                Installer { requires: &["curl", "bash"], command: "curl -fsSL https://ampcode.com/install.sh | bash" },
+            ],
+            ClientType::Goose => &[
+                Installer { requires: &["brew"], command: "brew install block-goose-cli" },
+                Installer {
+                    requires: &["curl", "bash"],
+                    command: "curl -fsSL https://github.com/block/goose/releases/download/stable/download_cli.sh | CONFIGURE=false bash",
+                },
+            ],
+            ClientType::QwenCode => &[
+                Installer { requires: &["npm"], command: "npm install -g @qwen-code/qwen-code" },
+                Installer { requires: &["brew"], command: "brew install qwen-code" },
+            ],
+            ClientType::Copilot => &[
+                Installer { requires: &["npm"], command: "npm install -g @github/copilot" },
+            ],
+            ClientType::Custom => &[],
+        }
+    }
+
+    /// Whether the agent can be told which model to run (see `apply_agent_overrides`)
+    pub fn supports_model(self) -> bool {
+        !matches!(self, ClientType::Amp | ClientType::Custom)
+    }
+
+    /// Arguments that make the agent pick up its most recent conversation in the working
+    /// directory instead of starting a new one, for agents that can
+    pub fn resume_args(self) -> Option<&'static [&'static str]> {
+        match self {
+            ClientType::ClaudeCode | ClientType::OpenCode | ClientType::Copilot => Some(&["--continue"]),
+            ClientType::Codex => Some(&["resume", "--last"]),
+            ClientType::Aider => Some(&["--restore-chat-history"]),
+            ClientType::Amp => Some(&["threads", "continue"]),
+            ClientType::Goose => Some(&["session", "--resume"]),
+            ClientType::QwenCode | ClientType::Custom => None,
+        }
+    }
+
+    /// Profiles the built-in client starts with, for the agent's autonomy levels
+    pub fn default_profiles(self) -> Vec<LaunchProfile> {
+        match self {
+            ClientType::ClaudeCode => vec![
+                LaunchProfile::new("plan", "Plan before making changes", &["--permission-mode", "plan"]),
+                LaunchProfile::new("accept-edits", "Edit files without asking", &["--permission-mode", "acceptEdits"]),
+                LaunchProfile::new("auto-approve", "Run without asking for permission", &["--dangerously-skip-permissions"]),
+            ],
+            ClientType::Codex => vec![
+                LaunchProfile::new("read-only", "Read files but change nothing", &["--sandbox", "read-only"]),
+                LaunchProfile::new("auto-approve", "Edit and run commands in the workspace", &["--full-auto"]),
+            ],
+            ClientType::Aider => vec![
+                LaunchProfile::new("plan", "Answer questions without editing", &["--chat-mode", "ask"]),
+                LaunchProfile::new("auto-approve", "Say yes to every confirmation", &["--yes-always"]),
+            ],
+            ClientType::Amp => vec![
+                LaunchProfile::new("auto-approve", "Run tools without asking", &["--dangerously-allow-all"]),
+            ],
+            ClientType::Goose => vec![
+                LaunchProfile::new("read-only", "Chat without using tools", &[]).with_env("GOOSE_MODE", "chat"),
+                LaunchProfile::new("auto-approve", "Use tools without asking", &[]).with_env("GOOSE_MODE", "auto"),
+            ],
+            ClientType::QwenCode => vec![
+                LaunchProfile::new("plan", "Plan before making changes", &["--approval-mode", "plan"]),
+                LaunchProfile::new("auto-approve", "Run without asking for approval", &["--yolo"]),
+            ],
+            ClientType::Copilot => vec![
+                LaunchProfile::new("auto-approve", "Use every tool without asking", &["--allow-all-tools"]),
+            ],
+            ClientType::OpenCode | ClientType::Custom => Vec::new(),
+        }
+    }
 }
 
 impl ClientConfig {
+    /// Build a client from a definition file's fields, merged over `base` (the client
+    /// already registered under `id`, e.g. a built-in one) so the file only needs the
+    /// fields it changes. Without a base, the client is a custom one named after its ID.
+    pub fn from_definition(
+        fields: serde_json::Map<String, serde_json::Value>,
+        id: &str,
+        base: Option<&ClientConfig>,
+    ) -> Result<Self> {
+        let mut merged = match base {
+            Some(base) => serde_json::to_value(base)?,
+            None => serde_json::json!({
+                "name": id,
+                "client_type": ClientType::Custom,
+                "args": [],
+                "env": {},
+                "description": "",
+                "installed": false,
+            }),
+        };
+        if let serde_json::Value::Object(merged) = &mut merged {
+            merged.extend(fields);
+            merged.insert("id".to_string(), id.into());
+        }
+        Ok(serde_json::from_value(merged)?)
+    }
+
+    /// Launch the agent with one of its profiles' args and env
+    pub fn apply_profile(&mut self, name: &str) -> Result<()> {
+        let profile = self
+            .profiles
+            .iter()
+            .find(|profile| profile.name == name)
+            .cloned()
+            .ok_or_else(|| Error::InvalidRequest(format!("{} has no launch profile '{}'", self.name, name)))?;
+        self.args.extend(profile.args);
+        self.env.extend(profile.env);
+        Ok(())
+    }
+
+    /// Launch the agent continuing its previous conversation (see `ClientType::resume_args`).
+    /// The resume arguments go first, since some agents take them as a subcommand.
+    pub fn continue_conversation(&mut self) -> Result<()> {
+        let resume_args = self
+            .client_type
+            .resume_args()
+            .ok_or_else(|| Error::InvalidRequest(format!("{} can't resume a previous conversation", self.name)))?;
+        self.args.splice(0..0, resume_args.iter().map(|arg| arg.to_string()));
+        Ok(())
+    }
+
     pub fn detect_installation(&mut self) {
         // First try which (uses PATH)
         if which::which(&self.command).is_ok() {
@@ -75,6 +283,35 @@ impl ClientConfig {
                 PathBuf::from("/usr/local/bin/codex"),
                 PathBuf::from("/opt/homebrew/bin/codex"),
             ],
+            ClientType::Aider => vec![
+                // pipx, uv tool and aider-install all link it here
+                home.join(".local/bin/aider"),
+                PathBuf::from("/usr/local/bin/aider"),
+                PathBuf::from("/opt/homebrew/bin/aider"),
+            ],
+            ClientType::Amp => vec![
+                home.join(".local/bin/amp"),
+                home.join(".amp/bin/amp"),
+                PathBuf::from("/usr/local/bin/amp"),
+                PathBuf::from("/opt/homebrew/bin/amp"),
+            ],
+            ClientType::Goose => vec![
+                home.join(".local/bin/goose"),
+                PathBuf::from("/usr/local/bin/goose"),
+                PathBuf::from("/opt/homebrew/bin/goose"),
+            ],
+            ClientType::QwenCode => vec![
+                home.join(".local/bin/qwen"),
+                home.join(".npm-global/bin/qwen"),
+                PathBuf::from("/usr/local/bin/qwen"),
+                PathBuf::from("/opt/homebrew/bin/qwen"),
+            ],
+            ClientType::Copilot => vec![
+                home.join(".local/bin/copilot"),
+                home.join(".npm-global/bin/copilot"),
+                PathBuf::from("/usr/local/bin/copilot"),
+                PathBuf::from("/opt/homebrew/bin/copilot"),
+            ],
             ClientType::Custom => vec![],
         }
     }
@@ -100,3 +337,59 @@ impl From<&ClientConfig> for ClientSummary {
         }
     }
 }
+
+/// Request to register an agent CLI as a client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddClientRequest {
+    /// Defaults to the name; either way lowercased with dashes (e.g. "My Agent" -> "my-agent")
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    /// Custom by default; a built-in type gets that agent's flags (see `apply_agent_overrides`)
+    #[serde(default)]
+    pub client_type: Option<ClientType>,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub shell: Option<ShellSetting>,
+    #[serde(default)]
+    pub terminal_env: TerminalEnv,
+    #[serde(default)]
+    pub profiles: Vec<LaunchProfile>,
+}
+
+/// Changes to a client; fields that are None are left as they are
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateClientRequest {
+    pub client_id: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub args: Option<Vec<String>>,
+    #[serde(default)]
+    pub env: Option<HashMap<String, String>>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub shell: Option<ShellSetting>,
+    #[serde(default)]
+    pub terminal_env: Option<TerminalEnv>,
+    #[serde(default)]
+    pub profiles: Option<Vec<LaunchProfile>>,
+}
+
+/// Client ID derived from a name: lowercase, with runs of other characters turned into '-'
+pub fn client_id_from_name(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
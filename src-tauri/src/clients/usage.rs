@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use chrono::{DateTime, Utc};
+
+use super::agent_logs::{claude_sessions, codex_sessions, entry_since, json_lines};
+use super::ClientType;
+
+/// Tokens an agent used, as recorded in its own session logs
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Input tokens served from the prompt cache
+    pub cache_read_tokens: u64,
+    /// Input tokens written to the prompt cache (Claude Code only)
+    pub cache_write_tokens: u64,
+    /// Agent sessions the tokens were counted from
+    pub sessions: u32,
+}
+
+impl TokenUsage {
+    pub fn add(&mut self, other: &TokenUsage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.cache_write_tokens += other.cache_write_tokens;
+        self.sessions += other.sessions;
+    }
+}
+
+/// Token usage of the agent sessions of `client_type` that ran in `working_dir` since `since`,
+/// read from the agent's session logs (`~/.claude/projects` for Claude Code, `~/.codex/sessions`
+/// for Codex). None for agents whose logs Ada can't read. Terminals sharing a working
+/// directory see each other's sessions.
+pub fn agent_token_usage(client_type: ClientType, working_dir: &Path, since: DateTime<Utc>) -> Option<TokenUsage> {
+    match client_type {
+        ClientType::ClaudeCode => Some(claude_usage(working_dir, since)),
+        ClientType::Codex => Some(codex_usage(working_dir, since)),
+        _ => None,
+    }
+}
+
+/// Claude Code logs the usage of every API response. A response split over several lines
+/// repeats its usage, so each message is counted once.
+fn claude_usage(working_dir: &Path, since: DateTime<Utc>) -> TokenUsage {
+    let mut usage = TokenUsage::default();
+    for file in claude_sessions(working_dir, since) {
+        let mut seen = HashSet::new();
+        let mut counted = false;
+        for entry in json_lines(&file) {
+            let Some(message_usage) = entry.pointer("/message/usage") else {
+                continue;
+            };
+            if !entry_since(&entry, since) {
+                continue;
+            }
+            let id = entry.pointer("/message/id").and_then(|v| v.as_str()).map(String::from);
+            if id.is_some_and(|id| !seen.insert(id)) {
+                continue;
+            }
+            usage.input_tokens += field(message_usage, "input_tokens");
+            usage.output_tokens += field(message_usage, "output_tokens");
+            usage.cache_read_tokens += field(message_usage, "cache_read_input_tokens");
+            usage.cache_write_tokens += field(message_usage, "cache_creation_input_tokens");
+            counted = true;
+        }
+        usage.sessions += u32::from(counted);
+    }
+    usage
+}
+
+/// Codex reports running totals in `token_count` events; the last one is the session's total.
+/// A session that was already running at `since` has its total up to then taken off.
+fn codex_usage(working_dir: &Path, since: DateTime<Utc>) -> TokenUsage {
+    let mut usage = TokenUsage::default();
+    for file in codex_sessions(working_dir, since) {
+        let mut before = None;
+        let mut total = None;
+        for entry in json_lines(&file) {
+            let Some(running_total) = entry.pointer("/payload/info/total_token_usage").cloned() else {
+                continue;
+            };
+            if entry_since(&entry, since) {
+                total = Some(running_total);
+            } else {
+                before = Some(running_total);
+            }
+        }
+        if let Some(total) = total {
+            let used = |name: &str| field(&total, name).saturating_sub(before.as_ref().map_or(0, |b| field(b, name)));
+            let cached = used("cached_input_tokens");
+            usage.input_tokens += used("input_tokens").saturating_sub(cached);
+            usage.output_tokens += used("output_tokens");
+            usage.cache_read_tokens += cached;
+            usage.sessions += 1;
+        }
+    }
+    usage
+}
+
+fn field(value: &serde_json::Value, name: &str) -> u64 {
+    value.get(name).and_then(|v| v.as_u64()).unwrap_or(0)
+}
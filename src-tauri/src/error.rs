@@ -27,6 +27,12 @@ pub enum Error {
     #[error("Worktree error: {0}")]
     WorktreeError(String),
 
+    #[error("Worktree has unsaved work: {0}")]
+    WorktreeDirty(crate::git::WorktreeDirtySummary),
+
+    #[error("Remote error: {0}")]
+    RemoteError(crate::git::RemoteError),
+
     #[error("Serialization error: {0}")]
     SerializationError(String),
 
@@ -40,6 +46,12 @@ impl From<std::io::Error> for Error {
     }
 }
 
+impl From<git2::Error> for Error {
+    fn from(err: git2::Error) -> Self {
+        Error::GitError(err.message().to_string())
+    }
+}
+
 impl From<serde_json::Error> for Error {
     fn from(err: serde_json::Error) -> Self {
         Error::SerializationError(err.to_string())
@@ -48,12 +60,34 @@ impl From<serde_json::Error> for Error {
 
 pub type Result<T> = std::result::Result<T, Error>;
 
-// Custom Serialize implementation for Tauri's command system
+// Custom Serialize implementation for Tauri's command system. Errors reach the frontend as
+// their message, except those the UI acts on: objects tagged by `kind`, with the message
+// and the details, e.g. `{kind: "worktree_dirty", message, summary}`
 impl serde::Serialize for Error {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        use serde::ser::SerializeStruct;
+
+        match self {
+            Error::WorktreeDirty(summary) => {
+                let mut error = serializer.serialize_struct("Error", 3)?;
+                error.serialize_field("kind", "worktree_dirty")?;
+                error.serialize_field("message", &self.to_string())?;
+                error.serialize_field("summary", summary)?;
+                error.end()
+            }
+            // `reason` is the remote error's own kind (authentication, network, ...)
+            Error::RemoteError(remote_error) => {
+                let mut error = serializer.serialize_struct("Error", 4)?;
+                error.serialize_field("kind", "remote_error")?;
+                error.serialize_field("message", &self.to_string())?;
+                error.serialize_field("reason", &remote_error.kind)?;
+                error.serialize_field("remote", &remote_error.remote)?;
+                error.end()
+            }
+            _ => serializer.serialize_str(&self.to_string()),
+        }
     }
 }
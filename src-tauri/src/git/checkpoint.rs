@@ -0,0 +1,222 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use git2::{build::CheckoutBuilder, Commit, IndexAddOption, Oid, Repository, ResetType};
+
+use crate::error::{Error, Result};
+use super::CommitIdentity;
+
+/// Namespace of the shadow refs checkpoints are kept on, one ref per terminal
+const CHECKPOINT_REF_PREFIX: &str = "refs/ada/checkpoints/";
+
+/// Trailer recording where HEAD was when a checkpoint was taken
+const HEAD_TRAILER: &str = "Head: ";
+
+/// Checkpoints allowed past the limit before `prune_checkpoints` trims a ref, so the chain
+/// isn't rewritten after every new checkpoint
+const PRUNE_SLACK: usize = 20;
+
+/// A snapshot of everything in a worktree (tracked and untracked, but not ignored files),
+/// committed to a shadow ref without touching the branch, index or files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub id: String,
+    pub message: String,
+    /// Commit HEAD was on when the checkpoint was taken (None before the first commit)
+    pub head: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+pub fn checkpoint_ref(terminal_id: &str) -> String {
+    format!("{}{}", CHECKPOINT_REF_PREFIX, terminal_id)
+}
+
+/// Snapshot the worktree at `worktree_path` onto `ref_name`, attributed to `identity`.
+/// Returns None when nothing changed since the previous checkpoint on that ref.
+pub fn create_checkpoint(
+    worktree_path: &Path,
+    ref_name: &str,
+    message: &str,
+    identity: &CommitIdentity,
+) -> Result<Option<Checkpoint>> {
+    let repo = Repository::open(worktree_path)?;
+
+    // Stage everything into an in-memory copy of the index; it is never written back,
+    // so the user's staging area is left alone
+    let mut index = repo.index()?;
+    index.add_all(["*"], IndexAddOption::DEFAULT, None)?;
+    index.update_all(["*"], None)?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+
+    let previous = repo
+        .find_reference(ref_name)
+        .ok()
+        .and_then(|reference| reference.peel_to_commit().ok());
+    let head = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    if let Some(previous) = &previous {
+        let same_head = checkpoint_head(previous) == head.as_ref().map(Commit::id);
+        if previous.tree_id() == tree.id() && same_head {
+            return Ok(None);
+        }
+    }
+
+    // HEAD is a parent too, so commits an agent made stay reachable from the checkpoints
+    let mut parents: Vec<&Commit> = Vec::new();
+    parents.extend(previous.as_ref());
+    parents.extend(head.as_ref().filter(|head| Some(head.id()) != previous.as_ref().map(Commit::id)));
+
+    let full_message = match &head {
+        Some(head) => format!("{}\n\n{}{}", message, HEAD_TRAILER, head.id()),
+        None => message.to_string(),
+    };
+    let signature = identity.signature(&repo)?;
+    let full_message = identity.message(&full_message);
+    let id = repo.commit(None, &signature, &signature, &full_message, &tree, &parents)?;
+    repo.reference(ref_name, id, true, "checkpoint")?;
+
+    let checkpoint = to_checkpoint(&repo.find_commit(id)?);
+    Ok(Some(checkpoint))
+}
+
+/// Checkpoints on `ref_name`, newest first
+pub fn list_checkpoints(repo_path: &Path, ref_name: &str, limit: usize) -> Result<Vec<Checkpoint>> {
+    let repo = Repository::open(repo_path)?;
+    let Ok(reference) = repo.find_reference(ref_name) else {
+        return Ok(Vec::new());
+    };
+
+    let mut checkpoints = Vec::new();
+    let mut next = Some(reference.peel_to_commit()?);
+    while let Some(commit) = next {
+        if checkpoints.len() >= limit {
+            break;
+        }
+        checkpoints.push(to_checkpoint(&commit));
+        // The first parent is the previous checkpoint, unless this is the first one
+        next = commit
+            .parents()
+            .next()
+            .filter(|parent| Some(parent.id()) != checkpoint_head(&commit));
+    }
+    Ok(checkpoints)
+}
+
+/// Drop the oldest checkpoints on `ref_name` once it holds more than `keep` of them (plus
+/// some slack). Checkpoints chain onto each other, so the ones kept are recommitted
+/// without the older ones and get new IDs.
+pub fn prune_checkpoints(repo_path: &Path, ref_name: &str, keep: usize) -> Result<()> {
+    let repo = Repository::open(repo_path)?;
+    let Ok(reference) = repo.find_reference(ref_name) else {
+        return Ok(());
+    };
+
+    let mut chain = Vec::new();
+    let mut next = Some(reference.peel_to_commit()?);
+    while let Some(commit) = next {
+        if chain.len() > keep + PRUNE_SLACK {
+            break;
+        }
+        next = commit
+            .parents()
+            .next()
+            .filter(|parent| Some(parent.id()) != checkpoint_head(&commit));
+        chain.push(commit);
+    }
+    if chain.len() <= keep + PRUNE_SLACK {
+        return Ok(());
+    }
+
+    let mut previous: Option<Commit> = None;
+    for commit in chain[..keep].iter().rev() {
+        let head = checkpoint_head(commit).map(|id| repo.find_commit(id)).transpose()?;
+        let mut parents: Vec<&Commit> = Vec::new();
+        parents.extend(previous.as_ref());
+        parents.extend(head.as_ref().filter(|head| Some(head.id()) != previous.as_ref().map(Commit::id)));
+
+        let id = repo.commit(
+            None,
+            &commit.author(),
+            &commit.committer(),
+            &String::from_utf8_lossy(commit.message_bytes()),
+            &commit.tree()?,
+            &parents,
+        )?;
+        previous = Some(repo.find_commit(id)?);
+    }
+    if let Some(newest) = previous {
+        repo.reference(ref_name, newest.id(), true, "prune checkpoints")?;
+    }
+    Ok(())
+}
+
+/// Delete the ref a terminal's checkpoints are kept on, leaving them to git's garbage collection
+pub fn delete_checkpoints(repo_path: &Path, ref_name: &str) -> Result<()> {
+    let repo = Repository::open(repo_path)?;
+    if let Ok(mut reference) = repo.find_reference(ref_name) {
+        reference.delete()?;
+    }
+    Ok(())
+}
+
+/// Put the worktree back the way it was at checkpoint `checkpoint_id` from `ref_name`:
+/// the branch is reset to where HEAD was, and the files to the snapshot, with the changes
+/// left unstaged. The current state is checkpointed first, so a restore can be undone.
+pub fn restore_checkpoint(
+    worktree_path: &Path,
+    ref_name: &str,
+    checkpoint_id: &str,
+    identity: &CommitIdentity,
+) -> Result<Checkpoint> {
+    let on_ref = list_checkpoints(worktree_path, ref_name, usize::MAX)?
+        .iter()
+        .any(|c| c.id == checkpoint_id);
+    if !on_ref {
+        return Err(Error::InvalidRequest(format!(
+            "{} is not a checkpoint of this terminal",
+            checkpoint_id
+        )));
+    }
+    let repo = Repository::open(worktree_path)?;
+    let checkpoint = repo.find_commit(Oid::from_str(checkpoint_id)?)?;
+
+    create_checkpoint(
+        worktree_path,
+        ref_name,
+        &format!("Before restoring checkpoint {}", &checkpoint_id[..7]),
+        identity,
+    )?;
+
+    if let Some(head) = checkpoint_head(&checkpoint) {
+        let head = repo.find_commit(head)?;
+        repo.reset(head.as_object(), ResetType::Mixed, None)?;
+    }
+    repo.checkout_tree(
+        checkpoint.as_object(),
+        Some(CheckoutBuilder::new().force().remove_untracked(true)),
+    )?;
+
+    // checkout_tree stages the snapshot; unstage it again so it shows up as changes
+    let mut index = repo.index()?;
+    match repo.head().ok().and_then(|head| head.peel_to_tree().ok()) {
+        Some(head_tree) => index.read_tree(&head_tree)?,
+        None => index.clear()?,
+    }
+    index.write()?;
+
+    Ok(to_checkpoint(&checkpoint))
+}
+
+fn checkpoint_head(commit: &Commit) -> Option<Oid> {
+    let message = commit.message()?;
+    let head = message.lines().rev().find_map(|line| line.strip_prefix(HEAD_TRAILER))?;
+    Oid::from_str(head.trim()).ok()
+}
+
+fn to_checkpoint(commit: &Commit) -> Checkpoint {
+    Checkpoint {
+        id: commit.id().to_string(),
+        message: commit.summary().unwrap_or_default().to_string(),
+        head: checkpoint_head(commit).map(|id| id.to_string()),
+        created_at: DateTime::from_timestamp(commit.time().seconds(), 0),
+    }
+}
@@ -0,0 +1,91 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::error::{Error, Result};
+use super::{add_worktree, checkout_worktree, create_worktree_internal, CheckoutBypass};
+
+/// Whether copy-on-write clones (APFS clonefile, btrfs/xfs reflink) work from `source_dir`
+/// into `target_dir`. Both must be on the same filesystem for this to succeed.
+pub fn supports_cow_clone(source_dir: &Path, target_dir: &Path) -> bool {
+    let probe_name = format!(".ada-cow-probe-{}", uuid::Uuid::new_v4());
+    let source = source_dir.join(&probe_name);
+    let target = target_dir.join(&probe_name);
+
+    if std::fs::create_dir_all(target_dir).is_err() || std::fs::write(&source, b"ada").is_err() {
+        return false;
+    }
+
+    let supported = cow_copy(&source, &target).is_ok();
+
+    let _ = std::fs::remove_file(&source);
+    let _ = std::fs::remove_file(&target);
+    supported
+}
+
+/// Create a worktree by cloning the main checkout's files copy-on-write instead of
+/// checking them out, then resetting to the branch. Untracked and ignored files
+/// (build output, dependencies) come along for free, which is most of the win on big repos.
+/// Falls back to a regular checkout when the filesystem can't clone.
+pub fn create_worktree_cloned(
+    repo_path: &Path,
+    branch: &str,
+    worktree_path: &Path,
+    bypass: CheckoutBypass,
+) -> Result<()> {
+    let target_parent = worktree_path
+        .parent()
+        .ok_or_else(|| Error::WorktreeError("Worktree path has no parent directory".into()))?;
+
+    if !supports_cow_clone(repo_path, target_parent) {
+        eprintln!("[Ada:Worktree] Copy-on-write clones not supported here, using a regular checkout");
+        return create_worktree_internal(repo_path, branch, worktree_path, bypass);
+    }
+
+    // Register the worktree and its branch without writing any files
+    add_worktree(repo_path, branch, worktree_path, false, bypass)?;
+
+    for entry in std::fs::read_dir(repo_path)? {
+        let source = entry?.path();
+        let name = match source.file_name() {
+            Some(name) => name.to_owned(),
+            None => continue,
+        };
+
+        // Keep the worktree's own .git file, and don't clone the worktrees into themselves
+        if name == ".git" || worktree_path.starts_with(&source) {
+            continue;
+        }
+
+        if let Err(e) = cow_copy(&source, &worktree_path.join(&name)) {
+            // Tracked files are restored by the reset below anyway
+            eprintln!("[Ada:Worktree] Failed to clone {:?}: {}", source, e);
+        }
+    }
+
+    // Bring the index and tracked files in line with the worktree's branch
+    checkout_worktree(worktree_path)
+}
+
+/// Recursively copy a path using filesystem clones, failing rather than doing a full copy
+fn cow_copy(source: &Path, target: &Path) -> Result<()> {
+    let mut cmd = Command::new("cp");
+    if cfg!(target_os = "macos") {
+        cmd.arg("-c");
+    } else {
+        cmd.arg("--reflink=always");
+    }
+
+    let output = cmd
+        .arg("-Rp")
+        .arg(source)
+        .arg(target)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(Error::WorktreeError(
+            String::from_utf8_lossy(&output.stderr).to_string()
+        ));
+    }
+
+    Ok(())
+}
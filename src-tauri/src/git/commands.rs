@@ -1,94 +1,855 @@
-use std::path::PathBuf;
-use tauri::State;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
 
 use crate::error::{Error, Result};
 use crate::state::AppState;
+use crate::telemetry;
 use super::{
-    BranchInfo, WorktreeInfo,
-    create_worktree_internal, remove_worktree_internal,
+    BranchInfo, WorktreeInfo, WorktreeProgress, WorktreeCreationResult,
+    create_worktree_with_options, create_worktrees_internal, remove_worktree_internal,
     list_worktrees_internal, get_branches_internal, get_current_branch_internal,
+    branch_from_spec, supports_cow_clone, WorktreeStatus,
+    get_worktree_status_internal, FileDiff, find_worktree, get_worktree_diff_internal,
+    CommitInfo, GitIdentity, commit_worktree_internal, MergeResult, merge_branch_internal,
+    RebaseProgress, RebaseResult, rebase_worktree_internal, WorktreePruneReport,
+    prune_worktrees_internal, FetchResult, PullResult, RemoteProgress, fetch_internal,
+    pull_internal, StashEntry, stash_worktree_internal, list_worktree_stashes_internal,
+    pop_worktree_stash_internal, CommitLogEntry, get_commit_log_internal, FileStatusEntry,
+    get_file_status_internal, check_merge_internal, measure_disk_usage, worktree_path_for,
+    WorktreeNameContext, WorktreePreflight, preflight_worktree,
 };
 
+/// Default number of commits `get_commit_log` returns
+const DEFAULT_COMMIT_LOG_LIMIT: usize = 50;
+
+/// Default number of worktrees `create_worktrees` and `create_terminal_group` add at the same time
+pub const DEFAULT_WORKTREE_PARALLELISM: usize = 4;
+
+/// `worktree-progress` event payload
+#[derive(Debug, Clone, Serialize)]
+struct WorktreeProgressEvent {
+    project_id: String,
+    #[serde(flatten)]
+    progress: WorktreeProgress,
+}
+
+/// `worktree-disk-usage` event payload
+#[derive(Debug, Clone, Serialize)]
+struct WorktreeDiskUsageEvent {
+    project_id: String,
+    path: String,
+    bytes: u64,
+}
+
+/// `worktree-rebase-progress` event payload
+#[derive(Debug, Clone, Serialize)]
+struct RebaseProgressEvent {
+    project_id: String,
+    worktree_path: String,
+    #[serde(flatten)]
+    progress: RebaseProgress,
+}
+
+/// `git-remote-progress` event payload
+#[derive(Debug, Clone, Serialize)]
+struct RemoteProgressEvent {
+    project_id: String,
+    /// The project root or worktree being fetched or pulled
+    path: String,
+    #[serde(flatten)]
+    progress: RemoteProgress,
+}
+
 #[tauri::command]
 pub async fn get_branches(
     state: State<'_, AppState>,
     project_id: String,
+    root: Option<String>,
 ) -> Result<Vec<BranchInfo>> {
     let projects = state.projects.read();
     let project = projects
         .get(&project_id)
         .ok_or_else(|| Error::ProjectNotFound(project_id))?;
     
-    get_branches_internal(&project.path)
+    get_branches_internal(&project.root_path(root.as_deref())?)
 }
 
+/// Create a worktree for a branch spec, set up as the project's settings say. With
+/// `init_submodules` (the project setting by default) its submodules are initialized too.
+/// Submodule and seeding steps are reported through `worktree-progress` events.
 #[tauri::command]
 pub async fn create_worktree(
     state: State<'_, AppState>,
     project_id: String,
     branch: String,
     worktree_path: Option<String>,
+    init_submodules: Option<bool>,
 ) -> Result<WorktreeInfo> {
-    let projects = state.projects.read();
-    let project = projects
-        .get(&project_id)
-        .ok_or_else(|| Error::ProjectNotFound(project_id))?;
-    
-    let wt_path = worktree_path
-        .map(PathBuf::from)
-        .unwrap_or_else(|| {
-            project.settings.worktree_base_path
-                .clone()
-                .unwrap_or_else(|| project.path.join(".worktrees"))
-                .join(branch.replace('/', "-"))
-        });
-    
-    create_worktree_internal(&project.path, &branch, &wt_path)?;
-    
+    let (repo_path, wt_path, options) = {
+        let projects = state.projects.read();
+        let project = projects
+            .get(&project_id)
+            .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
+
+        let wt_path = worktree_path
+            .map(PathBuf::from)
+            .unwrap_or_else(|| {
+                let worktree_base = project.settings.worktree_base_path
+                    .clone()
+                    .unwrap_or_else(|| project.path.join(".worktrees"));
+                worktree_path_for(
+                    &worktree_base,
+                    project.settings.worktree_name_template.as_deref(),
+                    &WorktreeNameContext {
+                        branch: &branch_from_spec(&project.path, &branch),
+                        agent: None,
+                        project: &project.name,
+                    },
+                )
+            });
+        let mut options = project.settings.worktree_options();
+        options.init_submodules = init_submodules.unwrap_or(options.init_submodules);
+        (project.path.clone(), wt_path, options)
+    };
+
+    let run_id = checkout_run_id(&state, &wt_path);
+    let mut span = telemetry::ipc_run_span("create_worktree", run_id.as_deref());
+    let created = create_worktree_with_options(&repo_path, &branch, &wt_path, &options, &|stage, message| {
+        let _ = state.app_handle.emit(
+            "worktree-progress",
+            WorktreeProgressEvent {
+                project_id: project_id.clone(),
+                progress: WorktreeProgress {
+                    branch: branch.clone(),
+                    path: wt_path.to_string_lossy().to_string(),
+                    stage,
+                    message: Some(message),
+                },
+            },
+        );
+    });
+    span.record_result(&created);
+    created?;
+
     Ok(WorktreeInfo {
         path: wt_path.to_string_lossy().to_string(),
-        branch,
+        branch: branch_from_spec(&repo_path, &branch),
         head: String::new(),
         is_bare: false,
+        disk_usage: None,
+    })
+}
+
+/// Create worktrees for several branch specs concurrently, emitting `worktree-progress`
+/// events as each one is created and checked out
+#[tauri::command]
+pub async fn create_worktrees(
+    state: State<'_, AppState>,
+    project_id: String,
+    branches: Vec<String>,
+    parallelism: Option<usize>,
+    defer_checkout: Option<bool>,
+    init_submodules: Option<bool>,
+) -> Result<Vec<WorktreeCreationResult>> {
+    let (repo_path, worktrees, options) = {
+        let projects = state.projects.read();
+        let project = projects
+            .get(&project_id)
+            .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
+        let worktree_base = project.worktree_base(None)?;
+        let worktrees: Vec<(String, PathBuf)> = branches
+            .into_iter()
+            .map(|spec| {
+                let path = worktree_path_for(
+                    &worktree_base,
+                    project.settings.worktree_name_template.as_deref(),
+                    &WorktreeNameContext {
+                        branch: &branch_from_spec(&project.path, &spec),
+                        agent: None,
+                        project: &project.name,
+                    },
+                );
+                (spec, path)
+            })
+            .collect();
+        let mut options = project.settings.worktree_options();
+        options.init_submodules = init_submodules.unwrap_or(options.init_submodules);
+        (project.path.clone(), worktrees, options)
+    };
+
+    let on_progress = worktree_progress_emitter(&state.app_handle, &project_id);
+
+    // Adding worktrees blocks, so keep the batch off the async runtime's threads
+    tauri::async_runtime::spawn_blocking(move || {
+        create_worktrees_internal(
+            &repo_path,
+            worktrees,
+            parallelism.unwrap_or(DEFAULT_WORKTREE_PARALLELISM),
+            defer_checkout.unwrap_or(false),
+            &options,
+            on_progress,
+        )
+    })
+    .await
+    .map_err(|e| Error::WorktreeError(e.to_string()))
+}
+
+/// Progress callback for `create_worktrees_internal` that emits `worktree-progress` events
+pub fn worktree_progress_emitter(
+    app_handle: &AppHandle,
+    project_id: &str,
+) -> Arc<dyn Fn(WorktreeProgress) + Send + Sync> {
+    let app_handle = app_handle.clone();
+    let project_id = project_id.to_string();
+    Arc::new(move |progress| {
+        let _ = app_handle.emit(
+            "worktree-progress",
+            WorktreeProgressEvent {
+                project_id: project_id.clone(),
+                progress,
+            },
+        );
     })
 }
 
+/// Whether worktrees for this project can be provisioned with copy-on-write clones
+#[tauri::command]
+pub async fn check_cow_worktree_support(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> Result<bool> {
+    let projects = state.projects.read();
+    let project = projects
+        .get(&project_id)
+        .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
+
+    let worktree_base = project.settings.worktree_base_path
+        .clone()
+        .unwrap_or_else(|| project.path.join(".worktrees"));
+
+    Ok(supports_cow_clone(&project.path, &worktree_base))
+}
+
+/// Check for Git LFS, hooks and smudge filters that could make adding worktrees to this
+/// project slow or fail, with suggestions (such as the project's `worktree_bypass` setting)
+#[tauri::command]
+pub async fn check_worktree_preflight(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> Result<WorktreePreflight> {
+    let repo_path = {
+        let projects = state.projects.read();
+        projects
+            .get(&project_id)
+            .map(|p| p.path.clone())
+            .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?
+    };
+
+    preflight_worktree(&repo_path)
+}
+
+/// Remove a worktree. Worktrees with uncommitted changes or unpushed commits are only
+/// removed with `force`. With `delete_branch` its branch is deleted too, provided it has
+/// been merged into the main checkout's branch (or `force` is set).
 #[tauri::command]
 pub async fn remove_worktree(
     state: State<'_, AppState>,
     project_id: String,
     worktree_path: String,
+    delete_branch: Option<bool>,
+    force: Option<bool>,
 ) -> Result<()> {
+    let worktree_path = PathBuf::from(worktree_path);
+    let run_id = checkout_run_id(&state, &worktree_path);
+    let mut span = telemetry::ipc_run_span("remove_worktree", run_id.as_deref());
+
     let projects = state.projects.read();
     let project = projects
         .get(&project_id)
         .ok_or_else(|| Error::ProjectNotFound(project_id))?;
-    
-    remove_worktree_internal(&project.path, &PathBuf::from(worktree_path))
+    let result = remove_worktree_internal(
+        project.root_containing(&worktree_path),
+        &worktree_path,
+        delete_branch.unwrap_or(false),
+        force.unwrap_or(false),
+    );
+    if result.is_ok() {
+        state.worktree_disk_usage.forget(&worktree_path);
+    }
+    span.record_result(&result);
+    result
+}
+
+/// Clean up after worktrees that were deleted outside of git (e.g. by removing their
+/// terminal's directory by hand): drop registrations whose directory is gone, and report
+/// (or, with `remove_orphans`, delete) worktree directories git no longer knows about.
+/// Also reports worktrees that no terminal uses any more.
+#[tauri::command]
+pub async fn prune_worktrees(
+    state: State<'_, AppState>,
+    project_id: String,
+    remove_orphans: Option<bool>,
+) -> Result<WorktreePruneReport> {
+    let (repo_path, worktree_base) = {
+        let projects = state.projects.read();
+        let project = projects
+            .get(&project_id)
+            .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
+        let worktree_base = project.settings.worktree_base_path
+            .clone()
+            .unwrap_or_else(|| project.path.join(".worktrees"));
+        (project.path.clone(), worktree_base)
+    };
+
+    let mut report = prune_worktrees_internal(
+        &repo_path,
+        &worktree_base,
+        remove_orphans.unwrap_or(false),
+    )?;
+
+    let in_use: Vec<PathBuf> = state
+        .terminals
+        .read()
+        .values()
+        .filter_map(|t| t.worktree_path.as_ref())
+        .filter_map(|path| path.canonicalize().ok())
+        .collect();
+    let worktree_base = worktree_base.canonicalize().unwrap_or(worktree_base);
+    report.unused = list_worktrees_internal(&repo_path)?
+        .into_iter()
+        .filter_map(|worktree| PathBuf::from(worktree.path).canonicalize().ok())
+        .filter(|path| path.starts_with(&worktree_base) && !in_use.contains(path))
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+
+    Ok(report)
 }
 
+/// The worktrees of the project (or of one of its additional roots), with the disk usage
+/// measured so far. Sizes that are missing or stale are measured in the background and
+/// reported with `worktree-disk-usage` events.
 #[tauri::command]
 pub async fn list_worktrees(
     state: State<'_, AppState>,
     project_id: String,
+    root: Option<String>,
 ) -> Result<Vec<WorktreeInfo>> {
-    let projects = state.projects.read();
-    let project = projects
+    let mut worktrees = {
+        let projects = state.projects.read();
+        let project = projects
+            .get(&project_id)
+            .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
+        list_worktrees_internal(&project.root_path(root.as_deref())?)?
+    };
+
+    for worktree in &mut worktrees {
+        let path = PathBuf::from(&worktree.path);
+        let (bytes, stale) = state.worktree_disk_usage.get(&path);
+        worktree.disk_usage = bytes;
+        if stale && !worktree.is_bare && state.worktree_disk_usage.start_measuring(&path) {
+            spawn_disk_usage_measurement(state.app_handle.clone(), project_id.clone(), path);
+        }
+    }
+
+    Ok(worktrees)
+}
+
+fn spawn_disk_usage_measurement(app_handle: AppHandle, project_id: String, path: PathBuf) {
+    std::thread::spawn(move || {
+        let bytes = measure_disk_usage(&path)
+            .inspect_err(|e| eprintln!("[Ada:Worktree] Failed to measure {:?}: {}", path, e))
+            .ok();
+        let Some(state) = app_handle.try_state::<AppState>() else {
+            return;
+        };
+        state.worktree_disk_usage.finish_measuring(&path, bytes);
+
+        if let Some(bytes) = bytes {
+            let _ = app_handle.emit(
+                "worktree-disk-usage",
+                WorktreeDiskUsageEvent {
+                    project_id,
+                    path: path.to_string_lossy().to_string(),
+                    bytes,
+                },
+            );
+        }
+    });
+}
+
+/// Uncommitted changes and ahead/behind counts for each of the project's worktrees (or
+/// those of an additional root), relative to `base_branch` (the main checkout's branch
+/// when omitted)
+#[tauri::command]
+pub async fn get_worktree_status(
+    state: State<'_, AppState>,
+    project_id: String,
+    base_branch: Option<String>,
+    root: Option<String>,
+) -> Result<Vec<WorktreeStatus>> {
+    let repo_path = {
+        let projects = state.projects.read();
+        projects
+            .get(&project_id)
+            .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?
+            .root_path(root.as_deref())?
+    };
+
+    get_worktree_status_internal(&repo_path, base_branch.as_deref())
+}
+
+/// The unstaged (or, with `staged`, the staged) changes in one of the project's worktrees,
+/// parsed into per-file hunks
+#[tauri::command]
+pub async fn get_worktree_diff(
+    state: State<'_, AppState>,
+    project_id: String,
+    worktree_path: String,
+    staged: bool,
+) -> Result<Vec<FileDiff>> {
+    let repo_path = {
+        let projects = state.projects.read();
+        projects
+            .get(&project_id)
+            .map(|p| p.root_containing(Path::new(&worktree_path)).to_path_buf())
+            .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?
+    };
+
+    let worktree = find_worktree(&repo_path, &PathBuf::from(worktree_path))?;
+    get_worktree_diff_internal(Path::new(&worktree.path), staged)
+}
+
+/// `commit_worktree` request
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommitWorktreeRequest {
+    pub project_id: String,
+    pub worktree_path: String,
+    /// Paths to stage first (relative to the worktree); empty commits what is already staged
+    #[serde(default)]
+    pub paths: Vec<String>,
+    pub message: String,
+    #[serde(default)]
+    pub author: Option<GitIdentity>,
+    #[serde(default)]
+    pub committer: Option<GitIdentity>,
+}
+
+/// Stage the given paths in one of the project's worktrees and commit them, attributed to
+/// the project's git identity unless the request names an author or committer
+#[tauri::command]
+pub async fn commit_worktree(
+    state: State<'_, AppState>,
+    request: CommitWorktreeRequest,
+) -> Result<CommitInfo> {
+    let (repo_path, identity) = {
+        let projects = state.projects.read();
+        projects
+            .get(&request.project_id)
+            .map(|p| (p.root_containing(Path::new(&request.worktree_path)).to_path_buf(), p.settings.commit_identity()))
+            .ok_or_else(|| Error::ProjectNotFound(request.project_id.clone()))?
+    };
+
+    let run_id = checkout_run_id(&state, Path::new(&request.worktree_path));
+    let mut span = telemetry::ipc_run_span("commit_worktree", run_id.as_deref());
+    let result = find_worktree(&repo_path, &PathBuf::from(&request.worktree_path)).and_then(|worktree| {
+        commit_worktree_internal(
+            Path::new(&worktree.path),
+            &request.paths,
+            &request.message,
+            request.author.as_ref(),
+            request.committer.as_ref(),
+            &identity,
+        )
+    });
+    span.record_result(&result);
+    result
+}
+
+/// Merge a worktree's branch into its base branch (the main checkout's branch by default),
+/// fast-forwarding when possible. Conflicts are reported in the result, leaving both
+/// branches untouched.
+#[tauri::command]
+pub async fn merge_worktree(
+    state: State<'_, AppState>,
+    project_id: String,
+    worktree_path: String,
+    base_branch: Option<String>,
+    no_ff: Option<bool>,
+) -> Result<MergeResult> {
+    let (repo_path, identity) = {
+        let projects = state.projects.read();
+        projects
+            .get(&project_id)
+            .map(|p| (p.root_containing(Path::new(&worktree_path)).to_path_buf(), p.settings.commit_identity()))
+            .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?
+    };
+
+    let worktree = find_worktree(&repo_path, &PathBuf::from(worktree_path))?;
+    if worktree.branch.is_empty() {
+        return Err(Error::WorktreeError(format!(
+            "{} is not on a branch",
+            worktree.path
+        )));
+    }
+
+    let run_id = checkout_run_id(&state, Path::new(&worktree.path));
+    let mut span = telemetry::ipc_run_span("merge_worktree", run_id.as_deref());
+    span.set_attribute("ada.branch", worktree.branch.clone());
+    // Merging (and checking out the result) blocks, so keep it off the async runtime's threads
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        merge_branch_internal(
+            &repo_path,
+            &worktree.branch,
+            base_branch.as_deref(),
+            no_ff.unwrap_or(false),
+            &identity,
+        )
+    })
+    .await
+    .map_err(|e| Error::GitError(e.to_string()))
+    .and_then(|result| result);
+    span.record_result(&result);
+    result
+}
+
+/// Dry run of `merge_worktree`: which files the worktree's branch would conflict in when
+/// merged into (or rebased onto) its base branch, without changing anything
+#[tauri::command]
+pub async fn check_worktree_merge(
+    state: State<'_, AppState>,
+    project_id: String,
+    worktree_path: String,
+    base_branch: Option<String>,
+) -> Result<MergeResult> {
+    let repo_path = {
+        let projects = state.projects.read();
+        projects
+            .get(&project_id)
+            .map(|p| p.root_containing(Path::new(&worktree_path)).to_path_buf())
+            .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?
+    };
+
+    let worktree = find_worktree(&repo_path, &PathBuf::from(worktree_path))?;
+    if worktree.branch.is_empty() {
+        return Err(Error::WorktreeError(format!(
+            "{} is not on a branch",
+            worktree.path
+        )));
+    }
+
+    let run_id = checkout_run_id(&state, Path::new(&worktree.path));
+    let mut span = telemetry::ipc_run_span("check_worktree_merge", run_id.as_deref());
+    span.set_attribute("ada.branch", worktree.branch.clone());
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        check_merge_internal(&repo_path, &worktree.branch, base_branch.as_deref())
+    })
+    .await
+    .map_err(|e| Error::GitError(e.to_string()))
+    .and_then(|result| result);
+    span.record_result(&result);
+    result
+}
+
+/// Fetch a worktree's base branch (the main checkout's branch by default) and rebase the
+/// worktree's branch onto it, emitting `worktree-rebase-progress` events along the way.
+/// On conflicts the rebase is rolled back and the conflicting commit and paths are returned.
+#[tauri::command]
+pub async fn rebase_worktree(
+    state: State<'_, AppState>,
+    project_id: String,
+    worktree_path: String,
+    base_branch: Option<String>,
+) -> Result<RebaseResult> {
+    let repo_path = {
+        let projects = state.projects.read();
+        projects
+            .get(&project_id)
+            .map(|p| p.root_containing(Path::new(&worktree_path)).to_path_buf())
+            .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?
+    };
+
+    let worktree = find_worktree(&repo_path, &PathBuf::from(worktree_path))?;
+    let on_progress = {
+        let app_handle = state.app_handle.clone();
+        let worktree_path = worktree.path.clone();
+        move |progress: RebaseProgress| {
+            let _ = app_handle.emit(
+                "worktree-rebase-progress",
+                RebaseProgressEvent {
+                    project_id: project_id.clone(),
+                    worktree_path: worktree_path.clone(),
+                    progress,
+                },
+            );
+        }
+    };
+
+    let run_id = checkout_run_id(&state, Path::new(&worktree.path));
+    let mut span = telemetry::ipc_run_span("rebase_worktree", run_id.as_deref());
+    span.set_attribute("ada.branch", worktree.branch.clone());
+    // The fetch waits on the network and the rebase blocks, so keep both off the async runtime
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        rebase_worktree_internal(
+            &repo_path,
+            Path::new(&worktree.path),
+            base_branch.as_deref(),
+            &on_progress,
+        )
+    })
+    .await
+    .map_err(|e| Error::GitError(e.to_string()))
+    .and_then(|result| result);
+    span.record_result(&result);
+    result
+}
+
+/// Stash a worktree's uncommitted changes (and untracked files, with `include_untracked`)
+/// so its terminal can be switched to another branch or agent.
+/// Returns None when there was nothing to stash.
+#[tauri::command]
+pub async fn stash_worktree(
+    state: State<'_, AppState>,
+    project_id: String,
+    worktree_path: String,
+    message: Option<String>,
+    include_untracked: Option<bool>,
+) -> Result<Option<StashEntry>> {
+    let repo_path = {
+        let projects = state.projects.read();
+        projects
+            .get(&project_id)
+            .map(|p| p.root_containing(Path::new(&worktree_path)).to_path_buf())
+            .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?
+    };
+
+    let worktree = find_worktree(&repo_path, &PathBuf::from(worktree_path))?;
+    let run_id = checkout_run_id(&state, Path::new(&worktree.path));
+    let mut span = telemetry::ipc_run_span("stash_worktree", run_id.as_deref());
+    span.set_attribute("ada.branch", worktree.branch.clone());
+    // Stashing walks and rewrites the worktree, so keep it off the async runtime's threads
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        stash_worktree_internal(
+            Path::new(&worktree.path),
+            message.as_deref(),
+            include_untracked.unwrap_or(true),
+        )
+    })
+    .await
+    .map_err(|e| Error::GitError(e.to_string()))
+    .and_then(|result| result);
+    span.record_result(&result);
+    result
+}
+
+/// Stashes made on a worktree's branch, newest first
+#[tauri::command]
+pub async fn list_worktree_stashes(
+    state: State<'_, AppState>,
+    project_id: String,
+    worktree_path: String,
+) -> Result<Vec<StashEntry>> {
+    let repo_path = {
+        let projects = state.projects.read();
+        projects
+            .get(&project_id)
+            .map(|p| p.root_containing(Path::new(&worktree_path)).to_path_buf())
+            .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?
+    };
+
+    let worktree = find_worktree(&repo_path, &PathBuf::from(worktree_path))?;
+    tauri::async_runtime::spawn_blocking(move || list_worktree_stashes_internal(Path::new(&worktree.path)))
+        .await
+        .map_err(|e| Error::GitError(e.to_string()))?
+}
+
+/// Re-apply a stash (by its `index` from `list_worktree_stashes`) to its worktree and drop it
+#[tauri::command]
+pub async fn pop_worktree_stash(
+    state: State<'_, AppState>,
+    project_id: String,
+    worktree_path: String,
+    index: usize,
+) -> Result<StashEntry> {
+    let repo_path = {
+        let projects = state.projects.read();
+        projects
+            .get(&project_id)
+            .map(|p| p.root_containing(Path::new(&worktree_path)).to_path_buf())
+            .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?
+    };
+
+    let worktree = find_worktree(&repo_path, &PathBuf::from(worktree_path))?;
+    let run_id = checkout_run_id(&state, Path::new(&worktree.path));
+    let mut span = telemetry::ipc_run_span("pop_worktree_stash", run_id.as_deref());
+    span.set_attribute("ada.branch", worktree.branch.clone());
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        pop_worktree_stash_internal(Path::new(&worktree.path), index)
+    })
+    .await
+    .map_err(|e| Error::GitError(e.to_string()))
+    .and_then(|result| result);
+    span.record_result(&result);
+    result
+}
+
+/// The run (terminal) working in a worktree, so git operations on it join the run's trace
+fn checkout_run_id(state: &AppState, path: &Path) -> Option<String> {
+    state
+        .terminals
+        .read()
+        .values()
+        .find(|t| t.worktree_path.as_deref() == Some(path))
+        .map(|t| t.id.clone())
+}
+
+/// Resolve the project root, or one of its worktrees (or additional roots) when
+/// `worktree_path` is given
+fn resolve_checkout(
+    state: &AppState,
+    project_id: &str,
+    worktree_path: Option<String>,
+) -> Result<PathBuf> {
+    let repo_path = {
+        let projects = state.projects.read();
+        projects
+            .get(project_id)
+            .map(|p| match &worktree_path {
+                Some(path) => p.root_containing(Path::new(path)).to_path_buf(),
+                None => p.path.clone(),
+            })
+            .ok_or_else(|| Error::ProjectNotFound(project_id.to_string()))?
+    };
+
+    match worktree_path {
+        Some(path) => Ok(PathBuf::from(find_worktree(&repo_path, &PathBuf::from(path))?.path)),
+        None => Ok(repo_path),
+    }
+}
+
+/// Fetch `remote` (all remotes by default) for the project, pruning deleted remote branches,
+/// so branch lists are current before creating a worktree. Emits `git-remote-progress` events.
+#[tauri::command]
+pub async fn fetch(
+    state: State<'_, AppState>,
+    project_id: String,
+    worktree_path: Option<String>,
+    remote: Option<String>,
+) -> Result<FetchResult> {
+    let path = resolve_checkout(&state, &project_id, worktree_path)?;
+    let on_progress = remote_progress_emitter(&state.app_handle, &project_id, &path);
+
+    let run_id = checkout_run_id(&state, &path);
+    let mut span = telemetry::ipc_run_span("fetch", run_id.as_deref());
+    // Fetching waits on the network, so keep it off the async runtime's threads
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        fetch_internal(&path, remote.as_deref(), &on_progress)
+    })
+    .await
+    .map_err(|e| Error::GitError(e.to_string()))
+    .and_then(|result| result);
+    span.record_result(&result);
+    result
+}
+
+/// Pull the upstream of the branch checked out in the project root (or `worktree_path`),
+/// merging by default or rebasing with `rebase`. Emits `git-remote-progress` events;
+/// conflicts are reported in the result, leaving the branch untouched.
+#[tauri::command]
+pub async fn pull(
+    state: State<'_, AppState>,
+    project_id: String,
+    worktree_path: Option<String>,
+    rebase: Option<bool>,
+) -> Result<PullResult> {
+    let path = resolve_checkout(&state, &project_id, worktree_path)?;
+    let on_progress = remote_progress_emitter(&state.app_handle, &project_id, &path);
+    let identity = state
+        .projects
+        .read()
         .get(&project_id)
-        .ok_or_else(|| Error::ProjectNotFound(project_id))?;
-    
-    list_worktrees_internal(&project.path)
+        .map(|p| p.settings.commit_identity())
+        .unwrap_or_default();
+
+    let run_id = checkout_run_id(&state, &path);
+    let mut span = telemetry::ipc_run_span("pull", run_id.as_deref());
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        pull_internal(&path, rebase.unwrap_or(false), &identity, &on_progress)
+    })
+    .await
+    .map_err(|e| Error::GitError(e.to_string()))
+    .and_then(|result| result);
+    if let Ok(pulled) = &result {
+        span.set_attribute("ada.branch", pulled.branch.clone());
+    }
+    span.record_result(&result);
+    result
+}
+
+/// Report fetch and pull progress for `path` as `git-remote-progress` events
+fn remote_progress_emitter(
+    app_handle: &AppHandle,
+    project_id: &str,
+    path: &Path,
+) -> impl Fn(RemoteProgress) + Send + 'static {
+    let app_handle = app_handle.clone();
+    let project_id = project_id.to_string();
+    let path = path.to_string_lossy().to_string();
+    move |progress: RemoteProgress| {
+        let _ = app_handle.emit(
+            "git-remote-progress",
+            RemoteProgressEvent {
+                project_id: project_id.clone(),
+                path: path.clone(),
+                progress,
+            },
+        );
+    }
+}
+
+/// History of `branch` (or of what the project root or `worktree_path` has checked out),
+/// newest first, for paging through with `limit` and `skip`
+#[tauri::command]
+pub async fn get_commit_log(
+    state: State<'_, AppState>,
+    project_id: String,
+    worktree_path: Option<String>,
+    branch: Option<String>,
+    limit: Option<usize>,
+    skip: Option<usize>,
+) -> Result<Vec<CommitLogEntry>> {
+    let path = resolve_checkout(&state, &project_id, worktree_path)?;
+    get_commit_log_internal(
+        &path,
+        branch.as_deref(),
+        limit.unwrap_or(DEFAULT_COMMIT_LOG_LIMIT),
+        skip.unwrap_or(0),
+    )
+}
+
+/// Changed files in the project root (or `worktree_path`), split into staged and unstaged
+/// changes, with renames and untracked files
+#[tauri::command]
+pub async fn get_file_status(
+    state: State<'_, AppState>,
+    project_id: String,
+    worktree_path: Option<String>,
+) -> Result<Vec<FileStatusEntry>> {
+    let path = resolve_checkout(&state, &project_id, worktree_path)?;
+    get_file_status_internal(&path)
 }
 
 #[tauri::command]
 pub async fn get_current_branch(
     state: State<'_, AppState>,
     project_id: String,
+    root: Option<String>,
 ) -> Result<String> {
     let projects = state.projects.read();
     let project = projects
         .get(&project_id)
         .ok_or_else(|| Error::ProjectNotFound(project_id))?;
     
-    get_current_branch_internal(&project.path)
+    get_current_branch_internal(&project.root_path(root.as_deref())?)
 }
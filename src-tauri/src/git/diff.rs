@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use git2::{Delta, Diff, DiffOptions, Patch, Repository};
+
+use crate::error::Result;
+
+/// How a file changed in a diff
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileChangeKind {
+    Added,
+    Deleted,
+    Modified,
+    Renamed,
+    Copied,
+    Untracked,
+    /// e.g. a file replaced by a symlink
+    TypeChanged,
+}
+
+/// One changed file, with its hunks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileDiff {
+    /// None for added files
+    pub old_path: Option<String>,
+    /// None for deleted files
+    pub new_path: Option<String>,
+    pub change: FileChangeKind,
+    /// Binary files have no hunks
+    pub binary: bool,
+    pub hunks: Vec<DiffHunk>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffHunk {
+    /// The `@@ -a,b +c,d @@` line, including any function context
+    pub header: String,
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffLineKind {
+    Context,
+    Addition,
+    Deletion,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    /// The line without its trailing newline
+    pub content: String,
+    pub old_lineno: Option<u32>,
+    pub new_lineno: Option<u32>,
+}
+
+/// Changes in a worktree: staged changes against HEAD (`git diff --cached`), or unstaged
+/// changes against the index including untracked files (`git diff` plus new files)
+pub fn get_worktree_diff_internal(worktree_path: &Path, staged: bool) -> Result<Vec<FileDiff>> {
+    let repo = Repository::open(worktree_path)?;
+
+    let mut options = DiffOptions::new();
+    let mut diff = if staged {
+        let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+        repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut options))?
+    } else {
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .show_untracked_content(true);
+        repo.diff_index_to_workdir(None, Some(&mut options))?
+    };
+    diff.find_similar(None)?;
+
+    parse_diff(&diff)
+}
+
+fn parse_diff(diff: &Diff) -> Result<Vec<FileDiff>> {
+    let mut files = Vec::new();
+
+    for (index, delta) in diff.deltas().enumerate() {
+        let change = match delta.status() {
+            Delta::Added => FileChangeKind::Added,
+            Delta::Deleted => FileChangeKind::Deleted,
+            Delta::Renamed => FileChangeKind::Renamed,
+            Delta::Copied => FileChangeKind::Copied,
+            Delta::Untracked => FileChangeKind::Untracked,
+            Delta::Typechange => FileChangeKind::TypeChanged,
+            Delta::Modified => FileChangeKind::Modified,
+            // Unmodified, ignored and unreadable entries only show up when asked for
+            _ => continue,
+        };
+
+        let path_of = |file: git2::DiffFile| file.path().map(|p| p.to_string_lossy().to_string());
+        let old_path = match change {
+            FileChangeKind::Added | FileChangeKind::Untracked => None,
+            _ => path_of(delta.old_file()),
+        };
+        let new_path = match change {
+            FileChangeKind::Deleted => None,
+            _ => path_of(delta.new_file()),
+        };
+
+        let mut file = FileDiff {
+            old_path,
+            new_path,
+            change,
+            binary: delta.flags().is_binary(),
+            hunks: Vec::new(),
+        };
+
+        if let Some(patch) = Patch::from_diff(diff, index)? {
+            // Binary-ness is only known for sure once the content has been loaded
+            file.binary = patch.delta().flags().is_binary();
+            for hunk_index in 0..patch.num_hunks() {
+                let (hunk, line_count) = patch.hunk(hunk_index)?;
+                let mut lines = Vec::with_capacity(line_count);
+                for line_index in 0..line_count {
+                    let line = patch.line_in_hunk(hunk_index, line_index)?;
+                    let kind = match line.origin() {
+                        '+' => DiffLineKind::Addition,
+                        '-' => DiffLineKind::Deletion,
+                        ' ' => DiffLineKind::Context,
+                        // "\ No newline at end of file" markers
+                        _ => continue,
+                    };
+                    let content = String::from_utf8_lossy(line.content());
+                    lines.push(DiffLine {
+                        kind,
+                        content: content.strip_suffix('\n').unwrap_or(&content).to_string(),
+                        old_lineno: line.old_lineno(),
+                        new_lineno: line.new_lineno(),
+                    });
+                }
+
+                file.hunks.push(DiffHunk {
+                    header: String::from_utf8_lossy(hunk.header()).trim_end().to_string(),
+                    old_start: hunk.old_start(),
+                    old_lines: hunk.old_lines(),
+                    new_start: hunk.new_start(),
+                    new_lines: hunk.new_lines(),
+                    lines,
+                });
+            }
+        }
+
+        files.push(file);
+    }
+
+    Ok(files)
+}
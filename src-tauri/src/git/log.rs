@@ -0,0 +1,84 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use git2::{Commit, DiffOptions, Repository, Sort};
+
+use crate::error::{Error, Result};
+
+/// One commit in a branch's history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitLogEntry {
+    pub hash: String,
+    pub short_hash: String,
+    pub author_name: String,
+    pub author_email: String,
+    /// Author date
+    pub date: Option<DateTime<Utc>>,
+    /// First line of the message
+    pub subject: String,
+    /// Files changed relative to the first parent
+    pub files_changed: usize,
+    pub parent_count: usize,
+}
+
+/// History of `branch` (HEAD of the checkout at `path` when None), newest first,
+/// skipping the first `skip` commits and returning at most `limit`
+pub fn get_commit_log_internal(
+    path: &Path,
+    branch: Option<&str>,
+    limit: usize,
+    skip: usize,
+) -> Result<Vec<CommitLogEntry>> {
+    let repo = Repository::open(path)?;
+    let start = match branch {
+        Some(branch) => repo
+            .revparse_single(branch)
+            .map_err(|_| Error::GitError(format!("Branch '{}' does not exist", branch)))?
+            .peel_to_commit()?
+            .id(),
+        None => match repo.head() {
+            Ok(head) => head.peel_to_commit()?.id(),
+            // No commits yet
+            Err(_) => return Ok(Vec::new()),
+        },
+    };
+
+    let mut revwalk = repo.revwalk()?;
+    // Topological so parents never come before children, even with equal timestamps
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    revwalk.push(start)?;
+
+    let mut entries = Vec::new();
+    for id in revwalk.skip(skip).take(limit) {
+        let commit = repo.find_commit(id?)?;
+        entries.push(log_entry(&repo, &commit)?);
+    }
+    Ok(entries)
+}
+
+fn log_entry(repo: &Repository, commit: &Commit) -> Result<CommitLogEntry> {
+    let tree = commit.tree()?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        // Root commit: everything in it is new
+        Err(_) => None,
+    };
+    let diff = repo.diff_tree_to_tree(
+        parent_tree.as_ref(),
+        Some(&tree),
+        Some(&mut DiffOptions::new()),
+    )?;
+
+    let hash = commit.id().to_string();
+    let author = commit.author();
+    Ok(CommitLogEntry {
+        short_hash: hash[..7].to_string(),
+        hash,
+        author_name: author.name().unwrap_or_default().to_string(),
+        author_email: author.email().unwrap_or_default().to_string(),
+        date: DateTime::from_timestamp(author.when().seconds(), 0),
+        subject: commit.summary().unwrap_or_default().to_string(),
+        files_changed: diff.deltas().len(),
+        parent_count: commit.parent_count(),
+    })
+}
@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use git2::build::CheckoutBuilder;
+use git2::{Commit, Oid, Repository};
+
+use crate::error::{Error, Result};
+use super::{get_current_branch_internal, CommitIdentity};
+
+/// What merging a branch did
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeKind {
+    /// The base already contained every commit of the branch
+    UpToDate,
+    FastForward,
+    /// A merge commit was created
+    Merged,
+    /// Nothing was changed because the branches conflict
+    Conflicted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeResult {
+    pub kind: MergeKind,
+    pub branch: String,
+    pub base_branch: String,
+    /// The base branch's new head, when it moved
+    pub commit_id: Option<String>,
+    /// Paths that conflict (only for `Conflicted`)
+    pub conflicts: Vec<String>,
+}
+
+/// Merge `branch` into `base_branch` in the main checkout at `repo_path`, fast-forwarding
+/// when possible (unless `no_ff`). The merge is worked out in memory first: on conflicts
+/// nothing is touched and the conflicting paths are returned instead.
+/// `base_branch` must be the branch checked out in the main checkout (the default).
+/// A merge commit is attributed to the project's `identity`.
+pub fn merge_branch_internal(
+    repo_path: &Path,
+    branch: &str,
+    base_branch: Option<&str>,
+    no_ff: bool,
+    identity: &CommitIdentity,
+) -> Result<MergeResult> {
+    let repo = Repository::open(repo_path)?;
+    let current_branch = get_current_branch_internal(repo_path)?;
+    let base_branch = base_branch.unwrap_or(&current_branch).to_string();
+    if base_branch != current_branch {
+        return Err(Error::InvalidRequest(format!(
+            "'{}' must be checked out in the main checkout to merge into it (it is on '{}')",
+            base_branch, current_branch
+        )));
+    }
+    if repo.state() != git2::RepositoryState::Clean {
+        return Err(Error::GitError(
+            "The main checkout has an operation in progress (merge, rebase, ...)".into(),
+        ));
+    }
+
+    let theirs = repo
+        .find_branch(branch, git2::BranchType::Local)
+        .map_err(|_| Error::GitError(format!("Branch '{}' does not exist", branch)))?
+        .get()
+        .peel_to_commit()?;
+
+    let message = format!("Merge branch '{}' into {}", branch, base_branch);
+    merge_into_head(&repo, &theirs, branch, &base_branch, &message, no_ff, identity)
+}
+
+/// Work out what merging `branch` into `base_branch` (the main checkout's branch when None)
+/// would do, without touching any branch or file: `kind` is what `merge_branch_internal`
+/// would report, and `conflicts` the paths that would conflict. Unlike a real merge the base
+/// doesn't need to be checked out, so this also predicts conflicts before a rebase.
+pub fn check_merge_internal(
+    repo_path: &Path,
+    branch: &str,
+    base_branch: Option<&str>,
+) -> Result<MergeResult> {
+    let repo = Repository::open(repo_path)?;
+    let base_branch = match base_branch {
+        Some(branch) => branch.to_string(),
+        None => get_current_branch_internal(repo_path)?,
+    };
+
+    let ours = repo
+        .revparse_single(&base_branch)
+        .map_err(|_| Error::GitError(format!("Base branch '{}' does not exist", base_branch)))?
+        .peel_to_commit()?;
+    let theirs = repo
+        .find_branch(branch, git2::BranchType::Local)
+        .map_err(|_| Error::GitError(format!("Branch '{}' does not exist", branch)))?
+        .get()
+        .peel_to_commit()?;
+
+    let mut result = MergeResult {
+        kind: MergeKind::UpToDate,
+        branch: branch.to_string(),
+        base_branch,
+        commit_id: None,
+        conflicts: Vec::new(),
+    };
+
+    let merge_base = repo.merge_base(ours.id(), theirs.id())?;
+    if merge_base == theirs.id() {
+        return Ok(result);
+    }
+    if merge_base == ours.id() {
+        result.kind = MergeKind::FastForward;
+        return Ok(result);
+    }
+
+    let index = repo.merge_commits(&ours, &theirs, None)?;
+    if index.has_conflicts() {
+        result.kind = MergeKind::Conflicted;
+        result.conflicts = conflict_paths(&index)?;
+    } else {
+        result.kind = MergeKind::Merged;
+    }
+    Ok(result)
+}
+
+/// Merge `theirs` into the commit HEAD of `repo` is on, and move HEAD to the result.
+/// `branch` and `base_branch` only label the result; a merge commit is made as `identity`.
+pub(super) fn merge_into_head(
+    repo: &Repository,
+    theirs: &Commit,
+    branch: &str,
+    base_branch: &str,
+    message: &str,
+    no_ff: bool,
+    identity: &CommitIdentity,
+) -> Result<MergeResult> {
+    let mut result = MergeResult {
+        kind: MergeKind::UpToDate,
+        branch: branch.to_string(),
+        base_branch: base_branch.to_string(),
+        commit_id: None,
+        conflicts: Vec::new(),
+    };
+
+    let ours = repo.head()?.peel_to_commit()?;
+    let merge_base = repo.merge_base(ours.id(), theirs.id())?;
+    if merge_base == theirs.id() {
+        return Ok(result);
+    }
+
+    let new_head = if merge_base == ours.id() && !no_ff {
+        result.kind = MergeKind::FastForward;
+        theirs.id()
+    } else {
+        let mut index = repo.merge_commits(&ours, theirs, None)?;
+        if index.has_conflicts() {
+            result.kind = MergeKind::Conflicted;
+            result.conflicts = conflict_paths(&index)?;
+            return Ok(result);
+        }
+
+        let tree = repo.find_tree(index.write_tree_to(repo)?)?;
+        let signature = identity.signature(repo)?;
+        result.kind = MergeKind::Merged;
+        repo.commit(None, &signature, &signature, &identity.message(message), &tree, &[&ours, theirs])?
+    };
+
+    move_head(repo, new_head, &format!("merge {}", branch))?;
+    result.commit_id = Some(new_head.to_string());
+    Ok(result)
+}
+
+/// Check out `target` without clobbering local changes, then point the current branch at it
+pub(super) fn move_head(repo: &Repository, target: Oid, reflog_message: &str) -> Result<()> {
+    let commit = repo.find_commit(target)?;
+    repo.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().safe()))
+        .map_err(|e| Error::GitError(format!(
+            "Uncommitted changes would be overwritten: {}",
+            e.message()
+        )))?;
+    repo.head()?.set_target(target, reflog_message)?;
+    Ok(())
+}
+
+pub(super) fn conflict_paths(index: &git2::Index) -> Result<Vec<String>> {
+    let mut paths = Vec::new();
+    for conflict in index.conflicts()? {
+        let conflict = conflict?;
+        let entry = conflict.our.or(conflict.their).or(conflict.ancestor);
+        if let Some(entry) = entry {
+            paths.push(String::from_utf8_lossy(&entry.path).to_string());
+        }
+    }
+    Ok(paths)
+}
@@ -1,4 +1,32 @@
 pub mod commands;
+mod checkpoint;
+mod clone;
+mod diff;
+mod log;
+mod merge;
+mod preflight;
+mod rebase;
+mod remote;
+mod repo;
+mod seed;
+mod stash;
+mod status;
+mod submodule;
+mod usage;
 mod worktree;
 
+pub use checkpoint::*;
+pub use clone::*;
+pub use diff::*;
+pub use log::*;
+pub use merge::*;
+pub use preflight::*;
+pub use rebase::*;
+pub use remote::*;
+pub use repo::*;
+pub use seed::*;
+pub use stash::*;
+pub use status::*;
+pub use submodule::*;
+pub use usage::*;
 pub use worktree::*;
@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use git2::Repository;
+
+use crate::error::Result;
+
+/// Parts of a repository's own checkout machinery to bypass when adding a worktree
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CheckoutBypass {
+    /// Don't run hooks (post-checkout), e.g. slow dependency installs
+    #[serde(default)]
+    pub hooks: bool,
+    /// Don't download Git LFS files; they are left as pointer files
+    #[serde(default)]
+    pub lfs: bool,
+}
+
+impl CheckoutBypass {
+    pub(super) fn apply(&self, cmd: &mut Command) {
+        if self.hooks {
+            // A hooks directory that doesn't exist has no hooks to run
+            cmd.args(["-c", "core.hooksPath=/dev/null"]);
+        }
+        if self.lfs {
+            cmd.env("GIT_LFS_SKIP_SMUDGE", "1");
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PreflightIssueKind {
+    /// The repository uses Git LFS, but `git-lfs` isn't installed
+    LfsNotInstalled,
+    /// A post-checkout hook will run in every new worktree
+    CheckoutHook,
+    /// `.gitattributes` uses a filter whose command isn't configured or can't be found
+    MissingFilter,
+}
+
+/// Something about a repository likely to make adding worktrees slow or fail
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreflightIssue {
+    pub kind: PreflightIssueKind,
+    pub message: String,
+    /// What to do about it
+    pub suggestion: String,
+}
+
+/// What `git worktree add` will run besides the checkout itself
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorktreePreflight {
+    /// Files are stored with Git LFS (`filter=lfs` in `.gitattributes`)
+    pub uses_lfs: bool,
+    pub lfs_installed: bool,
+    /// `core.hooksPath`, when set
+    pub hooks_path: Option<String>,
+    /// Whether the hooks directory (`core.hooksPath` or the repository's `hooks`) exists
+    pub hooks_dir_exists: bool,
+    /// Hooks that run when a worktree is checked out
+    pub checkout_hooks: Vec<String>,
+    /// Filters from `.gitattributes` with a smudge (or process) command configured
+    pub smudge_filters: Vec<String>,
+    pub issues: Vec<PreflightIssue>,
+}
+
+/// Look for Git LFS, hooks and smudge filters that run when a worktree of `repo_path`
+/// is checked out, and report the ones likely to cause trouble
+pub fn preflight_worktree(repo_path: &Path) -> Result<WorktreePreflight> {
+    let repo = Repository::open(repo_path)?;
+    let config = repo.config()?.snapshot()?;
+    let mut report = WorktreePreflight::default();
+
+    let attributes = std::fs::read_to_string(repo_path.join(".gitattributes")).unwrap_or_default();
+    let mut filters: Vec<&str> = attributes
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .flat_map(str::split_whitespace)
+        .filter_map(|attr| attr.strip_prefix("filter="))
+        .collect();
+    filters.sort();
+    filters.dedup();
+
+    report.uses_lfs = filters.contains(&"lfs");
+    report.lfs_installed = which::which("git-lfs").is_ok();
+    if report.uses_lfs && !report.lfs_installed {
+        report.issues.push(PreflightIssue {
+            kind: PreflightIssueKind::LfsNotInstalled,
+            message: "This repository stores files with Git LFS, but git-lfs isn't installed, \
+                      so worktrees get pointer files instead of their contents"
+                .into(),
+            suggestion: "Install git-lfs and run `git lfs install`".into(),
+        });
+    }
+
+    for filter in filters {
+        let command = ["process", "smudge"]
+            .iter()
+            .find_map(|key| config.get_string(&format!("filter.{}.{}", filter, key)).ok());
+        match command {
+            Some(command) => {
+                report.smudge_filters.push(filter.to_string());
+                let program = command.split_whitespace().next().unwrap_or_default();
+                if !program.is_empty() && which::which(program).is_err() {
+                    // git-lfs has its own issue above
+                    if filter != "lfs" {
+                        report.issues.push(PreflightIssue {
+                            kind: PreflightIssueKind::MissingFilter,
+                            message: format!("The '{}' filter runs '{}', which isn't installed", filter, program),
+                            suggestion: format!("Install '{}' so files using the filter can be checked out", program),
+                        });
+                    }
+                }
+            }
+            None if config.get_bool(&format!("filter.{}.required", filter)).unwrap_or(false) => {
+                report.issues.push(PreflightIssue {
+                    kind: PreflightIssueKind::MissingFilter,
+                    message: format!("The required '{}' filter isn't configured", filter),
+                    suggestion: format!("Configure filter.{}.smudge (see the project's setup docs)", filter),
+                });
+            }
+            None => {}
+        }
+    }
+
+    report.hooks_path = config.get_string("core.hooksPath").ok();
+    let hooks_dir = match &report.hooks_path {
+        Some(path) => repo_path.join(expand_home(path)),
+        None => repo.commondir().join("hooks"),
+    };
+    report.hooks_dir_exists = hooks_dir.is_dir();
+    let hook = hooks_dir.join("post-checkout");
+    if is_executable(&hook) {
+        report.checkout_hooks.push(hook.to_string_lossy().to_string());
+        report.issues.push(PreflightIssue {
+            kind: PreflightIssueKind::CheckoutHook,
+            message: format!("{} runs in every new worktree", hook.display()),
+            suggestion: "Skip hooks for new worktrees if it is slow or fails outside the main checkout".into(),
+        });
+    }
+
+    Ok(report)
+}
+
+/// Add what the pre-flight check found to a failed `git worktree add`, so the error says
+/// what probably went wrong and how to get past it
+pub(super) fn explain_failure(repo_path: &Path, message: String) -> String {
+    let issues = preflight_worktree(repo_path)
+        .map(|report| report.issues)
+        .unwrap_or_default();
+    if issues.is_empty() {
+        return message;
+    }
+
+    let hints: Vec<String> = issues
+        .iter()
+        .map(|issue| format!("- {}. {}.", issue.message, issue.suggestion))
+        .collect();
+    format!("{}\nPossible causes:\n{}", message.trim_end(), hints.join("\n"))
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().map(|home| home.join(rest)).unwrap_or_else(|| path.into()),
+        None => PathBuf::from(path),
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
@@ -0,0 +1,215 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use git2::{BranchType, ErrorCode, RebaseOptions, Repository, StatusOptions};
+
+use crate::error::{Error, Result};
+use super::get_current_branch_internal;
+use super::merge::{conflict_paths, move_head};
+use super::remote::{run_fetch, RemoteProgress};
+use super::worktree::head_branch;
+
+/// Stage reached while rebasing a worktree
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RebaseStage {
+    Fetching,
+    /// Replaying commit `current` of `total`
+    Applying,
+    /// Stopped and rolled back on a conflict
+    Conflicted,
+    Finished,
+}
+
+/// Progress event for a worktree rebase
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebaseProgress {
+    pub stage: RebaseStage,
+    /// Commit being applied of `total`; while fetching, percent done of 100 (when git says)
+    pub current: usize,
+    pub total: usize,
+    /// Summary of the commit being applied, what the fetch is doing, or what went wrong
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RebaseKind {
+    /// The branch already contained the base
+    UpToDate,
+    Rebased,
+    /// A commit didn't apply cleanly; the branch was left as it was
+    Conflicted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebaseResult {
+    pub kind: RebaseKind,
+    pub branch: String,
+    /// What the branch was rebased onto (the base's remote-tracking branch when it has one)
+    pub onto: String,
+    /// The branch's new head, when it moved
+    pub commit_id: Option<String>,
+    /// Commit that failed to apply (only for `Conflicted`)
+    pub conflicting_commit: Option<String>,
+    /// Paths that conflict (only for `Conflicted`)
+    pub conflicts: Vec<String>,
+}
+
+/// Fetch `base_branch` (the main checkout's branch when None) from its upstream, if it has one,
+/// and rebase the branch checked out at `worktree_path` onto it.
+/// The rebase runs in memory, so a conflict leaves the worktree and branch untouched.
+pub fn rebase_worktree_internal(
+    repo_path: &Path,
+    worktree_path: &Path,
+    base_branch: Option<&str>,
+    on_progress: &dyn Fn(RebaseProgress),
+) -> Result<RebaseResult> {
+    let repo = Repository::open(worktree_path)?;
+    let branch = head_branch(&repo).ok_or_else(|| {
+        Error::WorktreeError(format!("{} is not on a branch", worktree_path.display()))
+    })?;
+    let base_branch = match base_branch {
+        Some(branch) => branch.to_string(),
+        None => get_current_branch_internal(repo_path)?,
+    };
+
+    on_progress(RebaseProgress {
+        stage: RebaseStage::Fetching,
+        current: 0,
+        total: 0,
+        message: None,
+    });
+    let on_fetch_progress = |fetch: RemoteProgress| {
+        on_progress(RebaseProgress {
+            stage: RebaseStage::Fetching,
+            current: fetch.percent.unwrap_or(0) as usize,
+            total: if fetch.percent.is_some() { 100 } else { 0 },
+            message: fetch.phase.or(fetch.message),
+        });
+    };
+    let onto = fetch_upstream(&repo, repo_path, &base_branch, &on_fetch_progress)?.unwrap_or(base_branch);
+
+    rebase_head_onto(&repo, &branch, &onto, on_progress)
+}
+
+/// Rebase `branch`, checked out in `repo`, onto the revision `onto`. Refuses to run with
+/// uncommitted changes; a conflict rolls everything back.
+pub(super) fn rebase_head_onto(
+    repo: &Repository,
+    branch: &str,
+    onto: &str,
+    on_progress: &dyn Fn(RebaseProgress),
+) -> Result<RebaseResult> {
+    let progress = |stage: RebaseStage, current: usize, total: usize, message: Option<String>| {
+        on_progress(RebaseProgress { stage, current, total, message });
+    };
+
+    let mut status_options = StatusOptions::new();
+    status_options.include_untracked(false);
+    if !repo.statuses(Some(&mut status_options))?.is_empty() {
+        return Err(Error::WorktreeError(
+            "The worktree has uncommitted changes; commit or discard them before rebasing".into(),
+        ));
+    }
+
+    let mut result = RebaseResult {
+        kind: RebaseKind::UpToDate,
+        branch: branch.to_string(),
+        onto: onto.to_string(),
+        commit_id: None,
+        conflicting_commit: None,
+        conflicts: Vec::new(),
+    };
+
+    let branch_id = repo.head()?.peel_to_commit()?.id();
+    let onto_id = repo
+        .revparse_single(onto)
+        .map_err(|_| Error::GitError(format!("Base branch '{}' does not exist", onto)))?
+        .peel_to_commit()?
+        .id();
+    if branch_id == onto_id || repo.graph_descendant_of(branch_id, onto_id)? {
+        progress(RebaseStage::Finished, 0, 0, None);
+        return Ok(result);
+    }
+
+    let branch_commit = repo.find_annotated_commit(branch_id)?;
+    let onto_commit = repo.find_annotated_commit(onto_id)?;
+    let mut options = RebaseOptions::new();
+    options.inmemory(true);
+    let mut rebase = repo.rebase(Some(&branch_commit), Some(&onto_commit), None, Some(&mut options))?;
+
+    let signature = repo.signature()?;
+    let total = rebase.len();
+    let mut head = onto_id;
+    while let Some(operation) = rebase.next() {
+        let commit_id = operation?.id();
+        let current = rebase.operation_current().map_or(0, |i| i + 1);
+        let summary = repo.find_commit(commit_id)?.summary().map(String::from);
+        progress(RebaseStage::Applying, current, total, summary);
+
+        let index = rebase.inmemory_index()?;
+        if index.has_conflicts() {
+            result.kind = RebaseKind::Conflicted;
+            result.conflicts = conflict_paths(&index)?;
+            result.conflicting_commit = Some(commit_id.to_string());
+            rebase.abort()?;
+            progress(
+                RebaseStage::Conflicted,
+                current,
+                total,
+                Some(format!("Conflicts in {}", result.conflicts.join(", "))),
+            );
+            return Ok(result);
+        }
+
+        match rebase.commit(None, &signature, None) {
+            Ok(id) => head = id,
+            // The change is already in the base
+            Err(e) if e.code() == ErrorCode::Applied => {}
+            Err(e) => {
+                let _ = rebase.abort();
+                return Err(e.into());
+            }
+        }
+    }
+    rebase.finish(Some(&signature))?;
+
+    move_head(repo, head, &format!("rebase onto {}", onto))?;
+    result.kind = RebaseKind::Rebased;
+    result.commit_id = Some(head.to_string());
+    progress(RebaseStage::Finished, total, total, None);
+    Ok(result)
+}
+
+/// Fetch a branch's upstream, returning the remote-tracking branch (e.g. "origin/main"),
+/// or None if the branch doesn't track a remote
+pub(super) fn fetch_upstream(
+    repo: &Repository,
+    repo_path: &Path,
+    branch: &str,
+    on_progress: &dyn Fn(RemoteProgress),
+) -> Result<Option<String>> {
+    let local = repo
+        .find_branch(branch, BranchType::Local)
+        .map_err(|_| Error::GitError(format!("Base branch '{}' does not exist", branch)))?;
+    let Ok(upstream) = local.upstream() else {
+        return Ok(None);
+    };
+    let Some(tracking) = upstream.name()?.map(String::from) else {
+        return Ok(None);
+    };
+
+    let refname = format!("refs/heads/{}", branch);
+    let remote = repo.branch_upstream_remote(&refname)?;
+    let merge = repo.branch_upstream_merge(&refname)?;
+    let (Some(remote), Some(merge)) = (remote.as_str(), merge.as_str()) else {
+        return Ok(Some(tracking));
+    };
+
+    // Local branches can be upstreams too ("." remote); there is nothing to fetch then
+    if remote != "." {
+        run_fetch(repo_path, &[remote, merge], on_progress)?;
+    }
+
+    Ok(Some(tracking))
+}
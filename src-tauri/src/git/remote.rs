@@ -0,0 +1,432 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use std::process::Stdio;
+use git2::{Oid, Repository};
+
+use crate::error::{Error, Result};
+use super::merge::merge_into_head;
+use super::rebase::{fetch_upstream, rebase_head_onto};
+use super::worktree::{git_cli, head_branch};
+use super::{CommitIdentity, MergeKind, RebaseKind, RebaseProgress, RebaseStage};
+
+/// Stage reached while fetching or pulling
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteStage {
+    Fetching,
+    /// Merging or fast-forwarding the upstream into the branch
+    Merging,
+    /// Replaying the branch's commits onto the upstream
+    Rebasing,
+    Finished,
+}
+
+/// Progress event for a fetch or pull
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteProgress {
+    pub stage: RemoteStage,
+    /// What git is doing, e.g. "Receiving objects"
+    pub phase: Option<String>,
+    pub percent: Option<u32>,
+    pub message: Option<String>,
+}
+
+/// Why talking to a remote failed
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RemoteErrorKind {
+    /// Credentials were missing or rejected
+    Authentication,
+    /// The host couldn't be reached
+    Network,
+    /// The remote (or the repository behind it) doesn't exist
+    NotFound,
+    /// The branch doesn't track a remote branch
+    NoUpstream,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteError {
+    pub kind: RemoteErrorKind,
+    pub remote: Option<String>,
+    /// git's own explanation
+    pub message: String,
+}
+
+impl std::fmt::Display for RemoteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self.kind {
+            RemoteErrorKind::Authentication => "authentication failed",
+            RemoteErrorKind::Network => "could not reach the remote",
+            RemoteErrorKind::NotFound => "remote not found",
+            RemoteErrorKind::NoUpstream => "no upstream branch",
+            RemoteErrorKind::Other => "remote operation failed",
+        };
+        match &self.remote {
+            Some(remote) => write!(f, "{} ({}): {}", reason, remote, self.message),
+            None => write!(f, "{}: {}", reason, self.message),
+        }
+    }
+}
+
+/// Remote-tracking branches a fetch changed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FetchResult {
+    /// Remotes that were fetched
+    pub remotes: Vec<String>,
+    /// Remote-tracking branches that were created or moved, e.g. "origin/main"
+    pub updated: Vec<String>,
+    /// Remote-tracking branches deleted because they are gone from the remote
+    pub pruned: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PullKind {
+    UpToDate,
+    FastForward,
+    /// A merge commit was created
+    Merged,
+    Rebased,
+    /// Nothing was changed because the branch conflicts with its upstream
+    Conflicted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PullResult {
+    pub kind: PullKind,
+    pub branch: String,
+    /// Remote-tracking branch that was pulled, e.g. "origin/main"
+    pub upstream: String,
+    /// The branch's new head, when it moved
+    pub commit_id: Option<String>,
+    /// Paths that conflict (only for `Conflicted`)
+    pub conflicts: Vec<String>,
+}
+
+/// Fetch `remote` (every remote when None) into the repository `path` belongs to,
+/// pruning remote-tracking branches that were deleted on the remote
+pub fn fetch_internal(
+    path: &Path,
+    remote: Option<&str>,
+    on_progress: &dyn Fn(RemoteProgress),
+) -> Result<FetchResult> {
+    let repo = Repository::open(path)?;
+    let remotes: Vec<String> = match remote {
+        Some(remote) => {
+            repo.find_remote(remote).map_err(|_| {
+                Error::RemoteError(RemoteError {
+                    kind: RemoteErrorKind::NotFound,
+                    remote: Some(remote.to_string()),
+                    message: "No such remote".into(),
+                })
+            })?;
+            vec![remote.to_string()]
+        }
+        None => repo.remotes()?.iter().flatten().map(String::from).collect(),
+    };
+    if remotes.is_empty() {
+        return Err(Error::RemoteError(RemoteError {
+            kind: RemoteErrorKind::NotFound,
+            remote: None,
+            message: "The repository has no remotes".into(),
+        }));
+    }
+
+    let before = remote_branches(&repo)?;
+    match remote {
+        Some(remote) => run_fetch(path, &["--prune", remote], on_progress)?,
+        None => run_fetch(path, &["--prune", "--all"], on_progress)?,
+    }
+    let after = remote_branches(&repo)?;
+
+    let mut result = FetchResult {
+        remotes,
+        ..Default::default()
+    };
+    for (name, id) in &after {
+        if before.get(name) != Some(id) {
+            result.updated.push(name.clone());
+        }
+    }
+    for name in before.keys() {
+        if !after.contains_key(name) {
+            result.pruned.push(name.clone());
+        }
+    }
+    result.updated.sort();
+    result.pruned.sort();
+
+    on_progress(RemoteProgress {
+        stage: RemoteStage::Finished,
+        phase: None,
+        percent: None,
+        message: None,
+    });
+    Ok(result)
+}
+
+/// Fetch the upstream of the branch checked out at `path` and bring the branch up to date
+/// with it, by rebasing or by merging (fast-forwarding when possible).
+/// Merges and rebases run in memory, so on conflicts the branch and files are left untouched.
+pub fn pull_internal(
+    path: &Path,
+    rebase: bool,
+    identity: &CommitIdentity,
+    on_progress: &dyn Fn(RemoteProgress),
+) -> Result<PullResult> {
+    let repo = Repository::open(path)?;
+    let branch = head_branch(&repo).ok_or_else(|| {
+        Error::WorktreeError(format!("{} is not on a branch", path.display()))
+    })?;
+    if repo.state() != git2::RepositoryState::Clean {
+        return Err(Error::GitError(format!(
+            "{} has an operation in progress (merge, rebase, ...)",
+            path.display()
+        )));
+    }
+
+    let upstream = fetch_upstream(&repo, path, &branch, on_progress)?.ok_or_else(|| {
+        Error::RemoteError(RemoteError {
+            kind: RemoteErrorKind::NoUpstream,
+            remote: None,
+            message: format!("'{}' does not track a remote branch", branch),
+        })
+    })?;
+
+    let result = if rebase {
+        let on_rebase_progress = |progress: RebaseProgress| {
+            if progress.stage == RebaseStage::Applying {
+                on_progress(RemoteProgress {
+                    stage: RemoteStage::Rebasing,
+                    phase: None,
+                    percent: (progress.total > 0)
+                        .then(|| (progress.current * 100 / progress.total) as u32),
+                    message: progress.message,
+                });
+            }
+        };
+        let rebased = rebase_head_onto(&repo, &branch, &upstream, &on_rebase_progress)?;
+        PullResult {
+            kind: match rebased.kind {
+                RebaseKind::UpToDate => PullKind::UpToDate,
+                RebaseKind::Rebased => PullKind::Rebased,
+                RebaseKind::Conflicted => PullKind::Conflicted,
+            },
+            branch,
+            upstream,
+            commit_id: rebased.commit_id,
+            conflicts: rebased.conflicts,
+        }
+    } else {
+        on_progress(RemoteProgress {
+            stage: RemoteStage::Merging,
+            phase: None,
+            percent: None,
+            message: None,
+        });
+        let theirs = repo.revparse_single(&upstream)?.peel_to_commit()?;
+        let message = format!("Merge remote-tracking branch '{}' into {}", upstream, branch);
+        let merged = merge_into_head(&repo, &theirs, &upstream, &branch, &message, false, identity)?;
+        PullResult {
+            kind: match merged.kind {
+                MergeKind::UpToDate => PullKind::UpToDate,
+                MergeKind::FastForward => PullKind::FastForward,
+                MergeKind::Merged => PullKind::Merged,
+                MergeKind::Conflicted => PullKind::Conflicted,
+            },
+            branch,
+            upstream,
+            commit_id: merged.commit_id,
+            conflicts: merged.conflicts,
+        }
+    };
+
+    on_progress(RemoteProgress {
+        stage: RemoteStage::Finished,
+        phase: None,
+        percent: None,
+        message: None,
+    });
+    Ok(result)
+}
+
+/// Run `git fetch` with `args`, reporting git's progress meter as it goes.
+/// Failures are classified from git's output into a `RemoteError`.
+pub(super) fn run_fetch(
+    dir: &Path,
+    args: &[&str],
+    on_progress: &dyn Fn(RemoteProgress),
+) -> Result<()> {
+    eprintln!("[Ada:Worktree] git fetch {}", args.join(" "));
+
+    let mut child = git_cli(dir)
+        .args(["fetch", "--progress"])
+        .args(args)
+        // Fail instead of waiting for a password on a terminal nobody sees
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let mut messages = Vec::new();
+    let mut last = None;
+    let mut handle_line = |line: String| {
+        match parse_progress(&line) {
+            Some((phase, percent)) => {
+                if last.as_ref() != Some(&(phase.clone(), percent)) {
+                    last = Some((phase.clone(), percent));
+                    on_progress(RemoteProgress {
+                        stage: RemoteStage::Fetching,
+                        phase: Some(phase),
+                        percent: Some(percent),
+                        message: None,
+                    });
+                }
+            }
+            None => messages.push(line),
+        }
+    };
+
+    if let Some(stderr) = child.stderr.take() {
+        read_output_lines(stderr, &mut handle_line)?;
+    }
+
+    if child.wait()?.success() {
+        return Ok(());
+    }
+
+    let output = messages.join("\n");
+    Err(Error::RemoteError(RemoteError {
+        kind: classify_failure(&output),
+        remote: args.iter().find(|arg| !arg.starts_with('-')).map(|s| s.to_string()),
+        message: output,
+    }))
+}
+
+/// Feed each non-empty line of git's stderr to `on_line` as it arrives.
+/// The progress meter redraws itself with '\r', everything else ends in '\n'.
+pub(super) fn read_output_lines(
+    output: impl Read,
+    on_line: &mut dyn FnMut(String),
+) -> std::io::Result<()> {
+    let mut line = Vec::new();
+    let mut flush = |line: &mut Vec<u8>| {
+        let text = String::from_utf8_lossy(line).trim().to_string();
+        if !text.is_empty() {
+            on_line(text);
+        }
+        line.clear();
+    };
+
+    for byte in BufReader::new(output).bytes() {
+        match byte? {
+            b'\r' | b'\n' => flush(&mut line),
+            byte => line.push(byte),
+        }
+    }
+    flush(&mut line);
+    Ok(())
+}
+
+/// Parse a progress meter line like "Receiving objects:  45% (9/20), 1.2 MiB | 3 MiB/s"
+pub(super) fn parse_progress(line: &str) -> Option<(String, u32)> {
+    let line = line.strip_prefix("remote: ").unwrap_or(line);
+    let (phase, rest) = line.split_once(':')?;
+    let (percent, _) = rest.trim_start().split_once('%')?;
+    Some((phase.trim().to_string(), percent.parse().ok()?))
+}
+
+fn classify_failure(output: &str) -> RemoteErrorKind {
+    let output = output.to_lowercase();
+    let any = |needles: &[&str]| needles.iter().any(|needle| output.contains(needle));
+
+    if any(&[
+        "authentication failed",
+        "permission denied",
+        "could not read username",
+        "could not read password",
+        "terminal prompts disabled",
+        "host key verification failed",
+    ]) {
+        RemoteErrorKind::Authentication
+    } else if any(&[
+        "could not resolve host",
+        "connection refused",
+        "connection timed out",
+        "network is unreachable",
+        "operation timed out",
+        "could not connect",
+    ]) {
+        RemoteErrorKind::Network
+    } else if any(&[
+        "does not appear to be a git repository",
+        "repository not found",
+        "no such remote",
+        "couldn't find remote ref",
+    ]) {
+        RemoteErrorKind::NotFound
+    } else {
+        RemoteErrorKind::Other
+    }
+}
+
+/// Remote-tracking branches and where they point
+fn remote_branches(repo: &Repository) -> Result<HashMap<String, Oid>> {
+    let mut branches = HashMap::new();
+    for reference in repo.references_glob("refs/remotes/*")? {
+        let reference = reference?;
+        // Skip "origin/HEAD", which just names the remote's default branch
+        if reference.symbolic_target().is_some() {
+            continue;
+        }
+        if let (Some(name), Some(id)) = (reference.shorthand(), reference.target()) {
+            branches.insert(name.to_string(), id);
+        }
+    }
+    Ok(branches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_progress_lines() {
+        assert_eq!(
+            parse_progress("Receiving objects:  45% (450/1000), 1.2 MiB | 2 MiB/s"),
+            Some(("Receiving objects".to_string(), 45))
+        );
+        assert_eq!(
+            parse_progress("remote: Counting objects: 100% (12/12), done."),
+            Some(("Counting objects".to_string(), 100))
+        );
+        assert_eq!(parse_progress("From github.com:org/repo"), None);
+        assert_eq!(parse_progress("error: something: went wrong"), None);
+    }
+
+    #[test]
+    fn classifies_failures() {
+        assert_eq!(
+            classify_failure("fatal: Authentication failed for 'https://github.com/org/repo.git/'"),
+            RemoteErrorKind::Authentication
+        );
+        assert_eq!(
+            classify_failure("fatal: unable to access 'https://github.com/': Could not resolve host: github.com"),
+            RemoteErrorKind::Network
+        );
+        assert_eq!(
+            classify_failure("ssh: connect to host github.com port 22: Connection refused"),
+            RemoteErrorKind::Network
+        );
+        assert_eq!(
+            classify_failure("fatal: 'upstream' does not appear to be a git repository"),
+            RemoteErrorKind::NotFound
+        );
+        assert_eq!(classify_failure("fatal: refusing to merge unrelated histories"), RemoteErrorKind::Other);
+    }
+}
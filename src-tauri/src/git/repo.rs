@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use git2::{Commit, IndexAddOption, Oid, Repository, Signature, StatusOptions};
+
+use crate::error::{Error, Result};
+
+/// A name and email to record on a commit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitIdentity {
+    pub name: String,
+    pub email: String,
+}
+
+/// Who the commits Ada makes on a project's behalf are attributed to
+/// (see `ProjectSettings::commit_identity`)
+#[derive(Debug, Clone, Default)]
+pub struct CommitIdentity {
+    /// Author and committer (None = the configured `user.name`/`user.email`)
+    pub identity: Option<GitIdentity>,
+    /// Trailer appended to commit messages, e.g. "Co-authored-by: Agent <agent@example.com>"
+    pub trailer: Option<String>,
+}
+
+impl CommitIdentity {
+    pub fn signature(&self, repo: &Repository) -> Result<Signature<'static>> {
+        let signature = match &self.identity {
+            Some(identity) => Signature::now(&identity.name, &identity.email)?,
+            None => repo.signature()?,
+        };
+        Ok(signature)
+    }
+
+    /// `message` with the trailer added, to its trailer block when it already ends in one
+    pub fn message(&self, message: &str) -> String {
+        let message = message.trim_end();
+        let Some(trailer) = self.trailer.as_deref().map(str::trim).filter(|t| !t.is_empty()) else {
+            return message.to_string();
+        };
+        if message.lines().any(|line| line.trim() == trailer) {
+            return message.to_string();
+        }
+
+        let ends_in_trailers = message.rsplit_once("\n\n").is_some_and(|(_, last)| {
+            last.lines().all(|line| {
+                line.split_once(": ")
+                    .is_some_and(|(key, _)| !key.is_empty() && !key.contains(char::is_whitespace))
+            })
+        });
+        let separator = if ends_in_trailers { "\n" } else { "\n\n" };
+        format!("{}{}{}", message, separator, trailer)
+    }
+}
+
+/// A commit that was just created
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitInfo {
+    pub id: String,
+    /// Branch the commit was made on (empty when HEAD is detached)
+    pub branch: String,
+    pub summary: String,
+}
+
+/// Check if a git repository has at least one commit
+pub fn has_commits(repo_path: &Path) -> bool {
+    Repository::open(repo_path)
+        .map(|repo| repo.revparse_single("HEAD").is_ok())
+        .unwrap_or(false)
+}
+
+/// Whether git ignores `path` (relative to the repository root)
+pub fn is_path_ignored(repo_path: &Path, path: &Path) -> Result<bool> {
+    let repo = Repository::open(repo_path)?;
+    Ok(repo.is_path_ignored(path)?)
+}
+
+/// Create an empty repository (honoring `init.defaultBranch`)
+pub fn init_repo(repo_path: &Path) -> Result<()> {
+    Repository::init(repo_path)?;
+    Ok(())
+}
+
+/// Whether a file differs from HEAD, in the index or the working tree
+pub fn is_path_modified(repo_path: &Path, path: &str) -> Result<bool> {
+    let repo = Repository::open(repo_path)?;
+    let mut options = StatusOptions::new();
+    options.pathspec(path).include_untracked(true);
+    let modified = !repo.statuses(Some(&mut options))?.is_empty();
+    Ok(modified)
+}
+
+/// Stage `paths` and commit everything that is staged, like `git add` + `git commit`.
+/// Returns false when there was nothing to commit.
+pub fn commit_paths(
+    repo_path: &Path,
+    paths: &[&str],
+    message: &str,
+    identity: &CommitIdentity,
+) -> Result<bool> {
+    let repo = Repository::open(repo_path)?;
+
+    let mut index = repo.index()?;
+    for path in paths {
+        index.add_path(Path::new(path))?;
+    }
+    index.write()?;
+
+    let signature = identity.signature(&repo)?;
+    Ok(commit_index(&repo, &identity.message(message), &signature, &signature)?.is_some())
+}
+
+/// Stage `paths` (files, directories or globs, relative to the worktree; deletions included)
+/// in a worktree and commit everything staged. With no paths, only what is already staged
+/// is committed. `author` and `committer` default to the project's `identity`, and its
+/// trailer is added to the message.
+pub fn commit_worktree_internal(
+    worktree_path: &Path,
+    paths: &[String],
+    message: &str,
+    author: Option<&GitIdentity>,
+    committer: Option<&GitIdentity>,
+    identity: &CommitIdentity,
+) -> Result<CommitInfo> {
+    if message.trim().is_empty() {
+        return Err(Error::InvalidRequest("Commit message is empty".into()));
+    }
+
+    let repo = Repository::open(worktree_path)?;
+
+    if !paths.is_empty() {
+        let mut index = repo.index()?;
+        index.add_all(paths, IndexAddOption::DEFAULT, None)?;
+        // add_all skips tracked files that were deleted
+        index.update_all(paths, None)?;
+        index.write()?;
+    }
+
+    let signature_for = |given: Option<&GitIdentity>| match given {
+        Some(given) => Ok(Signature::now(&given.name, &given.email)?),
+        None => identity.signature(&repo),
+    };
+    let author = signature_for(author)?;
+    let committer = signature_for(committer)?;
+
+    let id = commit_index(&repo, &identity.message(message), &author, &committer)?
+        .ok_or_else(|| Error::GitError("Nothing to commit".into()))?;
+    let commit = repo.find_commit(id)?;
+
+    Ok(CommitInfo {
+        id: id.to_string(),
+        branch: repo
+            .head()
+            .ok()
+            .filter(|head| head.is_branch())
+            .and_then(|head| head.shorthand().map(String::from))
+            .unwrap_or_default(),
+        summary: commit.summary().unwrap_or_default().to_string(),
+    })
+}
+
+/// Commit the index on top of HEAD, or return None if it matches HEAD (nothing to commit)
+fn commit_index(
+    repo: &Repository,
+    message: &str,
+    author: &Signature,
+    committer: &Signature,
+) -> Result<Option<Oid>> {
+    let mut index = repo.index()?;
+    let tree_id = index.write_tree()?;
+
+    let parent = match repo.head() {
+        Ok(head) => Some(head.peel_to_commit()?),
+        Err(_) => None,
+    };
+    let unchanged = match &parent {
+        Some(parent) => parent.tree_id() == tree_id,
+        None => index.is_empty(),
+    };
+    if unchanged {
+        return Ok(None);
+    }
+
+    let tree = repo.find_tree(tree_id)?;
+    let parents: Vec<&Commit> = parent.iter().collect();
+    let id = repo.commit(Some("HEAD"), author, committer, message, &tree, &parents)?;
+    Ok(Some(id))
+}
@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path};
+
+use crate::error::{Error, Result};
+
+/// How seed files get from the main checkout into a new worktree
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SeedMode {
+    /// Independent copies the agent can change freely
+    #[default]
+    Copy,
+    /// Links back to the main checkout, so every worktree shares one file
+    Symlink,
+}
+
+/// Copy or link the files matching `patterns` (paths or globs relative to the project root,
+/// e.g. `.env` or `config/*.local.json`) from the main checkout into a new worktree.
+/// Matching directories are taken whole. Files the worktree already has are left alone.
+/// Returns the relative paths that were seeded.
+pub fn seed_worktree(
+    repo_path: &Path,
+    worktree_path: &Path,
+    patterns: &[String],
+    mode: SeedMode,
+) -> Result<Vec<String>> {
+    let mut seeded = Vec::new();
+
+    for pattern in patterns {
+        let escapes_project = Path::new(pattern)
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+        if escapes_project {
+            return Err(Error::InvalidRequest(format!(
+                "Seed pattern '{}' must be relative to the project root",
+                pattern
+            )));
+        }
+
+        let full_pattern = repo_path.join(pattern).to_string_lossy().to_string();
+        let matches = glob::glob(&full_pattern)
+            .map_err(|e| Error::InvalidRequest(format!("Invalid seed pattern '{}': {}", pattern, e)))?;
+
+        for source in matches.flatten() {
+            let Ok(relative) = source.strip_prefix(repo_path) else {
+                continue;
+            };
+            // Never seed git's own files, or worktrees into each other
+            let in_git_dir = relative.components().any(|c| c.as_os_str() == ".git");
+            let in_worktree_dir = worktree_path
+                .parent()
+                .is_some_and(|worktrees| worktrees != repo_path && source.starts_with(worktrees));
+            if in_git_dir || in_worktree_dir {
+                continue;
+            }
+
+            let target = worktree_path.join(relative);
+            if target.symlink_metadata().is_ok() {
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+
+            match mode {
+                SeedMode::Copy => copy_recursive(&source, &target)?,
+                SeedMode::Symlink => symlink(&source, &target)?,
+            }
+            seeded.push(relative.to_string_lossy().to_string());
+        }
+    }
+
+    if !seeded.is_empty() {
+        eprintln!("[Ada:Worktree] Seeded {:?} with {:?}", worktree_path, seeded);
+    }
+    Ok(seeded)
+}
+
+/// Copy a file or directory tree. Symlinks inside it are recreated as symlinks rather than
+/// followed, so links back up the tree don't loop and linked stores (e.g. pnpm's
+/// `node_modules`) aren't copied out in full.
+fn copy_recursive(source: &Path, target: &Path) -> std::io::Result<()> {
+    let file_type = std::fs::symlink_metadata(source)?.file_type();
+    if file_type.is_symlink() {
+        return copy_symlink(source, target);
+    }
+    if !file_type.is_dir() {
+        return std::fs::copy(source, target).map(|_| ());
+    }
+
+    std::fs::create_dir_all(target)?;
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        copy_recursive(&entry.path(), &target.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+/// Create a symlink at `target` pointing where the symlink `source` points
+#[cfg(unix)]
+fn copy_symlink(source: &Path, target: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(std::fs::read_link(source)?, target)
+}
+
+#[cfg(windows)]
+fn copy_symlink(source: &Path, target: &Path) -> std::io::Result<()> {
+    let link = std::fs::read_link(source)?;
+    // Whether the link is to a directory decides which kind of symlink it has to be
+    if source.is_dir() {
+        std::os::windows::fs::symlink_dir(link, target)
+    } else {
+        std::os::windows::fs::symlink_file(link, target)
+    }
+}
+
+#[cfg(unix)]
+fn symlink(source: &Path, target: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(source, target)
+}
+
+#[cfg(windows)]
+fn symlink(source: &Path, target: &Path) -> std::io::Result<()> {
+    if source.is_dir() {
+        std::os::windows::fs::symlink_dir(source, target)
+    } else {
+        std::os::windows::fs::symlink_file(source, target)
+    }
+}
@@ -0,0 +1,119 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use git2::{ErrorCode, Oid, Repository, StashFlags, StatusOptions};
+
+use crate::error::{Error, Result};
+use super::worktree::head_branch;
+
+/// Branch name git records in stash messages when HEAD is detached
+const DETACHED_STASH_BRANCH: &str = "(no branch)";
+
+/// A stash made from one of a repository's worktrees
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StashEntry {
+    /// Position in the repository's stash list (`stash@{index}`), which all worktrees share
+    pub index: usize,
+    /// Branch the stash was made on
+    pub branch: String,
+    /// Full stash message, e.g. "On main: half-finished refactor"
+    pub message: String,
+    pub commit_id: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// Stash the uncommitted changes in a worktree (and untracked files, with `include_untracked`).
+/// Returns None when there was nothing to stash.
+pub fn stash_worktree_internal(
+    worktree_path: &Path,
+    message: Option<&str>,
+    include_untracked: bool,
+) -> Result<Option<StashEntry>> {
+    let mut repo = Repository::open(worktree_path)?;
+    let signature = repo.signature()?;
+    let flags = include_untracked.then_some(StashFlags::INCLUDE_UNTRACKED);
+
+    match repo.stash_save2(&signature, message, flags) {
+        Ok(_) => Ok(stash_entries(&mut repo)?.into_iter().next()),
+        Err(e) if e.code() == ErrorCode::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Stashes made on the branch checked out in a worktree, newest first.
+/// The stash list is shared by every worktree, so stashes from other branches are left out.
+pub fn list_worktree_stashes_internal(worktree_path: &Path) -> Result<Vec<StashEntry>> {
+    let mut repo = Repository::open(worktree_path)?;
+    let branch = stash_branch(&repo);
+    Ok(stash_entries(&mut repo)?
+        .into_iter()
+        .filter(|entry| entry.branch == branch)
+        .collect())
+}
+
+/// Apply the stash at `index` to a worktree and drop it. The stash must have been made on the
+/// worktree's branch, and the worktree must be clean: libgit2 restores untracked files before
+/// it notices conflicts, so popping onto local changes could leave a half-applied stash.
+pub fn pop_worktree_stash_internal(worktree_path: &Path, index: usize) -> Result<StashEntry> {
+    let mut repo = Repository::open(worktree_path)?;
+    let branch = stash_branch(&repo);
+    let entry = stash_entries(&mut repo)?
+        .into_iter()
+        .find(|entry| entry.index == index)
+        .ok_or_else(|| Error::GitError(format!("stash@{{{}}} does not exist", index)))?;
+    if entry.branch != branch {
+        return Err(Error::InvalidRequest(format!(
+            "stash@{{{}}} was made on '{}', not on '{}'",
+            index, entry.branch, branch
+        )));
+    }
+
+    let mut status_options = StatusOptions::new();
+    status_options.include_untracked(true);
+    if !repo.statuses(Some(&mut status_options))?.is_empty() {
+        return Err(Error::WorktreeError(
+            "The worktree has uncommitted changes; commit or stash them before popping a stash"
+                .into(),
+        ));
+    }
+
+    repo.stash_pop(index, None)?;
+    Ok(entry)
+}
+
+/// Branch name a stash made in `repo` right now would record
+fn stash_branch(repo: &Repository) -> String {
+    head_branch(repo).unwrap_or_else(|| DETACHED_STASH_BRANCH.to_string())
+}
+
+fn stash_entries(repo: &mut Repository) -> Result<Vec<StashEntry>> {
+    let mut stashes: Vec<(usize, String, Oid)> = Vec::new();
+    repo.stash_foreach(|index, message, id| {
+        stashes.push((index, message.to_string(), *id));
+        true
+    })?;
+
+    let mut entries = Vec::with_capacity(stashes.len());
+    for (index, message, id) in stashes {
+        let created_at = repo
+            .find_commit(id)
+            .ok()
+            .and_then(|commit| DateTime::from_timestamp(commit.time().seconds(), 0));
+        entries.push(StashEntry {
+            index,
+            branch: message_branch(&message).unwrap_or_default(),
+            message,
+            commit_id: id.to_string(),
+            created_at,
+        });
+    }
+    Ok(entries)
+}
+
+/// Branch from a stash message: "On <branch>: <message>" or "WIP on <branch>: <sha> <subject>"
+fn message_branch(message: &str) -> Option<String> {
+    let rest = message
+        .strip_prefix("On ")
+        .or_else(|| message.strip_prefix("WIP on "))?;
+    rest.split_once(": ").map(|(branch, _)| branch.to_string())
+}
@@ -0,0 +1,229 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use git2::{BranchType, Oid, Repository, Status, StatusOptions};
+
+use crate::error::{Error, Result};
+use super::{get_current_branch_internal, list_worktrees_internal, FileChangeKind};
+use super::worktree::head_branch;
+
+/// Uncommitted and unmerged work in one worktree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorktreeStatus {
+    pub path: String,
+    /// Empty when HEAD is detached
+    pub branch: String,
+    /// Tracked files with staged or unstaged changes
+    pub modified: usize,
+    pub untracked: usize,
+    /// Branch `ahead` and `behind` are counted against
+    pub base_branch: String,
+    /// Commits on this worktree's HEAD that the base branch doesn't have
+    pub ahead: usize,
+    /// Commits on the base branch that this worktree's HEAD doesn't have
+    pub behind: usize,
+}
+
+/// State of one changed file in a checkout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileStatusEntry {
+    pub path: String,
+    /// Where a renamed file was before
+    pub old_path: Option<String>,
+    /// Change staged in the index, relative to HEAD
+    pub staged: Option<FileChangeKind>,
+    /// Change in the working tree not staged yet (`Untracked` for new files)
+    pub unstaged: Option<FileChangeKind>,
+    /// Unresolved merge conflict
+    pub conflicted: bool,
+}
+
+/// Work in a worktree that removing it would throw away
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorktreeDirtySummary {
+    pub path: String,
+    pub modified: usize,
+    pub untracked: usize,
+    /// Commits not on the branch's upstream (or, without one, not merged into the main
+    /// checkout's branch)
+    pub unpushed: usize,
+}
+
+impl WorktreeDirtySummary {
+    pub fn is_dirty(&self) -> bool {
+        self.modified > 0 || self.untracked > 0 || self.unpushed > 0
+    }
+}
+
+impl std::fmt::Display for WorktreeDirtySummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} has {} modified and {} untracked files and {} unpushed commits",
+            self.path, self.modified, self.untracked, self.unpushed
+        )
+    }
+}
+
+/// Count the uncommitted files and unpushed commits in one of the repository's worktrees
+pub fn get_worktree_dirty_summary(
+    repo_path: &Path,
+    worktree_path: &Path,
+) -> Result<WorktreeDirtySummary> {
+    let (modified, untracked) = count_changes(worktree_path)?;
+
+    let repo = Repository::open(worktree_path)?;
+    let unpushed = match repo.refname_to_id("HEAD") {
+        Ok(head) => {
+            // Compare against the upstream if the branch has one, otherwise the main checkout
+            let upstream = head_branch(&repo)
+                .and_then(|branch| repo.find_branch(&branch, BranchType::Local).ok())
+                .and_then(|branch| branch.upstream().ok())
+                .and_then(|upstream| upstream.get().target());
+            let base = match upstream {
+                Some(upstream) => Some(upstream),
+                None => Repository::open(repo_path)?.refname_to_id("HEAD").ok(),
+            };
+            match base {
+                Some(base) => repo.graph_ahead_behind(head, base)?.0,
+                None => 0,
+            }
+        }
+        // No commits yet
+        Err(_) => 0,
+    };
+
+    Ok(WorktreeDirtySummary {
+        path: worktree_path.to_string_lossy().to_string(),
+        modified,
+        untracked,
+        unpushed,
+    })
+}
+
+/// Status of every worktree of the repository at `repo_path`, relative to `base_branch`
+/// (the main checkout's branch when None). Worktrees whose directory is missing are skipped.
+pub fn get_worktree_status_internal(
+    repo_path: &Path,
+    base_branch: Option<&str>,
+) -> Result<Vec<WorktreeStatus>> {
+    let repo = Repository::open(repo_path)?;
+    let base_branch = match base_branch {
+        Some(branch) => branch.to_string(),
+        None => get_current_branch_internal(repo_path)?,
+    };
+    let base = repo
+        .revparse_single(&base_branch)
+        .map_err(|_| Error::GitError(format!("Base branch '{}' does not exist", base_branch)))?
+        .peel_to_commit()?
+        .id();
+
+    let mut statuses = Vec::new();
+    for worktree in list_worktrees_internal(repo_path)? {
+        if worktree.is_bare || !Path::new(&worktree.path).is_dir() {
+            continue;
+        }
+
+        let (modified, untracked) = count_changes(Path::new(&worktree.path))?;
+        let (ahead, behind) = match Oid::from_str(&worktree.head) {
+            Ok(head) => repo.graph_ahead_behind(head, base)?,
+            // No commits yet
+            Err(_) => (0, 0),
+        };
+
+        statuses.push(WorktreeStatus {
+            path: worktree.path,
+            branch: worktree.branch,
+            modified,
+            untracked,
+            base_branch: base_branch.clone(),
+            ahead,
+            behind,
+        });
+    }
+
+    Ok(statuses)
+}
+
+/// Changed files in the checkout at `path`, with staged and unstaged changes told apart
+/// and renames detected. Ignored files are left out.
+pub fn get_file_status_internal(path: &Path) -> Result<Vec<FileStatusEntry>> {
+    let repo = Repository::open(path)?;
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .renames_head_to_index(true)
+        .renames_index_to_workdir(true);
+
+    let path_of = |file: git2::DiffFile| file.path().map(|p| p.to_string_lossy().to_string());
+    let mut entries = Vec::new();
+    for entry in repo.statuses(Some(&mut options))?.iter() {
+        let status = entry.status();
+        let staged = change_kind(
+            status,
+            [
+                (Status::INDEX_NEW, FileChangeKind::Added),
+                (Status::INDEX_MODIFIED, FileChangeKind::Modified),
+                (Status::INDEX_DELETED, FileChangeKind::Deleted),
+                (Status::INDEX_RENAMED, FileChangeKind::Renamed),
+                (Status::INDEX_TYPECHANGE, FileChangeKind::TypeChanged),
+            ],
+        );
+        let unstaged = change_kind(
+            status,
+            [
+                (Status::WT_NEW, FileChangeKind::Untracked),
+                (Status::WT_MODIFIED, FileChangeKind::Modified),
+                (Status::WT_DELETED, FileChangeKind::Deleted),
+                (Status::WT_RENAMED, FileChangeKind::Renamed),
+                (Status::WT_TYPECHANGE, FileChangeKind::TypeChanged),
+            ],
+        );
+
+        // A rename can be staged, unstaged, or both (staged a -> b, then b -> c in the worktree)
+        let staged_delta = entry.head_to_index();
+        let unstaged_delta = entry.index_to_workdir();
+        let current_path = unstaged_delta
+            .as_ref()
+            .and_then(|delta| path_of(delta.new_file()))
+            .or_else(|| staged_delta.as_ref().and_then(|delta| path_of(delta.new_file())))
+            .or_else(|| entry.path().map(String::from));
+        let Some(current_path) = current_path else {
+            continue;
+        };
+        let old_path = if status.intersects(Status::INDEX_RENAMED) {
+            staged_delta.and_then(|delta| path_of(delta.old_file()))
+        } else if status.intersects(Status::WT_RENAMED) {
+            unstaged_delta.and_then(|delta| path_of(delta.old_file()))
+        } else {
+            None
+        };
+
+        entries.push(FileStatusEntry {
+            path: current_path,
+            old_path,
+            staged,
+            unstaged,
+            conflicted: status.intersects(Status::CONFLICTED),
+        });
+    }
+
+    Ok(entries)
+}
+
+fn change_kind(status: Status, kinds: [(Status, FileChangeKind); 5]) -> Option<FileChangeKind> {
+    kinds
+        .into_iter()
+        .find(|(flag, _)| status.intersects(*flag))
+        .map(|(_, kind)| kind)
+}
+
+/// Count modified (staged or not) and untracked files in a worktree
+pub(super) fn count_changes(worktree_path: &Path) -> Result<(usize, usize)> {
+    let entries = get_file_status_internal(worktree_path)?;
+    let untracked = entries
+        .iter()
+        .filter(|entry| entry.unstaged == Some(FileChangeKind::Untracked))
+        .count();
+    Ok((entries.len() - untracked, untracked))
+}
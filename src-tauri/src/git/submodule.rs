@@ -0,0 +1,51 @@
+use std::path::Path;
+use std::process::Stdio;
+
+use crate::error::{Error, Result};
+use super::remote::{parse_progress, read_output_lines};
+use super::worktree::git_cli;
+
+/// Clone and check out the submodules of a worktree, recursively
+/// (`git submodule update --init --recursive`). `on_progress` gets a short description of
+/// each step as git reports it. Does nothing in repositories without submodules.
+pub fn init_submodules(worktree_path: &Path, on_progress: &dyn Fn(String)) -> Result<()> {
+    if !worktree_path.join(".gitmodules").exists() {
+        return Ok(());
+    }
+    eprintln!("[Ada:Worktree] Initializing submodules in {:?}", worktree_path);
+
+    let mut child = git_cli(worktree_path)
+        .args(["submodule", "update", "--init", "--recursive", "--progress"])
+        // Fail instead of waiting for a password on a terminal nobody sees
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let mut messages = Vec::new();
+    let mut last_progress = None;
+    if let Some(stderr) = child.stderr.take() {
+        read_output_lines(stderr, &mut |line| match parse_progress(&line) {
+            Some((phase, percent)) => {
+                if last_progress.as_ref() != Some(&(phase.clone(), percent)) {
+                    on_progress(format!("{} {}%", phase, percent));
+                    last_progress = Some((phase, percent));
+                }
+            }
+            None => {
+                on_progress(line.clone());
+                messages.push(line);
+            }
+        })?;
+    }
+
+    if child.wait()?.success() {
+        return Ok(());
+    }
+
+    Err(Error::GitError(format!(
+        "Failed to initialize submodules: {}",
+        messages.join("\n")
+    )))
+}
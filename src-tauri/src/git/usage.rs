@@ -0,0 +1,78 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use parking_lot::Mutex;
+
+/// How long a worktree's measured size is trusted before it is measured again
+const DISK_USAGE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Worktree sizes, measured in the background since walking a large worktree takes a while
+#[derive(Default)]
+pub struct DiskUsageCache {
+    sizes: Mutex<HashMap<PathBuf, (u64, Instant)>>,
+    measuring: Mutex<HashSet<PathBuf>>,
+}
+
+impl DiskUsageCache {
+    /// Last measured size of `path` in bytes, and whether it is due to be measured again
+    pub fn get(&self, path: &Path) -> (Option<u64>, bool) {
+        match self.sizes.lock().get(path) {
+            Some((bytes, measured_at)) => (Some(*bytes), measured_at.elapsed() >= DISK_USAGE_TTL),
+            None => (None, true),
+        }
+    }
+
+    /// Claim `path` for measuring; false if it is already being measured
+    pub fn start_measuring(&self, path: &Path) -> bool {
+        self.measuring.lock().insert(path.to_path_buf())
+    }
+
+    pub fn finish_measuring(&self, path: &Path, bytes: Option<u64>) {
+        if let Some(bytes) = bytes {
+            self.sizes.lock().insert(path.to_path_buf(), (bytes, Instant::now()));
+        }
+        self.measuring.lock().remove(path);
+    }
+
+    pub fn forget(&self, path: &Path) {
+        self.sizes.lock().remove(path);
+    }
+}
+
+/// Space taken on disk by everything under `path`, not following symlinks.
+/// On unix this counts allocated blocks, so sparse files aren't overstated.
+pub fn measure_disk_usage(path: &Path) -> std::io::Result<u64> {
+    let mut total = 0;
+    let mut pending = vec![path.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            // The top level must exist; anything deeper may vanish or be unreadable mid-walk
+            Err(e) if dir == path => return Err(e),
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            }
+            total += allocated_size(&metadata);
+        }
+    }
+
+    Ok(total)
+}
+
+#[cfg(unix)]
+fn allocated_size(metadata: &std::fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.blocks() * 512
+}
+
+#[cfg(not(unix))]
+fn allocated_size(metadata: &std::fs::Metadata) -> u64 {
+    metadata.len()
+}
@@ -1,8 +1,21 @@
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Arc;
+use git2::{
+    BranchType, DiffOptions, ReferenceType, Repository, ResetType, StatusOptions,
+    WorktreePruneOptions,
+};
+use parking_lot::Mutex;
 
 use crate::error::{Error, Result};
+use super::preflight::explain_failure;
+use super::remote::run_fetch;
+use super::{
+    create_worktree_cloned, get_worktree_dirty_summary, init_submodules, seed_worktree,
+    CheckoutBypass, SeedMode,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorktreeInfo {
@@ -10,6 +23,84 @@ pub struct WorktreeInfo {
     pub branch: String,
     pub head: String,
     pub is_bare: bool,
+    /// Bytes the worktree takes on disk, once measured (see `list_worktrees`)
+    #[serde(default)]
+    pub disk_usage: Option<u64>,
+}
+
+/// Stage reached while creating a worktree as part of a batch
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WorktreeStage {
+    Started,
+    /// Registered with git and branch created (files may not be checked out yet)
+    Created,
+    /// Files of a worktree added with `defer_checkout` being checked out in the background
+    CheckingOut,
+    /// Files checked out, submodules being cloned (`message` says what git is doing)
+    Submodules,
+    /// Seed files being copied from the main checkout (`message` lists them)
+    Seeding,
+    /// Files checked out, ready for an agent
+    Ready,
+    Failed,
+}
+
+/// Progress event for batch worktree creation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorktreeProgress {
+    pub branch: String,
+    pub path: String,
+    pub stage: WorktreeStage,
+    pub message: Option<String>,
+}
+
+/// How new worktrees of a project are set up (see `ProjectSettings::worktree_options`)
+#[derive(Debug, Clone, Default)]
+pub struct WorktreeOptions {
+    /// Clone files from the main checkout instead of checking them out
+    /// (see `create_worktree_cloned`)
+    pub cow_clone: bool,
+    pub init_submodules: bool,
+    /// Files or globs copied from the main checkout once the worktree exists (see `seed_worktree`)
+    pub seed_files: Vec<String>,
+    pub seed_mode: SeedMode,
+    /// Hooks and LFS downloads to skip (see `preflight_worktree`)
+    pub bypass: CheckoutBypass,
+}
+
+/// Outcome of one worktree in a batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorktreeCreationResult {
+    pub branch: String,
+    pub path: String,
+    pub error: Option<String>,
+    /// Files are still being checked out; a `ready` or `failed` progress event follows
+    #[serde(default)]
+    pub checkout_pending: bool,
+}
+
+/// Summary of uncommitted work in a worktree, relative to its HEAD
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorktreeChanges {
+    /// Modified, added, deleted and untracked files
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+/// What `prune_worktrees_internal` found and cleaned up
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorktreePruneReport {
+    /// Worktrees whose directory was gone, and whose registration was removed
+    pub pruned: Vec<String>,
+    /// Directories in the worktree folder left over from worktrees git no longer knows about
+    pub orphaned_dirs: Vec<String>,
+    /// Whether `orphaned_dirs` were deleted
+    pub removed_orphans: bool,
+    /// Registered worktrees in the worktree folder that no terminal uses any more
+    /// (filled in by the caller, which knows about terminals)
+    pub unused: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,11 +111,202 @@ pub struct BranchInfo {
     pub upstream: Option<String>,
 }
 
-pub fn create_worktree_internal(repo_path: &Path, branch: &str, worktree_path: &Path) -> Result<()> {
-    eprintln!("[Ada:Worktree] create_worktree_internal called");
+/// What a branch spec asks `add_worktree` for
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum BranchSpec {
+    /// "wt-baseBranch/newBranchName": a new branch starting at the base, which may be a
+    /// remote branch ("wt-origin/main/newBranchName")
+    New { base: String, branch: String },
+    /// "origin/feature-x": a local "feature-x" tracking the remote branch
+    Remote { remote: String, branch: String },
+    /// An existing branch or revision, or a new branch from HEAD
+    Plain(String),
+}
+
+impl BranchSpec {
+    fn parse(spec: &str, remotes: &[String]) -> Self {
+        if let Some(rest) = spec.strip_prefix("wt-") {
+            // A remote base spans two segments ("origin/main"), a local one just the first
+            let remote_base = split_remote(rest, remotes)
+                .and_then(|(remote, after)| after.find('/').map(|i| remote.len() + 1 + i));
+            if let Some(i) = remote_base.or_else(|| rest.find('/')) {
+                return BranchSpec::New {
+                    base: rest[..i].to_string(),
+                    branch: rest[i + 1..].to_string(),
+                };
+            }
+        }
+        if let Some((remote, branch)) = split_remote(spec, remotes) {
+            return BranchSpec::Remote {
+                remote: remote.to_string(),
+                branch: branch.to_string(),
+            };
+        }
+        BranchSpec::Plain(spec.to_string())
+    }
+
+    /// Local branch the worktree ends up on
+    fn branch(&self) -> &str {
+        match self {
+            BranchSpec::New { branch, .. } | BranchSpec::Remote { branch, .. } => branch,
+            BranchSpec::Plain(branch) => branch,
+        }
+    }
+}
+
+/// Split "origin/feature-x" into remote and branch, when the part before the first '/'
+/// is one of `remotes`
+fn split_remote<'a>(spec: &'a str, remotes: &[String]) -> Option<(&'a str, &'a str)> {
+    let (remote, branch) = spec.split_once('/')?;
+    (!branch.is_empty() && remotes.iter().any(|r| r == remote)).then_some((remote, branch))
+}
+
+fn remote_names(repo: &Repository) -> Vec<String> {
+    repo.remotes()
+        .map(|remotes| remotes.iter().flatten().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// Extract the branch a worktree will be on from a branch spec.
+/// "wt-baseBranch/newBranchName" yields "newBranchName", a remote branch like
+/// "origin/feature-x" yields "feature-x", and anything else is the branch itself.
+pub fn branch_from_spec(repo_path: &Path, branch_spec: &str) -> String {
+    let remotes = Repository::open(repo_path)
+        .map(|repo| remote_names(&repo))
+        .unwrap_or_default();
+    BranchSpec::parse(branch_spec, &remotes).branch().to_string()
+}
+
+/// A plain branch spec for a branch that doesn't exist yet becomes a new branch starting at
+/// `base` ("wt-base/branch"); other specs are returned as they are
+pub fn branch_spec_with_base(repo_path: &Path, branch_spec: &str, base: &str) -> String {
+    let Ok(repo) = Repository::open(repo_path) else {
+        return branch_spec.to_string();
+    };
+    match BranchSpec::parse(branch_spec, &remote_names(&repo)) {
+        BranchSpec::Plain(branch) if !revision_exists(&repo, &branch) => format!("wt-{}/{}", base, branch),
+        _ => branch_spec.to_string(),
+    }
+}
+
+/// Placeholder values for a worktree name template (see `worktree_path_for`)
+pub struct WorktreeNameContext<'a> {
+    pub branch: &'a str,
+    /// Client the worktree is for, e.g. "claude-code"
+    pub agent: Option<&'a str>,
+    pub project: &'a str,
+}
+
+/// Path for a worktree of `branch` in `base`, named by a project's `template`:
+/// `{branch}`, `{agent}`, `{project}`, `{date}` (YYYY-MM-DD) and `{time}` (HHMMSS), e.g.
+/// "{date}-{agent}-{branch}". Without a template the name is just the branch.
+/// Characters that don't belong in a directory name become '-', and a taken name gets
+/// "-2", "-3", ... appended. A worktree already on `branch` in `base` is reused whatever
+/// its name, so a branch keeps its directory after the template (or the date) changes.
+pub fn worktree_path_for(base: &Path, template: Option<&str>, context: &WorktreeNameContext) -> PathBuf {
+    let existing = std::fs::read_dir(base).ok().and_then(|entries| {
+        entries
+            .flatten()
+            .map(|entry| entry.path())
+            .find(|path| is_worktree_on(path, context.branch))
+    });
+    if let Some(path) = existing {
+        return path;
+    }
+
+    let now = chrono::Local::now();
+    let template = template.map(str::trim).filter(|t| !t.is_empty()).unwrap_or("{branch}");
+    let rendered = template
+        .replace("{branch}", context.branch)
+        .replace("{agent}", context.agent.unwrap_or(""))
+        .replace("{project}", context.project)
+        .replace("{date}", &now.format("%Y-%m-%d").to_string())
+        .replace("{time}", &now.format("%H%M%S").to_string());
+
+    // Empty placeholders would otherwise leave "--" or a leading '-' behind
+    let mut name = String::new();
+    for c in rendered.chars() {
+        let c = if c.is_whitespace() || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') {
+            '-'
+        } else {
+            c
+        };
+        if !(c == '-' && (name.is_empty() || name.ends_with('-'))) {
+            name.push(c);
+        }
+    }
+    let name = match name.trim_end_matches('-') {
+        "" => context.branch.replace('/', "-"),
+        name => name.to_string(),
+    };
+
+    let mut path = base.join(&name);
+    let mut suffix = 2;
+    while path.exists() {
+        path = base.join(format!("{}-{}", name, suffix));
+        suffix += 1;
+    }
+    path
+}
+
+/// Whether `path` is a linked worktree with `branch` checked out
+fn is_worktree_on(path: &Path, branch: &str) -> bool {
+    Repository::open(path)
+        .ok()
+        .filter(|repo| repo.is_worktree())
+        .and_then(|repo| head_branch(&repo))
+        .is_some_and(|head| head == branch)
+}
+
+/// `git` for the operations still done through the CLI (adding and removing worktrees,
+/// and fetching, which needs the user's credential setup), with messages forced to English
+/// so errors read the same on every system
+pub(super) fn git_cli(dir: &Path) -> Command {
+    let mut cmd = Command::new("git");
+    cmd.current_dir(dir).env("LC_ALL", "C");
+    cmd
+}
+
+/// Whether a revision (branch, tag, commit, HEAD) resolves in the repository
+fn revision_exists(repo: &Repository, spec: &str) -> bool {
+    repo.revparse_single(spec).is_ok()
+}
+
+/// Branch HEAD points at (even before its first commit), or None when detached
+pub(super) fn head_branch(repo: &Repository) -> Option<String> {
+    let head = repo.find_reference("HEAD").ok()?;
+    head.symbolic_target()?.strip_prefix("refs/heads/").map(String::from)
+}
+
+pub fn create_worktree_internal(
+    repo_path: &Path,
+    branch: &str,
+    worktree_path: &Path,
+    bypass: CheckoutBypass,
+) -> Result<()> {
+    add_worktree(repo_path, branch, worktree_path, true, bypass)
+}
+
+/// Add a worktree for a branch spec. Remote branches ("origin/feature-x", or the base in
+/// "wt-origin/main/newBranchName") are fetched first. With `checkout` false the worktree is
+/// registered and its branch created, but no files are written (see `checkout_worktree`).
+/// On failure, the error includes likely causes from `preflight_worktree`.
+pub fn add_worktree(
+    repo_path: &Path,
+    branch: &str,
+    worktree_path: &Path,
+    checkout: bool,
+    bypass: CheckoutBypass,
+) -> Result<()> {
+    eprintln!("[Ada:Worktree] add_worktree called");
     eprintln!("[Ada:Worktree]   repo_path: {:?}", repo_path);
     eprintln!("[Ada:Worktree]   branch: {}", branch);
     eprintln!("[Ada:Worktree]   worktree_path: {:?}", worktree_path);
+    eprintln!("[Ada:Worktree]   checkout: {}", checkout);
+    eprintln!("[Ada:Worktree]   bypass: {:?}", bypass);
+
+    let checkout_flag: &[&str] = if checkout { &[] } else { &["--no-checkout"] };
+    let repo = Repository::open(repo_path)?;
 
     // Ensure parent directory exists
     if let Some(parent) = worktree_path.parent() {
@@ -32,126 +314,111 @@ pub fn create_worktree_internal(repo_path: &Path, branch: &str, worktree_path: &
         std::fs::create_dir_all(parent)?;
     }
 
-    // Check for special format: wt-baseBranch/newBranchName
-    // This means: create a new branch named newBranchName based on baseBranch
-    if branch.starts_with("wt-") {
-        let rest = branch.strip_prefix("wt-").unwrap();
-        eprintln!("[Ada:Worktree] Detected wt- prefix, rest: {}", rest);
-
-        // Split on first "/" to get baseBranch and newBranchName
-        if let Some(slash_pos) = rest.find('/') {
-            let base_branch = &rest[..slash_pos];
-            let new_branch = &rest[slash_pos + 1..];
-
+    let path = worktree_path.to_string_lossy();
+    let remotes = remote_names(&repo);
+    match BranchSpec::parse(branch, &remotes) {
+        BranchSpec::New { base, branch: new_branch } => {
             eprintln!("[Ada:Worktree] Parsed special format:");
-            eprintln!("[Ada:Worktree]   base_branch: {}", base_branch);
+            eprintln!("[Ada:Worktree]   base_branch: {}", base);
             eprintln!("[Ada:Worktree]   new_branch: {}", new_branch);
 
-            // Verify base branch exists
-            let verify_cmd = format!("git rev-parse --verify {}", base_branch);
-            eprintln!("[Ada:Worktree] Running: {}", verify_cmd);
+            // Start from the remote's latest commit, without tracking the base: pushing the
+            // new branch shouldn't go to origin/main
+            let remote_base = split_remote(&base, &remotes)
+                .map(|(remote, branch)| (remote.to_string(), branch.to_string()));
+            if let Some((remote, remote_branch)) = &remote_base {
+                fetch_remote_branch(&repo, repo_path, remote, remote_branch)?;
+            }
 
-            let base_exists = Command::new("git")
-                .args(["rev-parse", "--verify", base_branch])
-                .current_dir(repo_path)
-                .output()?
-                .status
-                .success();
+            // Verify base branch exists
+            let base_exists = revision_exists(&repo, &base);
 
-            eprintln!("[Ada:Worktree] Base branch '{}' exists: {}", base_branch, base_exists);
+            eprintln!("[Ada:Worktree] Base branch '{}' exists: {}", base, base_exists);
 
             if !base_exists {
                 return Err(Error::WorktreeError(
-                    format!("Base branch '{}' does not exist", base_branch)
+                    format!("Base branch '{}' does not exist", base)
                 ));
             }
 
             // Create worktree with new branch from base branch
-            let cmd = format!(
-                "git worktree add -b {} {} {}",
-                new_branch,
-                worktree_path.to_string_lossy(),
-                base_branch
-            );
-            eprintln!("[Ada:Worktree] Running: {}", cmd);
-
-            let output = Command::new("git")
-                .args(["worktree", "add", "-b", new_branch, &worktree_path.to_string_lossy(), base_branch])
-                .current_dir(repo_path)
-                .output()?;
-
-            eprintln!("[Ada:Worktree] Command exit status: {}", output.status);
-            if !output.stdout.is_empty() {
-                eprintln!("[Ada:Worktree] stdout: {}", String::from_utf8_lossy(&output.stdout));
-            }
-            if !output.stderr.is_empty() {
-                eprintln!("[Ada:Worktree] stderr: {}", String::from_utf8_lossy(&output.stderr));
-            }
+            let track_flag: &[&str] = if remote_base.is_some() { &["--no-track"] } else { &[] };
+            run_worktree_add(
+                repo_path,
+                bypass,
+                &[checkout_flag, track_flag, &["-b", &new_branch, &path, &base]].concat(),
+            )
+        }
+        BranchSpec::Remote { remote, branch: local_branch } => {
+            eprintln!("[Ada:Worktree] Remote branch: {}/{}", remote, local_branch);
+            fetch_remote_branch(&repo, repo_path, &remote, &local_branch)?;
 
-            if !output.status.success() {
+            let upstream = format!("{}/{}", remote, local_branch);
+            if repo.find_reference(&format!("refs/remotes/{}", upstream)).is_err() {
                 return Err(Error::WorktreeError(
-                    String::from_utf8_lossy(&output.stderr).to_string()
+                    format!("Remote branch '{}' does not exist", upstream)
                 ));
             }
 
-            eprintln!("[Ada:Worktree] Worktree created successfully");
-            return Ok(());
-        } else {
-            eprintln!("[Ada:Worktree] No slash found in wt- format, treating as regular branch");
+            // Reuse a local branch already tracking it, rather than clobbering its commits
+            match repo.find_branch(&local_branch, BranchType::Local) {
+                Ok(existing) => {
+                    let tracks = existing
+                        .upstream()
+                        .ok()
+                        .and_then(|u| u.name().ok().flatten().map(String::from));
+                    if tracks.as_deref() != Some(upstream.as_str()) {
+                        return Err(Error::WorktreeError(format!(
+                            "Local branch '{}' already exists and doesn't track '{}'",
+                            local_branch, upstream
+                        )));
+                    }
+                    run_worktree_add(repo_path, bypass, &[checkout_flag, &[&path, &local_branch]].concat())
+                }
+                Err(_) => run_worktree_add(
+                    repo_path,
+                    bypass,
+                    &[checkout_flag, &["--track", "-b", &local_branch, &path, &upstream]].concat(),
+                ),
+            }
         }
-    }
+        BranchSpec::Plain(branch) => {
+            // Standard worktree creation (existing branch or new branch from HEAD)
+            eprintln!("[Ada:Worktree] Standard worktree creation for branch: {}", branch);
 
-    // Standard worktree creation (existing branch or new branch from HEAD)
-    eprintln!("[Ada:Worktree] Standard worktree creation for branch: {}", branch);
+            let branch_exists = revision_exists(&repo, &branch);
 
-    let verify_cmd = format!("git rev-parse --verify {}", branch);
-    eprintln!("[Ada:Worktree] Running: {}", verify_cmd);
+            eprintln!("[Ada:Worktree] Branch '{}' exists: {}", branch, branch_exists);
 
-    let branch_exists = Command::new("git")
-        .args(["rev-parse", "--verify", branch])
-        .current_dir(repo_path)
-        .output()?
-        .status
-        .success();
+            if branch_exists {
+                // Use existing branch
+                return run_worktree_add(repo_path, bypass, &[checkout_flag, &[&path, &branch]].concat());
+            }
 
-    eprintln!("[Ada:Worktree] Branch '{}' exists: {}", branch, branch_exists);
+            // Check if HEAD is valid (repository has at least one commit)
+            let head_valid = revision_exists(&repo, "HEAD");
 
-    let output = if branch_exists {
-        // Use existing branch
-        let cmd = format!("git worktree add {} {}", worktree_path.to_string_lossy(), branch);
-        eprintln!("[Ada:Worktree] Running: {}", cmd);
+            eprintln!("[Ada:Worktree] HEAD is valid: {}", head_valid);
 
-        Command::new("git")
-            .args(["worktree", "add", &worktree_path.to_string_lossy(), branch])
-            .current_dir(repo_path)
-            .output()?
-    } else {
-        // Check if HEAD is valid (repository has at least one commit)
-        eprintln!("[Ada:Worktree] Running: git rev-parse --verify HEAD");
-        let head_valid = Command::new("git")
-            .args(["rev-parse", "--verify", "HEAD"])
-            .current_dir(repo_path)
-            .output()?
-            .status
-            .success();
-
-        eprintln!("[Ada:Worktree] HEAD is valid: {}", head_valid);
-
-        if !head_valid {
-            return Err(Error::WorktreeError(
-                "Cannot create a new branch: the repository has no commits yet. Please make an initial commit first.".to_string()
-            ));
+            if !head_valid {
+                return Err(Error::WorktreeError(
+                    "Cannot create a new branch: the repository has no commits yet. Please make an initial commit first.".to_string()
+                ));
+            }
+
+            // Create new branch from current HEAD
+            run_worktree_add(repo_path, bypass, &[checkout_flag, &["-b", &branch, &path]].concat())
         }
+    }
+}
 
-        // Create new branch from current HEAD
-        let cmd = format!("git worktree add -b {} {}", branch, worktree_path.to_string_lossy());
-        eprintln!("[Ada:Worktree] Running: {}", cmd);
+/// Run `git worktree add` with `args`, turning a failure into its stderr
+fn run_worktree_add(repo_path: &Path, bypass: CheckoutBypass, args: &[&str]) -> Result<()> {
+    eprintln!("[Ada:Worktree] Running: git worktree add {}", args.join(" "));
 
-        Command::new("git")
-            .args(["worktree", "add", "-b", branch, &worktree_path.to_string_lossy()])
-            .current_dir(repo_path)
-            .output()?
-    };
+    let mut cmd = git_cli(repo_path);
+    bypass.apply(&mut cmd);
+    let output = cmd.args(["worktree", "add"]).args(args).output()?;
 
     eprintln!("[Ada:Worktree] Command exit status: {}", output.status);
     if !output.stdout.is_empty() {
@@ -162,19 +429,232 @@ pub fn create_worktree_internal(repo_path: &Path, branch: &str, worktree_path: &
     }
 
     if !output.status.success() {
-        return Err(Error::WorktreeError(
-            String::from_utf8_lossy(&output.stderr).to_string()
-        ));
+        return Err(Error::WorktreeError(explain_failure(
+            repo_path,
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        )));
     }
 
     eprintln!("[Ada:Worktree] Worktree created successfully");
     Ok(())
 }
 
-pub fn remove_worktree_internal(repo_path: &Path, worktree_path: &Path) -> Result<()> {
-    let output = Command::new("git")
-        .args(["worktree", "remove", &worktree_path.to_string_lossy(), "--force"])
-        .current_dir(repo_path)
+/// Fetch one branch of a remote, so a worktree starts from its latest commit.
+/// When the fetch fails (e.g. offline), the last fetched state is used if there is one.
+fn fetch_remote_branch(repo: &Repository, repo_path: &Path, remote: &str, branch: &str) -> Result<()> {
+    let refspec = format!("+refs/heads/{0}:refs/remotes/{1}/{0}", branch, remote);
+    if let Err(e) = run_fetch(repo_path, &[remote, &refspec], &|_| {}) {
+        if repo.find_reference(&format!("refs/remotes/{}/{}", remote, branch)).is_err() {
+            return Err(e);
+        }
+        eprintln!("[Ada:Worktree] Using the last fetched {}/{}: {}", remote, branch, e);
+    }
+    Ok(())
+}
+
+/// Add a worktree for a branch spec and set it up as `options` say
+pub fn create_worktree_with_options(
+    repo_path: &Path,
+    branch: &str,
+    worktree_path: &Path,
+    options: &WorktreeOptions,
+    on_progress: &dyn Fn(WorktreeStage, String),
+) -> Result<()> {
+    if options.cow_clone {
+        create_worktree_cloned(repo_path, branch, worktree_path, options.bypass)?;
+    } else {
+        create_worktree_internal(repo_path, branch, worktree_path, options.bypass)?;
+    }
+    finish_worktree(repo_path, worktree_path, options, on_progress)
+}
+
+/// Steps that follow checking out a new worktree: initializing submodules and copying
+/// seed files, as `options` say
+pub fn finish_worktree(
+    repo_path: &Path,
+    worktree_path: &Path,
+    options: &WorktreeOptions,
+    on_progress: &dyn Fn(WorktreeStage, String),
+) -> Result<()> {
+    if options.init_submodules {
+        init_submodules(worktree_path, &|message| on_progress(WorktreeStage::Submodules, message))?;
+    }
+    if !options.seed_files.is_empty() {
+        on_progress(WorktreeStage::Seeding, options.seed_files.join(", "));
+        seed_worktree(repo_path, worktree_path, &options.seed_files, options.seed_mode)?;
+    }
+    Ok(())
+}
+
+/// Populate the files of a worktree that was added with `--no-checkout`
+pub fn checkout_worktree(worktree_path: &Path) -> Result<()> {
+    let repo = Repository::open(worktree_path)?;
+    let head = repo.head()?.peel_to_commit()?;
+    repo.reset(head.as_object(), ResetType::Hard, None)?;
+    Ok(())
+}
+
+/// Create several worktrees concurrently, at most `parallelism` at a time, each set up as
+/// `options` say. Without `defer_checkout` this returns once every worktree is ready.
+/// With it, worktrees are only registered (`--no-checkout`) before this returns; their
+/// files are checked out and set up on background threads, which report `ready` or
+/// `failed` through `on_progress`, so each agent can start as soon as its own tree is.
+pub fn create_worktrees_internal(
+    repo_path: &Path,
+    worktrees: Vec<(String, PathBuf)>,
+    parallelism: usize,
+    defer_checkout: bool,
+    options: &WorktreeOptions,
+    on_progress: Arc<dyn Fn(WorktreeProgress) + Send + Sync>,
+) -> Vec<WorktreeCreationResult> {
+    // Cloned worktrees come with their files
+    let defer_checkout = defer_checkout && !options.cow_clone;
+    let queue = Mutex::new(worktrees.into_iter().collect::<VecDeque<_>>());
+    let results = Mutex::new(Vec::new());
+    let workers = parallelism.max(1).min(queue.lock().len());
+
+    let progress = |branch: &str, path: &Path, stage: WorktreeStage, message: Option<String>| {
+        on_progress(WorktreeProgress {
+            branch: branch.to_string(),
+            path: path.to_string_lossy().to_string(),
+            stage,
+            message,
+        });
+    };
+
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let Some((branch, path)) = queue.lock().pop_front() else {
+                    break;
+                };
+
+                progress(&branch, &path, WorktreeStage::Started, None);
+                let created = if options.cow_clone {
+                    create_worktree_cloned(repo_path, &branch, &path, options.bypass)
+                } else {
+                    add_worktree(repo_path, &branch, &path, !defer_checkout, options.bypass)
+                        .map(|_| progress(&branch, &path, WorktreeStage::Created, None))
+                };
+
+                let result = match created {
+                    Ok(()) if defer_checkout => {
+                        spawn_deferred_checkout(repo_path, &branch, &path, options, on_progress.clone());
+                        Ok(())
+                    }
+                    Ok(()) => {
+                        let finished = finish_worktree(repo_path, &path, options, &|stage, message| {
+                            progress(&branch, &path, stage, Some(message))
+                        });
+                        report_finished(&*on_progress, &branch, &path, &finished);
+                        finished
+                    }
+                    Err(e) => {
+                        progress(&branch, &path, WorktreeStage::Failed, Some(e.to_string()));
+                        Err(e)
+                    }
+                };
+
+                results.lock().push(WorktreeCreationResult {
+                    branch,
+                    path: path.to_string_lossy().to_string(),
+                    checkout_pending: defer_checkout && result.is_ok(),
+                    error: result.err().map(|e| e.to_string()),
+                });
+            });
+        }
+    });
+
+    results.into_inner()
+}
+
+/// Check out and set up a worktree added with `--no-checkout` on a thread of its own
+fn spawn_deferred_checkout(
+    repo_path: &Path,
+    branch: &str,
+    path: &Path,
+    options: &WorktreeOptions,
+    on_progress: Arc<dyn Fn(WorktreeProgress) + Send + Sync>,
+) {
+    let repo_path = repo_path.to_path_buf();
+    let branch = branch.to_string();
+    let path = path.to_path_buf();
+    let options = options.clone();
+    std::thread::spawn(move || {
+        let progress = |stage: WorktreeStage, message: Option<String>| {
+            on_progress(WorktreeProgress {
+                branch: branch.clone(),
+                path: path.to_string_lossy().to_string(),
+                stage,
+                message,
+            });
+        };
+        progress(WorktreeStage::CheckingOut, None);
+        let finished = checkout_worktree(&path).and_then(|_| {
+            finish_worktree(&repo_path, &path, &options, &|stage, message| progress(stage, Some(message)))
+        });
+        report_finished(&*on_progress, &branch, &path, &finished);
+    });
+}
+
+/// Report a worktree of a batch as ready or failed
+fn report_finished(
+    on_progress: &(dyn Fn(WorktreeProgress) + Send + Sync),
+    branch: &str,
+    path: &Path,
+    result: &Result<()>,
+) {
+    let (stage, message) = match result {
+        Ok(()) => (WorktreeStage::Ready, None),
+        Err(e) => (WorktreeStage::Failed, Some(e.to_string())),
+    };
+    on_progress(WorktreeProgress {
+        branch: branch.to_string(),
+        path: path.to_string_lossy().to_string(),
+        stage,
+        message,
+    });
+}
+
+/// Remove a worktree, and with `delete_branch` the branch it had checked out.
+/// Unless `force` is set, a worktree with uncommitted changes or unpushed commits is kept
+/// (failing with `Error::WorktreeDirty`), and the branch is only deleted if it is merged
+/// into the main checkout's HEAD.
+pub fn remove_worktree_internal(
+    repo_path: &Path,
+    worktree_path: &Path,
+    delete_branch: bool,
+    force: bool,
+) -> Result<()> {
+    if !force {
+        let summary = get_worktree_dirty_summary(repo_path, worktree_path)?;
+        if summary.is_dirty() {
+            return Err(Error::WorktreeDirty(summary));
+        }
+    }
+
+    // Check the branch can go before removing anything
+    let branch = if delete_branch {
+        let worktree = find_worktree(repo_path, worktree_path)?;
+        if worktree.branch.is_empty() {
+            None
+        } else {
+            if !force && !is_branch_merged(repo_path, &worktree.branch)? {
+                return Err(Error::WorktreeError(format!(
+                    "Branch '{}' is not merged; remove it with force to delete it anyway",
+                    worktree.branch
+                )));
+            }
+            Some(worktree.branch)
+        }
+    } else {
+        None
+    };
+
+    let force_flag: &[&str] = if force { &["--force"] } else { &[] };
+    let output = git_cli(repo_path)
+        .args(["worktree", "remove", &worktree_path.to_string_lossy()])
+        .args(force_flag)
         .output()?;
     
     if !output.status.success() {
@@ -182,106 +662,240 @@ pub fn remove_worktree_internal(repo_path: &Path, worktree_path: &Path) -> Resul
             String::from_utf8_lossy(&output.stderr).to_string()
         ));
     }
+
+    if let Some(branch) = branch {
+        let repo = Repository::open(repo_path)?;
+        repo.find_branch(&branch, BranchType::Local)?.delete()?;
+        eprintln!("[Ada:Worktree] Deleted branch {}", branch);
+    }
     
     Ok(())
 }
 
-pub fn list_worktrees_internal(repo_path: &Path) -> Result<Vec<WorktreeInfo>> {
-    let output = Command::new("git")
-        .args(["worktree", "list", "--porcelain"])
-        .current_dir(repo_path)
+/// Whether every commit on `branch` is reachable from the main checkout's HEAD
+fn is_branch_merged(repo_path: &Path, branch: &str) -> Result<bool> {
+    let repo = Repository::open(repo_path)?;
+    let tip = repo.find_branch(branch, BranchType::Local)?.get().peel_to_commit()?.id();
+    let head = repo.head()?.peel_to_commit()?.id();
+    Ok(tip == head || repo.graph_descendant_of(head, tip)?)
+}
+
+/// Reconnect a repository with its worktrees after either was moved (`git worktree repair`).
+/// `worktree_paths` are the worktrees' current locations, needed when they moved too.
+pub fn repair_worktrees(repo_path: &Path, worktree_paths: &[PathBuf]) -> Result<()> {
+    let output = git_cli(repo_path)
+        .args(["worktree", "repair"])
+        .args(worktree_paths)
         .output()?;
-    
     if !output.status.success() {
-        return Err(Error::WorktreeError(
-            String::from_utf8_lossy(&output.stderr).to_string()
-        ));
+        return Err(Error::WorktreeError(format!(
+            "Failed to repair worktrees: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
     }
-    
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let mut worktrees = Vec::new();
-    let mut current_worktree: Option<WorktreeInfo> = None;
-    
-    for line in stdout.lines() {
-        if line.starts_with("worktree ") {
-            if let Some(wt) = current_worktree.take() {
-                worktrees.push(wt);
-            }
-            current_worktree = Some(WorktreeInfo {
-                path: line.strip_prefix("worktree ").unwrap_or("").to_string(),
+    Ok(())
+}
+
+/// Remove registrations of worktrees whose directory is gone (like `git worktree prune`)
+/// and find directories under `worktree_base` left behind by worktrees that are no longer
+/// registered, deleting them when `remove_orphans` is set.
+/// Only directories whose `.git` file points back into this repository count as orphaned.
+pub fn prune_worktrees_internal(
+    repo_path: &Path,
+    worktree_base: &Path,
+    remove_orphans: bool,
+) -> Result<WorktreePruneReport> {
+    scan_stale_worktrees(repo_path, worktree_base, true, remove_orphans)
+}
+
+/// What `prune_worktrees_internal` would clean up, without changing anything:
+/// `pruned` lists the registrations whose directory is gone
+pub fn find_stale_worktrees(repo_path: &Path, worktree_base: &Path) -> Result<WorktreePruneReport> {
+    scan_stale_worktrees(repo_path, worktree_base, false, false)
+}
+
+fn scan_stale_worktrees(
+    repo_path: &Path,
+    worktree_base: &Path,
+    prune: bool,
+    remove_orphans: bool,
+) -> Result<WorktreePruneReport> {
+    let repo = Repository::open(repo_path)?;
+    let main = Repository::open(repo.commondir())?;
+    let mut report = WorktreePruneReport::default();
+
+    for name in main.worktrees()?.iter().flatten() {
+        let worktree = main.find_worktree(name)?;
+        // Locked worktrees (e.g. on removable drives) are kept, as git does
+        if worktree.validate().is_ok() || worktree.is_prunable(None).ok() != Some(true) {
+            continue;
+        }
+        if prune {
+            worktree.prune(Some(&mut WorktreePruneOptions::new()))?;
+            eprintln!("[Ada:Worktree] Pruned stale worktree {}", name);
+        }
+        report.pruned.push(worktree.path().to_string_lossy().to_string());
+    }
+
+    let Ok(entries) = std::fs::read_dir(worktree_base) else {
+        return Ok(report);
+    };
+    let registered: Vec<PathBuf> = list_worktrees_internal(repo_path)?
+        .iter()
+        .filter_map(|worktree| Path::new(&worktree.path).canonicalize().ok())
+        .collect();
+    let admin_dir = main.path().join("worktrees");
+    let admin_dir = admin_dir.canonicalize().unwrap_or(admin_dir);
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(canonical) = path.canonicalize() else {
+            continue;
+        };
+        if !path.is_dir() || registered.contains(&canonical) {
+            continue;
+        }
+
+        if !links_to(&path, &admin_dir) {
+            continue;
+        }
+
+        if remove_orphans {
+            std::fs::remove_dir_all(&path)?;
+            eprintln!("[Ada:Worktree] Removed orphaned worktree directory {:?}", path);
+        }
+        report.orphaned_dirs.push(path.to_string_lossy().to_string());
+    }
+    report.removed_orphans = remove_orphans;
+
+    Ok(report)
+}
+
+/// Whether `path` is a linked worktree whose admin directory lives in `admin_dir`,
+/// going by its `.git` file ("gitdir: <repo>/.git/worktrees/<name>")
+fn links_to(path: &Path, admin_dir: &Path) -> bool {
+    let Some(gitdir) = std::fs::read_to_string(path.join(".git"))
+        .ok()
+        .and_then(|content| content.strip_prefix("gitdir:").map(|dir| path.join(dir.trim())))
+    else {
+        return false;
+    };
+
+    // Joining keeps absolute paths and resolves the relative ones newer git can write
+    gitdir.parent().is_some_and(|parent| {
+        parent == admin_dir || parent.canonicalize().is_ok_and(|parent| parent == admin_dir)
+    })
+}
+
+/// List the main worktree followed by the linked ones, like `git worktree list`
+pub fn list_worktrees_internal(repo_path: &Path) -> Result<Vec<WorktreeInfo>> {
+    // Go through the common dir so the main worktree comes first even from a linked one
+    let repo = Repository::open(repo_path)?;
+    let main = Repository::open(repo.commondir())?;
+
+    let mut worktrees = vec![worktree_info(&main)];
+    for name in main.worktrees()?.iter().flatten() {
+        let worktree = main.find_worktree(name)?;
+        match Repository::open_from_worktree(&worktree) {
+            Ok(repo) => worktrees.push(worktree_info(&repo)),
+            // The directory is gone; git still lists it until it is pruned
+            Err(_) => worktrees.push(WorktreeInfo {
+                path: worktree.path().to_string_lossy().to_string(),
                 branch: String::new(),
                 head: String::new(),
                 is_bare: false,
-            });
-        } else if let Some(ref mut wt) = current_worktree {
-            if line.starts_with("HEAD ") {
-                wt.head = line.strip_prefix("HEAD ").unwrap_or("").to_string();
-            } else if line.starts_with("branch ") {
-                wt.branch = line
-                    .strip_prefix("branch refs/heads/")
-                    .unwrap_or(line.strip_prefix("branch ").unwrap_or(""))
-                    .to_string();
-            } else if line == "bare" {
-                wt.is_bare = true;
-            }
+                disk_usage: None,
+            }),
         }
     }
-    
-    if let Some(wt) = current_worktree {
-        worktrees.push(wt);
-    }
-    
+
     Ok(worktrees)
 }
 
+/// Look up one of the repository's worktrees (including the main checkout) by path
+pub fn find_worktree(repo_path: &Path, worktree_path: &Path) -> Result<WorktreeInfo> {
+    let wanted = worktree_path.canonicalize().unwrap_or_else(|_| worktree_path.to_path_buf());
+
+    list_worktrees_internal(repo_path)?
+        .into_iter()
+        .find(|worktree| {
+            let path = Path::new(&worktree.path);
+            path.canonicalize().unwrap_or_else(|_| path.to_path_buf()) == wanted
+        })
+        .ok_or_else(|| Error::WorktreeError(format!(
+            "{} is not a worktree of this project",
+            worktree_path.display()
+        )))
+}
+
+fn worktree_info(repo: &Repository) -> WorktreeInfo {
+    let path = repo.workdir().unwrap_or_else(|| repo.path());
+
+    WorktreeInfo {
+        // Normalize away the trailing slash libgit2 reports
+        path: path.components().collect::<PathBuf>().to_string_lossy().to_string(),
+        branch: head_branch(repo).unwrap_or_default(),
+        head: repo.refname_to_id("HEAD").map(|oid| oid.to_string()).unwrap_or_default(),
+        is_bare: repo.is_bare(),
+        disk_usage: None,
+    }
+}
+
+/// Local branches followed by remote-tracking ones, each sorted by name
 pub fn get_branches_internal(repo_path: &Path) -> Result<Vec<BranchInfo>> {
-    let output = Command::new("git")
-        .args(["branch", "-a", "--format=%(refname:short)|%(HEAD)|%(upstream:short)"])
-        .current_dir(repo_path)
-        .output()?;
-    
-    if !output.status.success() {
-        return Err(Error::GitError(
-            String::from_utf8_lossy(&output.stderr).to_string()
-        ));
+    let repo = Repository::open(repo_path)?;
+
+    let mut branches = Vec::new();
+    for entry in repo.branches(None)? {
+        let (branch, branch_type) = entry?;
+        // Skip symbolic refs such as origin/HEAD
+        if branch.get().kind() == Some(ReferenceType::Symbolic) {
+            continue;
+        }
+        let Some(name) = branch.name()? else {
+            continue;
+        };
+
+        branches.push(BranchInfo {
+            name: name.to_string(),
+            is_current: branch.is_head(),
+            is_remote: branch_type == BranchType::Remote,
+            upstream: branch
+                .upstream()
+                .ok()
+                .and_then(|upstream| upstream.name().ok().flatten().map(String::from)),
+        });
     }
-    
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let branches: Vec<BranchInfo> = stdout
-        .lines()
-        .filter(|line| !line.is_empty())
-        .map(|line| {
-            let parts: Vec<&str> = line.split('|').collect();
-            let name = parts.first().unwrap_or(&"").to_string();
-            let is_current = parts.get(1).map_or(false, |s| *s == "*");
-            let upstream = parts.get(2).and_then(|s| {
-                if s.is_empty() { None } else { Some(s.to_string()) }
-            });
-            let is_remote = name.starts_with("origin/") || name.starts_with("remotes/");
-            
-            BranchInfo {
-                name,
-                is_current,
-                is_remote,
-                upstream,
-            }
-        })
-        .collect();
-    
+    branches.sort_by(|a, b| (a.is_remote, &a.name).cmp(&(b.is_remote, &b.name)));
+
     Ok(branches)
 }
 
+/// The checked-out branch, or "HEAD" when detached (as `git rev-parse --abbrev-ref HEAD`)
 pub fn get_current_branch_internal(repo_path: &Path) -> Result<String> {
-    let output = Command::new("git")
-        .args(["rev-parse", "--abbrev-ref", "HEAD"])
-        .current_dir(repo_path)
-        .output()?;
-    
-    if !output.status.success() {
-        return Err(Error::GitError(
-            String::from_utf8_lossy(&output.stderr).to_string()
-        ));
-    }
-    
-    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    let repo = Repository::open(repo_path)?;
+    Ok(head_branch(&repo).unwrap_or_else(|| "HEAD".to_string()))
+}
+
+pub fn get_worktree_changes_internal(worktree_path: &Path) -> Result<WorktreeChanges> {
+    let repo = Repository::open(worktree_path)?;
+
+    let mut status_options = StatusOptions::new();
+    status_options.include_untracked(true);
+    let mut changes = WorktreeChanges {
+        files_changed: repo.statuses(Some(&mut status_options))?.len(),
+        ..Default::default()
+    };
+
+    // Staged and unstaged changes to tracked files, as `git diff HEAD`
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let diff = repo.diff_tree_to_workdir_with_index(
+        head_tree.as_ref(),
+        Some(&mut DiffOptions::new()),
+    )?;
+    let stats = diff.stats()?;
+    changes.insertions = stats.insertions();
+    changes.deletions = stats.deletions();
+
+    Ok(changes)
 }
@@ -2,7 +2,12 @@ mod project;
 mod terminal;
 mod git;
 mod clients;
+mod settings;
+mod notifications;
+mod workspace;
+mod runtime_paths;
 mod state;
+mod telemetry;
 mod error;
 
 use state::AppState;
@@ -16,18 +21,56 @@ pub fn run() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_notification::init())
         .setup(|app| {
+            // The OTLP batch exporter needs to be set up on the async runtime
+            tauri::async_runtime::block_on(async { telemetry::init() });
+
             let app_state = AppState::new(app.handle().clone())?;
             app.manage(app_state);
+            state::spawn_persistence_flusher(app.handle().clone());
+            state::spawn_terminal_gc(app.handle().clone());
+            state::spawn_project_path_watcher(app.handle().clone());
+            terminal::keep_alive::spawn_keep_alive_loop(app.handle().clone());
+            terminal::checkpoint::spawn_checkpoint_loop(app.handle().clone());
+            terminal::agent_status::spawn_agent_status_loop(app.handle().clone());
+
+            // Relaunch agents in the background so startup isn't held up by spawning them
+            let app_handle = app.handle().clone();
+            std::thread::spawn(move || {
+                if let Some(state) = app_handle.try_state::<AppState>() {
+                    terminal::resume_terminals_on_start(&state);
+                }
+            });
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             // Project commands
             project::commands::create_project,
             project::commands::open_project,
+            project::commands::scan_for_projects,
             project::commands::list_projects,
+            project::commands::update_project_order,
+            project::commands::set_project_pinned,
+            project::commands::set_project_tags,
+            project::commands::set_mcp_server,
+            project::commands::remove_mcp_server,
+            project::commands::archive_project,
+            project::commands::unarchive_project,
+            project::commands::export_project,
+            project::commands::import_project,
+            project::commands::list_project_templates,
+            project::commands::save_project_template,
+            project::commands::create_template_from_project,
+            project::commands::delete_project_template,
             project::commands::delete_project,
+            project::commands::relocate_project,
             project::commands::get_project,
+            project::commands::project_doctor,
+            project::commands::get_project_stats,
+            project::commands::get_project_usage,
+            project::commands::add_project_root,
+            project::commands::remove_project_root,
             project::commands::update_project_settings,
             // Terminal commands
             terminal::commands::create_terminal,
@@ -35,23 +78,89 @@ pub fn run() {
             terminal::commands::list_terminals,
             terminal::commands::close_terminal,
             terminal::commands::write_terminal,
+            terminal::commands::write_terminals,
+            terminal::commands::paste_terminal,
             terminal::commands::resize_terminal,
+            terminal::commands::set_terminal_output_paused,
             terminal::commands::get_terminal,
+            terminal::commands::get_terminal_process,
+            terminal::commands::get_terminal_commands,
+            terminal::commands::get_command_history,
             terminal::commands::get_terminal_history,
+            terminal::commands::get_terminal_usage,
+            terminal::commands::get_terminal_transcript,
+            terminal::commands::get_agent_transcript,
+            terminal::commands::summarize_terminal,
             terminal::commands::restart_terminal,
+            terminal::commands::resume_terminal,
+            terminal::commands::set_terminal_auto_resume,
+            terminal::commands::set_terminal_keep_alive,
+            terminal::commands::set_terminal_checkpoints,
+            terminal::commands::create_terminal_checkpoint,
+            terminal::commands::list_terminal_checkpoints,
+            terminal::commands::restore_terminal_checkpoint,
+            terminal::commands::get_terminal_events,
             terminal::commands::mark_terminal_stopped,
             terminal::commands::switch_terminal_agent,
+            terminal::commands::get_terminal_invocations,
+            terminal::commands::create_terminal_group,
+            terminal::commands::list_terminal_groups,
+            terminal::commands::stop_terminal_group,
+            terminal::commands::get_terminal_group_status,
+            terminal::commands::prune_terminals,
             // Git commands
             git::commands::get_branches,
             git::commands::create_worktree,
+            git::commands::create_worktrees,
+            git::commands::check_cow_worktree_support,
+            git::commands::check_worktree_preflight,
             git::commands::remove_worktree,
             git::commands::list_worktrees,
+            git::commands::prune_worktrees,
+            git::commands::get_worktree_status,
+            git::commands::get_worktree_diff,
+            git::commands::commit_worktree,
+            git::commands::merge_worktree,
+            git::commands::check_worktree_merge,
+            git::commands::rebase_worktree,
+            git::commands::fetch,
+            git::commands::pull,
+            git::commands::stash_worktree,
+            git::commands::list_worktree_stashes,
+            git::commands::pop_worktree_stash,
+            git::commands::get_commit_log,
+            git::commands::get_file_status,
             git::commands::get_current_branch,
+            // Workspace commands
+            workspace::commands::create_workspace,
+            workspace::commands::list_workspaces,
+            workspace::commands::update_workspace,
+            workspace::commands::delete_workspace,
+            workspace::commands::open_workspace,
+            workspace::commands::get_workspace_status,
             // Client commands
             clients::commands::list_clients,
             clients::commands::get_client,
             clients::commands::detect_installed_clients,
+            clients::commands::refresh_clients,
+            clients::commands::install_client,
+            clients::commands::check_client_health,
+            clients::commands::add_client,
+            clients::commands::update_client,
+            clients::commands::remove_client,
+            // Settings commands
+            settings::commands::get_app_settings,
+            settings::commands::update_app_settings,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            // Write out any terminal output still waiting for the periodic flush
+            if let tauri::RunEvent::Exit = event {
+                if let Some(state) = app_handle.try_state::<AppState>() {
+                    state.flush_dirty_terminals();
+                }
+                telemetry::shutdown();
+            }
+        });
 }
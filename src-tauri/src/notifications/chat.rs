@@ -0,0 +1,23 @@
+use std::time::Duration;
+
+use super::{AgentNotification, ChatKind, ChatNotifier};
+
+/// How long a chat service gets to accept a message
+const POST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Post a notification to a Slack or Discord incoming webhook. Blocks until the service
+/// answers, so callers run it off the thread that recorded the event.
+pub fn post(notifier: &ChatNotifier, notification: &AgentNotification) {
+    let text = format!(
+        "{}: {}\n{}",
+        notification.title, notification.body, notification.link
+    );
+    let payload = match notifier.kind {
+        ChatKind::Slack => serde_json::json!({ "text": text }),
+        ChatKind::Discord => serde_json::json!({ "content": text }),
+    };
+
+    if let Err(e) = ureq::post(&notifier.webhook_url).timeout(POST_TIMEOUT).send_json(payload) {
+        eprintln!("[Ada] Failed to post notification to {:?} webhook: {}", notifier.kind, e);
+    }
+}
@@ -0,0 +1,17 @@
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+use super::AgentNotification;
+
+/// Show a notification in the OS notification center
+pub fn show(app_handle: &AppHandle, notification: &AgentNotification) {
+    let shown = app_handle
+        .notification()
+        .builder()
+        .title(&notification.title)
+        .body(&notification.body)
+        .show();
+    if let Err(e) = shown {
+        eprintln!("[Ada] Failed to show notification: {}", e);
+    }
+}
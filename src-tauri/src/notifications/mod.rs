@@ -0,0 +1,149 @@
+mod chat;
+mod desktop;
+mod rules;
+mod types;
+mod webhook;
+
+pub use rules::*;
+pub use types::*;
+
+use tauri::Manager;
+
+use crate::state::AppState;
+use crate::terminal::events::{TerminalEvent, TerminalEventKind};
+
+/// Label of Ada's window
+const MAIN_WINDOW: &str = "main";
+
+/// What a notification says about an agent
+#[derive(Debug, Clone)]
+pub struct AgentNotification {
+    pub title: String,
+    pub body: String,
+    /// `ada://` link to the agent's terminal
+    pub link: String,
+}
+
+/// Pass a terminal event on to the channels its project's notification rules (or the
+/// default rules) let it through to. Muted projects still reach webhooks, but not the
+/// desktop or chat, and neither hears about agents the user stopped. An agent waiting for
+/// permission shows as its status (see `terminal::agent_status`), which isn't an event.
+pub fn dispatch(state: &AppState, event: &TerminalEvent) {
+    let settings = state.settings.read().notifications.clone();
+    let (rules, muted) = match state.projects.read().get(&event.project_id) {
+        Some(project) => (
+            project
+                .settings
+                .notification_rules
+                .clone()
+                .unwrap_or_else(|| settings.default_rules()),
+            project.settings.mute_notifications,
+        ),
+        None => (settings.default_rules(), false),
+    };
+    let channels = evaluate_rules(
+        &rules,
+        event.kind.event_type(),
+        chrono::Local::now().time(),
+        window_focused(state),
+    );
+
+    if channels.contains(&NotificationChannel::Webhooks) {
+        send_webhooks(state, &settings.webhooks, event);
+    }
+    if muted {
+        return;
+    }
+
+    // The user already knows about agents they stopped themselves
+    if matches!(event.kind, TerminalEventKind::Exited { stopped: true, .. }) {
+        return;
+    }
+
+    let show_desktop = settings.desktop && channels.contains(&NotificationChannel::Desktop);
+    let chat: Vec<ChatNotifier> = if channels.contains(&NotificationChannel::Chat) {
+        settings.chat.into_iter().filter(|notifier| notifier.enabled).collect()
+    } else {
+        Vec::new()
+    };
+    if !show_desktop && chat.is_empty() {
+        return;
+    }
+
+    let Some(notification) = describe(state, event) else {
+        return;
+    };
+    if show_desktop {
+        desktop::show(&state.app_handle, &notification);
+    }
+    if !chat.is_empty() {
+        std::thread::spawn(move || {
+            for notifier in &chat {
+                chat::post(notifier, &notification);
+            }
+        });
+    }
+}
+
+/// Deliver an event to each webhook that wants it, on a thread per webhook so a slow or
+/// failing endpoint holds up neither the caller nor the others
+fn send_webhooks(state: &AppState, webhooks: &[Webhook], event: &TerminalEvent) {
+    let event_type = event.kind.event_type();
+    let webhooks: Vec<Webhook> = webhooks
+        .iter()
+        .filter(|webhook| webhook.wants(event_type))
+        .cloned()
+        .collect();
+    if webhooks.is_empty() {
+        return;
+    }
+
+    let payload = webhook::WebhookPayload {
+        event: event.clone(),
+        project_name: state.projects.read().get(&event.project_id).map(|p| p.name.clone()),
+        terminal_name: state.terminals.read().get(&event.terminal_id).map(|t| t.name.clone()),
+        link: terminal_link(&event.project_id, &event.terminal_id),
+    };
+    for webhook in webhooks {
+        let payload = payload.clone();
+        std::thread::spawn(move || webhook::deliver(&webhook, &payload));
+    }
+}
+
+fn window_focused(state: &AppState) -> bool {
+    state
+        .app_handle
+        .get_webview_window(MAIN_WINDOW)
+        .and_then(|window| window.is_focused().ok())
+        .unwrap_or(false)
+}
+
+/// The notification for an event; None if its terminal or project is gone
+fn describe(state: &AppState, event: &TerminalEvent) -> Option<AgentNotification> {
+    let terminal_name = state.terminals.read().get(&event.terminal_id)?.name.clone();
+    let project_name = state.projects.read().get(&event.project_id)?.name.clone();
+
+    let what = match &event.kind {
+        TerminalEventKind::Started { client_id } => format!("Agent {} started", client_id),
+        TerminalEventKind::Ready => "Agent is ready".to_string(),
+        TerminalEventKind::CommandFinished { command, exit_code } => {
+            let command = command.as_deref().map_or("Command".to_string(), |command| format!("`{}`", command));
+            match exit_code {
+                Some(0) | None => format!("{} finished", command),
+                Some(code) => format!("{} failed with code {}", command, code),
+            }
+        }
+        TerminalEventKind::Exited { exit_code: Some(0) | None, .. } => "Agent finished".to_string(),
+        TerminalEventKind::Exited { exit_code: Some(code), .. } => format!("Agent exited with code {}", code),
+    };
+    Some(AgentNotification {
+        title: terminal_name,
+        body: format!("{} in {}", what, project_name),
+        link: terminal_link(&event.project_id, &event.terminal_id),
+    })
+}
+
+/// Link that points Ada at a terminal
+fn terminal_link(project_id: &str, terminal_id: &str) -> String {
+    format!("ada://projects/{}/terminals/{}", project_id, terminal_id)
+}
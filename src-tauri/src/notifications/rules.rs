@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveTime;
+
+use crate::error::Result;
+use crate::terminal::events::TerminalEventType;
+use super::{NotificationSettings, QuietHours};
+
+/// Where a notification can go
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationChannel {
+    Desktop,
+    /// The Slack and Discord webhooks in `NotificationSettings::chat`
+    Chat,
+    /// The endpoints in `NotificationSettings::webhooks`
+    Webhooks,
+}
+
+/// Lets events through to a channel, when they match and the conditions hold. A channel gets
+/// an event if any of its rules lets it through; channels without rules get nothing.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NotificationRule {
+    pub channel: NotificationChannel,
+    /// Events the rule covers (empty = all of them)
+    #[serde(default)]
+    pub events: Vec<TerminalEventType>,
+    /// Hours in which the rule lets nothing through
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+    /// Only while Ada's window isn't focused
+    #[serde(default)]
+    pub only_when_unfocused: bool,
+}
+
+impl NotificationRule {
+    fn new(channel: NotificationChannel, events: Vec<TerminalEventType>) -> Self {
+        Self { channel, events, quiet_hours: None, only_when_unfocused: false }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if let Some(quiet_hours) = &self.quiet_hours {
+            quiet_hours.validate()?;
+        }
+        Ok(())
+    }
+
+    pub fn allows(&self, event_type: TerminalEventType, now: NaiveTime, window_focused: bool) -> bool {
+        (self.events.is_empty() || self.events.contains(&event_type))
+            && !self.quiet_hours.as_ref().is_some_and(|quiet_hours| quiet_hours.contains(now))
+            && !(self.only_when_unfocused && window_focused)
+    }
+}
+
+impl NotificationSettings {
+    /// Rules for projects without their own: agents exiting are shown on the desktop while
+    /// Ada isn't focused (outside quiet hours) and posted to chat, and webhooks get every event
+    pub fn default_rules(&self) -> Vec<NotificationRule> {
+        vec![
+            NotificationRule {
+                quiet_hours: self.quiet_hours.clone(),
+                only_when_unfocused: true,
+                ..NotificationRule::new(NotificationChannel::Desktop, vec![TerminalEventType::Exited])
+            },
+            NotificationRule::new(NotificationChannel::Chat, vec![TerminalEventType::Exited]),
+            NotificationRule::new(NotificationChannel::Webhooks, Vec::new()),
+        ]
+    }
+}
+
+/// The channels an event goes to under `rules`
+pub fn evaluate_rules(
+    rules: &[NotificationRule],
+    event_type: TerminalEventType,
+    now: NaiveTime,
+    window_focused: bool,
+) -> Vec<NotificationChannel> {
+    let mut channels = Vec::new();
+    for rule in rules {
+        if !channels.contains(&rule.channel) && rule.allows(event_type, now, window_focused) {
+            channels.push(rule.channel);
+        }
+    }
+    channels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(time, "%H:%M").unwrap()
+    }
+
+    fn quiet(start: &str, end: &str) -> QuietHours {
+        QuietHours { start: start.to_string(), end: end.to_string() }
+    }
+
+    #[test]
+    fn quiet_hours_can_run_past_midnight() {
+        let night = quiet("22:00", "07:00");
+        assert!(night.contains(at("23:30")));
+        assert!(night.contains(at("06:59")));
+        assert!(!night.contains(at("07:00")));
+        assert!(!night.contains(at("12:00")));
+
+        let lunch = quiet("12:00", "13:00");
+        assert!(lunch.contains(at("12:00")));
+        assert!(!lunch.contains(at("13:00")));
+
+        assert!(!quiet("09:00", "09:00").contains(at("09:00")));
+        assert!(!quiet("late", "09:00").contains(at("08:00")));
+    }
+
+    #[test]
+    fn rules_pick_channels() {
+        let rules = vec![
+            NotificationRule {
+                quiet_hours: Some(quiet("22:00", "07:00")),
+                only_when_unfocused: true,
+                ..NotificationRule::new(NotificationChannel::Desktop, vec![TerminalEventType::Exited])
+            },
+            NotificationRule::new(NotificationChannel::Webhooks, Vec::new()),
+            NotificationRule::new(NotificationChannel::Webhooks, vec![TerminalEventType::Exited]),
+        ];
+
+        assert_eq!(
+            evaluate_rules(&rules, TerminalEventType::Exited, at("12:00"), false),
+            vec![NotificationChannel::Desktop, NotificationChannel::Webhooks]
+        );
+        assert_eq!(
+            evaluate_rules(&rules, TerminalEventType::Exited, at("12:00"), true),
+            vec![NotificationChannel::Webhooks]
+        );
+        assert_eq!(
+            evaluate_rules(&rules, TerminalEventType::Exited, at("23:00"), false),
+            vec![NotificationChannel::Webhooks]
+        );
+        assert_eq!(
+            evaluate_rules(&rules, TerminalEventType::Ready, at("12:00"), false),
+            vec![NotificationChannel::Webhooks]
+        );
+        assert!(evaluate_rules(&[], TerminalEventType::Exited, at("12:00"), false).is_empty());
+    }
+}
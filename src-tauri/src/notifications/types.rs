@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use chrono::NaiveTime;
+
+use crate::error::{Error, Result};
+use crate::terminal::events::TerminalEventType;
+
+/// How Ada tells the user about agents they aren't watching (see `notifications::dispatch`)
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct NotificationSettings {
+    /// Show desktop notifications (for what and when is up to the notification rules)
+    #[serde(default)]
+    pub desktop: bool,
+    /// Hours in which the default rules show no desktop notifications
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+    /// Slack and Discord channels notified about agents
+    #[serde(default)]
+    pub chat: Vec<ChatNotifier>,
+    /// HTTP endpoints sent terminal events as JSON, e.g. for CI or dashboards
+    #[serde(default)]
+    pub webhooks: Vec<Webhook>,
+}
+
+impl NotificationSettings {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(quiet_hours) = &self.quiet_hours {
+            quiet_hours.validate()?;
+        }
+        for notifier in &self.chat {
+            notifier.validate()?;
+        }
+        for webhook in &self.webhooks {
+            webhook.validate()?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChatKind {
+    Slack,
+    Discord,
+}
+
+/// A chat channel notified through its incoming webhook
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ChatNotifier {
+    pub kind: ChatKind,
+    pub webhook_url: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl ChatNotifier {
+    pub fn validate(&self) -> Result<()> {
+        if !self.webhook_url.starts_with("https://") {
+            return Err(Error::InvalidRequest(format!(
+                "The {:?} webhook URL must start with https://",
+                self.kind
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// An HTTP endpoint that terminal events are POSTed to (see `notifications::webhook`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Webhook {
+    pub url: String,
+    /// Events sent (empty = all of them)
+    #[serde(default)]
+    pub events: Vec<TerminalEventType>,
+    /// Extra request headers, e.g. `Authorization`
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+impl Webhook {
+    pub fn validate(&self) -> Result<()> {
+        if !self.url.starts_with("https://") && !self.url.starts_with("http://") {
+            return Err(Error::InvalidRequest(format!(
+                "Webhook URL '{}' must start with http:// or https://",
+                self.url
+            )));
+        }
+        if self.headers.keys().any(|name| name.trim().is_empty()) {
+            return Err(Error::InvalidRequest(format!("Webhook '{}' has a header without a name", self.url)));
+        }
+        Ok(())
+    }
+
+    pub fn wants(&self, event_type: TerminalEventType) -> bool {
+        self.enabled && (self.events.is_empty() || self.events.contains(&event_type))
+    }
+}
+
+/// A daily stretch of local time as "HH:MM", e.g. 22:00 to 07:00; it may run past midnight
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+}
+
+impl QuietHours {
+    pub fn validate(&self) -> Result<()> {
+        parse_time(&self.start)?;
+        parse_time(&self.end)?;
+        Ok(())
+    }
+
+    /// Whether `time` falls within the quiet hours (never, if start and end are the same)
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        let (Ok(start), Ok(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return false;
+        };
+        if start <= end {
+            start <= time && time < end
+        } else {
+            time >= start || time < end
+        }
+    }
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+        .map_err(|_| Error::InvalidRequest(format!("'{}' is not a time of day like 22:30", value)))
+}
@@ -0,0 +1,56 @@
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::terminal::events::TerminalEvent;
+use super::Webhook;
+
+/// Attempts made to deliver an event before it is dropped
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Wait before the first retry, doubled for each one after
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// How long an endpoint gets to answer
+const POST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Body POSTed to webhooks: the terminal event, with names to show it by
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookPayload {
+    #[serde(flatten)]
+    pub event: TerminalEvent,
+    pub project_name: Option<String>,
+    pub terminal_name: Option<String>,
+    /// `ada://` link to the terminal
+    pub link: String,
+}
+
+/// POST an event to a webhook. Failures to connect, timeouts, 429s and server errors are
+/// retried with exponential backoff; other error statuses are not. Blocks through the
+/// retries, so callers run it on a thread of its own.
+pub fn deliver(webhook: &Webhook, payload: &WebhookPayload) {
+    let mut backoff = INITIAL_BACKOFF;
+    for attempt in 1..=MAX_ATTEMPTS {
+        let mut request = ureq::post(&webhook.url).timeout(POST_TIMEOUT);
+        for (name, value) in &webhook.headers {
+            request = request.set(name, value);
+        }
+
+        let error = match request.send_json(payload) {
+            Ok(_) => return,
+            Err(ureq::Error::Status(code, _)) if code != 429 && code < 500 => {
+                eprintln!("[Ada] Webhook {} rejected an event with status {}", webhook.url, code);
+                return;
+            }
+            Err(e) => e,
+        };
+        if attempt == MAX_ATTEMPTS {
+            eprintln!(
+                "[Ada] Dropped an event for webhook {} after {} attempts: {}",
+                webhook.url, MAX_ATTEMPTS, error
+            );
+            return;
+        }
+        std::thread::sleep(backoff);
+        backoff *= 2;
+    }
+}
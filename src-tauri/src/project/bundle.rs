@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use chrono::{DateTime, Utc};
+
+use crate::clients::AgentOverrides;
+use crate::terminal::checkpoint::CheckpointConfig;
+use crate::terminal::keep_alive::KeepAliveConfig;
+use crate::terminal::{ScrollbackLimit, Terminal, TerminalEnv, TerminalMode};
+use super::{AdaProject, ProjectSettings};
+
+/// Version of the bundle format written by `export_project`
+pub const PROJECT_BUNDLE_VERSION: u32 = 1;
+
+/// A project's Ada configuration, without the repository itself, for setting the same
+/// project up on another machine (see `export_project` and `import_project`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectBundle {
+    pub version: u32,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub exported_at: DateTime<Utc>,
+    /// Settings with machine-specific values removed (see `ProjectBundle::from_project`)
+    pub settings: ProjectSettings,
+    /// Terminals to recreate, besides the main terminal
+    #[serde(default)]
+    pub terminals: Vec<TerminalLayout>,
+}
+
+/// How one of a project's terminals is set up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalLayout {
+    pub name: String,
+    pub client_id: String,
+    pub mode: TerminalMode,
+    /// For Folder mode: path relative to the project root
+    #[serde(default)]
+    pub folder_path: Option<PathBuf>,
+    /// For Worktree mode: the branch the terminal works on
+    #[serde(default)]
+    pub branch: Option<String>,
+    /// Additional root the terminal runs in. Roots are machine-specific and aren't exported,
+    /// so `import_project` reports these terminals as errors.
+    #[serde(default)]
+    pub root: Option<String>,
+    #[serde(default)]
+    pub scrollback: Option<ScrollbackLimit>,
+    #[serde(default)]
+    pub auto_resume: Option<bool>,
+    #[serde(default)]
+    pub keep_alive: Option<KeepAliveConfig>,
+    #[serde(default)]
+    pub terminal_env: Option<TerminalEnv>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub checkpoints: Option<CheckpointConfig>,
+}
+
+impl ProjectBundle {
+    /// Bundle a project and its terminals. The worktree folder is kept only when it is
+    /// inside the project (made relative); other paths wouldn't mean anything elsewhere.
+    /// Environment variables are left out, since they often hold API keys; MCP servers keep
+    /// their variables' names, with empty values for whoever imports the bundle to fill in.
+    /// Agent overrides that can carry keys are emptied the same way (see `redact_overrides`).
+    pub fn from_project<'a>(
+        project: &AdaProject,
+        terminals: impl IntoIterator<Item = &'a Terminal>,
+    ) -> Self {
+        let mut settings = project.settings.clone();
+        settings.last_visited_terminal_id = None;
+        settings.env.clear();
+        for server in &mut settings.mcp_servers {
+            server.env.values_mut().for_each(String::clear);
+        }
+        settings.agent_overrides.values_mut().for_each(redact_overrides);
+        settings.worktree_base_path = settings
+            .worktree_base_path
+            .and_then(|path| path.strip_prefix(&project.path).ok().map(PathBuf::from));
+
+        let mut terminals: Vec<&Terminal> = terminals
+            .into_iter()
+            .filter(|t| t.project_id == project.id && !t.is_main)
+            .collect();
+        terminals.sort_by_key(|t| t.created_at);
+
+        Self {
+            version: PROJECT_BUNDLE_VERSION,
+            name: project.name.clone(),
+            description: project.description.clone(),
+            exported_at: Utc::now(),
+            settings,
+            terminals: terminals.into_iter().map(TerminalLayout::from).collect(),
+        }
+    }
+}
+
+/// Empty the values of Codex config entries (which can be headers or keys of a model
+/// provider) and of the variables in Claude Code settings, keeping their names, and drop
+/// Claude Code's key helper
+fn redact_overrides(overrides: &mut AgentOverrides) {
+    overrides.codex_config.values_mut().for_each(String::clear);
+
+    let Some(claude_settings) = overrides.claude_settings.as_mut().and_then(|s| s.as_object_mut()) else {
+        return;
+    };
+    if let Some(env) = claude_settings.get_mut("env").and_then(|env| env.as_object_mut()) {
+        env.values_mut().for_each(|value| *value = serde_json::Value::String(String::new()));
+    }
+    claude_settings.remove("apiKeyHelper");
+}
+
+impl From<&Terminal> for TerminalLayout {
+    fn from(terminal: &Terminal) -> Self {
+        Self {
+            name: terminal.name.clone(),
+            client_id: terminal.client_id.clone(),
+            mode: terminal.mode,
+            folder_path: terminal.folder_path.clone(),
+            branch: terminal.branch.clone().filter(|_| terminal.mode == TerminalMode::Worktree),
+            root: terminal.root.clone(),
+            scrollback: terminal.scrollback,
+            auto_resume: Some(terminal.auto_resume),
+            keep_alive: terminal.keep_alive.clone(),
+            terminal_env: terminal.terminal_env.clone(),
+            model: terminal.model.clone(),
+            profile: terminal.profile.clone(),
+            checkpoints: terminal.checkpoints.clone(),
+        }
+    }
+}
@@ -1,21 +1,26 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use tauri::State;
 use serde::{Deserialize, Serialize};
 
+use crate::clients::{AgentOverrides, McpServer};
 use crate::error::{Error, Result};
+use crate::git;
+use crate::notifications::NotificationRule;
 use crate::state::AppState;
-use crate::terminal::create_main_terminal_internal;
-use super::{AdaProject, CreateProjectRequest, ProjectSummary, ProjectSettings};
-
-/// Check if a git repository has at least one commit
-fn has_commits(repo_path: &Path) -> bool {
-    std::process::Command::new("git")
-        .args(["rev-parse", "--verify", "HEAD"])
-        .current_dir(repo_path)
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
-}
+use crate::terminal::{
+    create_main_terminal_internal, create_terminal_internal, CreateTerminalRequest, TerminalInfo,
+    PtyHandle, TerminalMode, TerminalStatus,
+};
+use crate::terminal::checkpoint::delete_terminal_checkpoints;
+use crate::terminal::pty::kill_ptys_off_runtime;
+use crate::terminal::shell::ShellSetting;
+use super::{
+    AdaProject, ArchiveProjectResult, CreateProjectRequest, DeleteProjectOptions, DeleteProjectResult, DoctorReport, ImportProjectResult, ProjectBundle,
+    ProjectCandidate, ProjectFilter, ProjectPathStatus, ProjectRoot, ProjectStats, ProjectUsage, ProjectSummary, ProjectSettings, ProjectTemplate,
+    SaveProjectTemplateRequest, TerminalLayout, DEFAULT_SCAN_DEPTH, PROJECT_BUNDLE_VERSION,
+    collect_project_stats, collect_project_usage, diagnose_project, normalize_tags, run_setup_script, scan_for_repositories,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateProjectSettingsRequest {
@@ -25,15 +30,66 @@ pub struct UpdateProjectSettingsRequest {
     pub worktree_base_path: Option<String>,
     #[serde(default)]
     pub last_visited_terminal_id: Option<String>,
+    #[serde(default)]
+    pub shell: Option<ShellSetting>,
+    #[serde(default)]
+    pub cow_worktrees: Option<bool>,
+    #[serde(default)]
+    pub init_submodules: Option<bool>,
+    #[serde(default)]
+    pub worktree_seed_files: Option<Vec<String>>,
+    #[serde(default)]
+    pub worktree_seed_mode: Option<git::SeedMode>,
+    #[serde(default)]
+    pub worktree_name_template: Option<String>,
+    #[serde(default)]
+    pub git_identity: Option<git::GitIdentity>,
+    #[serde(default)]
+    pub commit_trailer: Option<String>,
+    #[serde(default)]
+    pub worktree_bypass: Option<git::CheckoutBypass>,
+    #[serde(default)]
+    pub env: Option<HashMap<String, String>>,
+    #[serde(default)]
+    pub agent_overrides: Option<HashMap<String, AgentOverrides>>,
+    #[serde(default)]
+    pub setup_command: Option<String>,
+    #[serde(default)]
+    pub default_terminal_mode: Option<TerminalMode>,
+    #[serde(default)]
+    pub default_base_branch: Option<String>,
+    #[serde(default)]
+    pub allowed_clients: Option<Vec<String>>,
+    #[serde(default)]
+    pub denied_clients: Option<Vec<String>>,
+    #[serde(default)]
+    pub mcp_servers: Option<Vec<McpServer>>,
+    #[serde(default)]
+    pub mute_notifications: Option<bool>,
+    /// An empty list clears the project's rules
+    #[serde(default)]
+    pub notification_rules: Option<Vec<NotificationRule>>,
 }
 
-/// Create a new project - creates directory and optionally initializes git
+/// Create a new project - creates directory and optionally initializes git.
+/// With a template, its setup script runs in the new folder and its terminals are created.
 #[tauri::command]
 pub async fn create_project(
     state: State<'_, AppState>,
     request: CreateProjectRequest,
 ) -> Result<AdaProject> {
     let path = PathBuf::from(&request.path);
+    let template = match &request.template_id {
+        Some(template_id) => Some(
+            state
+                .project_templates
+                .read()
+                .get(template_id)
+                .cloned()
+                .ok_or_else(|| Error::InvalidRequest(format!("Project template not found: {}", template_id)))?,
+        ),
+        None => None,
+    };
 
     // Check if path already exists
     if path.exists() {
@@ -57,15 +113,29 @@ pub async fn create_project(
         std::fs::create_dir_all(&path)?;
     }
 
-    let is_git_repo = if request.init_git {
+    // Scaffolding tools expect an empty folder, so the script runs before git is set up
+    if let Some(script) = template.as_ref().and_then(|t| t.setup_script.as_deref()) {
+        run_setup_script(&path, script).await?;
+    }
+
+    let settings = ProjectSettings {
+        git_identity: request.git_identity,
+        commit_trailer: request.commit_trailer,
+        default_client: template.as_ref().and_then(|t| t.default_client.clone()),
+        ..Default::default()
+    };
+
+    let init_git = template.as_ref().map_or(request.init_git, |t| t.init_git);
+    let is_git_repo = if init_git {
         // Initialize git with .worktrees in .gitignore
-        init_git_with_worktree_ignore(&path)?;
+        init_git_with_worktree_ignore(&path, &settings.commit_identity())?;
         true
     } else {
         false
     };
 
-    let project = AdaProject::new(path, is_git_repo);
+    let mut project = AdaProject::new(path, is_git_repo);
+    project.settings = settings;
 
     // Save project
     state.save_project(&project)?;
@@ -73,22 +143,77 @@ pub async fn create_project(
     // Add to state
     state.projects.write().insert(project.id.clone(), project.clone());
 
+    let Some(template) = template else {
+        return Ok(project);
+    };
+
+    if let Some(client_id) = &project.settings.default_client {
+        if let Err(e) = create_main_terminal_internal(&state, &project.id, client_id) {
+            eprintln!("[Ada] Failed to create main terminal for new project: {}", e);
+        }
+    }
+    let (_, errors) = create_layout_terminals(&state, &project.id, template.terminals);
+    for error in errors {
+        eprintln!("[Ada] Failed to create terminal from template '{}': {}", template.name, error);
+    }
+
+    // Creating terminals updated the stored project
+    let project = state
+        .projects
+        .read()
+        .get(&project.id)
+        .cloned()
+        .unwrap_or(project);
     Ok(project)
 }
 
+/// Create terminals from bundle or template layouts, returning the ones created and an
+/// error for each that couldn't be
+fn create_layout_terminals(
+    state: &AppState,
+    project_id: &str,
+    layouts: Vec<TerminalLayout>,
+) -> (Vec<TerminalInfo>, Vec<String>) {
+    let mut terminals = Vec::new();
+    let mut errors = Vec::new();
+    for layout in layouts {
+        let request = CreateTerminalRequest {
+            project_id: project_id.to_string(),
+            name: layout.name.clone(),
+            client_id: layout.client_id,
+            mode: Some(layout.mode),
+            folder_path: layout.folder_path.map(|p| p.to_string_lossy().to_string()),
+            worktree_branch: layout.branch,
+            root: layout.root,
+            scrollback: layout.scrollback,
+            wait_for_ready: false,
+            auto_resume: layout.auto_resume,
+            keep_alive: layout.keep_alive,
+            terminal_env: layout.terminal_env,
+            model: layout.model,
+            profile: layout.profile,
+        };
+        match create_terminal_internal(state, request, None, false) {
+            Ok(mut info) => {
+                if layout.checkpoints.is_some() {
+                    if let Some(t) = state.terminals.write().get_mut(&info.id) {
+                        t.checkpoints = layout.checkpoints.clone();
+                    }
+                    let _ = state.save_terminal(&info.id);
+                    info.checkpoints = layout.checkpoints;
+                }
+                terminals.push(info);
+            }
+            Err(e) => errors.push(format!("{}: {}", layout.name, e)),
+        }
+    }
+    (terminals, errors)
+}
+
 /// Initialize git in a folder with .gitignore containing .worktrees/
-fn init_git_with_worktree_ignore(repo_path: &Path) -> Result<()> {
+fn init_git_with_worktree_ignore(repo_path: &Path, identity: &git::CommitIdentity) -> Result<()> {
     // Initialize git repository
-    let output = std::process::Command::new("git")
-        .args(["init"])
-        .current_dir(repo_path)
-        .output()?;
-
-    if !output.status.success() {
-        return Err(Error::GitError(
-            String::from_utf8_lossy(&output.stderr).to_string()
-        ));
-    }
+    git::init_repo(repo_path)?;
 
     // Create .gitignore with .worktrees/
     let gitignore_path = repo_path.join(".gitignore");
@@ -104,30 +229,8 @@ fn init_git_with_worktree_ignore(repo_path: &Path) -> Result<()> {
     };
     std::fs::write(&gitignore_path, gitignore_content)?;
 
-    // Stage .gitignore
-    let output = std::process::Command::new("git")
-        .args(["add", ".gitignore"])
-        .current_dir(repo_path)
-        .output()?;
-
-    if !output.status.success() {
-        return Err(Error::GitError(
-            String::from_utf8_lossy(&output.stderr).to_string()
-        ));
-    }
-
-    // Create initial commit
-    let output = std::process::Command::new("git")
-        .args(["commit", "-m", "Initial commit (created by Ada)"])
-        .current_dir(repo_path)
-        .output()?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        if !stderr.contains("nothing to commit") {
-            return Err(Error::GitError(stderr.to_string()));
-        }
-    }
+    // Stage .gitignore and create the initial commit
+    git::commit_paths(repo_path, &[".gitignore"], "Initial commit (created by Ada)", identity)?;
 
     Ok(())
 }
@@ -152,7 +255,7 @@ fn add_worktrees_to_gitignore(repo_path: &Path) -> Result<()> {
 /// - Has at least one commit
 /// - Has .worktrees/ in .gitignore
 /// Returns Ok(true) if the repo is properly configured, Ok(false) if not a git repo
-fn ensure_git_repo_configured(repo_path: &Path) -> Result<bool> {
+fn ensure_git_repo_configured(repo_path: &Path, identity: &git::CommitIdentity) -> Result<bool> {
     eprintln!("[Ada] ensure_git_repo_configured called for: {:?}", repo_path);
 
     let git_dir = repo_path.join(".git");
@@ -168,7 +271,7 @@ fn ensure_git_repo_configured(repo_path: &Path) -> Result<bool> {
     add_worktrees_to_gitignore(repo_path)?;
 
     // Check if repo has commits - if not, create initial commit with all necessary files
-    let has_existing_commits = has_commits(repo_path);
+    let has_existing_commits = git::has_commits(repo_path);
     eprintln!("[Ada] has_commits: {}", has_existing_commits);
 
     if !has_existing_commits {
@@ -182,61 +285,28 @@ fn ensure_git_repo_configured(repo_path: &Path) -> Result<bool> {
             eprintln!("[Ada] .gitkeep already exists");
         }
 
-        // Stage all Ada-related files
-        eprintln!("[Ada] Staging .gitignore and .gitkeep");
-        let output = std::process::Command::new("git")
-            .args(["add", ".gitignore", ".gitkeep"])
-            .current_dir(repo_path)
-            .output()?;
-
-        if !output.status.success() {
-            let err_msg = format!("Failed to stage files: {}", String::from_utf8_lossy(&output.stderr));
-            eprintln!("[Ada] {}", err_msg);
-            return Err(Error::GitError(err_msg));
-        }
-        eprintln!("[Ada] Files staged successfully");
-
-        // Create the initial commit
-        eprintln!("[Ada] Creating initial commit");
-        let output = std::process::Command::new("git")
-            .args(["commit", "-m", "Initial commit (created by Ada)"])
-            .current_dir(repo_path)
-            .output()?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            eprintln!("[Ada] Commit stderr: {}", stderr);
-            // Ignore "nothing to commit" - means files are already committed
-            if !stderr.contains("nothing to commit") {
-                return Err(Error::GitError(
-                    format!("Failed to create initial commit: {}", stderr)
-                ));
+        // Stage the Ada-related files and create the initial commit
+        eprintln!("[Ada] Creating initial commit with .gitignore and .gitkeep");
+        match git::commit_paths(repo_path, &[".gitignore", ".gitkeep"], "Initial commit (created by Ada)", identity) {
+            Ok(true) => eprintln!("[Ada] Initial commit created successfully"),
+            // Nothing to commit - the files are already committed
+            Ok(false) => eprintln!("[Ada] Nothing to commit"),
+            Err(e) => {
+                eprintln!("[Ada] Failed to create initial commit: {}", e);
+                return Err(Error::GitError(format!("Failed to create initial commit: {}", e)));
             }
-        } else {
-            eprintln!("[Ada] Initial commit created successfully");
         }
     } else {
         // Repo already has commits - just ensure .gitignore changes are committed
         eprintln!("[Ada] Repo already has commits, checking .gitignore status");
-        let output = std::process::Command::new("git")
-            .args(["status", "--porcelain", ".gitignore"])
-            .current_dir(repo_path)
-            .output()?;
-
-        let status = String::from_utf8_lossy(&output.stdout);
-        eprintln!("[Ada] .gitignore status: '{}'", status.trim());
-        if !status.is_empty() {
+        let gitignore_modified = git::is_path_modified(repo_path, ".gitignore")?;
+        eprintln!("[Ada] .gitignore modified: {}", gitignore_modified);
+        if gitignore_modified {
             // .gitignore has changes, stage and commit them
             eprintln!("[Ada] Committing .gitignore changes");
-            let _ = std::process::Command::new("git")
-                .args(["add", ".gitignore"])
-                .current_dir(repo_path)
-                .output();
-
-            let _ = std::process::Command::new("git")
-                .args(["commit", "-m", "Add .worktrees to .gitignore (Ada)"])
-                .current_dir(repo_path)
-                .output();
+            if let Err(e) = git::commit_paths(repo_path, &[".gitignore"], "Add .worktrees to .gitignore (Ada)", identity) {
+                eprintln!("[Ada] Failed to commit .gitignore: {}", e);
+            }
         }
     }
 
@@ -268,7 +338,7 @@ pub async fn open_project(
     }
 
     // Check if it's a git repository and ensure it's properly configured
-    let is_git_repo = ensure_git_repo_configured(&path)?;
+    let is_git_repo = ensure_git_repo_configured(&path, &git::CommitIdentity::default())?;
 
     let project = AdaProject::new(path, is_git_repo);
 
@@ -281,13 +351,702 @@ pub async fn open_project(
     Ok(project)
 }
 
+/// Find git repositories under `root_path` that could be added as projects in bulk
+/// (see `scan_for_repositories` for what is skipped). Each candidate says whether it is
+/// already open; the chosen ones are then added with `open_project`.
+#[tauri::command]
+pub async fn scan_for_projects(
+    state: State<'_, AppState>,
+    root_path: String,
+    max_depth: Option<usize>,
+    ignore: Option<Vec<String>>,
+) -> Result<Vec<ProjectCandidate>> {
+    let root = PathBuf::from(&root_path);
+    let repositories = scan_for_repositories(
+        &root,
+        max_depth.unwrap_or(DEFAULT_SCAN_DEPTH),
+        &ignore.unwrap_or_default(),
+    )?;
+
+    let projects = state.projects.read();
+    Ok(repositories
+        .into_iter()
+        .map(|path| ProjectCandidate {
+            name: path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| root_path.clone()),
+            branch: git::get_current_branch_internal(&path).ok().filter(|b| b != "HEAD"),
+            project_id: projects.values().find(|p| p.path == path).map(|p| p.id.clone()),
+            path,
+        })
+        .collect())
+}
+
+/// Projects matching `filter` (active ones by default), pinned first, then in the user's order
 #[tauri::command]
 pub async fn list_projects(
     state: State<'_, AppState>,
+    include_archived: Option<bool>,
+    filter: Option<ProjectFilter>,
 ) -> Result<Vec<ProjectSummary>> {
+    let mut filter = filter.unwrap_or_default();
+    filter.include_archived |= include_archived.unwrap_or(false);
     let projects = state.projects.read();
-    let summaries: Vec<ProjectSummary> = projects.values().map(|p| p.into()).collect();
-    Ok(summaries)
+    let mut projects: Vec<&AdaProject> = projects
+        .values()
+        .filter(|p| filter.matches(p))
+        .collect();
+    projects.sort_by_key(|p| p.list_order());
+    Ok(projects.into_iter().map(ProjectSummary::from).collect())
+}
+
+/// Reorder projects: the given projects come first, in the given order, followed by the rest
+/// in their current order. Pinned projects are still listed before unpinned ones.
+#[tauri::command]
+pub async fn update_project_order(
+    state: State<'_, AppState>,
+    project_ids: Vec<String>,
+) -> Result<Vec<ProjectSummary>> {
+    let mut projects = state.projects.write();
+    if let Some(missing) = project_ids.iter().find(|id| !projects.contains_key(*id)) {
+        return Err(Error::ProjectNotFound(missing.clone()));
+    }
+
+    let mut rest: Vec<&AdaProject> = projects
+        .values()
+        .filter(|p| !project_ids.contains(&p.id))
+        .collect();
+    rest.sort_by_key(|p| p.list_order());
+    let mut order: Vec<String> = Vec::with_capacity(projects.len());
+    for project_id in project_ids.into_iter().chain(rest.into_iter().map(|p| p.id.clone())) {
+        if !order.contains(&project_id) {
+            order.push(project_id);
+        }
+    }
+
+    for (index, project_id) in order.iter().enumerate() {
+        if let Some(project) = projects.get_mut(project_id) {
+            let sort_index = Some(index as u32);
+            if project.sort_index != sort_index {
+                project.sort_index = sort_index;
+                state.save_project(project)?;
+            }
+        }
+    }
+
+    let mut ordered: Vec<&AdaProject> = projects.values().collect();
+    ordered.sort_by_key(|p| p.list_order());
+    Ok(ordered.into_iter().map(ProjectSummary::from).collect())
+}
+
+/// Pin or unpin a project, keeping pinned projects at the top of `list_projects`
+#[tauri::command]
+pub async fn set_project_pinned(
+    state: State<'_, AppState>,
+    project_id: String,
+    pinned: bool,
+) -> Result<AdaProject> {
+    let mut projects = state.projects.write();
+    let project = projects
+        .get_mut(&project_id)
+        .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
+
+    project.pinned = pinned;
+    project.updated_at = chrono::Utc::now();
+    state.save_project(project)?;
+
+    Ok(project.clone())
+}
+
+/// Replace a project's tags. Tags are trimmed, and empty or repeated ones dropped.
+#[tauri::command]
+pub async fn set_project_tags(
+    state: State<'_, AppState>,
+    project_id: String,
+    tags: Vec<String>,
+) -> Result<AdaProject> {
+    let mut projects = state.projects.write();
+    let project = projects
+        .get_mut(&project_id)
+        .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
+
+    project.tags = normalize_tags(tags);
+    project.updated_at = chrono::Utc::now();
+    state.save_project(project)?;
+
+    Ok(project.clone())
+}
+
+/// Refuse MCP servers without a name, with the same name, or without exactly one of a
+/// command and a URL
+fn validate_mcp_servers(servers: &[McpServer]) -> Result<()> {
+    for (index, server) in servers.iter().enumerate() {
+        server.validate()?;
+        if servers[..index].iter().any(|other| other.name == server.name) {
+            return Err(Error::InvalidRequest(format!("There are two MCP servers named '{}'", server.name)));
+        }
+    }
+    Ok(())
+}
+
+/// Checks shared by `update_project_settings` and `import_project`
+fn validate_project_settings(settings: &ProjectSettings) -> Result<()> {
+    if matches!(settings.default_terminal_mode, Some(TerminalMode::Main | TerminalMode::Folder)) {
+        return Err(Error::InvalidRequest(
+            "The default terminal mode must be worktree or current branch".into(),
+        ));
+    }
+    validate_mcp_servers(&settings.mcp_servers)?;
+    for rule in settings.notification_rules.iter().flatten() {
+        rule.validate()?;
+    }
+    if let Some(default_client) = &settings.default_client {
+        settings.check_client_allowed(default_client)?;
+    }
+    Ok(())
+}
+
+/// Add an MCP server to a project, replacing the one with the same name. Agents started
+/// from then on get it; running ones pick it up when restarted.
+#[tauri::command]
+pub async fn set_mcp_server(
+    state: State<'_, AppState>,
+    project_id: String,
+    server: McpServer,
+) -> Result<AdaProject> {
+    server.validate()?;
+
+    let mut projects = state.projects.write();
+    let project = projects
+        .get_mut(&project_id)
+        .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
+
+    let servers = &mut project.settings.mcp_servers;
+    match servers.iter_mut().find(|existing| existing.name == server.name) {
+        Some(existing) => *existing = server,
+        None => servers.push(server),
+    }
+    project.updated_at = chrono::Utc::now();
+    state.save_project(project)?;
+
+    Ok(project.clone())
+}
+
+#[tauri::command]
+pub async fn remove_mcp_server(
+    state: State<'_, AppState>,
+    project_id: String,
+    name: String,
+) -> Result<AdaProject> {
+    let mut projects = state.projects.write();
+    let project = projects
+        .get_mut(&project_id)
+        .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
+
+    let count = project.settings.mcp_servers.len();
+    project.settings.mcp_servers.retain(|server| server.name != name);
+    if project.settings.mcp_servers.len() == count {
+        return Err(Error::InvalidRequest(format!("MCP server not found: {}", name)));
+    }
+    project.updated_at = chrono::Utc::now();
+    state.save_project(project)?;
+
+    Ok(project.clone())
+}
+
+/// Archive a project: stop its agents, optionally remove its worktrees, and hide it from
+/// `list_projects`. Terminals, settings and branches are kept, so `unarchive_project` brings
+/// it back as it was. Worktrees with uncommitted changes or unpushed commits are kept.
+#[tauri::command]
+pub async fn archive_project(
+    state: State<'_, AppState>,
+    project_id: String,
+    remove_worktrees: Option<bool>,
+) -> Result<ArchiveProjectResult> {
+    let project = {
+        let projects = state.projects.read();
+        projects
+            .get(&project_id)
+            .cloned()
+            .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?
+    };
+
+    // Stop the agents, but keep the terminals so the project can be picked up again
+    let terminal_ids: Vec<String> = {
+        let terminals = state.terminals.read();
+        // Including ones whose connection just dropped, which are stopped already
+        for t in terminals.values().filter(|t| t.project_id == project_id) {
+            state.keep_alive.cancel_reconnect(&t.id);
+        }
+        terminals
+            .values()
+            .filter(|t| t.project_id == project_id && t.status != TerminalStatus::Stopped)
+            .map(|t| t.id.clone())
+            .collect()
+    };
+    // Signal every agent before waiting on any of them
+    let pty_handles: Vec<PtyHandle> = {
+        let mut pty_handles = state.pty_handles.write();
+        terminal_ids.iter().filter_map(|id| pty_handles.remove(id)).collect()
+    };
+    kill_ptys_off_runtime(pty_handles).await;
+    for terminal_id in &terminal_ids {
+        if let Some(t) = state.terminals.write().get_mut(terminal_id) {
+            t.mark_stopped(chrono::Utc::now());
+        }
+        let _ = state.save_terminal(terminal_id);
+    }
+    state.pty_pool.drain_project(&project_id);
+
+    let (removed_worktrees, kept_worktrees) = if remove_worktrees.unwrap_or(false) {
+        remove_project_worktrees(&state, &project, false)
+    } else {
+        (Vec::new(), Vec::new())
+    };
+
+    let archived = {
+        let mut projects = state.projects.write();
+        let project = projects
+            .get_mut(&project_id)
+            .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
+        let now = chrono::Utc::now();
+        project.archived_at = Some(now);
+        project.updated_at = now;
+        project.clone()
+    };
+    state.save_project(&archived)?;
+
+    eprintln!(
+        "[Ada] Archived project {}: stopped {} terminals, removed {} worktrees",
+        project_id,
+        terminal_ids.len(),
+        removed_worktrees.len()
+    );
+
+    Ok(ArchiveProjectResult {
+        project: archived,
+        stopped_terminals: terminal_ids,
+        removed_worktrees,
+        kept_worktrees,
+    })
+}
+
+/// Remove the worktrees Ada created for a project, in its own folder and its additional
+/// roots; never the main checkouts. Unless `force` is set, worktrees with uncommitted
+/// changes or unpushed commits are kept. Returns the removed and the kept worktrees.
+fn remove_project_worktrees(
+    state: &AppState,
+    project: &AdaProject,
+    force: bool,
+) -> (Vec<String>, Vec<(String, String)>) {
+    let mut removed = Vec::new();
+    let mut kept = Vec::new();
+
+    let roots = std::iter::once((None, project.path.clone(), project.is_git_repo)).chain(
+        project.additional_roots.iter().map(|r| (Some(r.name.as_str()), r.path.clone(), r.is_git_repo)),
+    );
+    for (root, repo_path, is_git_repo) in roots {
+        if !is_git_repo || !repo_path.join(".git").exists() {
+            continue;
+        }
+        // Compared canonicalized, so symlinks (e.g. /tmp on macOS) or `..` in either
+        // path can't make a worktree look outside the base, or another folder inside it.
+        // A base that doesn't exist holds no worktrees.
+        let Ok(worktree_base) = project.worktree_base(root).and_then(|base| Ok(base.canonicalize()?)) else {
+            continue;
+        };
+        let worktrees = match git::list_worktrees_internal(&repo_path) {
+            Ok(worktrees) => worktrees,
+            Err(e) => {
+                eprintln!("[Ada] Failed to list worktrees of {}: {}", repo_path.display(), e);
+                continue;
+            }
+        };
+        for worktree in worktrees {
+            let path = PathBuf::from(&worktree.path);
+            // Only the worktrees Ada manages, never the main checkout (a worktree whose
+            // folder is gone can't be canonicalized, so it is compared as git recorded it)
+            let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+            if !canonical.starts_with(&worktree_base) {
+                continue;
+            }
+            match git::remove_worktree_internal(&repo_path, &path, false, force) {
+                Ok(()) => {
+                    state.worktree_disk_usage.forget(&path);
+                    removed.push(worktree.path);
+                }
+                Err(e) => kept.push((worktree.path, e.to_string())),
+            }
+        }
+    }
+
+    (removed, kept)
+}
+
+/// Export a project's Ada configuration (settings and terminal layouts, not the repository)
+/// as a bundle, written to `path` as JSON when given
+#[tauri::command]
+pub async fn export_project(
+    state: State<'_, AppState>,
+    project_id: String,
+    path: Option<String>,
+) -> Result<ProjectBundle> {
+    let bundle = {
+        let projects = state.projects.read();
+        let project = projects
+            .get(&project_id)
+            .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
+        ProjectBundle::from_project(project, state.terminals.read().values())
+    };
+
+    if let Some(path) = path {
+        std::fs::write(&path, serde_json::to_string_pretty(&bundle)?)?;
+    }
+    Ok(bundle)
+}
+
+/// Set up a project from a bundle written by `export_project`, for a checkout of the
+/// repository at `project_path` (cloned separately). The bundle's terminals are created
+/// and their agents launched; ones that fail (e.g. a client that isn't installed) are
+/// reported in the result.
+#[tauri::command]
+pub async fn import_project(
+    state: State<'_, AppState>,
+    bundle_path: String,
+    project_path: String,
+) -> Result<ImportProjectResult> {
+    let bundle: ProjectBundle = serde_json::from_str(&std::fs::read_to_string(&bundle_path)?)?;
+    if bundle.version > PROJECT_BUNDLE_VERSION {
+        return Err(Error::InvalidRequest(format!(
+            "This bundle was exported by a newer version of Ada (format {})",
+            bundle.version
+        )));
+    }
+
+    let path = PathBuf::from(&project_path);
+    if !path.exists() {
+        return Err(Error::InvalidRequest("The selected folder does not exist.".into()));
+    }
+    if state.projects.read().values().any(|p| p.path == path) {
+        return Err(Error::InvalidRequest(
+            "This folder is already open as a project.".into()
+        ));
+    }
+
+    let mut settings = bundle.settings;
+    validate_project_settings(&settings)?;
+    settings.worktree_base_path = settings.worktree_base_path.map(|base| path.join(base));
+    let is_git_repo = ensure_git_repo_configured(&path, &settings.commit_identity())?;
+
+    let mut project = AdaProject::new(path, is_git_repo);
+    project.name = bundle.name;
+    project.description = bundle.description;
+    project.settings = settings;
+    state.save_project(&project)?;
+    state.projects.write().insert(project.id.clone(), project.clone());
+
+    if let Some(client_id) = &project.settings.default_client {
+        if let Err(e) = create_main_terminal_internal(&state, &project.id, client_id) {
+            eprintln!("[Ada] Failed to create main terminal for imported project: {}", e);
+        }
+    }
+
+    let (terminals, errors) = create_layout_terminals(&state, &project.id, bundle.terminals);
+
+    let project = state
+        .projects
+        .read()
+        .get(&project.id)
+        .cloned()
+        .unwrap_or(project);
+    Ok(ImportProjectResult {
+        project,
+        terminals,
+        errors,
+    })
+}
+
+#[tauri::command]
+pub async fn list_project_templates(state: State<'_, AppState>) -> Result<Vec<ProjectTemplate>> {
+    let mut templates: Vec<ProjectTemplate> = state.project_templates.read().values().cloned().collect();
+    templates.sort_by_key(|t| t.created_at);
+    Ok(templates)
+}
+
+/// Create a project template, or replace the one with the request's `id`
+#[tauri::command]
+pub async fn save_project_template(
+    state: State<'_, AppState>,
+    request: SaveProjectTemplateRequest,
+) -> Result<ProjectTemplate> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(Error::InvalidRequest("A template needs a name".into()));
+    }
+
+    let (id, created_at) = match request.id {
+        Some(id) => {
+            let created_at = state
+                .project_templates
+                .read()
+                .get(&id)
+                .map(|t| t.created_at)
+                .ok_or_else(|| Error::InvalidRequest(format!("Project template not found: {}", id)))?;
+            (id, created_at)
+        }
+        None => (uuid::Uuid::new_v4().to_string(), chrono::Utc::now()),
+    };
+
+    let template = ProjectTemplate {
+        id,
+        name: name.to_string(),
+        description: request.description,
+        init_git: request.init_git,
+        default_client: request.default_client,
+        setup_script: request.setup_script.filter(|script| !script.trim().is_empty()),
+        terminals: request.terminals,
+        created_at,
+    };
+
+    state.save_project_template(&template)?;
+    state.project_templates.write().insert(template.id.clone(), template.clone());
+
+    Ok(template)
+}
+
+/// Save a project's default client and terminals as a new template
+#[tauri::command]
+pub async fn create_template_from_project(
+    state: State<'_, AppState>,
+    project_id: String,
+    name: String,
+) -> Result<ProjectTemplate> {
+    let request = {
+        let projects = state.projects.read();
+        let project = projects
+            .get(&project_id)
+            .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
+        let bundle = ProjectBundle::from_project(project, state.terminals.read().values());
+        SaveProjectTemplateRequest {
+            id: None,
+            name,
+            description: project.description.clone(),
+            init_git: project.is_git_repo,
+            default_client: project.settings.default_client.clone(),
+            setup_script: None,
+            terminals: bundle.terminals,
+        }
+    };
+
+    save_project_template(state, request).await
+}
+
+#[tauri::command]
+pub async fn delete_project_template(
+    state: State<'_, AppState>,
+    template_id: String,
+) -> Result<()> {
+    if state.project_templates.write().remove(&template_id).is_none() {
+        return Err(Error::InvalidRequest(format!("Project template not found: {}", template_id)));
+    }
+    state.delete_project_template_file(&template_id)?;
+    Ok(())
+}
+
+/// Make an archived project active again. Its agents are not restarted.
+#[tauri::command]
+pub async fn unarchive_project(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> Result<AdaProject> {
+    let project = {
+        let mut projects = state.projects.write();
+        let project = projects
+            .get_mut(&project_id)
+            .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
+        project.archived_at = None;
+        project.updated_at = chrono::Utc::now();
+        project.clone()
+    };
+    state.save_project(&project)?;
+    Ok(project)
+}
+
+/// Add a folder (e.g. a sibling repo) as an additional root of a project, so terminals can
+/// run in it with `root`. The name defaults to the folder name. Git repositories get the
+/// same set-up as `open_project` gives the project folder.
+#[tauri::command]
+pub async fn add_project_root(
+    state: State<'_, AppState>,
+    project_id: String,
+    path: String,
+    name: Option<String>,
+) -> Result<AdaProject> {
+    let path = PathBuf::from(&path);
+    if !path.is_dir() {
+        return Err(Error::InvalidRequest("The selected folder does not exist.".into()));
+    }
+
+    let identity = {
+        let projects = state.projects.read();
+        let project = projects
+            .get(&project_id)
+            .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
+        if project.path == path || project.additional_roots.iter().any(|r| r.path == path) {
+            return Err(Error::InvalidRequest(format!("{} is already part of this project", path.display())));
+        }
+        project.settings.commit_identity()
+    };
+
+    let name = name
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .or_else(|| path.file_name().map(|n| n.to_string_lossy().to_string()))
+        .ok_or_else(|| Error::InvalidRequest("The root needs a name".into()))?;
+    let is_git_repo = ensure_git_repo_configured(&path, &identity)?;
+
+    let mut projects = state.projects.write();
+    let project = projects
+        .get_mut(&project_id)
+        .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
+    if project.additional_roots.iter().any(|r| r.name == name) {
+        return Err(Error::InvalidRequest(format!("Project already has a root named '{}'", name)));
+    }
+
+    project.additional_roots.push(ProjectRoot { name, path, is_git_repo });
+    project.updated_at = chrono::Utc::now();
+    state.save_project(project)?;
+
+    Ok(project.clone())
+}
+
+/// Remove an additional root from a project. Its terminals have to be closed first;
+/// the folder itself is left alone.
+#[tauri::command]
+pub async fn remove_project_root(
+    state: State<'_, AppState>,
+    project_id: String,
+    name: String,
+) -> Result<AdaProject> {
+    let in_use = state
+        .terminals
+        .read()
+        .values()
+        .filter(|t| t.project_id == project_id && t.root.as_deref() == Some(name.as_str()))
+        .count();
+    if in_use > 0 {
+        return Err(Error::InvalidRequest(format!(
+            "Close the {} terminal(s) running in '{}' before removing it",
+            in_use, name
+        )));
+    }
+
+    let mut projects = state.projects.write();
+    let project = projects
+        .get_mut(&project_id)
+        .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
+    let before = project.additional_roots.len();
+    project.additional_roots.retain(|r| r.name != name);
+    if project.additional_roots.len() == before {
+        return Err(Error::InvalidRequest(format!("Project has no root named '{}'", name)));
+    }
+
+    project.updated_at = chrono::Utc::now();
+    state.save_project(project)?;
+
+    Ok(project.clone())
+}
+
+/// Check the project's folders, repository, worktrees, hooks and default agent, and
+/// report what is wrong with suggestions for fixing it
+#[tauri::command]
+pub async fn project_doctor(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> Result<DoctorReport> {
+    let project = state
+        .projects
+        .read()
+        .get(&project_id)
+        .cloned()
+        .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
+
+    // Look for the agent again, in case it was installed since Ada started
+    let client = project
+        .settings
+        .default_client
+        .as_ref()
+        .and_then(|client_id| state.clients.read().get(client_id).cloned())
+        .map(|mut client| {
+            client.detect_installation();
+            client
+        });
+    let terminals: Vec<_> = state
+        .terminals
+        .read()
+        .values()
+        .filter(|t| t.project_id == project_id)
+        .cloned()
+        .collect();
+
+    Ok(diagnose_project(&project, client.as_ref(), &terminals))
+}
+
+/// Tokens used by a project's agents, in total and by terminal, read from the agents' own
+/// session logs (Claude Code and Codex)
+#[tauri::command]
+pub async fn get_project_usage(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> Result<ProjectUsage> {
+    let project = state
+        .projects
+        .read()
+        .get(&project_id)
+        .cloned()
+        .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
+    let terminals: Vec<_> = state
+        .terminals
+        .read()
+        .values()
+        .filter(|t| t.project_id == project_id)
+        .cloned()
+        .collect();
+    let client_types: HashMap<String, _> = state
+        .clients
+        .read()
+        .values()
+        .map(|c| (c.id.clone(), c.client_type))
+        .collect();
+
+    // Reading the agents' session logs blocks, so keep it off the async runtime's threads
+    tauri::async_runtime::spawn_blocking(move || collect_project_usage(&project, &terminals, &client_types))
+        .await
+        .map_err(|e| Error::IoError(e.to_string()))
+}
+
+/// Session counts, agent time, shell command counts and worktrees of a project
+#[tauri::command]
+pub async fn get_project_stats(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> Result<ProjectStats> {
+    let project = state
+        .projects
+        .read()
+        .get(&project_id)
+        .cloned()
+        .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
+    let terminals: Vec<_> = state
+        .terminals
+        .read()
+        .values()
+        .filter(|t| t.project_id == project_id)
+        .cloned()
+        .collect();
+
+    Ok(collect_project_stats(&project, &terminals, chrono::Utc::now()))
 }
 
 #[tauri::command]
@@ -311,42 +1070,155 @@ pub async fn get_project(
     // Always check and configure git repo if .git exists
     // This handles both: transitioning from non-git to git, AND ensuring existing git repos are properly configured
     eprintln!("[Ada] Checking git configuration...");
-    let is_now_git = ensure_git_repo_configured(&project.path)?;
+    let is_now_git = ensure_git_repo_configured(&project.path, &project.settings.commit_identity())?;
     eprintln!("[Ada] ensure_git_repo_configured returned: {}", is_now_git);
 
-    // Update state if git status changed
-    if is_now_git && !project.is_git_repo {
-        eprintln!("[Ada] Project is now a git repo, updating state...");
+    // Record the visit, and update state if git status changed
+    let updated_project = {
         let mut projects = state.projects.write();
-        if let Some(p) = projects.get_mut(&project_id) {
+        let p = projects
+            .get_mut(&project_id)
+            .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
+        let now = chrono::Utc::now();
+        p.last_opened_at = Some(now);
+        if is_now_git && !p.is_git_repo {
+            eprintln!("[Ada] Project is now a git repo, updating state...");
             p.is_git_repo = true;
-            p.updated_at = chrono::Utc::now();
+            p.updated_at = now;
+        }
+        p.clone()
+    };
+
+    // Persist the change
+    state.save_project(&updated_project)?;
+
+    Ok(updated_project)
+}
+
+/// Point a project at the new location of its folder, after it was moved. The worktree
+/// folder, additional roots and terminals inside the old folder move with it, and git is
+/// told where the worktrees are now. The project's agents must be stopped first.
+#[tauri::command]
+pub async fn relocate_project(
+    state: State<'_, AppState>,
+    project_id: String,
+    path: String,
+) -> Result<AdaProject> {
+    let new_path = PathBuf::from(&path);
+    if !new_path.is_dir() {
+        return Err(Error::InvalidRequest("The selected folder does not exist.".into()));
+    }
+
+    let project = state
+        .projects
+        .read()
+        .get(&project_id)
+        .cloned()
+        .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
+    if let Some(other) = state.projects.read().values().find(|p| p.id != project_id && p.path == new_path) {
+        return Err(Error::InvalidRequest(format!("{} is already the project '{}'", path, other.name)));
+    }
+    let running = state
+        .terminals
+        .read()
+        .values()
+        .any(|t| t.project_id == project_id && t.status != TerminalStatus::Stopped && t.status != TerminalStatus::Error);
+    if running {
+        return Err(Error::InvalidRequest("Stop the project's agents before relocating it".into()));
+    }
 
-            let updated_project = p.clone();
-            drop(projects); // Release lock before saving
+    let old_path = project.path.clone();
+    let rebase = |path: &Path| path.strip_prefix(&old_path).ok().map(|relative| new_path.join(relative));
+
+    // Warm shells were started in the old folder
+    state.pty_pool.drain_project(&project_id);
+
+    let mut worktree_paths = Vec::new();
+    let terminal_ids: Vec<String> = {
+        let mut terminals = state.terminals.write();
+        terminals
+            .values_mut()
+            .filter(|t| t.project_id == project_id)
+            .map(|t| {
+                if let Some(working_dir) = rebase(&t.working_dir) {
+                    t.working_dir = working_dir;
+                }
+                if let Some(worktree_path) = t.worktree_path.as_deref().and_then(&rebase) {
+                    worktree_paths.push(worktree_path.clone());
+                    t.worktree_path = Some(worktree_path);
+                }
+                t.id.clone()
+            })
+            .collect()
+    };
+    for terminal_id in &terminal_ids {
+        let _ = state.save_terminal(terminal_id);
+    }
 
-            // Persist the change
-            state.save_project(&updated_project)?;
-            eprintln!("[Ada] Project state updated and saved");
+    let is_git_repo = ensure_git_repo_configured(&new_path, &project.settings.commit_identity())?;
 
-            return Ok(updated_project);
+    let relocated = {
+        let mut projects = state.projects.write();
+        let project = projects
+            .get_mut(&project_id)
+            .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
+        project.path = new_path.clone();
+        project.is_git_repo = is_git_repo;
+        project.path_status = ProjectPathStatus::Ok;
+        if let Some(base) = project.settings.worktree_base_path.as_deref().and_then(&rebase) {
+            project.settings.worktree_base_path = Some(base);
+        }
+        for root in &mut project.additional_roots {
+            if let Some(path) = rebase(&root.path) {
+                root.path = path;
+            }
+        }
+        project.updated_at = chrono::Utc::now();
+        project.clone()
+    };
+    state.save_project(&relocated)?;
+
+    // The repository and its worktrees still point at each other's old locations
+    if is_git_repo {
+        if let Ok(entries) = relocated.worktree_base(None).and_then(|base| Ok(std::fs::read_dir(base)?)) {
+            worktree_paths.extend(
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.join(".git").is_file()),
+            );
+        }
+        worktree_paths.sort();
+        worktree_paths.dedup();
+        worktree_paths.retain(|path| path.exists());
+        if let Err(e) = git::repair_worktrees(&new_path, &worktree_paths) {
+            eprintln!("[Ada] {} in {:?}", e, new_path);
         }
     }
 
-    Ok(project)
+    eprintln!("[Ada] Relocated project {} from {:?} to {:?}", project_id, old_path, new_path);
+
+    Ok(relocated)
 }
 
+/// Delete a project: stop and forget its terminals (deleting their saved metadata and
+/// transcripts), its terminal groups and warm shells, and take it out of any workspaces.
+/// With `remove_worktrees`, the worktrees Ada created for it are removed too; the
+/// repository itself is never touched.
 #[tauri::command]
 pub async fn delete_project(
     state: State<'_, AppState>,
     project_id: String,
-) -> Result<()> {
-    // Remove from state
-    let project = state.projects.write().remove(&project_id);
+    options: Option<DeleteProjectOptions>,
+) -> Result<DeleteProjectResult> {
+    let options = options.unwrap_or_default();
 
-    if project.is_none() {
-        return Err(Error::ProjectNotFound(project_id));
-    }
+    // Remove from state
+    let project = state
+        .projects
+        .write()
+        .remove(&project_id)
+        .ok_or_else(|| Error::ProjectNotFound(project_id.clone()))?;
 
     // Clean up terminals associated with this project
     let terminal_ids_to_remove: Vec<String> = {
@@ -358,27 +1230,72 @@ pub async fn delete_project(
             .collect()
     };
 
+    // Stop the processes, signalling every one before waiting on any of them
+    let pty_handles: Vec<PtyHandle> = {
+        let mut pty_handles = state.pty_handles.write();
+        terminal_ids_to_remove.iter().filter_map(|id| pty_handles.remove(id)).collect()
+    };
+    kill_ptys_off_runtime(pty_handles).await;
+
     for terminal_id in &terminal_ids_to_remove {
-        // Remove PTY handle (will stop the process)
-        state.pty_handles.write().remove(terminal_id);
         // Remove output buffer
         state.output_buffers.write().remove(terminal_id);
-        // Remove terminal from state
-        state.terminals.write().remove(terminal_id);
-        // Delete terminal file
+        // Remove terminal from state, with its checkpoints (before its worktree goes)
+        let terminal = state.terminals.write().remove(terminal_id);
+        if let Some(terminal) = terminal {
+            delete_terminal_checkpoints(&terminal);
+        }
+        // Delete terminal file and transcript
         let _ = state.delete_terminal_file(terminal_id);
     }
 
+    // Stop any warm spare shells kept for this project
+    state.pty_pool.drain_project(&project_id);
+
+    // Worktrees go once their agents are stopped, so nothing holds files open in them
+    let (removed_worktrees, kept_worktrees) = if options.remove_worktrees {
+        remove_project_worktrees(&state, &project, options.force_remove_worktrees)
+    } else {
+        (Vec::new(), Vec::new())
+    };
+
+    // Drop the project's terminal groups
+    let group_ids: Vec<String> = state
+        .terminal_groups
+        .read()
+        .values()
+        .filter(|g| g.project_id == project_id)
+        .map(|g| g.id.clone())
+        .collect();
+    for group_id in &group_ids {
+        state.terminal_groups.write().remove(group_id);
+        let _ = state.delete_terminal_group_file(group_id);
+    }
+
+    // Take the project out of any workspaces it belongs to
+    for workspace in state.workspaces.write().values_mut() {
+        if workspace.project_ids.contains(&project_id) {
+            workspace.project_ids.retain(|id| id != &project_id);
+            workspace.updated_at = chrono::Utc::now();
+            let _ = state.save_workspace(workspace);
+        }
+    }
+
     eprintln!(
-        "[Ada] Deleted project {} and {} associated terminals",
+        "[Ada] Deleted project {} and {} associated terminals, removed {} worktrees",
         project_id,
-        terminal_ids_to_remove.len()
+        terminal_ids_to_remove.len(),
+        removed_worktrees.len()
     );
 
     // Delete persisted project file
     state.delete_project_file(&project_id)?;
 
-    Ok(())
+    Ok(DeleteProjectResult {
+        closed_terminals: terminal_ids_to_remove,
+        removed_worktrees,
+        kept_worktrees,
+    })
 }
 
 #[tauri::command]
@@ -396,15 +1313,80 @@ pub async fn update_project_settings(
             .get_mut(&request.project_id)
             .ok_or_else(|| Error::ProjectNotFound(request.project_id.clone()))?;
 
-        // Update settings, preserving last_visited_terminal_id if not provided
+        // Update settings, preserving fields the request doesn't provide
         let last_visited = request.last_visited_terminal_id.or_else(|| project.settings.last_visited_terminal_id.clone());
+        let shell = request.shell.or_else(|| project.settings.shell.clone());
+        let cow_worktrees = request.cow_worktrees.unwrap_or(project.settings.cow_worktrees);
+        let init_submodules = request.init_submodules.unwrap_or(project.settings.init_submodules);
+        let worktree_seed_files = request
+            .worktree_seed_files
+            .unwrap_or_else(|| project.settings.worktree_seed_files.clone());
+        let worktree_seed_mode = request.worktree_seed_mode.unwrap_or(project.settings.worktree_seed_mode);
+        let worktree_name_template = request
+            .worktree_name_template
+            .or_else(|| project.settings.worktree_name_template.clone());
+        let git_identity = request.git_identity.or_else(|| project.settings.git_identity.clone());
+        let commit_trailer = request.commit_trailer.or_else(|| project.settings.commit_trailer.clone());
+        let worktree_bypass = request.worktree_bypass.unwrap_or(project.settings.worktree_bypass);
+        let env = request.env.unwrap_or_else(|| project.settings.env.clone());
+        let agent_overrides = request
+            .agent_overrides
+            .unwrap_or_else(|| project.settings.agent_overrides.clone());
+        // An empty command clears it
+        let setup_command = request
+            .setup_command
+            .or_else(|| project.settings.setup_command.clone())
+            .filter(|command| !command.trim().is_empty());
+        let default_terminal_mode = request.default_terminal_mode.or(project.settings.default_terminal_mode);
+        // An empty branch clears it
+        let default_base_branch = request
+            .default_base_branch
+            .or_else(|| project.settings.default_base_branch.clone())
+            .map(|branch| branch.trim().to_string())
+            .filter(|branch| !branch.is_empty());
+        let allowed_clients = request
+            .allowed_clients
+            .unwrap_or_else(|| project.settings.allowed_clients.clone());
+        let denied_clients = request
+            .denied_clients
+            .unwrap_or_else(|| project.settings.denied_clients.clone());
+        let mcp_servers = request
+            .mcp_servers
+            .unwrap_or_else(|| project.settings.mcp_servers.clone());
+        let mute_notifications = request.mute_notifications.unwrap_or(project.settings.mute_notifications);
+        let notification_rules = match request.notification_rules {
+            Some(rules) if rules.is_empty() => None,
+            Some(rules) => Some(rules),
+            None => project.settings.notification_rules.clone(),
+        };
 
-        project.settings = ProjectSettings {
+        let settings = ProjectSettings {
             default_client: request.default_client,
             auto_create_worktree: request.auto_create_worktree,
             worktree_base_path: request.worktree_base_path.map(PathBuf::from),
             last_visited_terminal_id: last_visited,
+            shell,
+            cow_worktrees,
+            init_submodules,
+            worktree_seed_files,
+            worktree_seed_mode,
+            worktree_name_template,
+            git_identity,
+            commit_trailer,
+            worktree_bypass,
+            env,
+            agent_overrides,
+            setup_command,
+            default_terminal_mode,
+            default_base_branch,
+            allowed_clients,
+            denied_clients,
+            mcp_servers,
+            mute_notifications,
+            notification_rules,
         };
+        validate_project_settings(&settings)?;
+        project.settings = settings;
         project.updated_at = chrono::Utc::now();
 
         project.clone()
@@ -0,0 +1,249 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use chrono::{DateTime, Utc};
+
+use crate::clients::ClientConfig;
+use crate::git;
+use crate::terminal::shell::resolve_shell;
+use crate::terminal::Terminal;
+use super::AdaProject;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DoctorStatus {
+    Ok,
+    /// Works, but something is likely to get in the way
+    Warning,
+    /// Agents or worktrees won't work until this is fixed
+    Error,
+    /// Not applicable, or couldn't be checked because of an earlier error
+    Skipped,
+}
+
+/// Outcome of one of `project_doctor`'s checks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoctorCheck {
+    /// Stable identifier, e.g. `worktrees_ignored`
+    pub name: String,
+    pub status: DoctorStatus,
+    pub message: String,
+    /// What to do about it
+    pub suggestion: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoctorReport {
+    pub project_id: String,
+    pub checked_at: DateTime<Utc>,
+    /// No check found an error
+    pub healthy: bool,
+    pub checks: Vec<DoctorCheck>,
+}
+
+impl DoctorCheck {
+    fn new(name: &str, status: DoctorStatus, message: impl Into<String>) -> Self {
+        Self { name: name.to_string(), status, message: message.into(), suggestion: None }
+    }
+
+    fn suggest(mut self, suggestion: impl Into<String>) -> Self {
+        self.suggestion = Some(suggestion.into());
+        self
+    }
+}
+
+/// Check that a project is set up the way Ada needs it: its folders exist, it is a usable
+/// git repository with its worktree folder ignored and no stale worktrees, and its default
+/// agent can be launched. `client` is the project's default client, if it has one.
+pub fn diagnose_project(
+    project: &AdaProject,
+    client: Option<&ClientConfig>,
+    terminals: &[Terminal],
+) -> DoctorReport {
+    let mut checks = Vec::new();
+
+    let path_ok = project.path.is_dir();
+    checks.push(if path_ok {
+        DoctorCheck::new("path", DoctorStatus::Ok, format!("{} exists", project.path.display()))
+    } else {
+        DoctorCheck::new("path", DoctorStatus::Error, format!("{} does not exist", project.path.display()))
+            .suggest("Restore the folder, or remove the project and open it from its new location")
+    });
+
+    let missing_roots: Vec<String> = project
+        .additional_roots
+        .iter()
+        .filter(|root| !root.path.is_dir())
+        .map(|root| format!("{} ({})", root.name, root.path.display()))
+        .collect();
+    if !project.additional_roots.is_empty() {
+        checks.push(if missing_roots.is_empty() {
+            DoctorCheck::new("additional_roots", DoctorStatus::Ok, "All additional roots exist")
+        } else {
+            DoctorCheck::new(
+                "additional_roots",
+                DoctorStatus::Warning,
+                format!("Missing additional roots: {}", missing_roots.join(", ")),
+            )
+            .suggest("Restore the folders or remove the roots from the project")
+        });
+    }
+
+    let is_repo = path_ok && project.path.join(".git").exists();
+    checks.push(check_git_repo(project, path_ok, is_repo));
+    if is_repo {
+        checks.push(check_worktrees_ignored(project));
+        checks.push(check_worktree_registrations(project, terminals));
+        checks.push(check_hooks(&project.path));
+    } else {
+        for name in ["worktrees_ignored", "worktree_registrations", "hooks"] {
+            checks.push(DoctorCheck::new(name, DoctorStatus::Skipped, "Not a git repository"));
+        }
+    }
+
+    checks.push(check_default_client(project, client));
+
+    DoctorReport {
+        project_id: project.id.clone(),
+        checked_at: Utc::now(),
+        healthy: checks.iter().all(|check| check.status != DoctorStatus::Error),
+        checks,
+    }
+}
+
+fn check_git_repo(project: &AdaProject, path_ok: bool, is_repo: bool) -> DoctorCheck {
+    const NAME: &str = "git_repo";
+    if !path_ok {
+        return DoctorCheck::new(NAME, DoctorStatus::Skipped, "The project folder is missing");
+    }
+    if !is_repo {
+        return if project.is_git_repo {
+            DoctorCheck::new(NAME, DoctorStatus::Error, "The project is no longer a git repository")
+                .suggest("Restore its .git folder or clone the repository again")
+        } else {
+            DoctorCheck::new(NAME, DoctorStatus::Skipped, "Not a git repository; worktree terminals are unavailable")
+        };
+    }
+
+    match git::get_current_branch_internal(&project.path) {
+        Err(e) => DoctorCheck::new(NAME, DoctorStatus::Error, format!("The repository can't be opened: {}", e)),
+        Ok(_) if !git::has_commits(&project.path) => {
+            DoctorCheck::new(NAME, DoctorStatus::Warning, "The repository has no commits, so worktrees can't be created")
+                .suggest("Make an initial commit")
+        }
+        Ok(branch) if branch == "HEAD" => {
+            DoctorCheck::new(NAME, DoctorStatus::Warning, "The main checkout is on a detached HEAD")
+                .suggest("Check out a branch, so new worktrees have a base to branch from")
+        }
+        Ok(branch) => DoctorCheck::new(NAME, DoctorStatus::Ok, format!("On branch {}", branch)),
+    }
+}
+
+fn check_worktrees_ignored(project: &AdaProject) -> DoctorCheck {
+    const NAME: &str = "worktrees_ignored";
+    let Ok(base) = project.worktree_base(None) else {
+        return DoctorCheck::new(NAME, DoctorStatus::Skipped, "No worktree folder");
+    };
+    let Ok(relative) = base.strip_prefix(&project.path) else {
+        return DoctorCheck::new(
+            NAME,
+            DoctorStatus::Ok,
+            format!("Worktrees are kept outside the repository, in {}", base.display()),
+        );
+    };
+
+    match git::is_path_ignored(&project.path, relative) {
+        Ok(true) => DoctorCheck::new(NAME, DoctorStatus::Ok, format!("{} is ignored", relative.display())),
+        Ok(false) => DoctorCheck::new(
+            NAME,
+            DoctorStatus::Warning,
+            format!("{} is not ignored, so worktrees show up as untracked files", relative.display()),
+        )
+        .suggest(format!("Add {}/ to .gitignore", relative.display())),
+        Err(e) => DoctorCheck::new(NAME, DoctorStatus::Warning, format!("Couldn't read ignore rules: {}", e)),
+    }
+}
+
+fn check_worktree_registrations(project: &AdaProject, terminals: &[Terminal]) -> DoctorCheck {
+    const NAME: &str = "worktree_registrations";
+    let base = match project.worktree_base(None) {
+        Ok(base) => base,
+        Err(e) => return DoctorCheck::new(NAME, DoctorStatus::Skipped, e.to_string()),
+    };
+    let report = match git::find_stale_worktrees(&project.path, &base) {
+        Ok(report) => report,
+        Err(e) => return DoctorCheck::new(NAME, DoctorStatus::Warning, format!("Couldn't list worktrees: {}", e)),
+    };
+
+    let mut problems = Vec::new();
+    if !report.pruned.is_empty() {
+        problems.push(format!("registered worktrees whose folder is gone: {}", report.pruned.join(", ")));
+    }
+    if !report.orphaned_dirs.is_empty() {
+        problems.push(format!("worktree folders git no longer knows about: {}", report.orphaned_dirs.join(", ")));
+    }
+    let missing: Vec<&str> = terminals
+        .iter()
+        .filter(|t| t.project_id == project.id)
+        .filter(|t| t.worktree_path.as_ref().is_some_and(|path| !path.exists()))
+        .map(|t| t.name.as_str())
+        .collect();
+    if !missing.is_empty() {
+        problems.push(format!("terminals whose worktree is gone: {}", missing.join(", ")));
+    }
+
+    if problems.is_empty() {
+        DoctorCheck::new(NAME, DoctorStatus::Ok, "Worktrees and their registrations match")
+    } else {
+        DoctorCheck::new(NAME, DoctorStatus::Warning, format!("Found {}", problems.join("; ")))
+            .suggest("Prune worktrees to clean up, and close terminals whose worktree is gone")
+    }
+}
+
+fn check_hooks(repo_path: &Path) -> DoctorCheck {
+    const NAME: &str = "hooks";
+    let report = match git::preflight_worktree(repo_path) {
+        Ok(report) => report,
+        Err(e) => return DoctorCheck::new(NAME, DoctorStatus::Warning, format!("Couldn't check hooks: {}", e)),
+    };
+
+    if let Some(hooks_path) = report.hooks_path.as_ref().filter(|_| !report.hooks_dir_exists) {
+        return DoctorCheck::new(
+                NAME,
+                DoctorStatus::Warning,
+                format!("core.hooksPath is set to {}, which doesn't exist", hooks_path),
+            )
+        .suggest("Run the project's hook setup (e.g. `npm install` for husky) or unset core.hooksPath");
+    }
+
+    match report.issues.as_slice() {
+        [] => DoctorCheck::new(NAME, DoctorStatus::Ok, "No hooks or filters that get in the way of new worktrees"),
+        issues => DoctorCheck::new(
+            NAME,
+            DoctorStatus::Warning,
+            issues.iter().map(|issue| issue.message.as_str()).collect::<Vec<_>>().join("; "),
+        )
+        .suggest(issues.iter().map(|issue| issue.suggestion.as_str()).collect::<Vec<_>>().join("; ")),
+    }
+}
+
+fn check_default_client(project: &AdaProject, client: Option<&ClientConfig>) -> DoctorCheck {
+    const NAME: &str = "default_client";
+    let Some(client_id) = &project.settings.default_client else {
+        return DoctorCheck::new(NAME, DoctorStatus::Warning, "No default agent is set")
+            .suggest("Pick a default agent in the project settings");
+    };
+    let Some(client) = client else {
+        return DoctorCheck::new(NAME, DoctorStatus::Error, format!("The default agent '{}' doesn't exist", client_id))
+            .suggest("Pick another default agent in the project settings");
+    };
+    if !client.installed {
+        return DoctorCheck::new(NAME, DoctorStatus::Error, format!("{} is not installed", client.name))
+            .suggest(format!("Install `{}` and make sure it is on your PATH", client.command));
+    }
+
+    match resolve_shell(project.settings.shell.as_ref(), client.shell.as_ref()) {
+        Ok(_) => DoctorCheck::new(NAME, DoctorStatus::Ok, format!("{} is installed", client.name)),
+        Err(e) => DoctorCheck::new(NAME, DoctorStatus::Error, format!("{} can't be launched: {}", client.name, e))
+            .suggest("Choose another shell in the project or agent settings"),
+    }
+}
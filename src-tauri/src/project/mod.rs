@@ -1,4 +1,14 @@
+mod bundle;
 pub mod commands;
+mod doctor;
+mod scan;
+mod stats;
+mod template;
 mod types;
 
+pub use bundle::*;
+pub use doctor::*;
+pub use scan::*;
+pub use stats::*;
+pub use template::*;
 pub use types::*;
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+
+/// How deep `scan_for_projects` looks below the root unless told otherwise
+pub const DEFAULT_SCAN_DEPTH: usize = 3;
+
+/// Folders never worth descending into when looking for repositories
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "vendor", "dist", "build", "venv", "__pycache__"];
+
+/// A git repository found by `scan_for_projects`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectCandidate {
+    pub path: PathBuf,
+    pub name: String,
+    /// Branch checked out in the repository (None when detached or unborn)
+    pub branch: Option<String>,
+    /// ID of the Ada project already open at this path
+    pub project_id: Option<String>,
+}
+
+/// Find git repositories under `root`, at most `max_depth` folders down (0 = only `root`).
+/// Hidden folders, dependency and build folders, and folders matching one of `ignore`
+/// (globs matched against the folder name or its path relative to `root`) are skipped.
+/// Repositories aren't searched for nested ones, and linked worktrees and submodules
+/// (where `.git` is a file) aren't returned.
+pub fn scan_for_repositories(root: &Path, max_depth: usize, ignore: &[String]) -> Result<Vec<PathBuf>> {
+    if !root.is_dir() {
+        return Err(Error::InvalidRequest(format!("{} is not a folder", root.display())));
+    }
+    let ignore = ignore
+        .iter()
+        .map(|pattern| {
+            glob::Pattern::new(pattern)
+                .map_err(|e| Error::InvalidRequest(format!("Invalid ignore pattern '{}': {}", pattern, e)))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut found = Vec::new();
+    let mut pending = vec![(root.to_path_buf(), 0)];
+    while let Some((dir, depth)) = pending.pop() {
+        if dir.join(".git").is_dir() {
+            found.push(dir);
+            continue;
+        }
+        if depth >= max_depth {
+            continue;
+        }
+
+        // Unreadable folders are skipped rather than failing the whole scan
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            // Symlinks are not followed, so links back up the tree can't loop
+            if !entry.file_type().is_ok_and(|t| t.is_dir()) {
+                continue;
+            }
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            let skipped = name.starts_with('.')
+                || SKIPPED_DIRS.contains(&name.as_str())
+                || ignore.iter().any(|p| p.matches(&name) || p.matches_path(relative));
+            if !skipped {
+                pending.push((path, depth + 1));
+            }
+        }
+    }
+
+    found.sort();
+    Ok(found)
+}
@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use chrono::{DateTime, Utc};
+
+use crate::clients::{agent_token_usage, ClientType, TokenUsage};
+use crate::git;
+use crate::terminal::{Terminal, TerminalMode, TerminalStatus};
+use super::AdaProject;
+
+/// Sessions and agent time of one client within a project
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClientStats {
+    pub sessions: usize,
+    /// Agent launches, counting restarts
+    pub launches: u64,
+    pub run_secs: u64,
+}
+
+/// Activity of a project, aggregated from its terminals' persisted metadata
+/// (see `get_project_stats`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectStats {
+    pub project_id: String,
+    /// Terminals (sessions) the project has, including the main terminal
+    pub sessions: usize,
+    /// Sessions whose agent is starting or running
+    pub running_sessions: usize,
+    /// Agent launches across all sessions, counting restarts
+    pub launches: u64,
+    /// Total seconds agents have run in this project, up to now for running ones
+    pub agent_run_secs: u64,
+    /// Keyed by client ID
+    pub clients: BTreeMap<String, ClientStats>,
+    /// Shell commands run in the project's terminals, from shell integration marks
+    pub commands_run: usize,
+    /// Of those, commands that exited with a non-zero code
+    pub commands_failed: usize,
+    /// Sessions working in a worktree
+    pub worktree_sessions: usize,
+    /// Worktrees registered with git across the project's roots, besides the main checkouts
+    pub worktrees: usize,
+    /// When any of the project's agents last produced output
+    pub last_activity: Option<DateTime<Utc>>,
+}
+
+/// Aggregate a project's session counts, agent time, shell command counts and worktrees
+pub fn collect_project_stats(project: &AdaProject, terminals: &[Terminal], now: DateTime<Utc>) -> ProjectStats {
+    let terminals: Vec<&Terminal> = terminals.iter().filter(|t| t.project_id == project.id).collect();
+
+    let mut clients: BTreeMap<String, ClientStats> = BTreeMap::new();
+    for terminal in &terminals {
+        let client = clients.entry(terminal.client_id.clone()).or_default();
+        client.sessions += 1;
+        client.launches += u64::from(terminal.respawn_count) + 1;
+        client.run_secs += terminal.run_time_secs(now);
+    }
+
+    let commands = terminals.iter().flat_map(|t| t.commands.iter());
+    let commands_run = commands.clone().filter(|c| c.started_at.is_some()).count();
+    let commands_failed = commands.filter(|c| c.exit_code.is_some_and(|code| code != 0)).count();
+
+    let roots = std::iter::once(&project.path).chain(
+        project.additional_roots.iter().filter(|r| r.is_git_repo).map(|r| &r.path),
+    );
+    let worktrees = roots
+        .filter(|path| path.join(".git").exists())
+        .filter_map(|path| git::list_worktrees_internal(path).ok())
+        .map(|list| list.len().saturating_sub(1))
+        .sum();
+
+    ProjectStats {
+        project_id: project.id.clone(),
+        sessions: terminals.len(),
+        running_sessions: terminals
+            .iter()
+            .filter(|t| matches!(t.status, TerminalStatus::Starting | TerminalStatus::Running))
+            .count(),
+        launches: clients.values().map(|c| c.launches).sum(),
+        agent_run_secs: clients.values().map(|c| c.run_secs).sum(),
+        clients,
+        commands_run,
+        commands_failed,
+        worktree_sessions: terminals.iter().filter(|t| t.mode == TerminalMode::Worktree).count(),
+        worktrees,
+        last_activity: terminals.iter().filter_map(|t| t.last_activity).max(),
+    }
+}
+
+/// Tokens used by a project's agents (see `get_project_usage`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectUsage {
+    pub project_id: String,
+    /// Each agent session counted once, even when terminals share a working directory
+    pub total: TokenUsage,
+    /// By terminal ID, for terminals whose agent logs usage Ada can read
+    pub terminals: BTreeMap<String, TokenUsage>,
+}
+
+/// Add up the token usage of a project's terminals; `client_types` maps client IDs to types
+pub fn collect_project_usage(
+    project: &AdaProject,
+    terminals: &[Terminal],
+    client_types: &HashMap<String, ClientType>,
+) -> ProjectUsage {
+    let mut per_terminal = BTreeMap::new();
+    // Terminals of the same agent in the same folder read the same logs
+    let mut folders: HashMap<(ClientType, PathBuf), DateTime<Utc>> = HashMap::new();
+    for terminal in terminals.iter().filter(|t| t.project_id == project.id) {
+        let Some(&client_type) = client_types.get(&terminal.client_id) else {
+            continue;
+        };
+        if let Some(usage) = agent_token_usage(client_type, &terminal.working_dir, terminal.created_at) {
+            per_terminal.insert(terminal.id.clone(), usage);
+            let since = folders.entry((client_type, terminal.working_dir.clone())).or_insert(terminal.created_at);
+            *since = (*since).min(terminal.created_at);
+        }
+    }
+
+    let mut total = TokenUsage::default();
+    for ((client_type, working_dir), since) in folders {
+        if let Some(usage) = agent_token_usage(client_type, &working_dir, since) {
+            total.add(&usage);
+        }
+    }
+
+    ProjectUsage { project_id: project.id.clone(), total, terminals: per_terminal }
+}
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+
+use crate::error::{Error, Result};
+use crate::terminal::pty::agent_path;
+use crate::terminal::shell::{ShellConfig, ShellKind};
+use super::types::default_true;
+use super::TerminalLayout;
+
+/// How long a template's setup script may run before it is stopped
+const SETUP_SCRIPT_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// A starting point for new projects: how the repository is set up and which agents
+/// are waiting in it (see `create_project`'s `template_id`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectTemplate {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Initialize a git repository (overrides the request's `init_git`)
+    #[serde(default = "default_true")]
+    pub init_git: bool,
+    /// Client for the project's main terminal
+    #[serde(default)]
+    pub default_client: Option<String>,
+    /// Shell script run in the new, still empty project folder before git is initialized,
+    /// e.g. `npm create vite@latest . -- --template react-ts && npm install`
+    #[serde(default)]
+    pub setup_script: Option<String>,
+    /// Terminals to create once the project is set up
+    #[serde(default)]
+    pub terminals: Vec<TerminalLayout>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// `save_project_template` request: a new template without `id`, otherwise a replacement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveProjectTemplateRequest {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default = "default_true")]
+    pub init_git: bool,
+    #[serde(default)]
+    pub default_client: Option<String>,
+    #[serde(default)]
+    pub setup_script: Option<String>,
+    #[serde(default)]
+    pub terminals: Vec<TerminalLayout>,
+}
+
+/// Run a template's setup script in `working_dir` through the user's login shell, so it
+/// sees the same tools as their terminal
+pub async fn run_setup_script(working_dir: &Path, script: &str) -> Result<()> {
+    let shell = ShellConfig::detect()
+        .ok_or_else(|| Error::ConfigError("Could not detect a shell to run the setup script".into()))?;
+
+    let mut cmd = tokio::process::Command::new(&shell.path);
+    match ShellKind::from_path(&shell.path) {
+        ShellKind::Posix => cmd.args(["-l", "-c", script]),
+        ShellKind::PowerShell => cmd.args(["-NoLogo", "-NonInteractive", "-Command", script]),
+        ShellKind::Cmd => cmd.args(["/C", script]),
+    };
+    cmd.current_dir(working_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(home) = dirs::home_dir().filter(|_| cfg!(not(windows))) {
+        cmd.env("PATH", agent_path(&home));
+    }
+
+    let output = tokio::time::timeout(SETUP_SCRIPT_TIMEOUT, cmd.output())
+        .await
+        .map_err(|_| Error::ConfigError("The setup script timed out".into()))??;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let detail = if stderr.trim().is_empty() { stdout } else { stderr };
+        // The end of the output is where the failure is
+        let tail: Vec<&str> = detail.trim().lines().rev().take(20).collect();
+        return Err(Error::ConfigError(format!(
+            "The setup script failed ({}):\n{}",
+            output.status,
+            tail.into_iter().rev().collect::<Vec<_>>().join("\n")
+        )));
+    }
+    Ok(())
+}
@@ -1,7 +1,15 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use chrono::{DateTime, Utc};
 
+use crate::clients::{AgentOverrides, McpServer};
+use crate::notifications::NotificationRule;
+use crate::error::{Error, Result};
+use crate::git::{CheckoutBypass, CommitIdentity, GitIdentity, SeedMode, WorktreeOptions};
+use crate::terminal::shell::ShellSetting;
+use crate::terminal::{TerminalInfo, TerminalMode};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AdaProject {
     pub id: String,
@@ -18,6 +26,67 @@ pub struct AdaProject {
     /// Whether this project has a git repository
     #[serde(default)]
     pub is_git_repo: bool,
+    /// When the project was archived (see `archive_project`); None while it is active
+    #[serde(default)]
+    pub archived_at: Option<DateTime<Utc>>,
+    /// Pinned projects are listed before the others
+    #[serde(default)]
+    pub pinned: bool,
+    /// Position in the user's ordering (see `update_project_order`); projects that were
+    /// never ordered come last, oldest first
+    #[serde(default)]
+    pub sort_index: Option<u32>,
+    /// Other folders (e.g. a sibling infra repo) that this project's terminals can run in
+    #[serde(default)]
+    pub additional_roots: Vec<ProjectRoot>,
+    /// When the project was last opened in Ada (see `get_project`), for listing recent projects
+    #[serde(default)]
+    pub last_opened_at: Option<DateTime<Utc>>,
+    /// Labels for grouping and filtering projects (see `ProjectFilter`)
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Whether the project folder was still there when Ada last looked
+    /// (see `AppState::validate_project_paths`)
+    #[serde(default)]
+    pub path_status: ProjectPathStatus,
+}
+
+/// Whether a project's folder exists
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectPathStatus {
+    #[default]
+    Ok,
+    /// The folder was moved or deleted; `relocate_project` points the project at its new place
+    Missing,
+}
+
+impl ProjectPathStatus {
+    /// Status of the project folder at `path`, as it is now
+    pub fn of(path: &Path) -> Self {
+        if path.is_dir() {
+            ProjectPathStatus::Ok
+        } else {
+            ProjectPathStatus::Missing
+        }
+    }
+}
+
+/// Emitted when a project's folder disappears or comes back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectPathStatusEvent {
+    pub project_id: String,
+    pub status: ProjectPathStatus,
+}
+
+/// An additional root of a project (see `AdaProject::additional_roots`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectRoot {
+    /// Name terminals use to pick this root, unique within the project
+    pub name: String,
+    pub path: PathBuf,
+    #[serde(default)]
+    pub is_git_repo: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -27,6 +96,104 @@ pub struct ProjectSettings {
     pub worktree_base_path: Option<PathBuf>,
     #[serde(default)]
     pub last_visited_terminal_id: Option<String>,
+    /// Shell to launch this project's agents through (overrides the client setting)
+    #[serde(default)]
+    pub shell: Option<ShellSetting>,
+    /// Provision new worktrees by copy-on-write cloning the main checkout, when supported
+    #[serde(default)]
+    pub cow_worktrees: bool,
+    /// Run `git submodule update --init --recursive` in new worktrees
+    #[serde(default)]
+    pub init_submodules: bool,
+    /// Files or globs (relative to the project root) seeded from the main checkout into
+    /// new worktrees, e.g. `.env` or `config/*.local.json`
+    #[serde(default)]
+    pub worktree_seed_files: Vec<String>,
+    /// Whether seed files are copied or symlinked
+    #[serde(default)]
+    pub worktree_seed_mode: SeedMode,
+    /// Directory name template for new worktrees, e.g. "{date}-{agent}-{branch}"
+    /// (see `git::worktree_path_for`; None = the branch name)
+    #[serde(default)]
+    pub worktree_name_template: Option<String>,
+    /// Author and committer of the commits Ada makes (initial commit, checkpoints,
+    /// `commit_worktree`), instead of the user's git config
+    #[serde(default)]
+    pub git_identity: Option<GitIdentity>,
+    /// Trailer added to those commits' messages, e.g. "Co-authored-by: Agent <agent@example.com>"
+    #[serde(default)]
+    pub commit_trailer: Option<String>,
+    /// Hooks and LFS downloads to skip when adding worktrees (see `git::preflight_worktree`)
+    #[serde(default)]
+    pub worktree_bypass: CheckoutBypass,
+    /// Environment variables set for every agent launched in this project
+    /// (on top of the client's own `env`)
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Model, settings and extra arguments for this project's agents, by client ID
+    #[serde(default)]
+    pub agent_overrides: HashMap<String, AgentOverrides>,
+    /// Shell command run in a new worktree before its agent starts, e.g. `direnv allow && npm ci`.
+    /// Its output shows up in the terminal; the agent starts even if it fails.
+    #[serde(default)]
+    pub setup_command: Option<String>,
+    /// Mode of terminals created without one: worktree or current branch (None = current branch)
+    #[serde(default)]
+    pub default_terminal_mode: Option<TerminalMode>,
+    /// Branch new worktree branches start from, e.g. `develop` or `origin/main` (None = HEAD)
+    #[serde(default)]
+    pub default_base_branch: Option<String>,
+    /// Client IDs this project's terminals may use (empty = any client)
+    #[serde(default)]
+    pub allowed_clients: Vec<String>,
+    /// Client IDs this project's terminals may not use, e.g. third-party agents
+    #[serde(default)]
+    pub denied_clients: Vec<String>,
+    /// MCP tool servers given to every agent in the project (see `apply_mcp_servers`)
+    #[serde(default)]
+    pub mcp_servers: Vec<McpServer>,
+    /// Don't notify about this project's agents on the desktop or in chat
+    #[serde(default)]
+    pub mute_notifications: bool,
+    /// Which events go to which notification channels, and when
+    /// (None = `NotificationSettings::default_rules`)
+    #[serde(default)]
+    pub notification_rules: Option<Vec<NotificationRule>>,
+}
+
+impl ProjectSettings {
+    /// Refuse clients the project's allow or deny list rules out
+    pub fn check_client_allowed(&self, client_id: &str) -> Result<()> {
+        let allowed = !self.denied_clients.iter().any(|id| id == client_id)
+            && (self.allowed_clients.is_empty() || self.allowed_clients.iter().any(|id| id == client_id));
+        if allowed {
+            Ok(())
+        } else {
+            Err(Error::InvalidRequest(format!("Client '{}' is not allowed in this project", client_id)))
+        }
+    }
+
+    /// How this project's new worktrees are set up
+    pub fn worktree_options(&self) -> WorktreeOptions {
+        WorktreeOptions {
+            cow_clone: self.cow_worktrees,
+            init_submodules: self.init_submodules,
+            seed_files: self.worktree_seed_files.clone(),
+            seed_mode: self.worktree_seed_mode,
+            bypass: self.worktree_bypass,
+        }
+    }
+
+    /// Who the commits Ada makes in this project are attributed to
+    pub fn commit_identity(&self) -> CommitIdentity {
+        CommitIdentity {
+            identity: self
+                .git_identity
+                .clone()
+                .filter(|identity| !identity.name.trim().is_empty() && !identity.email.trim().is_empty()),
+            trailer: self.commit_trailer.clone(),
+        }
+    }
 }
 
 impl AdaProject {
@@ -49,6 +216,13 @@ impl AdaProject {
             settings: ProjectSettings::default(),
             main_terminal_id: None,
             is_git_repo,
+            archived_at: None,
+            pinned: false,
+            sort_index: None,
+            additional_roots: Vec::new(),
+            last_opened_at: None,
+            tags: Vec::new(),
+            path_status: ProjectPathStatus::Ok,
         }
     }
     
@@ -63,6 +237,151 @@ impl AdaProject {
         self.terminal_ids.retain(|id| id != terminal_id);
         self.updated_at = Utc::now();
     }
+
+    pub fn is_archived(&self) -> bool {
+        self.archived_at.is_some()
+    }
+
+    /// Look at the project folder now, rather than trusting `path_status`
+    pub fn check_path(&self) -> ProjectPathStatus {
+        ProjectPathStatus::of(&self.path)
+    }
+
+    /// Path of the named additional root, or of the project itself when `root` is None
+    pub fn root_path(&self, root: Option<&str>) -> Result<PathBuf> {
+        match root {
+            None => Ok(self.path.clone()),
+            Some(name) => self
+                .additional_roots
+                .iter()
+                .find(|r| r.name == name)
+                .map(|r| r.path.clone())
+                .ok_or_else(|| Error::InvalidRequest(format!("Project '{}' has no root named '{}'", self.name, name))),
+        }
+    }
+
+    /// Where new worktrees of the given root go: the project's worktree folder setting
+    /// applies to the project itself, additional roots keep theirs in `.worktrees`
+    pub fn worktree_base(&self, root: Option<&str>) -> Result<PathBuf> {
+        match root {
+            None => Ok(self.settings.worktree_base_path
+                .clone()
+                .unwrap_or_else(|| self.path.join(".worktrees"))),
+            Some(_) => Ok(self.root_path(root)?.join(".worktrees")),
+        }
+    }
+
+    /// The root that `path` (e.g. a worktree) belongs to: the innermost additional root
+    /// containing it, or the project itself
+    pub fn root_containing(&self, path: &Path) -> &Path {
+        self.additional_roots
+            .iter()
+            .filter(|r| path.starts_with(&r.path))
+            .max_by_key(|r| r.path.components().count())
+            .map(|r| r.path.as_path())
+            .unwrap_or(&self.path)
+    }
+
+    /// Sort key for listing projects: pinned first, then by the user's ordering
+    pub fn list_order(&self) -> (bool, bool, Option<u32>, DateTime<Utc>) {
+        (!self.pinned, self.sort_index.is_none(), self.sort_index, self.created_at)
+    }
+}
+
+/// Which projects `list_projects` returns; unset fields match every project
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectFilter {
+    /// Projects having all of these tags (case-insensitive)
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Case-insensitive substring of the project's name or path
+    #[serde(default)]
+    pub query: Option<String>,
+    #[serde(default)]
+    pub is_git_repo: Option<bool>,
+    /// Include archived projects
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+impl ProjectFilter {
+    pub fn matches(&self, project: &AdaProject) -> bool {
+        if project.is_archived() && !self.include_archived {
+            return false;
+        }
+        if self.is_git_repo.is_some_and(|is_git_repo| project.is_git_repo != is_git_repo) {
+            return false;
+        }
+        if !self
+            .tags
+            .iter()
+            .all(|tag| project.tags.iter().any(|t| t.eq_ignore_ascii_case(tag.trim())))
+        {
+            return false;
+        }
+        match self.query.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+            Some(query) => {
+                let query = query.to_lowercase();
+                project.name.to_lowercase().contains(&query)
+                    || project.path.to_string_lossy().to_lowercase().contains(&query)
+            }
+            None => true,
+        }
+    }
+}
+
+/// Trim tags and drop empty and (case-insensitively) repeated ones, keeping their order
+pub fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !normalized.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            normalized.push(tag.to_string());
+        }
+    }
+    normalized
+}
+
+/// What `import_project` set up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportProjectResult {
+    pub project: AdaProject,
+    pub terminals: Vec<TerminalInfo>,
+    /// Terminals from the bundle that couldn't be created, with the reason
+    pub errors: Vec<String>,
+}
+
+/// What `archive_project` did
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveProjectResult {
+    pub project: AdaProject,
+    /// Terminals whose agents were stopped
+    pub stopped_terminals: Vec<String>,
+    /// Worktrees that were removed
+    pub removed_worktrees: Vec<String>,
+    /// Worktrees that were kept, with the reason (e.g. uncommitted changes)
+    pub kept_worktrees: Vec<(String, String)>,
+}
+
+/// What `delete_project` cleans up besides Ada's own records of the project
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeleteProjectOptions {
+    /// Remove the worktrees Ada created for the project
+    #[serde(default)]
+    pub remove_worktrees: bool,
+    /// Also remove worktrees with uncommitted changes or unpushed commits
+    #[serde(default)]
+    pub force_remove_worktrees: bool,
+}
+
+/// What `delete_project` did
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteProjectResult {
+    /// Terminals that were stopped and deleted, with their saved output
+    pub closed_terminals: Vec<String>,
+    pub removed_worktrees: Vec<String>,
+    /// Worktrees that were kept, with the reason (e.g. uncommitted changes)
+    pub kept_worktrees: Vec<(String, String)>,
 }
 
 /// Request to create a new project
@@ -72,9 +391,17 @@ pub struct CreateProjectRequest {
     /// Whether to initialize a git repository (default: true)
     #[serde(default = "default_true")]
     pub init_git: bool,
+    /// Identity for Ada's commits, starting with the initial one (see `ProjectSettings::git_identity`)
+    #[serde(default)]
+    pub git_identity: Option<GitIdentity>,
+    #[serde(default)]
+    pub commit_trailer: Option<String>,
+    /// Template to set the project up from (see `ProjectTemplate`)
+    #[serde(default)]
+    pub template_id: Option<String>,
 }
 
-fn default_true() -> bool {
+pub(super) fn default_true() -> bool {
     true
 }
 
@@ -88,6 +415,12 @@ pub struct ProjectSummary {
     pub updated_at: DateTime<Utc>,
     pub main_terminal_id: Option<String>,
     pub is_git_repo: bool,
+    pub archived_at: Option<DateTime<Utc>>,
+    pub pinned: bool,
+    pub sort_index: Option<u32>,
+    pub last_opened_at: Option<DateTime<Utc>>,
+    pub tags: Vec<String>,
+    pub path_status: ProjectPathStatus,
 }
 
 impl From<&AdaProject> for ProjectSummary {
@@ -101,6 +434,54 @@ impl From<&AdaProject> for ProjectSummary {
             updated_at: project.updated_at,
             main_terminal_id: project.main_terminal_id.clone(),
             is_git_repo: project.is_git_repo,
+            archived_at: project.archived_at,
+            pinned: project.pinned,
+            sort_index: project.sort_index,
+            last_opened_at: project.last_opened_at,
+            tags: project.tags.clone(),
+            path_status: project.path_status,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(name: &str, tags: &[&str]) -> AdaProject {
+        AdaProject {
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..AdaProject::new(PathBuf::from("/work").join(name), true)
+        }
+    }
+
+    #[test]
+    fn default_filter_matches_active_projects() {
+        let mut archived = project("old", &[]);
+        archived.archived_at = Some(Utc::now());
+
+        assert!(ProjectFilter::default().matches(&project("ada", &[])));
+        assert!(!ProjectFilter::default().matches(&archived));
+        assert!(ProjectFilter { include_archived: true, ..Default::default() }.matches(&archived));
+    }
+
+    #[test]
+    fn filters_by_tags_query_and_repo() {
+        let ada = project("ada", &["Rust", "desktop"]);
+
+        let tags = |tags: &[&str]| ProjectFilter {
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..Default::default()
+        };
+        assert!(tags(&["rust", " Desktop "]).matches(&ada));
+        assert!(!tags(&["rust", "web"]).matches(&ada));
+
+        let query = |query: &str| ProjectFilter { query: Some(query.to_string()), ..Default::default() };
+        assert!(query("AD").matches(&ada));
+        assert!(query("/work").matches(&ada));
+        assert!(query("  ").matches(&ada));
+        assert!(!query("infra").matches(&ada));
+
+        assert!(!ProjectFilter { is_git_repo: Some(false), ..Default::default() }.matches(&ada));
+    }
+}
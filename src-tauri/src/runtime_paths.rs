@@ -0,0 +1,199 @@
+use std::path::PathBuf;
+
+use crate::error::{Error, Result};
+
+/// Locations of everything Ada persists, rooted at the app data directory.
+/// All on-disk paths should come from here so they can't drift apart.
+#[derive(Debug, Clone)]
+pub struct RuntimePaths {
+    data_dir: PathBuf,
+    /// Configuration the user edits and shares between machines (`~/.ada`), kept apart
+    /// from app data so it survives reinstalls
+    config_dir: PathBuf,
+}
+
+impl RuntimePaths {
+    /// Resolve the data directory for this platform (e.g. `~/Library/Application Support/ada`)
+    pub fn resolve() -> Result<Self> {
+        let data_dir = dirs::data_dir()
+            .ok_or_else(|| Error::ConfigError("Could not find data directory".into()))?
+            .join("ada");
+        let config_dir = dirs::home_dir()
+            .ok_or_else(|| Error::ConfigError("Could not find home directory".into()))?
+            .join(".ada");
+        Ok(Self { data_dir, config_dir })
+    }
+
+    /// Create the data directory and all of its subdirectories
+    pub fn ensure_dirs(&self) -> Result<()> {
+        std::fs::create_dir_all(&self.data_dir)?;
+        for dir in [
+            self.projects_dir(),
+            self.terminals_dir(),
+            self.history_dir(),
+            self.groups_dir(),
+            self.workspaces_dir(),
+            self.templates_dir(),
+            self.clients_dir(),
+            self.transcripts_dir(),
+            self.events_dir(),
+            self.mcp_configs_dir(),
+            self.launch_env_dir(),
+        ] {
+            std::fs::create_dir_all(dir)?;
+        }
+        Ok(())
+    }
+
+    pub fn settings_file(&self) -> PathBuf {
+        self.data_dir.join("settings.json")
+    }
+
+    pub fn projects_dir(&self) -> PathBuf {
+        self.data_dir.join("projects")
+    }
+
+    pub fn project_file(&self, project_id: &str) -> PathBuf {
+        self.projects_dir().join(format!("{}.json", project_id))
+    }
+
+    pub fn terminals_dir(&self) -> PathBuf {
+        self.data_dir.join("terminals")
+    }
+
+    pub fn terminal_file(&self, terminal_id: &str) -> PathBuf {
+        self.terminals_dir().join(format!("{}.json", terminal_id))
+    }
+
+    pub fn history_dir(&self) -> PathBuf {
+        self.data_dir.join("history")
+    }
+
+    /// Output history of a terminal, apart from its metadata so loading that stays cheap
+    pub fn terminal_history_file(&self, terminal_id: &str) -> PathBuf {
+        self.history_dir().join(format!("{}.json", terminal_id))
+    }
+
+    pub fn groups_dir(&self) -> PathBuf {
+        self.data_dir.join("groups")
+    }
+
+    pub fn group_file(&self, group_id: &str) -> PathBuf {
+        self.groups_dir().join(format!("{}.json", group_id))
+    }
+
+    pub fn workspaces_dir(&self) -> PathBuf {
+        self.data_dir.join("workspaces")
+    }
+
+    pub fn workspace_file(&self, workspace_id: &str) -> PathBuf {
+        self.workspaces_dir().join(format!("{}.json", workspace_id))
+    }
+
+    pub fn templates_dir(&self) -> PathBuf {
+        self.data_dir.join("templates")
+    }
+
+    pub fn template_file(&self, template_id: &str) -> PathBuf {
+        self.templates_dir().join(format!("{}.json", template_id))
+    }
+
+    /// Client definitions added or changed by the user (`~/.ada/clients.d`), one JSON file each
+    pub fn clients_dir(&self) -> PathBuf {
+        self.config_dir.join("clients.d")
+    }
+
+    pub fn client_file(&self, client_id: &str) -> PathBuf {
+        self.clients_dir().join(format!("{}.json", client_id))
+    }
+
+    /// Cached results of looking for installed clients (see `DetectionCache`)
+    pub fn client_detection_file(&self) -> PathBuf {
+        self.data_dir.join("client-detection.json")
+    }
+
+    pub fn transcripts_dir(&self) -> PathBuf {
+        self.data_dir.join("transcripts")
+    }
+
+    pub fn events_dir(&self) -> PathBuf {
+        self.data_dir.join("events")
+    }
+
+    /// A terminal's event log (see `terminal::events`), one JSON event per line
+    pub fn terminal_events_file(&self, terminal_id: &str) -> PathBuf {
+        self.events_dir().join(format!("{}.jsonl", terminal_id))
+    }
+
+    pub fn mcp_configs_dir(&self) -> PathBuf {
+        self.data_dir.join("mcp")
+    }
+
+    /// MCP server config handed to a terminal's agent (see `apply_mcp_servers`)
+    pub fn mcp_config_file(&self, terminal_id: &str) -> PathBuf {
+        self.mcp_configs_dir().join(format!("{}.json", terminal_id))
+    }
+
+    pub fn launch_env_dir(&self) -> PathBuf {
+        self.data_dir.join("launch-env")
+    }
+
+    /// Environment a pooled shell sources (and deletes) before exec'ing a terminal's agent
+    pub fn launch_env_file(&self, terminal_id: &str) -> PathBuf {
+        self.launch_env_dir().join(format!("{}.sh", terminal_id))
+    }
+
+    /// Startup files that load Ada's shell integration into bash and zsh
+    pub fn shell_integration_dir(&self) -> PathBuf {
+        self.data_dir.join("shell-integration")
+    }
+
+    /// File holding output rotated out of memory for terminals with unlimited scrollback
+    pub fn transcript_file(&self, terminal_id: &str) -> PathBuf {
+        self.transcripts_dir().join(format!("{}.log", terminal_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_paths() -> RuntimePaths {
+        let root = std::env::temp_dir().join(format!("ada-paths-{}", uuid::Uuid::new_v4()));
+        RuntimePaths {
+            data_dir: root.join("data"),
+            config_dir: root.join("config"),
+        }
+    }
+
+    #[test]
+    fn ensure_dirs_creates_every_directory_files_go_in() {
+        let paths = temp_paths();
+        paths.ensure_dirs().unwrap();
+
+        for file in [
+            paths.project_file("p"),
+            paths.terminal_file("t"),
+            paths.terminal_history_file("t"),
+            paths.group_file("g"),
+            paths.workspace_file("w"),
+            paths.template_file("t"),
+            paths.client_file("c"),
+            paths.transcript_file("t"),
+            paths.terminal_events_file("t"),
+            paths.mcp_config_file("t"),
+            paths.launch_env_file("t"),
+        ] {
+            assert!(file.parent().unwrap().is_dir(), "{} has no directory", file.display());
+        }
+        std::fs::remove_dir_all(paths.data_dir.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn client_definitions_live_in_the_config_dir() {
+        let paths = temp_paths();
+        assert!(paths.client_file("aider").starts_with(&paths.config_dir));
+        assert!(paths.terminal_file("t").starts_with(&paths.data_dir));
+        assert_eq!(paths.terminal_file("abc").file_name().unwrap(), "abc.json");
+    }
+}
@@ -0,0 +1,23 @@
+use tauri::State;
+
+use crate::error::Result;
+use crate::state::AppState;
+use super::AppSettings;
+
+#[tauri::command]
+pub async fn get_app_settings(
+    state: State<'_, AppState>,
+) -> Result<AppSettings> {
+    Ok(state.settings.read().clone())
+}
+
+#[tauri::command]
+pub async fn update_app_settings(
+    state: State<'_, AppState>,
+    settings: AppSettings,
+) -> Result<AppSettings> {
+    settings.notifications.validate()?;
+    state.save_settings(&settings)?;
+    *state.settings.write() = settings.clone();
+    Ok(settings)
+}
@@ -0,0 +1,4 @@
+pub mod commands;
+mod types;
+
+pub use types::*;
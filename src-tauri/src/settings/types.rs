@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+
+use crate::notifications::NotificationSettings;
+use crate::terminal::ScrollbackLimit;
+
+/// Application-wide settings, persisted to `settings.json` in the data directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppSettings {
+    /// Record the full argv of every agent launch on its terminal
+    #[serde(default)]
+    pub log_agent_invocations: bool,
+    /// Agent flags that may never be passed (e.g. `--dangerously-skip-permissions`)
+    #[serde(default)]
+    pub denied_agent_flags: Vec<String>,
+    /// Number of warm idle shells kept per project for shell-launched terminals (0 = disabled)
+    #[serde(default)]
+    pub pty_pool_size: usize,
+    /// Idle shells older than this are recycled instead of adopted
+    #[serde(default = "default_pty_pool_max_idle_secs")]
+    pub pty_pool_max_idle_secs: u64,
+    /// How often terminals with new output or activity are written to disk
+    #[serde(default = "default_persist_interval_secs")]
+    pub persist_interval_secs: u64,
+    /// Days to keep stopped terminals before they are pruned (None = keep forever)
+    #[serde(default)]
+    pub terminal_retention_days: Option<u64>,
+    /// Default scrollback limit for terminals that don't set their own
+    #[serde(default)]
+    pub scrollback: ScrollbackLimit,
+    /// Relaunch restored terminals' agents when Ada starts, instead of leaving them stopped
+    #[serde(default)]
+    pub resume_terminals_on_start: bool,
+    /// Client run non-interactively to summarize terminal sessions (None = summaries disabled)
+    #[serde(default)]
+    pub summary_client_id: Option<String>,
+    /// Desktop notifications about agents (see `notifications::dispatch`)
+    #[serde(default)]
+    pub notifications: NotificationSettings,
+}
+
+fn default_pty_pool_max_idle_secs() -> u64 {
+    30 * 60
+}
+
+fn default_persist_interval_secs() -> u64 {
+    5
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            log_agent_invocations: false,
+            denied_agent_flags: Vec::new(),
+            pty_pool_size: 0,
+            pty_pool_max_idle_secs: default_pty_pool_max_idle_secs(),
+            persist_interval_secs: default_persist_interval_secs(),
+            terminal_retention_days: None,
+            scrollback: ScrollbackLimit::default(),
+            resume_terminals_on_start: false,
+            summary_client_id: None,
+            notifications: NotificationSettings::default(),
+        }
+    }
+}
+
+impl AppSettings {
+    /// Return the first denied flag present in `args`, matching both `--flag` and `--flag=value`
+    pub fn find_denied_flag(&self, args: &[String]) -> Option<&str> {
+        self.denied_agent_flags
+            .iter()
+            .find(|flag| {
+                args.iter().any(|arg| {
+                    arg == *flag
+                        || arg
+                            .strip_prefix(flag.as_str())
+                            .is_some_and(|rest| rest.starts_with('='))
+                })
+            })
+            .map(|flag| flag.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn finds_denied_flags_with_and_without_values() {
+        let settings = AppSettings {
+            denied_agent_flags: args(&["--dangerously-skip-permissions", "--yolo"]),
+            ..AppSettings::default()
+        };
+        assert_eq!(
+            settings.find_denied_flag(&args(&["-p", "--dangerously-skip-permissions"])),
+            Some("--dangerously-skip-permissions")
+        );
+        assert_eq!(settings.find_denied_flag(&args(&["--yolo=true"])), Some("--yolo"));
+        // Only whole flags count, not ones the denied flag is a prefix of
+        assert_eq!(settings.find_denied_flag(&args(&["--yolomode"])), None);
+        assert_eq!(settings.find_denied_flag(&args(&["--model", "opus"])), None);
+    }
+}
@@ -1,58 +1,122 @@
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use parking_lot::RwLock;
-use tauri::AppHandle;
+use std::time::Duration;
+use parking_lot::{Mutex, RwLock};
+use tauri::{AppHandle, Emitter, Manager};
 
-use crate::project::AdaProject;
-use crate::terminal::{Terminal, PtyHandle, TerminalOutputBuffer, TerminalData, TerminalStatus};
-use crate::clients::ClientConfig;
+use crate::git::DiskUsageCache;
+use crate::project::{AdaProject, ProjectPathStatus, ProjectPathStatusEvent, ProjectTemplate};
+use crate::terminal::{
+    Terminal, PtyHandle, TerminalOutputBuffer, TerminalHistory, TerminalMeta, TerminalStatus, TerminalGroup,
+    ScrollbackLimit, TranscriptWriter, marker_line,
+};
+use crate::terminal::pool::PtyPool;
+use crate::terminal::checkpoint::{delete_terminal_checkpoints, CheckpointTracker};
+use crate::terminal::keep_alive::KeepAliveTracker;
+use crate::clients::{client_id_from_name, detect_installations, ClientConfig, DetectionCache};
+use crate::workspace::Workspace;
+use crate::settings::AppSettings;
+use crate::runtime_paths::RuntimePaths;
 use crate::error::{Error, Result};
 
+/// How often the background pass removes stale terminals
+const TERMINAL_GC_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// How often project folders are checked for having been moved or deleted
+const PROJECT_PATH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
 pub struct AppState {
     pub projects: RwLock<HashMap<String, AdaProject>>,
     pub terminals: RwLock<HashMap<String, Terminal>>,
     pub pty_handles: RwLock<HashMap<String, PtyHandle>>,
     pub output_buffers: RwLock<HashMap<String, Arc<TerminalOutputBuffer>>>,
+    pub terminal_groups: RwLock<HashMap<String, TerminalGroup>>,
+    pub workspaces: RwLock<HashMap<String, Workspace>>,
+    pub project_templates: RwLock<HashMap<String, ProjectTemplate>>,
+    /// Terminals with output or metadata changes not yet written to disk
+    dirty_terminals: Mutex<HashSet<String>>,
+    pub pty_pool: PtyPool,
+    pub keep_alive: KeepAliveTracker,
+    pub checkpoints: CheckpointTracker,
+    pub worktree_disk_usage: DiskUsageCache,
     pub clients: RwLock<HashMap<String, ClientConfig>>,
-    pub data_dir: PathBuf,
+    pub settings: RwLock<AppSettings>,
+    pub paths: RuntimePaths,
     pub app_handle: AppHandle,
 }
 
 impl AppState {
     pub fn new(app_handle: AppHandle) -> Result<Self> {
-        let data_dir = dirs::data_dir()
-            .ok_or_else(|| Error::ConfigError("Could not find data directory".into()))?
-            .join("ada");
-
-        std::fs::create_dir_all(&data_dir)?;
-        std::fs::create_dir_all(data_dir.join("projects"))?;
-        std::fs::create_dir_all(data_dir.join("terminals"))?;
+        let paths = RuntimePaths::resolve()?;
+        paths.ensure_dirs()?;
 
         let state = Self {
             projects: RwLock::new(HashMap::new()),
             terminals: RwLock::new(HashMap::new()),
             pty_handles: RwLock::new(HashMap::new()),
             output_buffers: RwLock::new(HashMap::new()),
+            terminal_groups: RwLock::new(HashMap::new()),
+            workspaces: RwLock::new(HashMap::new()),
+            project_templates: RwLock::new(HashMap::new()),
+            dirty_terminals: Mutex::new(HashSet::new()),
+            pty_pool: PtyPool::default(),
+            keep_alive: KeepAliveTracker::default(),
+            checkpoints: CheckpointTracker::default(),
+            worktree_disk_usage: DiskUsageCache::default(),
             clients: RwLock::new(HashMap::new()),
-            data_dir,
+            settings: RwLock::new(AppSettings::default()),
+            paths,
             app_handle,
         };
 
+        // Load persisted app settings
+        state.load_settings()?;
+
         // Load persisted projects
         state.load_projects()?;
 
         // Load persisted terminals
         state.load_terminals()?;
 
-        // Initialize default clients
+        // Load persisted terminal groups
+        state.load_terminal_groups()?;
+
+        // Load persisted workspaces
+        state.load_workspaces()?;
+
+        // Load persisted project templates
+        state.load_project_templates()?;
+
+        // Initialize default clients, then the user's own clients and changes to the defaults,
+        // and find out which are installed
         state.init_default_clients();
+        state.load_clients()?;
+        state.detect_clients(false);
+
+        // Clear out terminals left behind by deleted projects or past retention
+        state.prune_terminals();
+        state.validate_project_paths();
 
         Ok(state)
     }
     
+    fn load_settings(&self) -> Result<()> {
+        let settings_file = self.paths.settings_file();
+
+        if settings_file.exists() {
+            let content = std::fs::read_to_string(&settings_file)?;
+            match serde_json::from_str::<AppSettings>(&content) {
+                Ok(settings) => *self.settings.write() = settings,
+                Err(e) => eprintln!("[Ada] Ignoring invalid settings.json: {}", e),
+            }
+        }
+
+        Ok(())
+    }
+
     fn load_projects(&self) -> Result<()> {
-        let projects_dir = self.data_dir.join("projects");
+        let projects_dir = self.paths.projects_dir();
 
         if projects_dir.exists() {
             for entry in std::fs::read_dir(&projects_dir)? {
@@ -72,7 +136,7 @@ impl AppState {
     }
 
     fn load_terminals(&self) -> Result<()> {
-        let terminals_dir = self.data_dir.join("terminals");
+        let terminals_dir = self.paths.terminals_dir();
 
         if terminals_dir.exists() {
             for entry in std::fs::read_dir(&terminals_dir)? {
@@ -81,19 +145,108 @@ impl AppState {
 
                 if path.extension().is_some_and(|ext| ext == "json") {
                     let content = std::fs::read_to_string(&path)?;
-                    if let Ok(mut terminal_data) = serde_json::from_str::<TerminalData>(&content) {
-                        // Mark terminal as stopped since the PTY is gone
-                        terminal_data.terminal.status = TerminalStatus::Stopped;
+                    // Only metadata is loaded here; the output history has a file of its own,
+                    // read on first access
+                    if let Ok(TerminalMeta { mut terminal }) = serde_json::from_str::<TerminalMeta>(&content) {
+                        // Only a process still running when Ada closed was cut short; one
+                        // that had already ended was restored (and marked) before
+                        if matches!(terminal.status, TerminalStatus::Starting | TerminalStatus::Running) {
+                            terminal.restored = true;
+                        }
+                        // Mark terminal as stopped since the PTY is gone; the last output
+                        // is the best estimate of when that happened
+                        let ended_at = terminal.last_activity.unwrap_or(terminal.created_at);
+                        terminal.mark_stopped(ended_at);
+
+                        self.terminals.write().insert(terminal.id.clone(), terminal);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn load_terminal_groups(&self) -> Result<()> {
+        let groups_dir = self.paths.groups_dir();
+
+        if groups_dir.exists() {
+            for entry in std::fs::read_dir(&groups_dir)? {
+                let entry = entry?;
+                let path = entry.path();
+
+                if path.extension().is_some_and(|ext| ext == "json") {
+                    let content = std::fs::read_to_string(&path)?;
+                    if let Ok(group) = serde_json::from_str::<TerminalGroup>(&content) {
+                        self.terminal_groups.write().insert(group.id.clone(), group);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn load_workspaces(&self) -> Result<()> {
+        let workspaces_dir = self.paths.workspaces_dir();
+
+        if workspaces_dir.exists() {
+            for entry in std::fs::read_dir(&workspaces_dir)? {
+                let entry = entry?;
+                let path = entry.path();
+
+                if path.extension().is_some_and(|ext| ext == "json") {
+                    let content = std::fs::read_to_string(&path)?;
+                    if let Ok(workspace) = serde_json::from_str::<Workspace>(&content) {
+                        self.workspaces.write().insert(workspace.id.clone(), workspace);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn load_project_templates(&self) -> Result<()> {
+        let templates_dir = self.paths.templates_dir();
+
+        if templates_dir.exists() {
+            for entry in std::fs::read_dir(&templates_dir)? {
+                let entry = entry?;
+                let path = entry.path();
+
+                if path.extension().is_some_and(|ext| ext == "json") {
+                    let content = std::fs::read_to_string(&path)?;
+                    if let Ok(template) = serde_json::from_str::<ProjectTemplate>(&content) {
+                        self.project_templates.write().insert(template.id.clone(), template);
+                    }
+                }
+            }
+        }
 
-                        let terminal_id = terminal_data.terminal.id.clone();
+        Ok(())
+    }
 
-                        // Restore output buffer
-                        let buffer = Arc::new(TerminalOutputBuffer::new());
-                        buffer.restore(terminal_data.output_history);
+    /// Load the client definitions in `clients.d` over the built-in clients. A file's client
+    /// ID defaults to its name, and a definition for an existing client only needs the
+    /// fields it changes (see `ClientConfig::from_definition`).
+    fn load_clients(&self) -> Result<()> {
+        let clients_dir = self.paths.clients_dir();
 
-                        self.terminals.write().insert(terminal_id.clone(), terminal_data.terminal);
-                        self.output_buffers.write().insert(terminal_id, buffer);
+        if clients_dir.exists() {
+            let mut paths: Vec<PathBuf> = std::fs::read_dir(&clients_dir)?
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                .collect();
+            paths.sort();
+
+            for path in paths {
+                match self.load_client_definition(&path) {
+                    Ok(client) => {
+                        self.clients.write().insert(client.id.clone(), client);
                     }
+                    Err(e) => eprintln!("[Ada] Skipping client definition {}: {}", path.display(), e),
                 }
             }
         }
@@ -101,38 +254,295 @@ impl AppState {
         Ok(())
     }
 
+    fn load_client_definition(&self, path: &Path) -> Result<ClientConfig> {
+        let content = std::fs::read_to_string(path)?;
+        let serde_json::Value::Object(fields) = serde_json::from_str(&content)? else {
+            return Err(Error::InvalidRequest("a client definition must be a JSON object".into()));
+        };
+        let id = match fields.get("id").and_then(|v| v.as_str()) {
+            Some(id) => client_id_from_name(id),
+            None => client_id_from_name(&path.file_stem().unwrap_or_default().to_string_lossy()),
+        };
+        if id.is_empty() {
+            return Err(Error::InvalidRequest("the client ID must contain letters or digits".into()));
+        }
+
+        let base = self.clients.read().get(&id).cloned();
+        ClientConfig::from_definition(fields, &id, base.as_ref())
+    }
+
+    /// Create an output buffer for a terminal with the given scrollback limit
+    /// (falling back to the app-wide setting). In unlimited mode, output that no longer
+    /// fits in memory is appended to the terminal's transcript file.
+    pub fn create_output_buffer(
+        &self,
+        terminal_id: &str,
+        scrollback: Option<ScrollbackLimit>,
+    ) -> Arc<TerminalOutputBuffer> {
+        let scrollback = scrollback.unwrap_or(self.settings.read().scrollback);
+
+        let transcript = match scrollback {
+            ScrollbackLimit::Bytes { .. } => None,
+            ScrollbackLimit::Unlimited { .. } => {
+                match TranscriptWriter::open(self.paths.transcript_file(terminal_id)) {
+                    Ok(writer) => Some(writer),
+                    Err(e) => {
+                        eprintln!("[Ada] Failed to open transcript for terminal {}: {}", terminal_id, e);
+                        None
+                    }
+                }
+            }
+        };
+
+        Arc::new(TerminalOutputBuffer::new(scrollback.max_bytes(), transcript))
+    }
+
+    pub fn save_settings(&self, settings: &AppSettings) -> Result<()> {
+        let settings_file = self.paths.settings_file();
+        let content = serde_json::to_string_pretty(settings)?;
+        std::fs::write(settings_file, content)?;
+        Ok(())
+    }
+
     pub fn save_project(&self, project: &AdaProject) -> Result<()> {
-        let project_file = self.data_dir.join("projects").join(format!("{}.json", project.id));
+        let project_file = self.paths.project_file(&project.id);
         let content = serde_json::to_string_pretty(project)?;
         std::fs::write(project_file, content)?;
         Ok(())
     }
 
-    pub fn save_terminal(&self, terminal_id: &str) -> Result<()> {
-        let terminals = self.terminals.read();
-        let terminal = terminals
-            .get(terminal_id)
-            .ok_or_else(|| Error::TerminalNotFound(terminal_id.to_string()))?;
+    /// Output buffer for a terminal, loading its saved history from disk on first access
+    pub fn output_buffer(&self, terminal_id: &str) -> Option<Arc<TerminalOutputBuffer>> {
+        if let Some(buffer) = self.output_buffers.read().get(terminal_id) {
+            return Some(buffer.clone());
+        }
+
+        let (scrollback, restored) = {
+            let terminals = self.terminals.read();
+            let terminal = terminals.get(terminal_id)?;
+            (terminal.scrollback, terminal.restored)
+        };
+
+        let buffer = self.create_output_buffer(terminal_id, scrollback);
+        match self.read_output_history(terminal_id) {
+            Ok((start, history)) => buffer.restore(start, history),
+            Err(e) => eprintln!("[Ada] Failed to load history for terminal {}: {}", terminal_id, e),
+        }
+        if restored {
+            // Note that the original process is gone
+            buffer.append(marker_line("Session restored - the previous process ended when Ada closed"));
+        }
+
+        // Another caller may have loaded it in the meantime; keep whichever got there first
+        let buffer = self.output_buffers.write().entry(terminal_id.to_string()).or_insert(buffer).clone();
+
+        if restored {
+            // The marker is in the scrollback now; saving it with the flag cleared keeps
+            // the next load from adding another
+            if let Some(terminal) = self.terminals.write().get_mut(terminal_id) {
+                terminal.restored = false;
+            }
+            self.mark_terminal_dirty(terminal_id);
+        }
+        Some(buffer)
+    }
+
+    /// Saved output history and the output-stream offset it starts at
+    fn read_output_history(&self, terminal_id: &str) -> Result<(u64, Vec<String>)> {
+        let mut history_file = self.paths.terminal_history_file(terminal_id);
+        if !history_file.exists() {
+            // Saved before the history had a file of its own
+            history_file = self.paths.terminal_file(terminal_id);
+        }
+        if !history_file.exists() {
+            return Ok((0, Vec::new()));
+        }
+        let content = std::fs::read_to_string(history_file)?;
+        let history = serde_json::from_str::<TerminalHistory>(&content)?;
+        Ok((history.output_start, history.output_history))
+    }
 
-        let output_history = self.output_buffers
+    /// Save a terminal's metadata, and its history if it has been loaded (history that
+    /// hasn't is still on disk as it was)
+    pub fn save_terminal(&self, terminal_id: &str) -> Result<()> {
+        let terminal = self
+            .terminals
             .read()
             .get(terminal_id)
-            .map(|b| b.get_history())
-            .unwrap_or_default();
+            .cloned()
+            .ok_or_else(|| Error::TerminalNotFound(terminal_id.to_string()))?;
 
-        let terminal_data = TerminalData {
-            terminal: terminal.clone(),
-            output_history,
+        let terminal_file = self.paths.terminal_file(terminal_id);
+        let history_file = self.paths.terminal_history_file(terminal_id);
+        let loaded = self.output_buffers.read().get(terminal_id).cloned();
+        let buffer = match loaded {
+            Some(buffer) => Some(buffer),
+            // The history of older terminals is in the file about to be overwritten,
+            // so it is loaded to be moved out first
+            None if !history_file.exists() && terminal_file.exists() => self.output_buffer(terminal_id),
+            None => None,
         };
 
-        let terminal_file = self.data_dir.join("terminals").join(format!("{}.json", terminal_id));
-        let content = serde_json::to_string_pretty(&terminal_data)?;
+        // History first, so the metadata never lands without it
+        if let Some(buffer) = buffer {
+            let history = TerminalHistory {
+                output_start: buffer.start(),
+                output_history: buffer.get_history(),
+            };
+            std::fs::write(history_file, serde_json::to_string(&history)?)?;
+        }
+        let content = serde_json::to_string_pretty(&TerminalMeta { terminal })?;
         std::fs::write(terminal_file, content)?;
         Ok(())
     }
 
+    pub fn save_terminal_group(&self, group: &TerminalGroup) -> Result<()> {
+        let group_file = self.paths.group_file(&group.id);
+        let content = serde_json::to_string_pretty(group)?;
+        std::fs::write(group_file, content)?;
+        Ok(())
+    }
+
+    pub fn save_workspace(&self, workspace: &Workspace) -> Result<()> {
+        let workspace_file = self.paths.workspace_file(&workspace.id);
+        let content = serde_json::to_string_pretty(workspace)?;
+        std::fs::write(workspace_file, content)?;
+        Ok(())
+    }
+
+    pub fn save_project_template(&self, template: &ProjectTemplate) -> Result<()> {
+        let template_file = self.paths.template_file(&template.id);
+        let content = serde_json::to_string_pretty(template)?;
+        std::fs::write(template_file, content)?;
+        Ok(())
+    }
+
+    pub fn save_client(&self, client: &ClientConfig) -> Result<()> {
+        let client_file = self.paths.client_file(&client.id);
+        let content = serde_json::to_string_pretty(client)?;
+        std::fs::write(client_file, content)?;
+        Ok(())
+    }
+
+    /// Queue a terminal for the next periodic save, instead of writing it on the output path
+    pub fn mark_terminal_dirty(&self, terminal_id: &str) {
+        let mut dirty = self.dirty_terminals.lock();
+        if !dirty.contains(terminal_id) {
+            dirty.insert(terminal_id.to_string());
+        }
+    }
+
+    /// Save every terminal queued by `mark_terminal_dirty`
+    pub fn flush_dirty_terminals(&self) {
+        let dirty: Vec<String> = self.dirty_terminals.lock().drain().collect();
+        for terminal_id in dirty {
+            match self.save_terminal(&terminal_id) {
+                // Terminals closed since they were marked have nothing left to save
+                Ok(()) | Err(Error::TerminalNotFound(_)) => {}
+                Err(e) => eprintln!("[Ada] Failed to save terminal {}: {}", terminal_id, e),
+            }
+        }
+    }
+
+    /// Remove stored terminals whose project no longer exists and, when a retention period
+    /// is configured, stopped terminals that ended longer ago than that. Main terminals and
+    /// terminals with a live process are never removed. Returns the removed terminal IDs.
+    pub fn prune_terminals(&self) -> Vec<String> {
+        let retention = self
+            .settings
+            .read()
+            .terminal_retention_days
+            .map(|days| chrono::Duration::days(days as i64));
+        let now = chrono::Utc::now();
+
+        let to_remove: Vec<(String, String)> = {
+            let projects = self.projects.read();
+            let pty_handles = self.pty_handles.read();
+            self.terminals
+                .read()
+                .values()
+                .filter(|t| !pty_handles.contains_key(&t.id))
+                .filter(|t| {
+                    let orphaned = !projects.contains_key(&t.project_id);
+                    let expired = !t.is_main
+                        && t.status != TerminalStatus::Running
+                        && matches!((retention, t.ended_at), (Some(retention), Some(ended_at)) if now - ended_at > retention);
+                    orphaned || expired
+                })
+                .map(|t| (t.id.clone(), t.project_id.clone()))
+                .collect()
+        };
+
+        for (terminal_id, project_id) in &to_remove {
+            let terminal = self.terminals.write().remove(terminal_id);
+            if let Some(terminal) = terminal {
+                delete_terminal_checkpoints(&terminal);
+            }
+            self.output_buffers.write().remove(terminal_id);
+            self.dirty_terminals.lock().remove(terminal_id);
+            let _ = self.delete_terminal_file(terminal_id);
+
+            let mut projects = self.projects.write();
+            if let Some(project) = projects.get_mut(project_id) {
+                project.remove_terminal(terminal_id);
+                let _ = self.save_project(project);
+            }
+        }
+
+        if !to_remove.is_empty() {
+            eprintln!("[Ada] Pruned {} stale terminals", to_remove.len());
+        }
+
+        to_remove.into_iter().map(|(terminal_id, _)| terminal_id).collect()
+    }
+
+    /// Check that every project's folder still exists, saving and emitting
+    /// `project-path-status` for projects whose status changed. Returns their IDs.
+    pub fn validate_project_paths(&self) -> Vec<String> {
+        let paths: Vec<(String, PathBuf)> = self
+            .projects
+            .read()
+            .values()
+            .map(|project| (project.id.clone(), project.path.clone()))
+            .collect();
+        // Looked at without the lock, as a folder on a slow or unreachable volume can take a while
+        let statuses: Vec<(String, PathBuf, ProjectPathStatus)> = paths
+            .into_iter()
+            .map(|(project_id, path)| {
+                let status = ProjectPathStatus::of(&path);
+                (project_id, path, status)
+            })
+            .collect();
+
+        let changed: Vec<AdaProject> = {
+            let mut projects = self.projects.write();
+            statuses
+                .into_iter()
+                .filter_map(|(project_id, path, status)| {
+                    let project = projects.get_mut(&project_id)?;
+                    // Skip projects relocated in the meantime
+                    if project.path != path || project.path_status == status {
+                        return None;
+                    }
+                    project.path_status = status;
+                    Some(project.clone())
+                })
+                .collect()
+        };
+
+        for project in &changed {
+            eprintln!("[Ada] Project {} folder {:?} is now {:?}", project.id, project.path, project.path_status);
+            let _ = self.save_project(project);
+            let _ = self.app_handle.emit(
+                "project-path-status",
+                ProjectPathStatusEvent { project_id: project.id.clone(), status: project.path_status },
+            );
+        }
+        changed.into_iter().map(|project| project.id).collect()
+    }
+
     pub fn delete_project_file(&self, project_id: &str) -> Result<()> {
-        let project_file = self.data_dir.join("projects").join(format!("{}.json", project_id));
+        let project_file = self.paths.project_file(project_id);
         if project_file.exists() {
             std::fs::remove_file(project_file)?;
         }
@@ -140,15 +550,67 @@ impl AppState {
     }
 
     pub fn delete_terminal_file(&self, terminal_id: &str) -> Result<()> {
-        let terminal_file = self.data_dir.join("terminals").join(format!("{}.json", terminal_id));
+        let terminal_file = self.paths.terminal_file(terminal_id);
         if terminal_file.exists() {
             std::fs::remove_file(terminal_file)?;
         }
+        let history_file = self.paths.terminal_history_file(terminal_id);
+        if history_file.exists() {
+            std::fs::remove_file(history_file)?;
+        }
+        for transcript_file in TranscriptWriter::files(&self.paths.transcript_file(terminal_id)) {
+            std::fs::remove_file(transcript_file)?;
+        }
+        let events_file = self.paths.terminal_events_file(terminal_id);
+        if events_file.exists() {
+            std::fs::remove_file(events_file)?;
+        }
+        let mcp_config_file = self.paths.mcp_config_file(terminal_id);
+        if mcp_config_file.exists() {
+            std::fs::remove_file(mcp_config_file)?;
+        }
+        let launch_env_file = self.paths.launch_env_file(terminal_id);
+        if launch_env_file.exists() {
+            std::fs::remove_file(launch_env_file)?;
+        }
         Ok(())
     }
     
+    pub fn delete_terminal_group_file(&self, group_id: &str) -> Result<()> {
+        let group_file = self.paths.group_file(group_id);
+        if group_file.exists() {
+            std::fs::remove_file(group_file)?;
+        }
+        Ok(())
+    }
+
+    pub fn delete_workspace_file(&self, workspace_id: &str) -> Result<()> {
+        let workspace_file = self.paths.workspace_file(workspace_id);
+        if workspace_file.exists() {
+            std::fs::remove_file(workspace_file)?;
+        }
+        Ok(())
+    }
+
+    pub fn delete_project_template_file(&self, template_id: &str) -> Result<()> {
+        let template_file = self.paths.template_file(template_id);
+        if template_file.exists() {
+            std::fs::remove_file(template_file)?;
+        }
+        Ok(())
+    }
+
+    pub fn delete_client_file(&self, client_id: &str) -> Result<()> {
+        let client_file = self.paths.client_file(client_id);
+        if client_file.exists() {
+            std::fs::remove_file(client_file)?;
+        }
+        Ok(())
+    }
+
     fn init_default_clients(&self) {
         use crate::clients::{ClientConfig, ClientType};
+        use crate::terminal::TerminalEnv;
         
         let default_clients = vec![
             ClientConfig {
@@ -160,6 +622,9 @@ impl AppState {
                 env: HashMap::new(),
                 description: "Anthropic's Claude Code CLI agent".into(),
                 installed: false,
+                shell: None,
+                terminal_env: TerminalEnv::default(),
+                profiles: ClientType::ClaudeCode.default_profiles(),
             },
             ClientConfig {
                 id: "opencode".into(),
@@ -170,6 +635,9 @@ impl AppState {
                 env: HashMap::new(),
                 description: "OpenCode AI coding assistant".into(),
                 installed: false,
+                shell: None,
+                terminal_env: TerminalEnv::default(),
+                profiles: ClientType::OpenCode.default_profiles(),
             },
             ClientConfig {
                 id: "codex".into(),
@@ -180,13 +648,153 @@ impl AppState {
                 env: HashMap::new(),
                 description: "OpenAI Codex CLI agent".into(),
                 installed: false,
+                shell: None,
+                terminal_env: TerminalEnv::default(),
+                profiles: ClientType::Codex.default_profiles(),
+            },
+            ClientConfig {
+                id: "aider".into(),
+                name: "Aider".into(),
+                client_type: ClientType::Aider,
+                command: "aider".into(),
+                args: vec![],
+                env: HashMap::new(),
+                description: "Aider AI pair programming in your terminal".into(),
+                installed: false,
+                shell: None,
+                terminal_env: TerminalEnv::default(),
+                profiles: ClientType::Aider.default_profiles(),
+            },
+            ClientConfig {
+                id: "amp".into(),
+                name: "Amp".into(),
+                client_type: ClientType::Amp,
+                command: "amp".into(),
+                args: vec![],
+                env: HashMap::new(),
+                description: "Sourcegraph's Amp coding agent".into(),
+                installed: false,
+                shell: None,
+                terminal_env: TerminalEnv::default(),
+                profiles: ClientType::Amp.default_profiles(),
+            },
+            ClientConfig {
+                id: "goose".into(),
+                name: "Goose".into(),
+                client_type: ClientType::Goose,
+                command: "goose".into(),
+                args: vec![],
+                env: HashMap::new(),
+                description: "Block's Goose open-source AI agent".into(),
+                installed: false,
+                shell: None,
+                terminal_env: TerminalEnv::default(),
+                profiles: ClientType::Goose.default_profiles(),
+            },
+            ClientConfig {
+                id: "qwen-code".into(),
+                name: "Qwen Code".into(),
+                client_type: ClientType::QwenCode,
+                command: "qwen".into(),
+                args: vec![],
+                env: HashMap::new(),
+                description: "Qwen Code CLI agent".into(),
+                installed: false,
+                shell: None,
+                terminal_env: TerminalEnv::default(),
+                profiles: ClientType::QwenCode.default_profiles(),
+            },
+            ClientConfig {
+                id: "copilot".into(),
+                name: "GitHub Copilot".into(),
+                client_type: ClientType::Copilot,
+                command: "copilot".into(),
+                args: vec![],
+                env: HashMap::new(),
+                description: "GitHub Copilot CLI agent".into(),
+                installed: false,
+                shell: None,
+                terminal_env: TerminalEnv::default(),
+                profiles: ClientType::Copilot.default_profiles(),
             },
         ];
         
         let mut clients = self.clients.write();
-        for mut client in default_clients {
-            client.detect_installation();
+        for client in default_clients {
             clients.insert(client.id.clone(), client);
         }
     }
+
+    /// Update which clients are installed, looking for them all in parallel. Unless `force`
+    /// is set, results cached within the last day are used instead of looking again.
+    pub fn detect_clients(&self, force: bool) {
+        let cache_file = self.paths.client_detection_file();
+        let mut cache = if force { DetectionCache::default() } else { DetectionCache::load(&cache_file) };
+        let now = chrono::Utc::now();
+
+        // Detect on copies, so the clients stay readable while agents are looked for
+        let mut clients: Vec<ClientConfig> = self.clients.read().values().cloned().collect();
+        let mut stale: Vec<&mut ClientConfig> = clients
+            .iter_mut()
+            .filter_map(|client| (!cache.apply(client, now)).then_some(client))
+            .collect();
+        if !stale.is_empty() {
+            detect_installations(stale.iter_mut().map(|client| &mut **client));
+            for client in &stale {
+                cache.record(client, now);
+            }
+        }
+
+        cache.retain_clients(clients.iter().map(|client| &client.id));
+        if let Err(e) = cache.save(&cache_file) {
+            eprintln!("[Ada] Failed to save client detection cache: {}", e);
+        }
+
+        let mut current = self.clients.write();
+        for client in clients {
+            if let Some(existing) = current.get_mut(&client.id) {
+                existing.installed = client.installed;
+            }
+        }
+    }
+}
+
+/// Prune stale terminals once an hour, in addition to the pass at startup
+pub fn spawn_terminal_gc(app_handle: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(TERMINAL_GC_INTERVAL);
+        match app_handle.try_state::<AppState>() {
+            Some(state) => {
+                state.prune_terminals();
+            }
+            None => return,
+        }
+    });
+}
+
+/// Watch for project folders being moved or deleted (see `AppState::validate_project_paths`)
+pub fn spawn_project_path_watcher(app_handle: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(PROJECT_PATH_CHECK_INTERVAL);
+        match app_handle.try_state::<AppState>() {
+            Some(state) => {
+                state.validate_project_paths();
+            }
+            None => return,
+        }
+    });
+}
+
+/// Periodically write terminals with new output to disk, at the `persist_interval_secs` setting
+pub fn spawn_persistence_flusher(app_handle: AppHandle) {
+    std::thread::spawn(move || loop {
+        let interval = match app_handle.try_state::<AppState>() {
+            Some(state) => {
+                state.flush_dirty_terminals();
+                state.settings.read().persist_interval_secs.max(1)
+            }
+            None => return,
+        };
+        std::thread::sleep(Duration::from_secs(interval));
+    });
 }
@@ -0,0 +1,195 @@
+//! OpenTelemetry traces for IPC requests, terminal lifecycle and agent turns.
+//!
+//! Compiled in with the `telemetry` feature and switched on at runtime by the standard
+//! `OTEL_EXPORTER_OTLP_ENDPOINT` (or `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT`) variable.
+//! Without either, every function here is a no-op.
+//!
+//! Spans belonging to one terminal share a trace ("run") derived from the terminal id,
+//! so worktree creation, agent turns and restarts for a run line up in one trace.
+
+#[cfg(feature = "telemetry")]
+use std::collections::HashMap;
+#[cfg(feature = "telemetry")]
+use std::sync::OnceLock;
+
+#[cfg(feature = "telemetry")]
+use opentelemetry::trace::{
+    Span as _, SpanContext, SpanId, Status, TraceContextExt, TraceFlags, TraceId, TraceState,
+    Tracer as _,
+};
+#[cfg(feature = "telemetry")]
+use opentelemetry::{global, Context, KeyValue};
+#[cfg(feature = "telemetry")]
+use parking_lot::Mutex;
+
+#[cfg(feature = "telemetry")]
+const TRACER_NAME: &str = "ada";
+
+#[cfg(feature = "telemetry")]
+static PROVIDER: OnceLock<opentelemetry_sdk::trace::TracerProvider> = OnceLock::new();
+
+/// Agent turns in progress, keyed by terminal id
+#[cfg(feature = "telemetry")]
+static TURNS: OnceLock<Mutex<HashMap<String, Span>>> = OnceLock::new();
+
+/// A span that ends when dropped. Empty when telemetry is off.
+#[derive(Default)]
+pub struct Span {
+    #[cfg(feature = "telemetry")]
+    inner: Option<global::BoxedSpan>,
+}
+
+impl Span {
+    pub fn set_attribute(&mut self, key: &'static str, value: impl Into<String>) {
+        #[cfg(feature = "telemetry")]
+        if let Some(span) = &mut self.inner {
+            span.set_attribute(KeyValue::new(key, value.into()));
+        }
+        #[cfg(not(feature = "telemetry"))]
+        let _ = (key, value);
+    }
+
+    /// Mark the span failed if `result` is an error
+    pub fn record_result<T>(&mut self, result: &crate::Result<T>) {
+        #[cfg(feature = "telemetry")]
+        if let (Some(span), Err(e)) = (&mut self.inner, result) {
+            span.set_status(Status::error(e.to_string()));
+        }
+        #[cfg(not(feature = "telemetry"))]
+        let _ = result;
+    }
+}
+
+/// Set up the OTLP exporter if an endpoint is configured. Must run inside the async runtime.
+pub fn init() {
+    #[cfg(feature = "telemetry")]
+    {
+        use opentelemetry_otlp::WithExportConfig;
+
+        let configured = ["OTEL_EXPORTER_OTLP_ENDPOINT", "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT"]
+            .iter()
+            .any(|var| std::env::var_os(var).is_some());
+        if !configured {
+            return;
+        }
+
+        // Endpoint, headers and protocol details are read from the OTEL_* environment
+        let exporter = match opentelemetry_otlp::SpanExporter::builder()
+            .with_http()
+            .with_protocol(opentelemetry_otlp::Protocol::HttpBinary)
+            .build()
+        {
+            Ok(exporter) => exporter,
+            Err(e) => {
+                eprintln!("[Ada] Failed to set up OpenTelemetry exporter: {}", e);
+                return;
+            }
+        };
+
+        let provider = opentelemetry_sdk::trace::TracerProvider::builder()
+            .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
+            .with_resource(opentelemetry_sdk::Resource::new([
+                KeyValue::new("service.name", "ada"),
+                KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+            ]))
+            .build();
+
+        global::set_tracer_provider(provider.clone());
+        let _ = PROVIDER.set(provider);
+        eprintln!("[Ada] Exporting traces via OpenTelemetry");
+    }
+}
+
+/// Flush buffered spans before the app exits
+pub fn shutdown() {
+    #[cfg(feature = "telemetry")]
+    if let Some(provider) = PROVIDER.get() {
+        if let Some(turns) = TURNS.get() {
+            turns.lock().clear();
+        }
+        let _ = provider.shutdown();
+    }
+}
+
+/// Start a span for an IPC request
+pub fn ipc_span(command: &'static str) -> Span {
+    ipc_run_span(command, None)
+}
+
+/// Start a span for an IPC request acting on the run (terminal) `run_id`, in that run's
+/// trace, so e.g. commits and merges of a run's worktree line up with its other spans
+pub fn ipc_run_span(command: &'static str, run_id: Option<&str>) -> Span {
+    #[cfg(feature = "telemetry")]
+    if PROVIDER.get().is_some() {
+        let context = run_id.map_or_else(Context::current, run_context);
+        let mut span = global::tracer(TRACER_NAME).start_with_context(command, &context);
+        span.set_attribute(KeyValue::new("rpc.system", "tauri"));
+        span.set_attribute(KeyValue::new("rpc.method", command));
+        if let Some(run_id) = run_id {
+            span.set_attribute(KeyValue::new("ada.run_id", run_id.to_string()));
+        }
+        return Span { inner: Some(span) };
+    }
+    #[cfg(not(feature = "telemetry"))]
+    let _ = (command, run_id);
+    Span::default()
+}
+
+/// Start a span in the trace of the run (terminal) `run_id`
+pub fn run_span(name: &'static str, run_id: &str) -> Span {
+    #[cfg(feature = "telemetry")]
+    if PROVIDER.get().is_some() {
+        let mut span = global::tracer(TRACER_NAME).start_with_context(name, &run_context(run_id));
+        span.set_attribute(KeyValue::new("ada.run_id", run_id.to_string()));
+        return Span { inner: Some(span) };
+    }
+    #[cfg(not(feature = "telemetry"))]
+    let _ = (name, run_id);
+    Span::default()
+}
+
+/// Record input submitted to an agent, ending its previous turn and starting a new one
+pub fn begin_turn(terminal_id: &str) {
+    #[cfg(feature = "telemetry")]
+    if PROVIDER.get().is_some() {
+        let span = run_span("agent.turn", terminal_id);
+        // The replaced span is dropped (and so ended) here
+        turns().lock().insert(terminal_id.to_string(), span);
+    }
+    #[cfg(not(feature = "telemetry"))]
+    let _ = terminal_id;
+}
+
+/// End the terminal's current turn, if any (e.g. because its process exited)
+pub fn end_turn(terminal_id: &str) {
+    #[cfg(feature = "telemetry")]
+    if let Some(turns) = TURNS.get() {
+        turns.lock().remove(terminal_id);
+    }
+    #[cfg(not(feature = "telemetry"))]
+    let _ = terminal_id;
+}
+
+#[cfg(feature = "telemetry")]
+fn turns() -> &'static Mutex<HashMap<String, Span>> {
+    TURNS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// A remote parent whose trace id is the run's uuid, so all of a run's spans share a trace
+#[cfg(feature = "telemetry")]
+fn run_context(run_id: &str) -> Context {
+    let Ok(uuid) = uuid::Uuid::parse_str(run_id) else {
+        return Context::new();
+    };
+    let bytes = uuid.into_bytes();
+    let mut span_id = [0u8; 8];
+    span_id.copy_from_slice(&bytes[8..]);
+
+    Context::new().with_remote_span_context(SpanContext::new(
+        TraceId::from_bytes(bytes),
+        SpanId::from_bytes(span_id),
+        TraceFlags::SAMPLED,
+        true,
+        TraceState::default(),
+    ))
+}
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use chrono::Utc;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::clients::ClientType;
+use crate::state::AppState;
+use super::escapes::strip_escapes;
+use super::types::TerminalStatus;
+
+/// How often the status loop reads the output of agents it can tell the status of
+const STATUS_TICK: Duration = Duration::from_secs(1);
+
+/// An agent that produced output this recently is taken to be working
+const WORKING_WINDOW: Duration = Duration::from_secs(3);
+
+/// Bytes at the end of the output read to find a prompt in
+const STATUS_TAIL_BYTES: u64 = 2048;
+
+/// Lines at the end of the output a prompt has to be on to count, so one the user
+/// already answered stops counting once the agent moves on
+const PROMPT_LINES: usize = 8;
+
+/// What an agent is doing, as far as its output shows
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentStatus {
+    /// Producing output, e.g. streaming a reply or showing a spinner
+    Working,
+    /// Quiet, ready for the user's next prompt
+    Idle,
+    /// Asking the user to allow something, such as a command or an edit
+    Permission,
+}
+
+/// Emitted when a terminal's agent status changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalAgentStatus {
+    pub terminal_id: String,
+    pub status: Option<AgentStatus>,
+}
+
+/// Text an agent shows while asking for permission. These are agents without a hook API
+/// to report their status through, so it is read off their output instead (best effort:
+/// the patterns follow what each CLI prints and need updating when that changes).
+fn permission_prompts(client_type: ClientType) -> Option<&'static [&'static str]> {
+    match client_type {
+        // Aider's confirmations, e.g. "Add file to the chat? (Y)es/(N)o/(D)on't ask again [Yes]:"
+        ClientType::Aider => Some(&["(Y)es/(N)o"]),
+        // Copilot CLI's tool approval dialog
+        ClientType::Copilot => Some(&[
+            "Do you want to run this command?",
+            "No, and tell Copilot what to do differently",
+        ]),
+        // Amp's approval of a tool call it isn't allowed to make by itself
+        ClientType::Amp => Some(&["Waiting for approval", "Approve this"]),
+        // Goose's tool confirmation (in approve mode)
+        ClientType::Goose => Some(&["Goose would like to call the above tool"]),
+        // Qwen Code keeps Gemini CLI's confirmation dialogs
+        ClientType::QwenCode => Some(&["Allow execution", "Apply this change?", "Yes, allow once"]),
+        _ => None,
+    }
+}
+
+/// The status of an agent of `client_type` whose output ends with `tail`, having been
+/// quiet for `quiet_for`; None for agents whose output Ada can't read
+pub fn detect_agent_status(client_type: ClientType, tail: &str, quiet_for: Duration) -> Option<AgentStatus> {
+    let prompts = permission_prompts(client_type)?;
+
+    let plain = strip_escapes(tail);
+    let mut lines: Vec<&str> = plain.lines().filter(|line| !line.trim().is_empty()).collect();
+    lines.drain(..lines.len().saturating_sub(PROMPT_LINES));
+
+    if lines.iter().any(|line| prompts.iter().any(|prompt| line.contains(prompt))) {
+        Some(AgentStatus::Permission)
+    } else if quiet_for < WORKING_WINDOW {
+        Some(AgentStatus::Working)
+    } else {
+        Some(AgentStatus::Idle)
+    }
+}
+
+/// Periodically update the status of running agents from their output
+pub fn spawn_agent_status_loop(app_handle: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(STATUS_TICK);
+        let Some(state) = app_handle.try_state::<AppState>() else {
+            return;
+        };
+        update_agent_statuses(&state);
+    });
+}
+
+fn update_agent_statuses(state: &AppState) {
+    let client_types: HashMap<String, ClientType> = state
+        .clients
+        .read()
+        .values()
+        .map(|client| (client.id.clone(), client.client_type))
+        .collect();
+
+    let now = Utc::now();
+    let candidates: Vec<(String, ClientType, Duration)> = state
+        .terminals
+        .read()
+        .values()
+        .filter(|t| t.status == TerminalStatus::Running)
+        .filter_map(|t| {
+            let client_type = *client_types.get(&t.client_id)?;
+            permission_prompts(client_type)?;
+            let quiet_for = t
+                .last_activity
+                .and_then(|at| (now - at).to_std().ok())
+                .unwrap_or_default();
+            Some((t.id.clone(), client_type, quiet_for))
+        })
+        .collect();
+
+    for (terminal_id, client_type, quiet_for) in candidates {
+        let Some(buffer) = state.output_buffers.read().get(&terminal_id).cloned() else {
+            continue;
+        };
+        let end = buffer.end();
+        let start = end.saturating_sub(STATUS_TAIL_BYTES).max(buffer.start());
+        let tail = buffer.read_range(start, end).unwrap_or_default();
+        let status = detect_agent_status(client_type, &tail, quiet_for);
+
+        let changed = match state.terminals.write().get_mut(&terminal_id) {
+            // Left alone if it stopped while its output was being read
+            Some(terminal) if terminal.status == TerminalStatus::Running && terminal.agent_status != status => {
+                terminal.agent_status = status;
+                true
+            }
+            _ => false,
+        };
+        if changed {
+            let _ = state.app_handle.emit(
+                "terminal-agent-status",
+                TerminalAgentStatus { terminal_id, status },
+            );
+        }
+    }
+}
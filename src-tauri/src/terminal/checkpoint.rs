@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::git;
+use crate::state::AppState;
+use super::commands::terminal_commit_identity;
+use super::types::{Terminal, TerminalStatus};
+
+/// How often the checkpoint loop looks for terminals due a checkpoint
+const CHECKPOINT_TICK: Duration = Duration::from_secs(10);
+
+/// Automatic checkpoints kept per terminal; the oldest are dropped as new ones are taken
+const MAX_CHECKPOINTS: usize = 100;
+
+/// When a terminal takes automatic checkpoints of its worktree (see `git::create_checkpoint`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CheckpointConfig {
+    /// Checkpoint at most this often while the agent is making changes (None = never)
+    #[serde(default)]
+    pub interval_secs: Option<u64>,
+    /// Checkpoint once the agent has been quiet this long after producing output,
+    /// i.e. when it has finished a turn (None = never)
+    #[serde(default)]
+    pub idle_secs: Option<u64>,
+}
+
+/// Per-terminal checkpoint bookkeeping, kept for the lifetime of the app
+#[derive(Default)]
+pub struct CheckpointTracker {
+    /// When each terminal was last checked for changes to checkpoint
+    last_checked: Mutex<HashMap<String, (Instant, DateTime<Utc>)>>,
+}
+
+/// Periodically checkpoint the worktrees of terminals that opted in
+pub fn spawn_checkpoint_loop(app_handle: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(CHECKPOINT_TICK);
+        let Some(state) = app_handle.try_state::<AppState>() else {
+            return;
+        };
+        take_due_checkpoints(&state);
+    });
+}
+
+fn take_due_checkpoints(state: &AppState) {
+    let now = Utc::now();
+    let due: Vec<(String, PathBuf)> = {
+        let last_checked = state.checkpoints.last_checked.lock();
+        state
+            .terminals
+            .read()
+            .values()
+            // A stopped agent changes nothing
+            .filter(|t| t.status == TerminalStatus::Running)
+            .filter_map(|t| {
+                let config = t.checkpoints.as_ref()?;
+                let last = last_checked.get(&t.id);
+
+                let interval_due = config.interval_secs.is_some_and(|secs| {
+                    last.is_none_or(|(at, _)| at.elapsed() >= Duration::from_secs(secs))
+                });
+                // Quiet for long enough, with output since the last check
+                let idle_due = config.idle_secs.zip(t.last_activity).is_some_and(|(secs, active_at)| {
+                    let idle = (now - active_at).to_std().unwrap_or_default();
+                    idle >= Duration::from_secs(secs)
+                        && last.is_none_or(|(_, checked_at)| active_at > *checked_at)
+                });
+
+                (interval_due || idle_due).then(|| (t.id.clone(), t.checkpoint_dir().to_path_buf()))
+            })
+            .collect()
+    };
+
+    for (terminal_id, dir) in due {
+        state
+            .checkpoints
+            .last_checked
+            .lock()
+            .insert(terminal_id.clone(), (Instant::now(), Utc::now()));
+
+        let ref_name = git::checkpoint_ref(&terminal_id);
+        let identity = terminal_commit_identity(state, &terminal_id);
+        match git::create_checkpoint(&dir, &ref_name, "Automatic checkpoint", &identity) {
+            Ok(Some(checkpoint)) => {
+                eprintln!("[Ada] Checkpointed terminal {} at {}", terminal_id, checkpoint.id);
+                if let Err(e) = git::prune_checkpoints(&dir, &ref_name, MAX_CHECKPOINTS) {
+                    eprintln!("[Ada] Failed to prune checkpoints of terminal {}: {}", terminal_id, e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("[Ada] Failed to checkpoint terminal {}: {}", terminal_id, e),
+        }
+    }
+}
+
+/// Delete a removed terminal's checkpoints, so they don't pile up in the user's repository
+pub fn delete_terminal_checkpoints(terminal: &Terminal) {
+    let dir = terminal.checkpoint_dir();
+    if !dir.exists() {
+        return;
+    }
+    if let Err(e) = git::delete_checkpoints(dir, &git::checkpoint_ref(&terminal.id)) {
+        eprintln!("[Ada] Failed to delete checkpoints of terminal {}: {}", terminal.id, e);
+    }
+}
@@ -1,18 +1,298 @@
-use tauri::State;
-use chrono::Utc;
+use tauri::{Emitter, State};
+use chrono::{DateTime, Utc};
 use std::sync::Arc;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::watch;
 
 use crate::error::{Error, Result};
 use crate::state::AppState;
 use crate::git;
-use super::{Terminal, TerminalInfo, TerminalStatus, TerminalMode, CreateTerminalRequest, ResizeTerminalRequest, TerminalOutputBuffer};
-use super::pty::{spawn_pty, write_to_pty, resize_pty};
+use crate::clients::{
+    agent_token_usage, agent_transcript, apply_agent_overrides, apply_mcp_servers, TokenUsage, TranscriptEntry,
+};
+use crate::project::{AdaProject, ProjectPathStatus};
+use crate::telemetry;
+use super::{
+    Terminal, TerminalInfo, TerminalStatus, TerminalMode, CreateTerminalRequest,
+    ResizeTerminalRequest, TerminalOutputBuffer, TranscriptWriter, PtyHandle, AgentInvocation, marker_line,
+    TerminalGroup, CreateTerminalGroupRequest, CreateTerminalGroupResult, TerminalGroupStatus,
+    TerminalGroupMemberStatus, TerminalOutputPaused, TerminalSummary,
+    TerminalProcessInfo, TerminalCommands, TerminalEnv, CommandHistoryEntry,
+};
+use super::pty::{
+    spawn_pty, write_to_pty, paste_to_pty, resize_pty, kill_ptys_off_runtime, mark_terminal_ready,
+    is_process_running, PtySpawnOptions,
+};
+use super::shell::{fallback_shells, resolve_shell, resolve_shell_path, ResolvedShell, ShellKind};
+use super::pool::replenish_pool;
+use super::checkpoint::{delete_terminal_checkpoints, CheckpointConfig};
+use super::events::{read_events, record_event, TerminalEvent, TerminalEventKind};
+use super::keep_alive::{apply_ssh_keep_alive, KeepAliveConfig};
+use super::shell_integration::apply_shell_integration;
+use super::summary::{recent_transcript, summarize_transcript, summarizer_args};
+
+/// How long `wait_for_ready` waits for a new terminal's first output
+const READY_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// An agent launched by `spawn_agent`
+struct SpawnedAgent {
+    pty_handle: PtyHandle,
+    /// Audit record of the launch, when invocation logging is enabled
+    invocation: Option<AgentInvocation>,
+    /// The shell the agent actually runs under
+    shell: Option<PathBuf>,
+    /// Set when the requested shell failed to spawn and a fallback was used
+    shell_warning: Option<String>,
+}
+
+/// A terminal's own settings for launching its agent
+#[derive(Default)]
+struct AgentLaunch<'a> {
+    keep_alive: Option<&'a KeepAliveConfig>,
+    terminal_env: Option<&'a TerminalEnv>,
+    model: Option<&'a str>,
+    profile: Option<&'a str>,
+}
+
+impl<'a> AgentLaunch<'a> {
+    fn of(terminal: &'a Terminal) -> Self {
+        Self {
+            keep_alive: terminal.keep_alive.as_ref(),
+            terminal_env: terminal.terminal_env.as_ref(),
+            model: terminal.model.as_deref(),
+            profile: terminal.profile.as_deref(),
+        }
+    }
+}
+
+/// Check an agent launch against the flag deny-list, then spawn it in a PTY
+/// (adopting a warm pooled shell when possible).
+/// If the shell fails to spawn, the remaining login shell candidates are tried in turn.
+fn spawn_agent(
+    state: &AppState,
+    project_id: &str,
+    terminal_id: &str,
+    options: PtySpawnOptions<'_>,
+    launch: AgentLaunch<'_>,
+    output_buffer: Arc<TerminalOutputBuffer>,
+) -> Result<SpawnedAgent> {
+    let mut client = options.client.clone();
+    apply_ssh_keep_alive(&mut client, launch.keep_alive);
+    // The project's variables, MCP servers and agent overrides go on top of the client's
+    // config, and the terminal's model, profile and TERM/locale over those
+    if let Some(project) = state.projects.read().get(project_id) {
+        client.env.extend(project.settings.env.clone());
+        apply_mcp_servers(
+            &mut client,
+            &project.settings.mcp_servers,
+            &state.paths.mcp_config_file(terminal_id),
+        )?;
+        let mut overrides = project.settings.agent_overrides.get(&client.id).cloned().unwrap_or_default();
+        if let Some(model) = launch.model {
+            overrides.model = Some(model.to_string());
+        }
+        apply_agent_overrides(&mut client, &overrides);
+    }
+    if let Some(profile) = launch.profile {
+        client.apply_profile(profile)?;
+    }
+    let env = client.terminal_env.merged(launch.terminal_env).to_env();
+    client.env.extend(env);
+    apply_shell_integration(&mut client, &state.paths.shell_integration_dir());
+    let options = PtySpawnOptions { client: &client, ..options };
+
+    let (log_invocations, denied_flag, pool_size, pool_max_idle) = {
+        let settings = state.settings.read();
+        (
+            settings.log_agent_invocations,
+            settings.find_denied_flag(&options.client.args).map(String::from),
+            settings.pty_pool_size,
+            Duration::from_secs(settings.pty_pool_max_idle_secs),
+        )
+    };
+
+    if let Some(flag) = denied_flag {
+        eprintln!(
+            "[Ada] Blocked launch of '{}' in terminal {}: flag '{}' is denied",
+            options.client.id, terminal_id, flag
+        );
+        return Err(Error::InvalidRequest(format!(
+            "Agent flag '{}' is blocked by the denied flags setting",
+            flag
+        )));
+    }
+
+    let mut invocation = log_invocations.then(|| AgentInvocation {
+        client_id: options.client.id.clone(),
+        argv: std::iter::once(options.client.get_command_path().to_string_lossy().to_string())
+            .chain(options.client.args.iter().cloned())
+            .collect(),
+        shell: options.shell.map(|p| p.to_string_lossy().to_string()),
+        working_dir: options.working_dir.to_string_lossy().to_string(),
+        timestamp: Utc::now(),
+    });
+
+    // Pooled shells are driven with POSIX syntax, and only ever run the agent itself
+    let pool_size = match options.shell {
+        Some(shell) if ShellKind::from_path(shell) != ShellKind::Posix => 0,
+        _ if options.setup_command.is_some() => 0,
+        _ => pool_size,
+    };
+
+    let pooled_handle = if pool_size > 0 {
+        let env_file = state.paths.launch_env_file(terminal_id);
+        state.pty_pool.try_adopt(project_id, terminal_id, options, output_buffer.clone(), &env_file, pool_max_idle)
+    } else {
+        None
+    };
+    let mut shell = options.shell.map(PathBuf::from);
+    let mut shell_warning = None;
+    let pty_handle = match pooled_handle {
+        Some(pty_handle) => pty_handle,
+        None => match spawn_pty(&state.app_handle, terminal_id, options, output_buffer.clone()) {
+            Ok(pty_handle) => pty_handle,
+            Err(e) => {
+                let Some(failed) = options.shell else {
+                    return Err(e);
+                };
+                let (pty_handle, fallback) = spawn_with_fallback_shell(
+                    state,
+                    terminal_id,
+                    options,
+                    failed,
+                    output_buffer,
+                )
+                .ok_or(e)?;
+
+                let warning = format!(
+                    "Shell {} failed to start; using {} instead",
+                    failed.display(),
+                    fallback.display()
+                );
+                eprintln!("[Ada] Terminal {}: {}", terminal_id, warning);
+                if let Some(invocation) = &mut invocation {
+                    invocation.shell = Some(fallback.to_string_lossy().to_string());
+                }
+                shell = Some(fallback);
+                shell_warning = Some(warning);
+                pty_handle
+            }
+        },
+    };
+
+    // Keep spares warm for the next shell-launched terminal in this project
+    if let (Some(shell), true) = (shell.as_deref(), pool_size > 0) {
+        replenish_pool(&state.app_handle, project_id, shell, options.working_dir, pool_size);
+    }
+
+    record_event(state, terminal_id, project_id, TerminalEventKind::Started { client_id: client.id.clone() });
+
+    Ok(SpawnedAgent { pty_handle, invocation, shell, shell_warning })
+}
+
+/// Try each fallback shell for a launch whose shell `failed` to spawn,
+/// returning the first that starts
+fn spawn_with_fallback_shell(
+    state: &AppState,
+    terminal_id: &str,
+    options: PtySpawnOptions<'_>,
+    failed: &std::path::Path,
+    output_buffer: Arc<TerminalOutputBuffer>,
+) -> Option<(PtyHandle, PathBuf)> {
+    fallback_shells(failed).into_iter().find_map(|fallback| {
+        let options = PtySpawnOptions { shell: Some(&fallback), ..options };
+        spawn_pty(&state.app_handle, terminal_id, options, output_buffer.clone())
+            .ok()
+            .map(|pty_handle| (pty_handle, fallback))
+    })
+}
 
 #[tauri::command]
 pub async fn create_terminal(
     state: State<'_, AppState>,
     request: CreateTerminalRequest,
+) -> Result<TerminalInfo> {
+    let mut span = telemetry::ipc_span("create_terminal");
+    let wait_for_ready = request.wait_for_ready;
+    let result = create_terminal_internal(&state, request, None, false);
+    span.record_result(&result);
+    let info = result?;
+
+    if !wait_for_ready {
+        return Ok(info);
+    }
+
+    wait_until_ready(&state, &info.id).await;
+    let terminals = state.terminals.read();
+    let shell_warning = info.shell_warning.clone();
+    let mut info = terminals.get(&info.id).map(TerminalInfo::from).unwrap_or(info);
+    info.shell_warning = shell_warning;
+    Ok(info)
+}
+
+/// Wait (up to `READY_TIMEOUT`) for a terminal's process to produce its first output
+async fn wait_until_ready(state: &AppState, terminal_id: &str) {
+    let ready = state.pty_handles.read().get(terminal_id).map(|h| h.ready.clone());
+    if let Some(mut ready) = ready {
+        // Errors mean the process exited first; time-outs just leave it Starting
+        let _ = tokio::time::timeout(READY_TIMEOUT, ready.wait_for(|ready| *ready)).await;
+    }
+}
+
+/// Store a terminal's new PTY and record its process id
+fn attach_pty(state: &AppState, terminal_id: &str, pty_handle: PtyHandle) {
+    if let Some(terminal) = state.terminals.write().get_mut(terminal_id) {
+        terminal.pid = pty_handle.pid;
+    }
+    let ready = pty_handle.ready.clone();
+    state.pty_handles.write().insert(terminal_id.to_string(), pty_handle);
+    sync_ready_status(state, terminal_id, &ready);
+}
+
+/// Catch up on readiness reported by the output reader before the terminal was stored
+fn sync_ready_status(state: &AppState, terminal_id: &str, ready: &watch::Receiver<bool>) {
+    if *ready.borrow() {
+        mark_terminal_ready(&state.app_handle, terminal_id);
+    }
+}
+
+/// Where a worktree terminal on `branch_spec` runs: the spec to add the worktree with (new
+/// branches start at the project's base branch, which doesn't apply to other roots), the
+/// branch, and the worktree folder, named by the project's template (the branch name by
+/// default)
+fn plan_worktree(
+    project: &AdaProject,
+    root: Option<&str>,
+    client_id: &str,
+    branch_spec: &str,
+) -> Result<(String, String, PathBuf)> {
+    let root_path = project.root_path(root)?;
+    let branch_spec = match (root, &project.settings.default_base_branch) {
+        (None, Some(base)) => git::branch_spec_with_base(&root_path, branch_spec, base),
+        _ => branch_spec.to_string(),
+    };
+    // Could be "wt-baseBranch/newBranchName" or just a branch name
+    let branch = git::branch_from_spec(&root_path, &branch_spec);
+    let worktree_path = git::worktree_path_for(
+        &project.worktree_base(root)?,
+        project.settings.worktree_name_template.as_deref(),
+        &git::WorktreeNameContext {
+            branch: &branch,
+            agent: Some(client_id),
+            project: &project.name,
+        },
+    );
+    Ok((branch_spec, branch, worktree_path))
+}
+
+/// Create a terminal, optionally as a member of a run group. `prepared_worktree` says the
+/// caller has just added the terminal's worktree, so it is set up like a new one.
+pub fn create_terminal_internal(
+    state: &AppState,
+    request: CreateTerminalRequest,
+    group_id: Option<String>,
+    prepared_worktree: bool,
 ) -> Result<TerminalInfo> {
     // Get project
     let project = {
@@ -22,6 +302,21 @@ pub async fn create_terminal(
             .cloned()
             .ok_or_else(|| Error::ProjectNotFound(request.project_id.clone()))?
     };
+    if project.is_archived() {
+        return Err(Error::InvalidRequest(format!(
+            "Project '{}' is archived; unarchive it to start agents",
+            project.name
+        )));
+    }
+    if project.check_path() == ProjectPathStatus::Missing {
+        return Err(Error::InvalidRequest(format!(
+            "The folder of project '{}' ({}) was moved or deleted; relocate the project to its new location",
+            project.name,
+            project.path.display()
+        )));
+    }
+
+    project.settings.check_client_allowed(&request.client_id)?;
 
     // Get client configuration
     let client = {
@@ -32,13 +327,36 @@ pub async fn create_terminal(
             .ok_or_else(|| Error::ClientNotFound(request.client_id.clone()))?
     };
 
+    let model = request.model.as_deref().map(str::trim).filter(|m| !m.is_empty()).map(String::from);
+    if model.is_some() && !client.client_type.supports_model() {
+        return Err(Error::InvalidRequest(format!("{} doesn't take a model", client.name)));
+    }
+
+    let profile = request.profile.as_deref().map(str::trim).filter(|p| !p.is_empty()).map(String::from);
+    if let Some(profile) = &profile {
+        if !client.profiles.iter().any(|p| &p.name == profile) {
+            return Err(Error::InvalidRequest(format!("{} has no launch profile '{}'", client.name, profile)));
+        }
+    }
+
     let terminal_id = uuid::Uuid::new_v4().to_string();
+    let mut span = telemetry::run_span("terminal.create", &terminal_id);
+    span.set_attribute("ada.project_id", request.project_id.clone());
+    span.set_attribute("ada.client_id", request.client_id.clone());
+
+    // The folder the mode applies to: the project itself or one of its additional roots
+    let root_path = project.root_path(request.root.as_deref())?;
+
+    let mode = request
+        .mode
+        .or(project.settings.default_terminal_mode)
+        .unwrap_or(TerminalMode::CurrentBranch);
 
     // Determine working directory, worktree path, branch, and folder_path based on mode
-    let (working_dir, worktree_path, branch, folder_path) = match request.mode {
+    let (working_dir, worktree_path, branch, folder_path, new_worktree) = match mode {
         TerminalMode::Main | TerminalMode::CurrentBranch => {
             // Run at project root on current branch
-            (project.path.clone(), None, None, None)
+            (root_path.clone(), None, None, None, false)
         }
         TerminalMode::Folder => {
             // Run in a subfolder of project
@@ -46,81 +364,126 @@ pub async fn create_terminal(
                 Error::InvalidRequest("Folder mode requires folder_path".into())
             })?;
             let folder_path_buf = PathBuf::from(folder);
-            let working_dir = project.path.join(&folder_path_buf);
+            let working_dir = root_path.join(&folder_path_buf);
             if !working_dir.exists() {
                 return Err(Error::InvalidRequest(format!("Folder does not exist: {}", folder)));
             }
-            (working_dir, None, None, Some(folder_path_buf))
+            (working_dir, None, None, Some(folder_path_buf), false)
         }
         TerminalMode::Worktree => {
             // Run in an isolated worktree
             let branch_spec = request.worktree_branch.as_ref().ok_or_else(|| {
                 Error::InvalidRequest("Worktree mode requires worktree_branch".into())
             })?;
-
-            // Parse branch spec - could be "wt-baseBranch/newBranchName" or just a branch name
-            let actual_branch = if branch_spec.starts_with("wt-") {
-                // Extract the new branch name from the format
-                let rest = branch_spec.strip_prefix("wt-").unwrap();
-                if let Some(slash_pos) = rest.find('/') {
-                    rest[slash_pos + 1..].to_string()
+            let (branch_spec, actual_branch, worktree_path) =
+                plan_worktree(&project, request.root.as_deref(), &client.id, branch_spec)?;
+            let branch_spec = &branch_spec;
+
+            // Create worktree if it doesn't exist; an existing worktree on the branch is reused
+            let new_worktree = !worktree_path.exists();
+            if new_worktree {
+                let mut worktree_span = telemetry::run_span("worktree.create", &terminal_id);
+                worktree_span.set_attribute("ada.branch", actual_branch.clone());
+                let options = project.settings.worktree_options();
+                let created = if options.cow_clone {
+                    git::create_worktree_cloned(&root_path, branch_spec, &worktree_path, options.bypass)
                 } else {
-                    branch_spec.clone()
+                    git::create_worktree_internal(&root_path, branch_spec, &worktree_path, options.bypass)
+                };
+                worktree_span.record_result(&created);
+                created?;
+
+                // A build without its submodules or seed files is no use to the agent,
+                // but it can still run
+                if let Err(e) = git::finish_worktree(&root_path, &worktree_path, &options, &|_, _| {}) {
+                    eprintln!("[Ada] {} for {:?}", e, worktree_path);
                 }
-            } else {
-                branch_spec.clone()
-            };
-
-            let worktree_base = project.settings.worktree_base_path
-                .clone()
-                .unwrap_or_else(|| project.path.join(".worktrees"));
-
-            // Use the actual branch name for the worktree path
-            let worktree_path = worktree_base.join(actual_branch.replace('/', "-"));
-
-            // Create worktree if it doesn't exist
-            if !worktree_path.exists() {
-                git::create_worktree_internal(&project.path, branch_spec, &worktree_path)?;
             }
 
-            (worktree_path.clone(), Some(worktree_path), Some(actual_branch), None)
+            (
+                worktree_path.clone(),
+                Some(worktree_path),
+                Some(actual_branch),
+                None,
+                new_worktree || prepared_worktree,
+            )
         }
     };
 
+    // Fresh worktrees get the project's setup (dependencies, direnv, ...) before the agent starts
+    let setup_command = project.settings.setup_command.as_deref().filter(|_| new_worktree);
+
+    let resolved_shell = resolve_shell(project.settings.shell.as_ref(), client.shell.as_ref())?;
+
     // Create output buffer
-    let output_buffer = Arc::new(TerminalOutputBuffer::new());
+    let output_buffer = state.create_output_buffer(&terminal_id, request.scrollback);
 
     // Spawn PTY
-    let pty_handle = spawn_pty(
-        &state.app_handle,
+    let spawned = spawn_agent(
+        state,
+        &request.project_id,
         &terminal_id,
-        &working_dir,
-        &client,
-        120,
-        30,
+        PtySpawnOptions {
+            working_dir: &working_dir,
+            client: &client,
+            shell: resolved_shell.path.as_deref(),
+            setup_command,
+            cols: 120,
+            rows: 30,
+        },
+        AgentLaunch {
+            keep_alive: request.keep_alive.as_ref(),
+            terminal_env: request.terminal_env.as_ref(),
+            model: model.as_deref(),
+            profile: profile.as_deref(),
+        },
         output_buffer.clone(),
     )?;
 
-    let terminal = Terminal {
+    let mut terminal = Terminal {
         id: terminal_id.clone(),
         project_id: request.project_id.clone(),
         name: request.name,
         client_id: request.client_id,
         working_dir,
+        current_dir: None,
         branch,
         worktree_path,
-        status: TerminalStatus::Running,
+        status: TerminalStatus::Starting,
         created_at: Utc::now(),
-        mode: request.mode,
+        mode,
         is_main: false,
         folder_path,
+        root: request.root,
+        shell: spawned.shell,
+        restored: false,
+        respawn_count: 0,
+        invocations: Vec::new(),
+        last_activity: None,
+        group_id,
+        ended_at: None,
+        started_at: Some(Utc::now()),
+        run_secs: 0,
+        scrollback: request.scrollback,
+        auto_resume: request.auto_resume.unwrap_or(true),
+        keep_alive: request.keep_alive,
+        terminal_env: request.terminal_env,
+        model,
+        profile,
+        checkpoints: None,
+        summary: None,
+        commands: Vec::new(),
+        pid: spawned.pty_handle.pid,
+        agent_status: None,
     };
+    terminal.record_invocation(spawned.invocation);
 
-    let terminal_info = TerminalInfo::from(&terminal);
+    let mut terminal_info = TerminalInfo::from(&terminal);
+    terminal_info.shell_warning = spawned.shell_warning.or(resolved_shell.warning);
 
     // Add terminal, pty handle, and output buffer to state
     state.terminals.write().insert(terminal_id.clone(), terminal);
-    state.pty_handles.write().insert(terminal_id.clone(), pty_handle);
+    attach_pty(state, &terminal_id, spawned.pty_handle);
     state.output_buffers.write().insert(terminal_id.clone(), output_buffer);
 
     // Update project
@@ -162,6 +525,8 @@ pub fn create_main_terminal_internal(
         }
     }
 
+    project.settings.check_client_allowed(client_id)?;
+
     // Get client configuration
     let client = {
         let clients = state.clients.read();
@@ -180,41 +545,72 @@ pub fn create_main_terminal_internal(
     }
 
     let terminal_id = uuid::Uuid::new_v4().to_string();
+    let resolved_shell = resolve_shell(project.settings.shell.as_ref(), client.shell.as_ref())?;
 
     // Create output buffer
-    let output_buffer = Arc::new(TerminalOutputBuffer::new());
+    let output_buffer = state.create_output_buffer(&terminal_id, None);
 
     // Spawn PTY at project root
-    let pty_handle = spawn_pty(
-        &state.app_handle,
+    let spawned = spawn_agent(
+        state,
+        project_id,
         &terminal_id,
-        &project.path,
-        &client,
-        120,
-        30,
+        PtySpawnOptions {
+            working_dir: &project.path,
+            client: &client,
+            shell: resolved_shell.path.as_deref(),
+            setup_command: None,
+            cols: 120,
+            rows: 30,
+        },
+        AgentLaunch::default(),
         output_buffer.clone(),
     )?;
 
-    let terminal = Terminal {
+    let mut terminal = Terminal {
         id: terminal_id.clone(),
         project_id: project_id.to_string(),
         name: "main".to_string(),
         client_id: client_id.to_string(),
         working_dir: project.path.clone(),
+        current_dir: None,
         branch: None,
         worktree_path: None,
-        status: TerminalStatus::Running,
+        status: TerminalStatus::Starting,
         created_at: Utc::now(),
         mode: TerminalMode::Main,
         is_main: true,
         folder_path: None,
+        root: None,
+        shell: spawned.shell,
+        restored: false,
+        respawn_count: 0,
+        invocations: Vec::new(),
+        last_activity: None,
+        group_id: None,
+        ended_at: None,
+        started_at: Some(Utc::now()),
+        run_secs: 0,
+        scrollback: None,
+        auto_resume: true,
+        keep_alive: None,
+        terminal_env: None,
+        model: None,
+        profile: None,
+        checkpoints: None,
+        summary: None,
+        commands: Vec::new(),
+        pid: spawned.pty_handle.pid,
+        agent_status: None,
     };
+    terminal.record_invocation(spawned.invocation);
 
-    let terminal_info = TerminalInfo::from(&terminal);
+    let mut terminal_info = TerminalInfo::from(&terminal);
+    terminal_info.shell_warning = spawned.shell_warning.or(resolved_shell.warning);
 
     // Add terminal, pty handle, and output buffer to state
     state.terminals.write().insert(terminal_id.clone(), terminal);
-    state.pty_handles.write().insert(terminal_id.clone(), pty_handle);
+    attach_pty(state, &terminal_id, spawned.pty_handle);
     state.output_buffers.write().insert(terminal_id.clone(), output_buffer);
 
     // Update project with main terminal ID
@@ -281,10 +677,65 @@ pub async fn write_terminal(
         .ok_or_else(|| Error::TerminalNotFound(terminal_id.clone()))?;
     
     write_to_pty(pty_handle, data.as_bytes())?;
+    if data.contains('\r') {
+        telemetry::begin_turn(&terminal_id);
+    }
     
     Ok(())
 }
 
+/// Paste text into a terminal, using bracketed paste when the program supports it so a
+/// multi-line prompt is received as a whole instead of being submitted line by line
+#[tauri::command]
+pub async fn paste_terminal(
+    state: State<'_, AppState>,
+    terminal_id: String,
+    text: String,
+) -> Result<()> {
+    let pty_handles = state.pty_handles.read();
+    let pty_handle = pty_handles
+        .get(&terminal_id)
+        .ok_or_else(|| Error::TerminalNotFound(terminal_id.clone()))?;
+
+    paste_to_pty(pty_handle, &text)
+}
+
+/// Write the same input to several terminals at once (e.g. one prompt to every worktree agent).
+/// Every terminal is attempted; failures are reported together afterwards.
+#[tauri::command]
+pub async fn write_terminals(
+    state: State<'_, AppState>,
+    terminal_ids: Vec<String>,
+    data: String,
+) -> Result<()> {
+    let pty_handles = state.pty_handles.read();
+    let mut failed = Vec::new();
+
+    for terminal_id in &terminal_ids {
+        let result = pty_handles
+            .get(terminal_id)
+            .ok_or_else(|| Error::TerminalNotFound(terminal_id.clone()))
+            .and_then(|pty_handle| write_to_pty(pty_handle, data.as_bytes()));
+
+        match result {
+            Ok(()) if data.contains('\r') => telemetry::begin_turn(terminal_id),
+            Ok(()) => {}
+            Err(e) => failed.push(format!("{} ({})", terminal_id, e)),
+        }
+    }
+
+    if !failed.is_empty() {
+        return Err(Error::TerminalError(format!(
+            "Failed to write to {} of {} terminals: {}",
+            failed.len(),
+            terminal_ids.len(),
+            failed.join(", ")
+        )));
+    }
+
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn resize_terminal(
     state: State<'_, AppState>,
@@ -300,6 +751,29 @@ pub async fn resize_terminal(
     Ok(())
 }
 
+/// Pause or resume reading a terminal's output. A paused PTY isn't drained, so a process
+/// flooding the terminal blocks on its own writes until resumed instead of being killed.
+#[tauri::command]
+pub async fn set_terminal_output_paused(
+    state: State<'_, AppState>,
+    terminal_id: String,
+    paused: bool,
+) -> Result<()> {
+    {
+        let pty_handles = state.pty_handles.read();
+        let pty_handle = pty_handles
+            .get(&terminal_id)
+            .ok_or_else(|| Error::TerminalNotFound(terminal_id.clone()))?;
+        pty_handle.paused.send_replace(paused);
+    }
+
+    let _ = state.app_handle.emit(
+        "terminal-output-paused",
+        TerminalOutputPaused { terminal_id, paused },
+    );
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn close_terminal(
     state: State<'_, AppState>,
@@ -315,15 +789,23 @@ pub async fn close_terminal(
         }
     }
 
-    // Remove terminal, pty handle, and output buffer
+    let _span = telemetry::run_span("terminal.close", &terminal_id);
+    telemetry::end_turn(&terminal_id);
+
+    // Stop the process (and its process group) before dropping any state
+    let pty_handle = state.pty_handles.write().remove(&terminal_id);
+    kill_ptys_off_runtime(pty_handle.into_iter().collect()).await;
+
+    // Remove terminal and output buffer
     let terminal = state.terminals.write().remove(&terminal_id);
-    state.pty_handles.write().remove(&terminal_id);
     state.output_buffers.write().remove(&terminal_id);
 
     // Delete terminal file
     let _ = state.delete_terminal_file(&terminal_id);
 
     if let Some(terminal) = terminal {
+        delete_terminal_checkpoints(&terminal);
+
         // Update project
         let mut projects = state.projects.write();
         if let Some(project) = projects.get_mut(&terminal.project_id) {
@@ -335,19 +817,258 @@ pub async fn close_terminal(
     Ok(())
 }
 
+/// Tokens the terminal's agent has used since the terminal was created, from the agent's
+/// session logs. None for agents whose logs Ada can't read.
+#[tauri::command]
+pub async fn get_terminal_usage(
+    state: State<'_, AppState>,
+    terminal_id: String,
+) -> Result<Option<TokenUsage>> {
+    let terminal = state
+        .terminals
+        .read()
+        .get(&terminal_id)
+        .cloned()
+        .ok_or_else(|| Error::TerminalNotFound(terminal_id.clone()))?;
+    let client_type = state
+        .clients
+        .read()
+        .get(&terminal.client_id)
+        .map(|c| c.client_type)
+        .ok_or_else(|| Error::ClientNotFound(terminal.client_id.clone()))?;
+
+    // Reading the agent's session logs blocks, so keep it off the async runtime's threads
+    tauri::async_runtime::spawn_blocking(move || {
+        agent_token_usage(client_type, &terminal.working_dir, terminal.created_at)
+    })
+    .await
+    .map_err(|e| Error::TerminalError(e.to_string()))
+}
+
 #[tauri::command]
 pub async fn get_terminal_history(
     state: State<'_, AppState>,
     terminal_id: String,
 ) -> Result<Vec<String>> {
-    let output_buffers = state.output_buffers.read();
-    let buffer = output_buffers
-        .get(&terminal_id)
+    let buffer = state
+        .output_buffer(&terminal_id)
         .ok_or_else(|| Error::TerminalNotFound(terminal_id))?;
 
     Ok(buffer.get_history())
 }
 
+/// Full output of a terminal: anything rotated out to its transcript files followed by
+/// the in-memory scrollback. Without unlimited scrollback this is just the history.
+#[tauri::command]
+pub async fn get_terminal_transcript(
+    state: State<'_, AppState>,
+    terminal_id: String,
+) -> Result<String> {
+    let history = state
+        .output_buffer(&terminal_id)
+        .map(|b| b.get_history())
+        .ok_or_else(|| Error::TerminalNotFound(terminal_id.clone()))?;
+
+    let mut transcript = String::new();
+    for file in TranscriptWriter::files(&state.paths.transcript_file(&terminal_id)) {
+        transcript.push_str(&String::from_utf8_lossy(&std::fs::read(file)?));
+    }
+
+    transcript.extend(history);
+    Ok(transcript)
+}
+
+/// The terminal agent's conversation (prompts, replies, tool calls and their results) since
+/// the terminal was created, from the agent's session logs rather than its scrollback.
+/// None for agents whose logs Ada can't read.
+#[tauri::command]
+pub async fn get_agent_transcript(
+    state: State<'_, AppState>,
+    terminal_id: String,
+) -> Result<Option<Vec<TranscriptEntry>>> {
+    let terminal = state
+        .terminals
+        .read()
+        .get(&terminal_id)
+        .cloned()
+        .ok_or_else(|| Error::TerminalNotFound(terminal_id.clone()))?;
+    let client_type = state
+        .clients
+        .read()
+        .get(&terminal.client_id)
+        .map(|c| c.client_type)
+        .ok_or_else(|| Error::ClientNotFound(terminal.client_id.clone()))?;
+
+    // Reading the agent's session logs blocks, so keep it off the async runtime's threads
+    tauri::async_runtime::spawn_blocking(move || {
+        agent_transcript(client_type, &terminal.working_dir, terminal.created_at)
+    })
+    .await
+    .map_err(|e| Error::TerminalError(e.to_string()))
+}
+
+/// Commands run in a terminal's shell, as reported by shell integration (OSC 133),
+/// with offsets into its output for jumping between them
+#[tauri::command]
+pub async fn get_terminal_commands(
+    state: State<'_, AppState>,
+    terminal_id: String,
+) -> Result<TerminalCommands> {
+    let commands = {
+        let terminals = state.terminals.read();
+        terminals
+            .get(&terminal_id)
+            .map(|t| t.commands.clone())
+            .ok_or_else(|| Error::TerminalNotFound(terminal_id.clone()))?
+    };
+    let scrollback_start = state
+        .output_buffer(&terminal_id)
+        .map(|buffer| buffer.start())
+        .unwrap_or(0);
+
+    Ok(TerminalCommands { scrollback_start, commands })
+}
+
+/// Commands that ran in a terminal's shell, oldest first, with their timing and exit codes.
+/// Requires shell integration, so agents launched directly have no history.
+#[tauri::command]
+pub async fn get_command_history(
+    state: State<'_, AppState>,
+    terminal_id: String,
+) -> Result<Vec<CommandHistoryEntry>> {
+    let terminals = state.terminals.read();
+    let terminal = terminals
+        .get(&terminal_id)
+        .ok_or_else(|| Error::TerminalNotFound(terminal_id.clone()))?;
+
+    Ok(terminal
+        .commands
+        .iter()
+        .filter(|command| command.output_offset.is_some())
+        .map(CommandHistoryEntry::from)
+        .collect())
+}
+
+/// Check whether a terminal's agent process is alive and how much memory and CPU
+/// it (with everything it spawned) is using
+#[tauri::command]
+pub async fn get_terminal_process(
+    state: State<'_, AppState>,
+    terminal_id: String,
+) -> Result<TerminalProcessInfo> {
+    if !state.terminals.read().contains_key(&terminal_id) {
+        return Err(Error::TerminalNotFound(terminal_id));
+    }
+
+    let (pid, alive) = {
+        let pty_handles = state.pty_handles.read();
+        match pty_handles.get(&terminal_id) {
+            Some(pty_handle) => (pty_handle.pid, is_process_running(pty_handle)),
+            None => (None, false),
+        }
+    };
+    let pgid = pid.filter(|_| cfg!(unix));
+
+    #[cfg(unix)]
+    let usage = match pgid.filter(|_| alive) {
+        // Sampling CPU usage takes a while
+        Some(pgid) => tauri::async_runtime::spawn_blocking(move || super::pty::process_group_usage(pgid))
+            .await
+            .map_err(|e| Error::TerminalError(e.to_string()))?,
+        None => None,
+    };
+    #[cfg(not(unix))]
+    let usage: Option<(u64, f64)> = None;
+
+    Ok(TerminalProcessInfo {
+        terminal_id,
+        pid,
+        pgid,
+        alive,
+        rss_kb: usage.map(|(rss_kb, _)| rss_kb),
+        cpu_percent: usage.map(|(_, cpu_percent)| cpu_percent),
+    })
+}
+
+/// Summarize a terminal's recent output with the agent set as `summary_client_id`,
+/// storing the summary on the terminal so listings can show it
+#[tauri::command]
+pub async fn summarize_terminal(
+    state: State<'_, AppState>,
+    terminal_id: String,
+) -> Result<TerminalInfo> {
+    let client_id = state
+        .settings
+        .read()
+        .summary_client_id
+        .clone()
+        .ok_or_else(|| Error::InvalidRequest("No summary agent is configured".into()))?;
+
+    let client = {
+        let clients = state.clients.read();
+        clients
+            .get(&client_id)
+            .cloned()
+            .ok_or_else(|| Error::ClientNotFound(client_id.clone()))?
+    };
+
+    let args = summarizer_args(&client);
+    if let Some(flag) = state.settings.read().find_denied_flag(&args) {
+        return Err(Error::InvalidRequest(format!(
+            "Agent flag '{}' is blocked by the denied flags setting",
+            flag
+        )));
+    }
+
+    let working_dir = {
+        let terminals = state.terminals.read();
+        terminals
+            .get(&terminal_id)
+            .map(|t| t.working_dir.clone())
+            .ok_or_else(|| Error::TerminalNotFound(terminal_id.clone()))?
+    };
+
+    let buffer = state
+        .output_buffer(&terminal_id)
+        .ok_or_else(|| Error::TerminalNotFound(terminal_id.clone()))?;
+    let transcript = recent_transcript(&buffer.get_history());
+    if transcript.trim().is_empty() {
+        return Err(Error::InvalidRequest("Terminal has no output to summarize".into()));
+    }
+
+    let text = summarize_transcript(&client, &args, &working_dir, &transcript).await?;
+
+    let info = {
+        let mut terminals = state.terminals.write();
+        let terminal = terminals
+            .get_mut(&terminal_id)
+            .ok_or_else(|| Error::TerminalNotFound(terminal_id.clone()))?;
+        terminal.summary = Some(TerminalSummary {
+            text,
+            client_id,
+            created_at: Utc::now(),
+        });
+        TerminalInfo::from(&*terminal)
+    };
+
+    let _ = state.save_terminal(&terminal_id);
+    Ok(info)
+}
+
+/// A terminal's event log (agent launches, first output, finished shell commands and
+/// exits), oldest first; with `since`, only the events after it
+#[tauri::command]
+pub async fn get_terminal_events(
+    state: State<'_, AppState>,
+    terminal_id: String,
+    since: Option<DateTime<Utc>>,
+) -> Result<Vec<TerminalEvent>> {
+    if !state.terminals.read().contains_key(&terminal_id) {
+        return Err(Error::TerminalNotFound(terminal_id));
+    }
+    Ok(read_events(&state.paths.terminal_events_file(&terminal_id), since))
+}
+
 #[tauri::command]
 pub async fn mark_terminal_stopped(
     state: State<'_, AppState>,
@@ -357,7 +1078,7 @@ pub async fn mark_terminal_stopped(
     {
         let mut terminals = state.terminals.write();
         if let Some(t) = terminals.get_mut(&terminal_id) {
-            t.status = TerminalStatus::Stopped;
+            t.mark_stopped(Utc::now());
         }
     }
 
@@ -380,6 +1101,17 @@ pub async fn switch_terminal_agent(
     terminal_id: String,
     new_client_id: String,
 ) -> Result<TerminalInfo> {
+    // The project may rule the new agent out
+    let project_id = state
+        .terminals
+        .read()
+        .get(&terminal_id)
+        .map(|terminal| terminal.project_id.clone())
+        .ok_or_else(|| Error::TerminalNotFound(terminal_id.clone()))?;
+    if let Some(project) = state.projects.read().get(&project_id) {
+        project.settings.check_client_allowed(&new_client_id)?;
+    }
+
     // Get client configuration
     let client = {
         let clients = state.clients.read();
@@ -389,27 +1121,59 @@ pub async fn switch_terminal_agent(
             .ok_or_else(|| Error::ClientNotFound(new_client_id.clone()))?
     };
 
-    // Get or create output buffer (fresh for new agent)
-    let output_buffer = Arc::new(TerminalOutputBuffer::new());
+    // Stop the previous agent, if it is still running
+    let old_pty_handle = state.pty_handles.write().remove(&terminal_id);
+    kill_ptys_off_runtime(old_pty_handle.into_iter().collect()).await;
 
     // Get terminal and update client_id
-    let working_dir = {
+    let (working_dir, project_id, scrollback, keep_alive, terminal_env) = {
         let mut terminals = state.terminals.write();
         let terminal = terminals
             .get_mut(&terminal_id)
             .ok_or_else(|| Error::TerminalNotFound(terminal_id.clone()))?;
         terminal.client_id = new_client_id;
-        terminal.working_dir.clone()
+        // Model names and profiles belong to the previous agent
+        terminal.model = None;
+        terminal.profile = None;
+        (
+            terminal.working_dir.clone(),
+            terminal.project_id.clone(),
+            terminal.scrollback,
+            terminal.keep_alive.clone(),
+            terminal.terminal_env.clone(),
+        )
     };
 
+    // Create a fresh output buffer for the new agent
+    let output_buffer = state.create_output_buffer(&terminal_id, scrollback);
+
+    // Re-resolve the shell, since the new client may be configured differently
+    let project_shell = state
+        .projects
+        .read()
+        .get(&project_id)
+        .and_then(|p| p.settings.shell.clone());
+    let resolved_shell = resolve_shell(project_shell.as_ref(), client.shell.as_ref())?;
+
     // Spawn new PTY with new client
-    let pty_handle = spawn_pty(
-        &state.app_handle,
+    let spawned = spawn_agent(
+        &state,
+        &project_id,
         &terminal_id,
-        &working_dir,
-        &client,
-        120,
-        30,
+        PtySpawnOptions {
+            working_dir: &working_dir,
+            client: &client,
+            shell: resolved_shell.path.as_deref(),
+            setup_command: None,
+            cols: 120,
+            rows: 30,
+        },
+        AgentLaunch {
+            keep_alive: keep_alive.as_ref(),
+            terminal_env: terminal_env.as_ref(),
+            model: None,
+            profile: None,
+        },
         output_buffer.clone(),
     )?;
 
@@ -417,12 +1181,15 @@ pub async fn switch_terminal_agent(
     {
         let mut terminals = state.terminals.write();
         if let Some(t) = terminals.get_mut(&terminal_id) {
-            t.status = TerminalStatus::Running;
+            t.mark_started(Utc::now());
+            t.shell = spawned.shell;
+            t.commands.clear();
+            t.record_invocation(spawned.invocation);
         }
     }
 
     // Store pty handle and output buffer
-    state.pty_handles.write().insert(terminal_id.clone(), pty_handle);
+    attach_pty(&state, &terminal_id, spawned.pty_handle);
     state.output_buffers.write().insert(terminal_id.clone(), output_buffer);
 
     // Save terminal to disk
@@ -430,14 +1197,22 @@ pub async fn switch_terminal_agent(
 
     let terminals = state.terminals.read();
     let terminal = terminals.get(&terminal_id).unwrap();
-    Ok(TerminalInfo::from(terminal))
+    let mut info = TerminalInfo::from(terminal);
+    info.shell_warning = spawned.shell_warning.or(resolved_shell.warning);
+    Ok(info)
 }
 
+/// Start the terminal's agent over in a fresh scrollback. With `resume`, the agent
+/// continues its previous conversation, for agents that support it.
 #[tauri::command]
 pub async fn restart_terminal(
     state: State<'_, AppState>,
     terminal_id: String,
+    resume: Option<bool>,
 ) -> Result<TerminalInfo> {
+    let resume = resume.unwrap_or(false);
+    let _span = telemetry::run_span("terminal.restart", &terminal_id);
+
     // Get the existing terminal
     let terminal = {
         let terminals = state.terminals.read();
@@ -448,32 +1223,50 @@ pub async fn restart_terminal(
     };
 
     // Get client configuration
-    let client = {
+    let mut client = {
         let clients = state.clients.read();
         clients
             .get(&terminal.client_id)
             .cloned()
             .ok_or_else(|| Error::ClientNotFound(terminal.client_id.clone()))?
     };
+    if resume {
+        client.continue_conversation()?;
+    }
 
     // Kill existing PTY if running (allows restart of both stopped and running terminals)
-    {
-        let mut pty_handles = state.pty_handles.write();
-        pty_handles.remove(&terminal_id);
-        // PTY handle is dropped here, which closes file descriptors and sends SIGHUP
-    }
+    let old_pty_handle = state.pty_handles.write().remove(&terminal_id);
+    kill_ptys_off_runtime(old_pty_handle.into_iter().collect()).await;
+
+    // Reuse the shell the terminal was launched with, falling back if it has gone away
+    let resolved_shell = match terminal.shell.as_deref() {
+        Some(shell) => resolve_shell_path(shell)?,
+        None => ResolvedShell::default(),
+    };
 
     // Create fresh output buffer (clears history for clean restart)
-    let output_buffer = Arc::new(TerminalOutputBuffer::new());
+    let output_buffer = state.create_output_buffer(&terminal_id, terminal.scrollback);
+    let respawn_count = terminal.respawn_count + 1;
+    output_buffer.append(marker_line(&format!(
+        "Process restarted (restart #{}){}",
+        respawn_count,
+        if resume { ", continuing the previous conversation" } else { "" }
+    )));
 
     // Spawn new PTY
-    let pty_handle = spawn_pty(
-        &state.app_handle,
+    let spawned = spawn_agent(
+        &state,
+        &terminal.project_id,
         &terminal_id,
-        &terminal.working_dir,
-        &client,
-        120,
-        30,
+        PtySpawnOptions {
+            working_dir: &terminal.working_dir,
+            client: &client,
+            shell: resolved_shell.path.as_deref(),
+            setup_command: None,
+            cols: 120,
+            rows: 30,
+        },
+        AgentLaunch::of(&terminal),
         output_buffer.clone(),
     )?;
 
@@ -481,12 +1274,16 @@ pub async fn restart_terminal(
     {
         let mut terminals = state.terminals.write();
         if let Some(t) = terminals.get_mut(&terminal_id) {
-            t.status = TerminalStatus::Running;
+            t.mark_started(Utc::now());
+            t.respawn_count = respawn_count;
+            t.shell = spawned.shell;
+            t.commands.clear();
+            t.record_invocation(spawned.invocation);
         }
     }
 
     // Store pty handle and output buffer
-    state.pty_handles.write().insert(terminal_id.clone(), pty_handle);
+    attach_pty(&state, &terminal_id, spawned.pty_handle);
     state.output_buffers.write().insert(terminal_id.clone(), output_buffer);
 
     // Save terminal to disk
@@ -494,5 +1291,490 @@ pub async fn restart_terminal(
 
     let terminals = state.terminals.read();
     let terminal = terminals.get(&terminal_id).unwrap();
-    Ok(TerminalInfo::from(terminal))
+    let mut info = TerminalInfo::from(terminal);
+    info.shell_warning = spawned.shell_warning.or(resolved_shell.warning);
+    Ok(info)
+}
+
+/// Start a stopped terminal's agent again, keeping its scrollback (unlike `restart_terminal`).
+/// Restored terminals are loaded without a process, so this is how they come back to life.
+/// Does nothing if the terminal is already running.
+#[tauri::command]
+pub async fn resume_terminal(
+    state: State<'_, AppState>,
+    terminal_id: String,
+) -> Result<TerminalInfo> {
+    resume_terminal_internal(&state, &terminal_id)
+}
+
+pub fn resume_terminal_internal(state: &AppState, terminal_id: &str) -> Result<TerminalInfo> {
+    if state.pty_handles.read().contains_key(terminal_id) {
+        let terminals = state.terminals.read();
+        return terminals
+            .get(terminal_id)
+            .map(TerminalInfo::from)
+            .ok_or_else(|| Error::TerminalNotFound(terminal_id.to_string()));
+    }
+
+    let _span = telemetry::run_span("terminal.resume", terminal_id);
+
+    let terminal = {
+        let terminals = state.terminals.read();
+        terminals
+            .get(terminal_id)
+            .cloned()
+            .ok_or_else(|| Error::TerminalNotFound(terminal_id.to_string()))?
+    };
+
+    let client = {
+        let clients = state.clients.read();
+        clients
+            .get(&terminal.client_id)
+            .cloned()
+            .ok_or_else(|| Error::ClientNotFound(terminal.client_id.clone()))?
+    };
+
+    let resolved_shell = match terminal.shell.as_deref() {
+        Some(shell) => resolve_shell_path(shell)?,
+        None => ResolvedShell::default(),
+    };
+
+    // Continue in the existing scrollback, loading it from disk if needed
+    let output_buffer = state
+        .output_buffer(terminal_id)
+        .ok_or_else(|| Error::TerminalNotFound(terminal_id.to_string()))?;
+    output_buffer.append(marker_line("Session resumed"));
+
+    let spawned = spawn_agent(
+        state,
+        &terminal.project_id,
+        terminal_id,
+        PtySpawnOptions {
+            working_dir: &terminal.working_dir,
+            client: &client,
+            shell: resolved_shell.path.as_deref(),
+            setup_command: None,
+            cols: 120,
+            rows: 30,
+        },
+        AgentLaunch::of(&terminal),
+        output_buffer,
+    )?;
+
+    {
+        let mut terminals = state.terminals.write();
+        if let Some(t) = terminals.get_mut(terminal_id) {
+            t.mark_started(Utc::now());
+            t.respawn_count += 1;
+            t.shell = spawned.shell;
+            t.record_invocation(spawned.invocation);
+        }
+    }
+
+    attach_pty(state, terminal_id, spawned.pty_handle);
+
+    // Save terminal to disk
+    let _ = state.save_terminal(terminal_id);
+
+    let terminals = state.terminals.read();
+    let terminal = terminals.get(terminal_id).unwrap();
+    let mut info = TerminalInfo::from(terminal);
+    info.shell_warning = spawned.shell_warning.or(resolved_shell.warning);
+    Ok(info)
+}
+
+/// Resume restored terminals at startup when `resume_terminals_on_start` is enabled,
+/// skipping any that opted out with `auto_resume: false`
+pub fn resume_terminals_on_start(state: &AppState) {
+    if !state.settings.read().resume_terminals_on_start {
+        return;
+    }
+
+    let terminal_ids: Vec<String> = {
+        let projects = state.projects.read();
+        state
+            .terminals
+            .read()
+            .values()
+            .filter(|t| t.restored && t.auto_resume && t.status == TerminalStatus::Stopped)
+            .filter(|t| projects.get(&t.project_id).is_some_and(|p| !p.is_archived()))
+            .map(|t| t.id.clone())
+            .collect()
+    };
+
+    for terminal_id in terminal_ids {
+        if let Err(e) = resume_terminal_internal(state, &terminal_id) {
+            eprintln!("[Ada] Failed to resume terminal {} on start: {}", terminal_id, e);
+        }
+    }
+}
+
+/// Set (or clear, with None) a terminal's keep-alive behavior.
+/// The ssh settings take effect the next time the agent is launched.
+#[tauri::command]
+pub async fn set_terminal_keep_alive(
+    state: State<'_, AppState>,
+    terminal_id: String,
+    keep_alive: Option<KeepAliveConfig>,
+) -> Result<TerminalInfo> {
+    let info = {
+        let mut terminals = state.terminals.write();
+        let terminal = terminals
+            .get_mut(&terminal_id)
+            .ok_or_else(|| Error::TerminalNotFound(terminal_id.clone()))?;
+        terminal.keep_alive = keep_alive;
+        TerminalInfo::from(&*terminal)
+    };
+
+    let _ = state.save_terminal(&terminal_id);
+    Ok(info)
+}
+
+/// Turn automatic checkpoints of a terminal's worktree on (or off, with None)
+#[tauri::command]
+pub async fn set_terminal_checkpoints(
+    state: State<'_, AppState>,
+    terminal_id: String,
+    checkpoints: Option<CheckpointConfig>,
+) -> Result<TerminalInfo> {
+    let info = {
+        let mut terminals = state.terminals.write();
+        let terminal = terminals
+            .get_mut(&terminal_id)
+            .ok_or_else(|| Error::TerminalNotFound(terminal_id.clone()))?;
+        terminal.checkpoints = checkpoints;
+        TerminalInfo::from(&*terminal)
+    };
+
+    let _ = state.save_terminal(&terminal_id);
+    Ok(info)
+}
+
+/// Checkpoint a terminal's worktree now. Returns None when nothing changed since the last one.
+#[tauri::command]
+pub async fn create_terminal_checkpoint(
+    state: State<'_, AppState>,
+    terminal_id: String,
+    message: Option<String>,
+) -> Result<Option<git::Checkpoint>> {
+    let dir = terminal_checkpoint_dir(&state, &terminal_id)?;
+    git::create_checkpoint(
+        &dir,
+        &git::checkpoint_ref(&terminal_id),
+        message.as_deref().unwrap_or("Checkpoint"),
+        &terminal_commit_identity(&state, &terminal_id),
+    )
+}
+
+/// A terminal's checkpoints, newest first
+#[tauri::command]
+pub async fn list_terminal_checkpoints(
+    state: State<'_, AppState>,
+    terminal_id: String,
+    limit: Option<usize>,
+) -> Result<Vec<git::Checkpoint>> {
+    let dir = terminal_checkpoint_dir(&state, &terminal_id)?;
+    git::list_checkpoints(&dir, &git::checkpoint_ref(&terminal_id), limit.unwrap_or(usize::MAX))
+}
+
+/// Roll a terminal's worktree back to one of its checkpoints (undoable: the current state
+/// is checkpointed first)
+#[tauri::command]
+pub async fn restore_terminal_checkpoint(
+    state: State<'_, AppState>,
+    terminal_id: String,
+    checkpoint_id: String,
+) -> Result<git::Checkpoint> {
+    let dir = terminal_checkpoint_dir(&state, &terminal_id)?;
+    let mut span = telemetry::ipc_span("restore_terminal_checkpoint");
+    let result = git::restore_checkpoint(
+        &dir,
+        &git::checkpoint_ref(&terminal_id),
+        &checkpoint_id,
+        &terminal_commit_identity(&state, &terminal_id),
+    );
+    span.record_result(&result);
+    result
+}
+
+fn terminal_checkpoint_dir(state: &AppState, terminal_id: &str) -> Result<PathBuf> {
+    state
+        .terminals
+        .read()
+        .get(terminal_id)
+        .map(|t| t.checkpoint_dir().to_path_buf())
+        .ok_or_else(|| Error::TerminalNotFound(terminal_id.to_string()))
+}
+
+/// Identity for commits in a terminal's worktree, from its project's settings
+pub(crate) fn terminal_commit_identity(state: &AppState, terminal_id: &str) -> git::CommitIdentity {
+    let project_id = state.terminals.read().get(terminal_id).map(|t| t.project_id.clone());
+    project_id
+        .and_then(|id| state.projects.read().get(&id).map(|p| p.settings.commit_identity()))
+        .unwrap_or_default()
+}
+
+/// Choose whether a terminal is resumed automatically when Ada starts
+#[tauri::command]
+pub async fn set_terminal_auto_resume(
+    state: State<'_, AppState>,
+    terminal_id: String,
+    auto_resume: bool,
+) -> Result<TerminalInfo> {
+    let info = {
+        let mut terminals = state.terminals.write();
+        let terminal = terminals
+            .get_mut(&terminal_id)
+            .ok_or_else(|| Error::TerminalNotFound(terminal_id.clone()))?;
+        terminal.auto_resume = auto_resume;
+        TerminalInfo::from(&*terminal)
+    };
+
+    let _ = state.save_terminal(&terminal_id);
+    Ok(info)
+}
+
+#[tauri::command]
+pub async fn get_terminal_invocations(
+    state: State<'_, AppState>,
+    terminal_id: String,
+) -> Result<Vec<AgentInvocation>> {
+    let terminals = state.terminals.read();
+    terminals
+        .get(&terminal_id)
+        .map(|t| t.invocations.clone())
+        .ok_or(Error::TerminalNotFound(terminal_id))
+}
+
+/// Launch one worktree terminal per member under a new run group, sending each the group's prompt.
+/// The members' new worktrees are added together first (see `create_worktrees_internal`),
+/// reporting `worktree-progress`, then the agents are started in them.
+/// Members that fail to launch are reported in the result rather than failing the whole group.
+#[tauri::command]
+pub async fn create_terminal_group(
+    state: State<'_, AppState>,
+    request: CreateTerminalGroupRequest,
+) -> Result<CreateTerminalGroupResult> {
+    let project = state
+        .projects
+        .read()
+        .get(&request.project_id)
+        .cloned()
+        .ok_or_else(|| Error::ProjectNotFound(request.project_id.clone()))?;
+    if request.members.is_empty() {
+        return Err(Error::InvalidRequest("A terminal group needs at least one member".into()));
+    }
+
+    let group_id = uuid::Uuid::new_v4().to_string();
+    let mut terminals = Vec::new();
+    let mut errors = Vec::new();
+
+    // Members whose client can't run here are turned away before any worktree is added
+    let mut members = Vec::new();
+    let mut new_worktrees: Vec<(String, PathBuf)> = Vec::new();
+    for member in request.members {
+        let planned = project
+            .settings
+            .check_client_allowed(&member.client_id)
+            .and_then(|_| match state.clients.read().get(&member.client_id) {
+                Some(client) if client.installed => Ok(()),
+                Some(client) => Err(Error::InvalidRequest(format!("Client '{}' is not installed", client.name))),
+                None => Err(Error::ClientNotFound(member.client_id.clone())),
+            })
+            .and_then(|_| plan_worktree(&project, None, &member.client_id, &member.worktree_branch));
+        match planned {
+            Ok((branch_spec, _, path)) => {
+                if !path.exists() && !new_worktrees.iter().any(|(_, planned)| planned == &path) {
+                    new_worktrees.push((branch_spec, path.clone()));
+                }
+                members.push((member, path));
+            }
+            Err(e) => {
+                eprintln!("[Ada] Failed to launch group member on {}: {}", member.worktree_branch, e);
+                errors.push(format!("{}: {}", member.worktree_branch, e));
+            }
+        }
+    }
+
+    // Adding worktrees blocks, so keep the batch off the async runtime's threads
+    let repo_path = project.path.clone();
+    let options = project.settings.worktree_options();
+    let on_progress = git::commands::worktree_progress_emitter(&state.app_handle, &request.project_id);
+    let created = tauri::async_runtime::spawn_blocking(move || {
+        git::create_worktrees_internal(
+            &repo_path,
+            new_worktrees,
+            git::commands::DEFAULT_WORKTREE_PARALLELISM,
+            false,
+            &options,
+            on_progress,
+        )
+    })
+    .await
+    .map_err(|e| Error::WorktreeError(e.to_string()))?;
+
+    for (member, worktree_path) in members {
+        let creation = created
+            .iter()
+            .find(|result| Path::new(&result.path) == worktree_path.as_path());
+        if let Some(error) = creation.and_then(|result| result.error.as_ref()) {
+            // A worktree missing its submodules or seed files can still be worked in
+            if !worktree_path.exists() {
+                eprintln!("[Ada] Failed to launch group member on {}: {}", member.worktree_branch, error);
+                errors.push(format!("{}: {}", member.worktree_branch, error));
+                continue;
+            }
+            eprintln!("[Ada] {} for {:?}", error, worktree_path);
+        }
+
+        let terminal_request = CreateTerminalRequest {
+            project_id: request.project_id.clone(),
+            name: member.name.unwrap_or_else(|| git::branch_from_spec(&project.path, &member.worktree_branch)),
+            client_id: member.client_id,
+            mode: Some(TerminalMode::Worktree),
+            folder_path: None,
+            worktree_branch: Some(member.worktree_branch.clone()),
+            root: None,
+            scrollback: None,
+            wait_for_ready: false,
+            auto_resume: None,
+            keep_alive: None,
+            terminal_env: None,
+            model: None,
+            profile: None,
+        };
+
+        match create_terminal_internal(&state, terminal_request, Some(group_id.clone()), creation.is_some()) {
+            Ok(info) => terminals.push(info),
+            Err(e) => {
+                eprintln!("[Ada] Failed to launch group member on {}: {}", member.worktree_branch, e);
+                errors.push(format!("{}: {}", member.worktree_branch, e));
+            }
+        }
+    }
+
+    if terminals.is_empty() {
+        return Err(Error::TerminalError(format!(
+            "No group members could be launched: {}",
+            errors.join(", ")
+        )));
+    }
+
+    // Send the prompt once each agent is up, so it isn't lost during startup
+    if let Some(prompt) = &request.prompt {
+        for info in &terminals {
+            wait_until_ready(&state, &info.id).await;
+
+            // Pasted so multi-line prompts are submitted once, by the final Enter
+            let pty_handles = state.pty_handles.read();
+            if let Some(pty_handle) = pty_handles.get(&info.id) {
+                let sent = paste_to_pty(pty_handle, prompt)
+                    .and_then(|_| write_to_pty(pty_handle, b"\r"));
+                if let Err(e) = sent {
+                    errors.push(format!("{}: failed to send prompt: {}", info.name, e));
+                }
+            }
+        }
+    }
+
+    let group = TerminalGroup {
+        id: group_id.clone(),
+        project_id: request.project_id,
+        name: request.name,
+        prompt: request.prompt,
+        terminal_ids: terminals.iter().map(|t| t.id.clone()).collect(),
+        created_at: Utc::now(),
+    };
+
+    state.terminal_groups.write().insert(group_id, group.clone());
+    let _ = state.save_terminal_group(&group);
+
+    Ok(CreateTerminalGroupResult { group, terminals, errors })
+}
+
+#[tauri::command]
+pub async fn list_terminal_groups(
+    state: State<'_, AppState>,
+    project_id: String,
+) -> Result<Vec<TerminalGroup>> {
+    let groups = state.terminal_groups.read();
+    let mut project_groups: Vec<TerminalGroup> = groups
+        .values()
+        .filter(|g| g.project_id == project_id)
+        .cloned()
+        .collect();
+    project_groups.sort_by_key(|g| g.created_at);
+    Ok(project_groups)
+}
+
+/// Stop every running member of a group, keeping the terminals and their worktrees for review
+#[tauri::command]
+pub async fn stop_terminal_group(
+    state: State<'_, AppState>,
+    group_id: String,
+) -> Result<Vec<TerminalInfo>> {
+    let terminal_ids = state
+        .terminal_groups
+        .read()
+        .get(&group_id)
+        .map(|g| g.terminal_ids.clone())
+        .ok_or_else(|| Error::InvalidRequest(format!("Terminal group not found: {}", group_id)))?;
+
+    // Signal every member before waiting on any of them
+    let pty_handles: Vec<PtyHandle> = {
+        let mut pty_handles = state.pty_handles.write();
+        terminal_ids.iter().filter_map(|id| pty_handles.remove(id)).collect()
+    };
+    kill_ptys_off_runtime(pty_handles).await;
+
+    for terminal_id in &terminal_ids {
+        state.keep_alive.cancel_reconnect(terminal_id);
+        if let Some(t) = state.terminals.write().get_mut(terminal_id) {
+            t.mark_stopped(Utc::now());
+        }
+        let _ = state.save_terminal(terminal_id);
+    }
+
+    let terminals = state.terminals.read();
+    Ok(terminal_ids
+        .iter()
+        .filter_map(|id| terminals.get(id))
+        .map(TerminalInfo::from)
+        .collect())
+}
+
+/// Collect each member's terminal state and worktree changes, for comparing agent results
+#[tauri::command]
+pub async fn get_terminal_group_status(
+    state: State<'_, AppState>,
+    group_id: String,
+) -> Result<TerminalGroupStatus> {
+    let group = state
+        .terminal_groups
+        .read()
+        .get(&group_id)
+        .cloned()
+        .ok_or_else(|| Error::InvalidRequest(format!("Terminal group not found: {}", group_id)))?;
+
+    // Closed terminals drop out of the group
+    let members: Vec<Terminal> = {
+        let terminals = state.terminals.read();
+        group.terminal_ids.iter().filter_map(|id| terminals.get(id).cloned()).collect()
+    };
+
+    let members = members
+        .iter()
+        .map(|terminal| TerminalGroupMemberStatus {
+            terminal: TerminalInfo::from(terminal),
+            changes: git::get_worktree_changes_internal(&terminal.working_dir).ok(),
+        })
+        .collect();
+
+    Ok(TerminalGroupStatus { group, members })
+}
+
+/// Delete stored terminals that are orphaned or past the retention period (see `AppState::prune_terminals`)
+#[tauri::command]
+pub async fn prune_terminals(state: State<'_, AppState>) -> Result<Vec<String>> {
+    Ok(state.prune_terminals())
 }
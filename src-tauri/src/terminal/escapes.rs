@@ -0,0 +1,293 @@
+use std::path::PathBuf;
+
+/// Longest OSC payload we buffer; anything longer is discarded
+const MAX_OSC_LEN: usize = 4096;
+
+/// Longest CSI parameter string we buffer; anything longer is discarded
+const MAX_CSI_LEN: usize = 64;
+
+/// DEC private mode for bracketed paste (`CSI ? 2004 h`)
+pub const BRACKETED_PASTE_MODE: u16 = 2004;
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+
+/// An escape sequence of interest found in PTY output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EscapeSequence {
+    /// Operating System Command payload, e.g. `7;file://host/path`
+    Osc(String),
+    /// A DEC private mode being set or reset, e.g. `CSI ? 2004 h`
+    PrivateMode { mode: u16, enabled: bool },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScanState {
+    Ground,
+    Escape,
+    Osc,
+    OscEscape,
+    Csi,
+}
+
+/// Incremental scanner that picks escape sequences out of raw PTY output.
+/// Sequences may be split across reads, so state is kept between calls to `feed`.
+pub struct EscapeScanner {
+    state: ScanState,
+    osc: Vec<u8>,
+    csi: Vec<u8>,
+    overflowed: bool,
+}
+
+impl EscapeScanner {
+    pub fn new() -> Self {
+        Self {
+            state: ScanState::Ground,
+            osc: Vec::new(),
+            csi: Vec::new(),
+            overflowed: false,
+        }
+    }
+
+    /// Scan a chunk of output, returning every sequence completed within it
+    /// along with the offset in `data` just past its last byte
+    pub fn feed(&mut self, data: &[u8]) -> Vec<(usize, EscapeSequence)> {
+        let mut found = Vec::new();
+        let mut ends = Vec::new();
+
+        for (index, &byte) in data.iter().enumerate() {
+            self.state = match (self.state, byte) {
+                (ScanState::Ground, ESC) => ScanState::Escape,
+                (ScanState::Ground, _) => ScanState::Ground,
+                (ScanState::Escape, b']') => {
+                    self.osc.clear();
+                    self.overflowed = false;
+                    ScanState::Osc
+                }
+                (ScanState::Escape, b'[') => {
+                    self.csi.clear();
+                    self.overflowed = false;
+                    ScanState::Csi
+                }
+                (ScanState::Escape, ESC) => ScanState::Escape,
+                (ScanState::Escape, _) => ScanState::Ground,
+                (ScanState::Osc, BEL) => {
+                    self.finish_osc(&mut found);
+                    ScanState::Ground
+                }
+                (ScanState::Osc, ESC) => ScanState::OscEscape,
+                (ScanState::Osc, _) => {
+                    self.push_osc(byte);
+                    ScanState::Osc
+                }
+                // ESC \ (string terminator) ends the OSC; any other ESC aborts it
+                (ScanState::OscEscape, b'\\') => {
+                    self.finish_osc(&mut found);
+                    ScanState::Ground
+                }
+                (ScanState::OscEscape, b']') => {
+                    self.osc.clear();
+                    self.overflowed = false;
+                    ScanState::Osc
+                }
+                (ScanState::OscEscape, b'[') => {
+                    self.csi.clear();
+                    self.overflowed = false;
+                    ScanState::Csi
+                }
+                (ScanState::OscEscape, ESC) => ScanState::Escape,
+                (ScanState::OscEscape, _) => ScanState::Ground,
+                // Parameter and intermediate bytes, then a final byte in @..~
+                (ScanState::Csi, 0x20..=0x3f) => {
+                    if self.csi.len() < MAX_CSI_LEN {
+                        self.csi.push(byte);
+                    } else {
+                        self.overflowed = true;
+                    }
+                    ScanState::Csi
+                }
+                (ScanState::Csi, 0x40..=0x7e) => {
+                    self.finish_csi(byte, &mut found);
+                    ScanState::Ground
+                }
+                (ScanState::Csi, ESC) => ScanState::Escape,
+                (ScanState::Csi, _) => ScanState::Ground,
+            };
+            ends.resize(found.len(), index + 1);
+        }
+
+        ends.into_iter().zip(found).collect()
+    }
+
+    fn push_osc(&mut self, byte: u8) {
+        if self.osc.len() < MAX_OSC_LEN {
+            self.osc.push(byte);
+        } else {
+            self.overflowed = true;
+        }
+    }
+
+    fn finish_osc(&mut self, found: &mut Vec<EscapeSequence>) {
+        if !self.overflowed {
+            found.push(EscapeSequence::Osc(String::from_utf8_lossy(&self.osc).to_string()));
+        }
+        self.osc.clear();
+        self.overflowed = false;
+    }
+
+    /// Report DEC private mode changes (`CSI ? Pm h` / `CSI ? Pm l`); other CSIs are ignored
+    fn finish_csi(&mut self, final_byte: u8, found: &mut Vec<EscapeSequence>) {
+        let enabled = match final_byte {
+            b'h' => true,
+            b'l' => false,
+            _ => return,
+        };
+        if self.overflowed {
+            return;
+        }
+        let Some(params) = self.csi.strip_prefix(b"?") else {
+            return;
+        };
+
+        for param in params.split(|&b| b == b';') {
+            let mode = std::str::from_utf8(param).ok().and_then(|p| p.parse::<u16>().ok());
+            if let Some(mode) = mode {
+                found.push(EscapeSequence::PrivateMode { mode, enabled });
+            }
+        }
+    }
+}
+
+/// A shell integration mark (OSC 133 semantic prompt sequence)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShellMark {
+    /// `A`: the prompt is about to be drawn
+    PromptStart,
+    /// `B`: the prompt is drawn and the user's input starts
+    InputStart,
+    /// `E;command`: the command line about to run, as the shell has it (sent before `C`).
+    /// `\\` and `\xNN` escapes stand for a backslash and any byte, e.g. `;` or a newline.
+    CommandLine(String),
+    /// `C`: the command line was submitted and its output starts
+    OutputStart,
+    /// `D[;exit_code]`: the command finished
+    CommandFinished(Option<i32>),
+}
+
+/// Parse an OSC 133 payload, e.g. `133;A` or `133;D;1`
+pub fn parse_osc133(payload: &str) -> Option<ShellMark> {
+    let payload = payload.strip_prefix("133;")?;
+    if let Some(command) = payload.strip_prefix("E;") {
+        return Some(ShellMark::CommandLine(unescape_command_line(command)));
+    }
+    let mut params = payload.split(';');
+
+    match params.next()? {
+        "A" => Some(ShellMark::PromptStart),
+        "B" => Some(ShellMark::InputStart),
+        "C" => Some(ShellMark::OutputStart),
+        "D" => Some(ShellMark::CommandFinished(params.next().and_then(|code| code.parse().ok()))),
+        _ => None,
+    }
+}
+
+/// Undo the escaping of an OSC 133 `E` command line
+fn unescape_command_line(escaped: &str) -> String {
+    let bytes = escaped.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'\\' {
+            if bytes.get(i + 1) == Some(&b'\\') {
+                decoded.push(b'\\');
+                i += 2;
+                continue;
+            }
+            if bytes.get(i + 1) == Some(&b'x') && i + 3 < bytes.len() {
+                let hex = std::str::from_utf8(&bytes[i + 2..i + 4]).ok();
+                if let Some(value) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                    decoded.push(value);
+                    i += 4;
+                    continue;
+                }
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Parse an OSC 7 (current working directory) payload: `7;file://hostname/some/path`
+pub fn parse_osc7(payload: &str) -> Option<PathBuf> {
+    let url = payload.strip_prefix("7;")?;
+    let rest = url.strip_prefix("file://")?;
+
+    // Skip the hostname; the path starts at the first slash
+    let path = &rest[rest.find('/')?..];
+    Some(PathBuf::from(percent_decode(path)))
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(value) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                decoded.push(value);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Strip escape sequences and control characters from terminal output, leaving plain text.
+/// Carriage returns are dropped, so `\r\n` line endings become `\n`.
+pub fn strip_escapes(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                // CSI: parameters and intermediates up to a final byte in @..~
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC and other strings: up to BEL or ESC \
+                Some(']' | 'P' | '_' | '^') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' {
+                            break;
+                        }
+                        if c == '\x1b' {
+                            chars.next_if_eq(&'\\');
+                            break;
+                        }
+                    }
+                }
+                // Two-byte sequences, e.g. ESC 7 / ESC =
+                _ => {}
+            },
+            '\n' | '\t' => plain.push(c),
+            c if c.is_control() => {}
+            c => plain.push(c),
+        }
+    }
+
+    plain
+}
@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use chrono::{DateTime, Utc};
+use tauri::Emitter;
+
+use crate::state::AppState;
+
+/// Event logs are cut down to their newest half once they grow past this
+const MAX_EVENT_LOG_BYTES: u64 = 256 * 1024;
+
+/// Something that happened to a terminal's agent
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TerminalEventKind {
+    /// The agent was launched: created, restarted, resumed or switched to another client
+    Started { client_id: String },
+    /// The agent produced its first output
+    Ready,
+    /// A shell command finished, as reported by shell integration
+    CommandFinished { command: Option<String>, exit_code: Option<i32> },
+    /// The agent's process ended (the exit code is None if it wasn't known yet). `stopped`
+    /// is set when Ada ended it: closing, restarting or switching the terminal, stopping
+    /// its group, or archiving or deleting its project.
+    Exited {
+        exit_code: Option<u32>,
+        #[serde(default)]
+        stopped: bool,
+    },
+}
+
+/// The kinds of terminal events without their details, for picking which ones to hear about
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TerminalEventType {
+    Started,
+    Ready,
+    CommandFinished,
+    Exited,
+}
+
+impl TerminalEventKind {
+    pub fn event_type(&self) -> TerminalEventType {
+        match self {
+            Self::Started { .. } => TerminalEventType::Started,
+            Self::Ready => TerminalEventType::Ready,
+            Self::CommandFinished { .. } => TerminalEventType::CommandFinished,
+            Self::Exited { .. } => TerminalEventType::Exited,
+        }
+    }
+}
+
+/// An entry of a terminal's event log, also emitted as `terminal-event`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalEvent {
+    pub terminal_id: String,
+    pub project_id: String,
+    pub timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    pub kind: TerminalEventKind,
+}
+
+/// Record an event of a terminal: appended to its event log, so the history outlives
+/// reloads and restarts, passed on to the notifiers and emitted to the frontend
+pub fn record_event(state: &AppState, terminal_id: &str, project_id: &str, kind: TerminalEventKind) {
+    let event = TerminalEvent {
+        terminal_id: terminal_id.to_string(),
+        project_id: project_id.to_string(),
+        timestamp: Utc::now(),
+        kind,
+    };
+
+    if let Err(e) = append_event(&state.paths.terminal_events_file(terminal_id), &event) {
+        eprintln!("[Ada] Failed to record event of terminal {}: {}", terminal_id, e);
+    }
+    crate::notifications::dispatch(state, &event);
+    let _ = state.app_handle.emit("terminal-event", event);
+}
+
+/// Like `record_event`, for a terminal known only by ID; events of terminals that are
+/// gone (or never were, like installer sessions) are dropped
+pub fn record_terminal_event(state: &AppState, terminal_id: &str, kind: TerminalEventKind) {
+    let project_id = state.terminals.read().get(terminal_id).map(|t| t.project_id.clone());
+    if let Some(project_id) = project_id {
+        record_event(state, terminal_id, &project_id, kind);
+    }
+}
+
+fn append_event(path: &Path, event: &TerminalEvent) -> std::io::Result<()> {
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(event)?)?;
+
+    if file.metadata()?.len() > MAX_EVENT_LOG_BYTES {
+        drop(file);
+        let content = std::fs::read_to_string(path)?;
+        let lines: Vec<&str> = content.lines().collect();
+        let kept = lines[lines.len() / 2..].join("\n");
+        std::fs::write(path, kept + "\n")?;
+    }
+    Ok(())
+}
+
+/// A terminal's logged events, oldest first, optionally only those since `since`
+pub fn read_events(path: &Path, since: Option<DateTime<Utc>>) -> Vec<TerminalEvent> {
+    let Ok(file) = std::fs::File::open(path) else {
+        return Vec::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(|line| line.ok())
+        .filter_map(|line| serde_json::from_str::<TerminalEvent>(&line).ok())
+        .filter(|event| since.is_none_or(|since| event.timestamp > since))
+        .collect()
+}
@@ -0,0 +1,247 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use chrono::Utc;
+use parking_lot::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::clients::ClientConfig;
+use crate::state::AppState;
+use super::commands::resume_terminal_internal;
+use super::pty::{is_process_running, kill_pty, write_to_pty};
+use super::types::marker_line;
+
+/// How often the keep-alive loop checks for idle terminals
+const KEEP_ALIVE_TICK: Duration = Duration::from_secs(5);
+
+/// Pause before relaunching a terminal whose connection dropped
+const RECONNECT_DELAY: Duration = Duration::from_secs(3);
+
+/// Automatic reconnects allowed within `RECONNECT_WINDOW` before giving up
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+const RECONNECT_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// How much of the end of a session's output is searched for a dropped connection
+const DISCONNECT_TAIL_BYTES: usize = 512;
+
+/// Output that means a remote session's connection went away
+const DISCONNECT_PATTERNS: &[&str] = &[
+    "Connection reset by peer",
+    "Broken pipe",
+    "client_loop: send disconnect",
+    "packet_write_wait: Connection",
+    "Connection timed out",
+    "closed by remote host",
+];
+
+/// Keep-alive behavior for terminals whose agent runs over ssh or against remote services
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct KeepAliveConfig {
+    /// Send `input` after this many seconds without output (None = never)
+    #[serde(default)]
+    pub input_interval_secs: Option<u64>,
+    /// Harmless input to send when idle, e.g. a bare newline
+    #[serde(default)]
+    pub input: String,
+    /// ServerAliveInterval for ssh: passed as `-o` when the agent command is ssh,
+    /// and via GIT_SSH_COMMAND for git over ssh (None = leave ssh defaults)
+    #[serde(default)]
+    pub ssh_alive_interval_secs: Option<u64>,
+    /// Relaunch the agent (keeping scrollback) when its output shows the connection dropped
+    #[serde(default)]
+    pub restart_on_disconnect: bool,
+}
+
+/// Per-terminal keep-alive bookkeeping, kept for the lifetime of the app
+#[derive(Default)]
+pub struct KeepAliveTracker {
+    /// (attempts in the current window, time of the first attempt in it)
+    reconnects: Mutex<HashMap<String, (u32, Instant)>>,
+    /// When keep-alive input was last sent to each terminal
+    last_sent: Mutex<HashMap<String, Instant>>,
+    /// Terminals waiting out `RECONNECT_DELAY` before being relaunched
+    pending: Mutex<HashSet<String>>,
+}
+
+impl KeepAliveTracker {
+    /// Record a reconnect attempt, returning false once the attempt budget is used up
+    fn try_reconnect(&self, terminal_id: &str) -> bool {
+        let mut reconnects = self.reconnects.lock();
+        let entry = reconnects
+            .entry(terminal_id.to_string())
+            .or_insert((0, Instant::now()));
+        if entry.1.elapsed() > RECONNECT_WINDOW {
+            *entry = (0, Instant::now());
+        }
+        entry.0 += 1;
+        entry.0 <= MAX_RECONNECT_ATTEMPTS
+    }
+
+    /// Call off a reconnect that hasn't happened yet, for terminals the user stopped.
+    /// (The frontend marks a terminal stopped as soon as its output ends, so a stopped
+    /// status alone doesn't tell the user's stop apart from the connection dropping.)
+    pub fn cancel_reconnect(&self, terminal_id: &str) {
+        self.pending.lock().remove(terminal_id);
+    }
+}
+
+/// The end of a PTY's output, kept so a dropped connection can be recognised once the
+/// process exits, even when the message was split across reads
+#[derive(Default)]
+pub struct OutputTail(String);
+
+impl OutputTail {
+    pub fn push(&mut self, output: &str) {
+        self.0.push_str(output);
+        if self.0.len() > 2 * DISCONNECT_TAIL_BYTES {
+            let mut cut = self.0.len() - DISCONNECT_TAIL_BYTES;
+            while !self.0.is_char_boundary(cut) {
+                cut += 1;
+            }
+            self.0.drain(..cut);
+        }
+    }
+
+    /// Whether the session's last output shows its connection dropping. Only the last
+    /// `DISCONNECT_TAIL_BYTES` count, so e.g. a "Broken pipe" long before the exit doesn't.
+    pub fn shows_disconnect(&self) -> bool {
+        let mut start = self.0.len().saturating_sub(DISCONNECT_TAIL_BYTES);
+        while !self.0.is_char_boundary(start) {
+            start += 1;
+        }
+        let tail = &self.0[start..];
+        DISCONNECT_PATTERNS.iter().any(|pattern| tail.contains(pattern))
+    }
+}
+
+/// Apply the ssh parts of a keep-alive config to the client about to be launched
+pub fn apply_ssh_keep_alive(client: &mut ClientConfig, config: Option<&KeepAliveConfig>) {
+    let Some(interval) = config.and_then(|c| c.ssh_alive_interval_secs) else {
+        return;
+    };
+    let option = format!("ServerAliveInterval={}", interval);
+
+    if Path::new(&client.command).file_name().is_some_and(|name| name == "ssh") {
+        client.args.splice(0..0, ["-o".to_string(), option.clone()]);
+    }
+
+    // Don't override an ssh command the user configured themselves
+    if !client.env.contains_key("GIT_SSH_COMMAND") && std::env::var_os("GIT_SSH_COMMAND").is_none() {
+        client.env.insert("GIT_SSH_COMMAND".into(), format!("ssh -o {}", option));
+    }
+}
+
+/// Called when a PTY whose last output showed a dropped connection exits by itself:
+/// relaunch its terminal if the keep-alive config asks for that
+pub fn handle_disconnect(app_handle: &AppHandle, terminal_id: &str) {
+    let Some(state) = app_handle.try_state::<AppState>() else {
+        return;
+    };
+
+    let restart = state
+        .terminals
+        .read()
+        .get(terminal_id)
+        .and_then(|t| t.keep_alive.as_ref())
+        .is_some_and(|config| config.restart_on_disconnect);
+    if !restart {
+        return;
+    }
+    state.keep_alive.pending.lock().insert(terminal_id.to_string());
+
+    let app_handle = app_handle.clone();
+    let terminal_id = terminal_id.to_string();
+    std::thread::spawn(move || {
+        std::thread::sleep(RECONNECT_DELAY);
+        let Some(state) = app_handle.try_state::<AppState>() else {
+            return;
+        };
+        reconnect(&state, &terminal_id);
+    });
+}
+
+fn reconnect(state: &AppState, terminal_id: &str) {
+    // Stopped by the user (or reconnected some other way) in the meantime
+    if !state.keep_alive.pending.lock().remove(terminal_id) {
+        return;
+    }
+    let Some(buffer) = state.output_buffer(terminal_id) else {
+        return; // Closed in the meantime
+    };
+
+    // Already relaunched (e.g. restarted by hand) since the connection dropped
+    let running = state
+        .pty_handles
+        .read()
+        .get(terminal_id)
+        .is_some_and(is_process_running);
+    if running {
+        return;
+    }
+
+    if !state.keep_alive.try_reconnect(terminal_id) {
+        buffer.append(marker_line("Connection lost - giving up after repeated reconnects"));
+        return;
+    }
+
+    // The old process is gone; clear it out so the terminal can be resumed
+    let pty_handle = state.pty_handles.write().remove(terminal_id);
+    if let Some(pty_handle) = pty_handle {
+        kill_pty(pty_handle);
+    }
+    if let Some(terminal) = state.terminals.write().get_mut(terminal_id) {
+        terminal.mark_stopped(Utc::now());
+    }
+
+    buffer.append(marker_line("Connection lost - reconnecting"));
+    if let Err(e) = resume_terminal_internal(state, terminal_id) {
+        eprintln!("[Ada] Failed to reconnect terminal {}: {}", terminal_id, e);
+    }
+}
+
+/// Periodically send keep-alive input to idle terminals that ask for it
+pub fn spawn_keep_alive_loop(app_handle: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(KEEP_ALIVE_TICK);
+        let Some(state) = app_handle.try_state::<AppState>() else {
+            return;
+        };
+        send_keep_alive_input(&state);
+    });
+}
+
+fn send_keep_alive_input(state: &AppState) {
+    let now = Utc::now();
+    let due: Vec<(String, String)> = {
+        let last_sent = state.keep_alive.last_sent.lock();
+        state
+            .terminals
+            .read()
+            .values()
+            .filter_map(|t| {
+                let config = t.keep_alive.as_ref()?;
+                let interval = Duration::from_secs(config.input_interval_secs?);
+                if config.input.is_empty() {
+                    return None;
+                }
+
+                let idle = t
+                    .last_activity
+                    .and_then(|at| (now - at).to_std().ok())
+                    .unwrap_or(interval);
+                let since_sent = last_sent.get(&t.id).map_or(interval, Instant::elapsed);
+                (idle >= interval && since_sent >= interval).then(|| (t.id.clone(), config.input.clone()))
+            })
+            .collect()
+    };
+
+    let pty_handles = state.pty_handles.read();
+    for (terminal_id, input) in due {
+        if let Some(pty_handle) = pty_handles.get(&terminal_id) {
+            if write_to_pty(pty_handle, input.as_bytes()).is_ok() {
+                state.keep_alive.last_sent.lock().insert(terminal_id, Instant::now());
+            }
+        }
+    }
+}
@@ -1,10 +1,24 @@
+pub mod agent_status;
+pub mod checkpoint;
 pub mod commands;
 mod types;
+mod escapes;
+pub mod events;
+pub mod keep_alive;
 pub mod pty;
+mod pty_io;
+mod summary;
+mod utf8;
+pub mod pool;
+pub mod shell;
+mod shell_integration;
 
 pub use types::{
     Terminal, TerminalStatus, TerminalMode, TerminalInfo,
     CreateTerminalRequest, ResizeTerminalRequest, PtyHandle,
-    TerminalData, TerminalOutputBuffer,
+    TerminalHistory, TerminalMeta, TerminalOutputBuffer, TranscriptWriter, AgentInvocation, marker_line,
+    TerminalGroup, CreateTerminalGroupRequest, CreateTerminalGroupResult,
+    TerminalGroupStatus, TerminalGroupMemberStatus, TerminalOutputPaused, ScrollbackLimit,
+    TerminalSummary, TerminalProcessInfo, TerminalCommands, TerminalEnv, CommandHistoryEntry,
 };
-pub use commands::create_main_terminal_internal;
+pub use commands::{create_main_terminal_internal, create_terminal_internal, resume_terminals_on_start};
@@ -0,0 +1,225 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use parking_lot::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::clients::write_private;
+use crate::error::Result;
+use crate::state::AppState;
+use super::pty::{
+    is_process_running, kill_pty, resize_pty, spawn_idle_shell, write_to_pty, PtyBinding, PtySpawnOptions,
+    SharedPtyRoute,
+};
+use super::shell::{format_command_line, ShellKind};
+use super::types::{PtyHandle, TerminalOutputBuffer};
+
+/// Idle shells are keyed by project and shell executable
+type PoolKey = (String, PathBuf);
+
+/// An idle login shell waiting to be adopted by a new terminal
+struct PooledPty {
+    pty_handle: PtyHandle,
+    route: SharedPtyRoute,
+    spawned_at: Instant,
+}
+
+impl PooledPty {
+    fn has_exited(&self) -> bool {
+        !is_process_running(&self.pty_handle)
+    }
+}
+
+/// Warm spare shells per project, so terminals launched through a shell skip
+/// openpty and shell startup (rc files etc.) and just `cd` + `exec` the agent.
+#[derive(Default)]
+pub struct PtyPool {
+    idle: Mutex<HashMap<PoolKey, Vec<PooledPty>>>,
+    /// Keys with a refill thread currently running
+    refilling: Mutex<HashSet<PoolKey>>,
+}
+
+impl PtyPool {
+    /// Launch the agent described by `options` in a pooled shell, if a healthy one is available.
+    /// Its environment goes through `env_file`, which the shell deletes once it has read it.
+    /// Returns None when the launch should fall back to a cold spawn.
+    pub fn try_adopt(
+        &self,
+        project_id: &str,
+        terminal_id: &str,
+        options: PtySpawnOptions<'_>,
+        output_buffer: Arc<TerminalOutputBuffer>,
+        env_file: &Path,
+        max_idle: Duration,
+    ) -> Option<PtyHandle> {
+        let shell = options.shell?;
+        // Only variables the shell can `export` can be passed through the env file
+        if !options.client.env.keys().all(|key| is_shell_name(key)) {
+            return None;
+        }
+        let pooled = self.take((project_id.to_string(), shell.to_path_buf()), max_idle)?;
+
+        match adopt(pooled, terminal_id, options, output_buffer, env_file) {
+            Ok(pty_handle) => Some(pty_handle),
+            Err(e) => {
+                eprintln!("[Ada] Failed to adopt pooled shell for terminal {}: {}", terminal_id, e);
+                None
+            }
+        }
+    }
+
+    /// Stop and discard all idle shells belonging to a project
+    pub fn drain_project(&self, project_id: &str) {
+        let drained: Vec<PooledPty> = {
+            let mut idle = self.idle.lock();
+            let keys: Vec<PoolKey> = idle.keys().filter(|(id, _)| id == project_id).cloned().collect();
+            keys.iter().filter_map(|key| idle.remove(key)).flatten().collect()
+        };
+        for pooled in drained {
+            kill_pty(pooled.pty_handle);
+        }
+    }
+
+    /// Take the oldest healthy idle shell, recycling any that exited or sat idle too long
+    fn take(&self, key: PoolKey, max_idle: Duration) -> Option<PooledPty> {
+        let mut stale = Vec::new();
+        let found = {
+            let mut idle = self.idle.lock();
+            let entries = idle.get_mut(&key)?;
+            let mut found = None;
+            while !entries.is_empty() {
+                let entry = entries.remove(0);
+                if entry.spawned_at.elapsed() > max_idle || entry.has_exited() {
+                    stale.push(entry);
+                } else {
+                    found = Some(entry);
+                    break;
+                }
+            }
+            found
+        };
+
+        if !stale.is_empty() {
+            std::thread::spawn(move || {
+                for pooled in stale {
+                    kill_pty(pooled.pty_handle);
+                }
+            });
+        }
+
+        found
+    }
+}
+
+/// Top the pool up to `target` idle shells for a project in the background
+pub fn replenish_pool(
+    app_handle: &AppHandle,
+    project_id: &str,
+    shell: &Path,
+    working_dir: &Path,
+    target: usize,
+) {
+    let app_handle = app_handle.clone();
+    let key: PoolKey = (project_id.to_string(), shell.to_path_buf());
+    let working_dir = working_dir.to_path_buf();
+
+    std::thread::spawn(move || {
+        let Some(state) = app_handle.try_state::<AppState>() else {
+            return;
+        };
+        let pool = &state.pty_pool;
+
+        if !pool.refilling.lock().insert(key.clone()) {
+            return; // Another refill for this key is already running
+        }
+
+        loop {
+            let count = pool.idle.lock().get(&key).map_or(0, Vec::len);
+            if count >= target {
+                break;
+            }
+
+            match spawn_idle_shell(&app_handle, &key.1, &working_dir) {
+                Ok((pty_handle, route)) => {
+                    pool.idle.lock().entry(key.clone()).or_default().push(PooledPty {
+                        pty_handle,
+                        route,
+                        spawned_at: Instant::now(),
+                    });
+                }
+                Err(e) => {
+                    eprintln!("[Ada] Failed to spawn pooled shell {:?}: {}", key.1, e);
+                    break;
+                }
+            }
+        }
+
+        pool.refilling.lock().remove(&key);
+    });
+}
+
+/// Point a pooled shell at a terminal and have it `cd` + `exec` the agent.
+/// Output is routed to the terminal only after a unique sentinel printed right
+/// before the exec, so the shell prompt and the typed command never show up.
+/// The client env (often API keys) is sourced from a file only the user can read,
+/// rather than typed, so it never shows in `ps` or the command line.
+fn adopt(
+    pooled: PooledPty,
+    terminal_id: &str,
+    options: PtySpawnOptions<'_>,
+    output_buffer: Arc<TerminalOutputBuffer>,
+    env_file: &Path,
+) -> Result<PtyHandle> {
+    let nonce = uuid::Uuid::new_v4().to_string();
+    let sentinel = format!("\x1b]633;AdaAdopt;{}\x07", nonce);
+
+    let mut steps = vec![format_command_line(
+        ShellKind::Posix,
+        Path::new("cd"),
+        &[options.working_dir.to_string_lossy().to_string()],
+    )];
+    if !options.client.env.is_empty() {
+        let exports: String = options
+            .client
+            .env
+            .iter()
+            .map(|(key, value)| {
+                format_command_line(ShellKind::Posix, Path::new("export"), &[format!("{}={}", key, value)]) + "\n"
+            })
+            .collect();
+        write_private(env_file, &exports)?;
+
+        let env_file = env_file.to_string_lossy().to_string();
+        steps.push(format_command_line(ShellKind::Posix, Path::new("."), std::slice::from_ref(&env_file)));
+        steps.push(format_command_line(ShellKind::Posix, Path::new("rm"), &["-f".to_string(), env_file]));
+    }
+    steps.push(format!("printf '\\033]633;AdaAdopt;%s\\007' {}", nonce));
+    steps.push(format!(
+        "exec {}",
+        format_command_line(ShellKind::Posix, &options.client.get_command_path(), &options.client.args)
+    ));
+
+    pooled.route.lock().pending = Some((
+        sentinel.into_bytes(),
+        PtyBinding {
+            terminal_id: terminal_id.to_string(),
+            output_buffer,
+        },
+    ));
+
+    resize_pty(&pooled.pty_handle, options.cols, options.rows)?;
+
+    // Leading space keeps the line out of shell history (HISTCONTROL/HIST_IGNORE_SPACE)
+    let command_line = format!(" {}\n", steps.join(" && "));
+    write_to_pty(&pooled.pty_handle, command_line.as_bytes())?;
+
+    Ok(pooled.pty_handle)
+}
+
+/// Whether `name` can be used as a shell variable name
+fn is_shell_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
@@ -1,158 +1,913 @@
 use portable_pty::{CommandBuilder, NativePtySystem, PtySize, PtySystem};
-use std::io::Read;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use parking_lot::Mutex;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::watch;
 
 use crate::clients::ClientConfig;
 use crate::error::{Error, Result};
-use super::types::{PtyHandle, TerminalOutput, TerminalOutputBuffer};
+use crate::state::AppState;
+use crate::telemetry;
+use super::events::{record_terminal_event, TerminalEventKind};
+use super::escapes::{
+    parse_osc133, parse_osc7, strip_escapes, EscapeScanner, EscapeSequence, ShellMark, BRACKETED_PASTE_MODE,
+};
+use super::keep_alive::{handle_disconnect, OutputTail};
+use super::pty_io::{send_pty_command, spawn_output_reader, spawn_pty_io, PtyCommand};
+use super::shell::{format_command_line, ShellConfig, ShellKind};
+use super::utf8::Utf8Decoder;
+use super::types::{PtyHandle, TerminalCommandEvent, TerminalCwdChanged, TerminalOutput, TerminalOutputBuffer, TerminalStatus};
+
+/// How long to wait after SIGHUP before escalating to SIGTERM
+const HANGUP_GRACE_PERIOD: Duration = Duration::from_millis(500);
+
+/// How long to wait after SIGTERM before escalating to SIGKILL
+const TERMINATE_GRACE_PERIOD: Duration = Duration::from_millis(1500);
+
+/// Interval between exit checks while waiting for the child to terminate
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Minimum interval between last-activity updates written to terminal state
+const ACTIVITY_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long CPU usage is measured over (see `process_group_usage`)
+#[cfg(unix)]
+const CPU_SAMPLE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Environment variable carrying the agent command line into cmd.exe
+const CMD_LINE_VAR: &str = "ADA_COMMAND_LINE";
+
+/// What to launch in a new PTY and how
+#[derive(Clone, Copy)]
+pub struct PtySpawnOptions<'a> {
+    pub working_dir: &'a Path,
+    pub client: &'a ClientConfig,
+    /// Shell to run the client command through, or None to exec it directly
+    pub shell: Option<&'a Path>,
+    /// Command run in the shell before the agent starts, e.g. the project's `setup_command`
+    /// in a new worktree. Without a shell, the login shell runs it.
+    pub setup_command: Option<&'a str>,
+    pub cols: u16,
+    pub rows: u16,
+}
+
+/// The terminal a PTY's output is delivered to
+#[derive(Clone)]
+pub struct PtyBinding {
+    pub terminal_id: String,
+    pub output_buffer: Arc<TerminalOutputBuffer>,
+}
+
+/// Where a PTY's output currently goes. Pooled PTYs start unbound and discard
+/// their output until adopted by a terminal.
+#[derive(Default)]
+pub struct PtyRoute {
+    pub binding: Option<PtyBinding>,
+    /// Binding to switch to once this byte sequence appears in the output
+    pub pending: Option<(Vec<u8>, PtyBinding)>,
+}
+
+pub type SharedPtyRoute = Arc<Mutex<PtyRoute>>;
 
 pub fn spawn_pty(
     app_handle: &AppHandle,
     terminal_id: &str,
-    working_dir: &Path,
-    client: &ClientConfig,
-    cols: u16,
-    rows: u16,
+    options: PtySpawnOptions<'_>,
     output_buffer: Arc<TerminalOutputBuffer>,
 ) -> Result<PtyHandle> {
-    let pty_system = NativePtySystem::default();
-
-    let pair = pty_system
-        .openpty(PtySize {
-            rows,
-            cols,
-            pixel_width: 0,
-            pixel_height: 0,
-        })
-        .map_err(|e| Error::TerminalError(e.to_string()))?;
+    let PtySpawnOptions { working_dir, client, shell, setup_command, cols, rows } = options;
 
     // Use full path to command (macOS GUI apps don't inherit shell PATH)
     let command_path = client.get_command_path();
-    let mut cmd = CommandBuilder::new(&command_path);
-    cmd.args(&client.args);
+    let setup_shell = setup_command
+        .filter(|_| shell.is_none())
+        .and_then(|_| ShellConfig::detect())
+        .map(|config| config.path);
+    let mut cmd = match shell.or(setup_shell.as_deref()) {
+        Some(shell) => shell_command(shell, &command_path, &client.args, setup_command),
+        // Batch files (e.g. npm's claude.cmd shims) can only be run by cmd.exe
+        None if cfg!(windows) && is_batch_file(&command_path) => {
+            let comspec = std::env::var_os("COMSPEC")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from("cmd.exe"));
+            shell_command(&comspec, &command_path, &client.args, setup_command)
+        }
+        None => {
+            let mut cmd = CommandBuilder::new(&command_path);
+            cmd.args(&client.args);
+            cmd
+        }
+    };
     cmd.cwd(working_dir);
+    apply_base_env(&mut cmd);
 
-    // Set up proper PATH environment for the PTY
-    // This ensures child processes can find common tools
-    if let Some(home) = dirs::home_dir() {
-        let path_dirs = vec![
-            home.join(".local/bin"),
-            home.join(".cargo/bin"),
-            home.join(".bun/bin"),
-            std::path::PathBuf::from("/opt/homebrew/bin"),
-            std::path::PathBuf::from("/opt/homebrew/sbin"),
-            std::path::PathBuf::from("/usr/local/bin"),
-            std::path::PathBuf::from("/usr/bin"),
-            std::path::PathBuf::from("/bin"),
-            std::path::PathBuf::from("/usr/sbin"),
-            std::path::PathBuf::from("/sbin"),
-        ];
-
-        let path_value: String = path_dirs
-            .iter()
-            .filter(|p| p.exists())
-            .map(|p| p.to_string_lossy().to_string())
-            .collect::<Vec<_>>()
-            .join(":");
-
-        cmd.env("PATH", &path_value);
-        cmd.env("HOME", home.to_string_lossy().to_string());
+    // Set environment variables from client config
+    for (key, value) in &client.env {
+        cmd.env(key, value);
     }
 
-    // Set TERM for proper terminal emulation
-    cmd.env("TERM", "xterm-256color");
+    let route = Arc::new(Mutex::new(PtyRoute {
+        binding: Some(PtyBinding {
+            terminal_id: terminal_id.to_string(),
+            output_buffer,
+        }),
+        pending: None,
+    }));
 
-    // Set environment variables from client config
+    launch_pty(app_handle, cmd, cols, rows, route)
+}
+
+/// Build a command that has `shell` run the agent, exiting when it does. A setup command
+/// runs first, in the same shell; the agent starts even if it fails.
+fn shell_command(shell: &Path, program: &Path, args: &[String], setup: Option<&str>) -> CommandBuilder {
+    let kind = ShellKind::from_path(shell);
+    let command_line = format_command_line(kind, program, args);
+
+    let mut cmd = CommandBuilder::new(shell);
+    match kind {
+        ShellKind::Posix => {
+            // Let the shell load the user's profile, then replace itself with the agent
+            let setup = setup
+                .map(|setup| {
+                    format!(
+                        "{{ {}\n}} || printf '\\n[Ada] Setup command failed (exit %s)\\n' \"$?\"; ",
+                        setup
+                    )
+                })
+                .unwrap_or_default();
+            cmd.args(["-l".to_string(), "-c".to_string(), format!("{}exec {}", setup, command_line)]);
+        }
+        ShellKind::PowerShell => {
+            // The profile is loaded by default; pass the agent's exit code back out
+            let setup = setup
+                .map(|setup| format!("{}; if (-not $?) {{ Write-Host \"`n[Ada] Setup command failed\" }}; ", setup))
+                .unwrap_or_default();
+            cmd.args([
+                "-NoLogo".to_string(),
+                "-Command".to_string(),
+                format!("{}{}; exit $LASTEXITCODE", setup, command_line),
+            ]);
+        }
+        ShellKind::Cmd => {
+            // portable-pty quotes every argument MSVC-style, which cmd.exe doesn't understand.
+            // Going through a variable keeps the line verbatim: %VAR% is expanded once,
+            // before cmd.exe parses quotes and operators.
+            let command_line = match setup {
+                Some(setup) => format!("({}) & {}", setup, command_line),
+                None => command_line,
+            };
+            cmd.env(CMD_LINE_VAR, &command_line);
+            cmd.args(["/d".to_string(), "/c".to_string(), format!("%{}%", CMD_LINE_VAR)]);
+        }
+    }
+    cmd
+}
+
+fn is_batch_file(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.eq_ignore_ascii_case("cmd") || ext.eq_ignore_ascii_case("bat"))
+        .unwrap_or(false)
+}
+
+/// Spawn an interactive login shell that is not attached to any terminal yet.
+/// Used to keep warm spares around so new terminals skip shell startup.
+pub fn spawn_idle_shell(
+    app_handle: &AppHandle,
+    shell: &Path,
+    working_dir: &Path,
+) -> Result<(PtyHandle, SharedPtyRoute)> {
+    let mut cmd = CommandBuilder::new(shell);
+    cmd.arg("-l");
+    cmd.cwd(working_dir);
+    apply_base_env(&mut cmd);
+
+    let route: SharedPtyRoute = Arc::new(Mutex::new(PtyRoute::default()));
+    let pty_handle = launch_pty(app_handle, cmd, 120, 30, route.clone())?;
+    Ok((pty_handle, route))
+}
+
+/// Run a shell script in a new PTY through `shell`, e.g. an agent installer
+/// (see `clients::install`). Its output is delivered like a terminal's, under `session_id`.
+pub fn spawn_script(
+    app_handle: &AppHandle,
+    session_id: &str,
+    shell: &Path,
+    script: &str,
+    working_dir: &Path,
+    output_buffer: Arc<TerminalOutputBuffer>,
+) -> Result<PtyHandle> {
+    let mut cmd = CommandBuilder::new(shell);
+    match ShellKind::from_path(shell) {
+        ShellKind::Posix => cmd.args(["-l", "-c", script]),
+        ShellKind::PowerShell => cmd.args(["-NoLogo", "-Command", &format!("{}; exit $LASTEXITCODE", script)]),
+        ShellKind::Cmd => {
+            // See `shell_command` for why the script goes through a variable
+            cmd.env(CMD_LINE_VAR, script);
+            cmd.args(["/d".to_string(), "/c".to_string(), format!("%{}%", CMD_LINE_VAR)]);
+        }
+    }
+    cmd.cwd(working_dir);
+    apply_base_env(&mut cmd);
+
+    let route = Arc::new(Mutex::new(PtyRoute {
+        binding: Some(PtyBinding {
+            terminal_id: session_id.to_string(),
+            output_buffer,
+        }),
+        pending: None,
+    }));
+
+    launch_pty(app_handle, cmd, 120, 30, route)
+}
+
+/// Output and exit of a program run by `run_captured`
+#[derive(Debug, Clone)]
+pub struct CapturedRun {
+    /// None if it was killed for running too long
+    pub exit_code: Option<u32>,
+    /// Plain text, without escape sequences
+    pub output: String,
+}
+
+/// Run a client's command with `args` in a PTY of its own, outside any terminal, and
+/// capture its output. Agents that check for a TTY behave as they would in a terminal.
+/// The process group is killed if it runs longer than `timeout`.
+pub fn run_captured(client: &ClientConfig, args: &[&str], working_dir: &Path, timeout: Duration) -> Result<CapturedRun> {
+    let pair = NativePtySystem::default()
+        .openpty(PtySize { rows: 30, cols: 120, pixel_width: 0, pixel_height: 0 })
+        .map_err(|e| Error::TerminalError(e.to_string()))?;
+
+    let mut cmd = CommandBuilder::new(client.get_command_path());
+    cmd.args(args);
+    cmd.cwd(working_dir);
+    apply_base_env(&mut cmd);
     for (key, value) in &client.env {
         cmd.env(key, value);
     }
-    
-    // Spawn the child process
-    let _child = pair
+
+    let mut child = pair
         .slave
         .spawn_command(cmd)
         .map_err(|e| Error::TerminalError(e.to_string()))?;
-    
-    // Drop the slave to avoid blocking
     drop(pair.slave);
-    
-    // Get reader for output
     let mut reader = pair
         .master
         .try_clone_reader()
         .map_err(|e| Error::TerminalError(e.to_string()))?;
-    
-    // Spawn a thread to read output and emit events
-    let app_handle_clone = app_handle.clone();
-    let terminal_id_clone = terminal_id.to_string();
 
-    // Get the writer before spawning the read thread
+    let (output_tx, output_rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let mut output = Vec::new();
+        let _ = std::io::Read::read_to_end(&mut reader, &mut output);
+        let _ = output_tx.send(output);
+    });
+
+    let started = Instant::now();
+    let exit_code = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status.exit_code()),
+            Ok(None) if started.elapsed() < timeout => std::thread::sleep(EXIT_POLL_INTERVAL),
+            _ => {
+                kill_pty_child(child.as_mut());
+                break None;
+            }
+        }
+    };
+
+    // A subprocess left holding the PTY would keep the read open, so don't wait long for it
+    drop(pair.master);
+    let output = output_rx.recv_timeout(TERMINATE_GRACE_PERIOD).unwrap_or_default();
+    Ok(CapturedRun {
+        exit_code,
+        output: strip_escapes(&String::from_utf8_lossy(&output)),
+    })
+}
+
+/// Kill a child that isn't attached to a terminal, with its process group
+fn kill_pty_child(child: &mut (dyn portable_pty::Child + Send + Sync)) {
+    #[cfg(unix)]
+    if let Some(pid) = child.process_id() {
+        unsafe {
+            libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+        }
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Set up proper PATH, HOME and TERM for the PTY.
+/// This ensures child processes can find common tools
+fn apply_base_env(cmd: &mut CommandBuilder) {
+    // Windows GUI apps inherit the full user PATH, and these directories are Unix-only
+    #[cfg(not(windows))]
+    if let Some(home) = dirs::home_dir() {
+        cmd.env("PATH", agent_path(&home));
+        cmd.env("HOME", home.to_string_lossy().to_string());
+    }
+
+    // Set TERM for proper terminal emulation (clients and terminals can override it)
+    cmd.env("TERM", "xterm-256color");
+
+    // macOS GUI apps start without a locale, which leaves agents unable to print UTF-8
+    if std::env::var_os("LANG").is_none() {
+        cmd.env("LANG", "en_US.UTF-8");
+    }
+}
+
+/// PATH for agent processes, built from the usual install locations
+/// (macOS GUI apps don't inherit the shell PATH)
+pub fn agent_path(home: &Path) -> String {
+    let path_dirs = vec![
+        home.join(".local/bin"),
+        home.join(".cargo/bin"),
+        home.join(".bun/bin"),
+        std::path::PathBuf::from("/opt/homebrew/bin"),
+        std::path::PathBuf::from("/opt/homebrew/sbin"),
+        std::path::PathBuf::from("/usr/local/bin"),
+        std::path::PathBuf::from("/usr/bin"),
+        std::path::PathBuf::from("/bin"),
+        std::path::PathBuf::from("/usr/sbin"),
+        std::path::PathBuf::from("/sbin"),
+    ];
+
+    path_dirs
+        .iter()
+        .filter(|p| p.exists())
+        .map(|p| p.to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Open a PTY, spawn `cmd` in it and start forwarding its output along `route`
+fn launch_pty(
+    app_handle: &AppHandle,
+    cmd: CommandBuilder,
+    cols: u16,
+    rows: u16,
+    route: SharedPtyRoute,
+) -> Result<PtyHandle> {
+    let pty_system = NativePtySystem::default();
+
+    let pair = pty_system
+        .openpty(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|e| Error::TerminalError(e.to_string()))?;
+
+    // Spawn the child process
+    let child = pair
+        .slave
+        .spawn_command(cmd)
+        .map_err(|e| Error::TerminalError(e.to_string()))?;
+    let pid = child.process_id();
+
+    // Drop the slave to avoid blocking
+    drop(pair.slave);
+
+    // Get the writer before starting the reader
     let writer = pair
         .master
         .take_writer()
         .map_err(|e| Error::TerminalError(e.to_string()))?;
 
-    std::thread::spawn(move || {
-        let mut buffer = [0u8; 4096];
-
-        loop {
-            match reader.read(&mut buffer) {
-                Ok(0) => break, // EOF
-                Ok(n) => {
-                    let output = String::from_utf8_lossy(&buffer[..n]).to_string();
-
-                    // Store in output buffer for persistence
-                    output_buffer.append(output.clone());
-
-                    // Emit output event for frontend
-                    let _ = app_handle_clone.emit(
-                        "terminal-output",
-                        TerminalOutput {
-                            terminal_id: terminal_id_clone.clone(),
-                            data: output,
-                        },
-                    );
+    // Flipped on the first output delivered to a terminal
+    let (ready_tx, ready_rx) = watch::channel(false);
+    let bracketed_paste = Arc::new(AtomicBool::new(false));
+    let stopping = Arc::new(AtomicBool::new(false));
+
+    let pump = OutputPump {
+        app_handle: app_handle.clone(),
+        route,
+        ready: ready_tx,
+        bracketed_paste: bracketed_paste.clone(),
+        stopping: stopping.clone(),
+        escapes: EscapeScanner::new(),
+        decoder: Utf8Decoder::default(),
+        last_activity_update: None,
+        idle_tail: Vec::new(),
+        output_tail: OutputTail::default(),
+    };
+    let (paused_tx, paused_rx) = watch::channel(false);
+    let stop_reader = spawn_output_reader(pair.master.as_ref(), pump, paused_rx)?;
+
+    Ok(PtyHandle {
+        commands: spawn_pty_io(pair.master, writer),
+        child: Arc::new(Mutex::new(child)),
+        pid,
+        ready: ready_rx,
+        _stop_reader: stop_reader,
+        paused: paused_tx,
+        bracketed_paste,
+        stopping,
+    })
+}
+
+/// Delivers a PTY's output to its terminal: scrollback, events, activity and
+/// escape-sequence tracking. Fed by whichever reader drives the PTY.
+pub struct OutputPump {
+    app_handle: AppHandle,
+    route: SharedPtyRoute,
+    ready: watch::Sender<bool>,
+    /// Tracks whether the program has turned on bracketed paste
+    bracketed_paste: Arc<AtomicBool>,
+    /// Set when Ada is stopping the process rather than it exiting by itself
+    stopping: Arc<AtomicBool>,
+    escapes: EscapeScanner,
+    /// Carries characters split across reads over to the next chunk
+    decoder: Utf8Decoder,
+    last_activity_update: Option<Instant>,
+    /// Output seen while unbound, kept only to match an adoption sentinel across reads
+    idle_tail: Vec<u8>,
+    /// The end of the output, checked for a dropped remote connection on exit (see `keep_alive`)
+    output_tail: OutputTail,
+}
+
+impl OutputPump {
+    /// Handle one chunk read from the PTY
+    pub fn feed(&mut self, chunk: &[u8]) {
+        let (binding, data) = match route_output(&self.route, &mut self.idle_tail, chunk) {
+            Some(routed) => routed,
+            None => return,
+        };
+        if data.is_empty() {
+            return;
+        }
+        let terminal_id = &binding.terminal_id;
+
+        if self.last_activity_update.is_none_or(|t| t.elapsed() >= ACTIVITY_UPDATE_INTERVAL) {
+            record_activity(&self.app_handle, terminal_id);
+            self.last_activity_update = Some(Instant::now());
+        }
+
+        // Where this chunk starts in the terminal's output stream
+        let stream_offset = binding.output_buffer.end();
+        let output = self.decoder.decode(&data);
+        // Scanned once decoded, so offsets count the bytes that go into the output buffer
+        // (a character split across reads shifts them otherwise)
+        let sequences = self.escapes.feed(output.as_bytes());
+
+        if !output.is_empty() {
+            self.output_tail.push(&output);
+
+            // Store in output buffer for persistence (written out by the periodic flush)
+            binding.output_buffer.append(output.clone());
+            if let Some(state) = self.app_handle.try_state::<AppState>() {
+                state.mark_terminal_dirty(terminal_id);
+            }
+        }
+
+        // Handled once the chunk is buffered, so shell marks can read the text they delimit
+        for (end, sequence) in sequences {
+            match sequence {
+                EscapeSequence::PrivateMode { mode: BRACKETED_PASTE_MODE, enabled } => {
+                    self.bracketed_paste.store(enabled, Ordering::Relaxed);
                 }
-                Err(_) => break,
+                sequence => handle_escape_sequence(
+                    &self.app_handle,
+                    terminal_id,
+                    &binding.output_buffer,
+                    stream_offset + end as u64,
+                    sequence,
+                ),
             }
         }
 
-        // Emit terminal closed event
-        let _ = app_handle_clone.emit("terminal-closed", terminal_id_clone);
-    });
+        if output.is_empty() {
+            return;
+        }
 
-    Ok(PtyHandle {
-        master: Arc::new(Mutex::new(pair.master)),
-        writer: Arc::new(Mutex::new(writer)),
-    })
+        // Emit output event for frontend
+        let _ = self.app_handle.emit(
+            "terminal-output",
+            TerminalOutput {
+                terminal_id: terminal_id.clone(),
+                data: output,
+            },
+        );
+
+        if !*self.ready.borrow() {
+            self.ready.send_replace(true);
+            mark_terminal_ready(&self.app_handle, terminal_id);
+        }
+    }
+
+    /// Called once the PTY has closed or reading was stopped
+    pub fn finish(self) {
+        // Emit terminal closed event (idle pooled shells have no terminal to notify)
+        let binding = {
+            let route = self.route.lock();
+            route.binding.clone().or_else(|| route.pending.as_ref().map(|(_, b)| b.clone()))
+        };
+        if let Some(binding) = binding {
+            let stopped = self.stopping.load(Ordering::SeqCst);
+            telemetry::end_turn(&binding.terminal_id);
+            if let Some(state) = self.app_handle.try_state::<AppState>() {
+                // The child has usually been reaped by the time its output ends
+                let exit_code = state
+                    .pty_handles
+                    .read()
+                    .get(&binding.terminal_id)
+                    .and_then(|handle| handle.child.lock().try_wait().ok().flatten())
+                    .map(|status| status.exit_code());
+                record_terminal_event(
+                    &state,
+                    &binding.terminal_id,
+                    TerminalEventKind::Exited { exit_code, stopped },
+                );
+            }
+            if !stopped && self.output_tail.shows_disconnect() {
+                handle_disconnect(&self.app_handle, &binding.terminal_id);
+            }
+            let _ = self.app_handle.emit("terminal-closed", binding.terminal_id);
+        }
+    }
+}
+
+/// Decide where a chunk of output goes. Returns the binding and the bytes to deliver,
+/// or None if the PTY is idle and the chunk should be discarded.
+fn route_output(
+    route: &SharedPtyRoute,
+    idle_tail: &mut Vec<u8>,
+    chunk: &[u8],
+) -> Option<(PtyBinding, Vec<u8>)> {
+    let mut route = route.lock();
+
+    if let Some(binding) = &route.binding {
+        return Some((binding.clone(), chunk.to_vec()));
+    }
+
+    let sentinel_len = route.pending.as_ref()?.0.len();
+    idle_tail.extend_from_slice(chunk);
+
+    let position = idle_tail
+        .windows(sentinel_len)
+        .position(|window| route.pending.as_ref().is_some_and(|(sentinel, _)| window == sentinel.as_slice()));
+
+    match position {
+        Some(position) => {
+            // Everything after the sentinel belongs to the adopting terminal
+            let rest = idle_tail.split_off(position + sentinel_len);
+            idle_tail.clear();
+            let (_, binding) = route.pending.take()?;
+            route.binding = Some(binding.clone());
+            Some((binding, rest))
+        }
+        None => {
+            // Only the tail can still be the start of a sentinel split across reads
+            let keep_from = idle_tail.len().saturating_sub(sentinel_len);
+            idle_tail.drain(..keep_from);
+            None
+        }
+    }
+}
+
+/// Terminate the process behind a PTY handle, escalating SIGHUP -> SIGTERM -> SIGKILL.
+/// Signals go to the child's whole process group so agent subprocesses are not left running.
+/// Blocks until the child has exited (at most the sum of the grace periods plus reaping).
+pub fn kill_pty(pty_handle: PtyHandle) {
+    kill_ptys(vec![pty_handle]);
+}
+
+/// `kill_pty` for several PTYs at once: each step of the escalation signals every process
+/// group still running before waiting on any of them, so stopping N agents takes as long
+/// as the slowest one rather than the sum
+pub fn kill_ptys(pty_handles: Vec<PtyHandle>) {
+    // Marked first, so the exits these cause aren't reported as the agents finishing
+    for pty_handle in &pty_handles {
+        pty_handle.stopping.store(true, Ordering::SeqCst);
+    }
+
+    // Nothing to do for processes that already exited on their own
+    let mut running: Vec<PtyHandle> = pty_handles
+        .into_iter()
+        .filter(|pty_handle| !matches!(pty_handle.child.lock().try_wait(), Ok(Some(_))))
+        .collect();
+
+    #[cfg(unix)]
+    {
+        for (signal, grace_period) in [
+            (libc::SIGHUP, HANGUP_GRACE_PERIOD),
+            (libc::SIGTERM, TERMINATE_GRACE_PERIOD),
+        ] {
+            if running.is_empty() {
+                return;
+            }
+            for pty_handle in &running {
+                signal_pty(pty_handle, signal);
+            }
+            running = wait_for_exits(running, grace_period);
+        }
+
+        if !running.is_empty() {
+            eprintln!("[Ada] {} PTY children ignored SIGHUP/SIGTERM, sending SIGKILL", running.len());
+        }
+        for pty_handle in &running {
+            signal_pty(pty_handle, libc::SIGKILL);
+        }
+        for pty_handle in running {
+            let _ = pty_handle.child.lock().wait();
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        for pty_handle in &running {
+            let _ = pty_handle.child.lock().kill();
+        }
+        if !wait_for_exits(running, TERMINATE_GRACE_PERIOD).is_empty() {
+            eprintln!("[Ada] PTY child did not exit after kill");
+        }
+    }
+}
+
+/// `kill_ptys` on a blocking thread, so async commands don't hold up the runtime while
+/// agents take their time to exit
+pub async fn kill_ptys_off_runtime(pty_handles: Vec<PtyHandle>) {
+    if pty_handles.is_empty() {
+        return;
+    }
+    if let Err(e) = tauri::async_runtime::spawn_blocking(move || kill_ptys(pty_handles)).await {
+        eprintln!("[Ada] Failed to stop PTYs: {}", e);
+    }
+}
+
+/// Send a signal to the child's process group (portable-pty calls setsid() before exec, so
+/// the child leads its own); a child without a known pid is killed outright
+#[cfg(unix)]
+fn signal_pty(pty_handle: &PtyHandle, signal: libc::c_int) {
+    match pty_handle.pid {
+        Some(pid) => unsafe {
+            libc::killpg(pid as libc::pid_t, signal);
+        },
+        None => {
+            let _ = pty_handle.child.lock().kill();
+        }
+    }
+}
+
+/// Whether the process behind a PTY handle is still running
+pub fn is_process_running(pty_handle: &PtyHandle) -> bool {
+    matches!(pty_handle.child.lock().try_wait(), Ok(None))
+}
+
+/// Resident memory (KiB) and CPU usage (percent of one core) summed over a process group.
+/// Read from `ps`, so it covers everything the agent spawned, not just the agent itself.
+/// CPU usage is measured over `CPU_SAMPLE_INTERVAL` (ps's own `%cpu` averages over each
+/// process's lifetime), so this blocks for that long.
+#[cfg(unix)]
+pub fn process_group_usage(pgid: u32) -> Option<(u64, f64)> {
+    let (_, before) = sample_process_group(pgid)?;
+    let started = Instant::now();
+    std::thread::sleep(CPU_SAMPLE_INTERVAL);
+    let (rss_kb, after) = sample_process_group(pgid)?;
+
+    // Processes that exited in between are left out; ones started since count from zero
+    let cpu_secs: f64 = after
+        .iter()
+        .map(|(pid, secs)| (secs - before.get(pid).copied().unwrap_or(0.0)).max(0.0))
+        .sum();
+    Some((rss_kb, cpu_secs / started.elapsed().as_secs_f64() * 100.0))
+}
+
+/// Resident memory (KiB) of a process group and the CPU time (seconds) of each of its processes
+#[cfg(unix)]
+fn sample_process_group(pgid: u32) -> Option<(u64, std::collections::HashMap<u32, f64>)> {
+    let output = std::process::Command::new("ps")
+        .args(["-A", "-o", "pid=,pgid=,rss=,time="])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let mut total_rss = 0;
+    let mut cpu_times = std::collections::HashMap::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(pid), Some(group), Some(rss), Some(time)) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        if group.parse::<u32>().ok() != Some(pgid) {
+            continue;
+        }
+        let Ok(pid) = pid.parse::<u32>() else {
+            continue;
+        };
+
+        total_rss += rss.parse::<u64>().unwrap_or(0);
+        cpu_times.insert(pid, process_cpu_time(pid, time).unwrap_or(0.0));
+    }
+    (!cpu_times.is_empty()).then_some((total_rss, cpu_times))
+}
+
+/// CPU time of a process in seconds. Linux's `ps` only has whole seconds, too coarse for a
+/// short sample, so it is read from `/proc` there instead.
+#[cfg(target_os = "linux")]
+fn process_cpu_time(pid: u32, _ps_time: &str) -> Option<f64> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name is in parentheses and may contain spaces; utime and stime are
+    // the 12th and 13th fields after it
+    let mut fields = stat.rsplit_once(')')?.1.split_whitespace().skip(11);
+    let utime = fields.next()?.parse::<u64>().ok()?;
+    let stime = fields.next()?.parse::<u64>().ok()?;
+    // SAFETY: sysconf only reads a system constant
+    let ticks_per_sec = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    (ticks_per_sec > 0).then(|| (utime + stime) as f64 / ticks_per_sec as f64)
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn process_cpu_time(_pid: u32, ps_time: &str) -> Option<f64> {
+    parse_cpu_time(ps_time)
+}
+
+/// Seconds in a `ps` CPU time, e.g. `MM:SS.ss` on macOS or `[DD-]HH:MM:SS` elsewhere
+#[cfg(all(unix, not(target_os = "linux")))]
+fn parse_cpu_time(time: &str) -> Option<f64> {
+    let (days, clock) = match time.split_once('-') {
+        Some((days, clock)) => (days.parse::<f64>().ok()?, clock),
+        None => (0.0, time),
+    };
+    let mut secs = days * 86400.0;
+    for (field, scale) in clock.rsplit(':').zip([1.0, 60.0, 3600.0]) {
+        secs += field.parse::<f64>().ok()? * scale;
+    }
+    Some(secs)
+}
+
+/// Poll the children until they exit or the timeout elapses. Returns those still running.
+fn wait_for_exits(mut pty_handles: Vec<PtyHandle>, timeout: Duration) -> Vec<PtyHandle> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        // A child that can no longer be waited on is treated as gone
+        pty_handles.retain(|pty_handle| matches!(pty_handle.child.lock().try_wait(), Ok(None)));
+        if pty_handles.is_empty() || Instant::now() >= deadline {
+            return pty_handles;
+        }
+        std::thread::sleep(EXIT_POLL_INTERVAL);
+    }
+}
+
+/// Stamp the terminal's last-activity time (persisted with the next save)
+fn record_activity(app_handle: &AppHandle, terminal_id: &str) {
+    if let Some(state) = app_handle.try_state::<AppState>() {
+        if let Some(terminal) = state.terminals.write().get_mut(terminal_id) {
+            terminal.last_activity = Some(chrono::Utc::now());
+        }
+    }
+}
+
+/// Move a terminal from Starting to Running once its process has produced output
+pub fn mark_terminal_ready(app_handle: &AppHandle, terminal_id: &str) {
+    let Some(state) = app_handle.try_state::<AppState>() else {
+        return;
+    };
+
+    let became_ready = match state.terminals.write().get_mut(terminal_id) {
+        Some(terminal) if terminal.status == TerminalStatus::Starting => {
+            terminal.status = TerminalStatus::Running;
+            true
+        }
+        _ => false,
+    };
+
+    if became_ready {
+        record_terminal_event(&state, terminal_id, TerminalEventKind::Ready);
+        let _ = app_handle.emit("terminal-ready", terminal_id.to_string());
+    }
+}
+
+/// React to an escape sequence the child wrote to the terminal, ending at `offset`
+/// in its output stream
+fn handle_escape_sequence(
+    app_handle: &AppHandle,
+    terminal_id: &str,
+    output_buffer: &TerminalOutputBuffer,
+    offset: u64,
+    sequence: EscapeSequence,
+) {
+    match sequence {
+        EscapeSequence::Osc(payload) => {
+            if let Some(working_dir) = parse_osc7(&payload) {
+                update_working_dir(app_handle, terminal_id, working_dir);
+            } else if let Some(mark) = parse_osc133(&payload) {
+                record_shell_mark(app_handle, terminal_id, output_buffer, mark, offset);
+            }
+        }
+        EscapeSequence::PrivateMode { .. } => {}
+    }
+}
+
+/// Track command boundaries reported by shell integration (OSC 133)
+fn record_shell_mark(
+    app_handle: &AppHandle,
+    terminal_id: &str,
+    output_buffer: &TerminalOutputBuffer,
+    mark: ShellMark,
+    offset: u64,
+) {
+    let Some(state) = app_handle.try_state::<AppState>() else {
+        return;
+    };
+
+    let command = state
+        .terminals
+        .write()
+        .get_mut(terminal_id)
+        .and_then(|terminal| terminal.record_shell_mark(mark.clone(), offset, output_buffer).cloned());
+    let Some(command) = command else {
+        return;
+    };
+
+    // Persisted with the next periodic flush
+    state.mark_terminal_dirty(terminal_id);
+
+    if let ShellMark::CommandFinished(exit_code) = mark {
+        let command = command.command.clone();
+        record_terminal_event(&state, terminal_id, TerminalEventKind::CommandFinished { command, exit_code });
+    }
+
+    // Commands starting and finishing are what the UI cares about, not every prompt
+    if matches!(mark, ShellMark::OutputStart | ShellMark::CommandFinished(_)) {
+        let _ = app_handle.emit(
+            "terminal-command",
+            TerminalCommandEvent {
+                terminal_id: terminal_id.to_string(),
+                command,
+            },
+        );
+    }
+}
+
+/// Track a working directory reported by the shell via OSC 7. The launch directory is
+/// left alone, so a `cd` doesn't move where the terminal is relaunched.
+fn update_working_dir(app_handle: &AppHandle, terminal_id: &str, working_dir: PathBuf) {
+    // Remote shells (e.g. over ssh) report paths that don't exist locally
+    if !working_dir.is_dir() {
+        return;
+    }
+
+    let Some(state) = app_handle.try_state::<AppState>() else {
+        return;
+    };
+
+    let changed = {
+        let mut terminals = state.terminals.write();
+        match terminals.get_mut(terminal_id) {
+            Some(terminal) if terminal.current_dir.as_ref() != Some(&working_dir) => {
+                terminal.current_dir = Some(working_dir.clone());
+                true
+            }
+            _ => false,
+        }
+    };
+
+    if changed {
+        // Saved by the periodic flush rather than from the output path
+        state.mark_terminal_dirty(terminal_id);
+        let _ = app_handle.emit(
+            "terminal-cwd-changed",
+            TerminalCwdChanged {
+                terminal_id: terminal_id.to_string(),
+                working_dir: working_dir.to_string_lossy().to_string(),
+            },
+        );
+    }
 }
 
 pub fn write_to_pty(pty_handle: &PtyHandle, data: &[u8]) -> Result<()> {
-    use std::io::Write;
+    send_pty_command(pty_handle, |reply| PtyCommand::Write(data.to_vec(), reply))
+}
 
-    let mut writer = pty_handle.writer.lock();
-    writer
-        .write_all(data)
-        .map_err(|e| Error::TerminalError(e.to_string()))?;
-    writer
-        .flush()
-        .map_err(|e| Error::TerminalError(e.to_string()))?;
+/// Paste text into a PTY the way a terminal emulator would: newlines become carriage
+/// returns, and if the program enabled bracketed paste the text is wrapped in paste
+/// markers so it arrives as one paste rather than being run line by line.
+pub fn paste_to_pty(pty_handle: &PtyHandle, text: &str) -> Result<()> {
+    let text = text.replace("\r\n", "\r").replace('\n', "\r");
 
-    Ok(())
+    let data = if pty_handle.bracketed_paste.load(Ordering::Relaxed) {
+        // An embedded end marker would let the rest of the paste escape the brackets
+        format!("\x1b[200~{}\x1b[201~", text.replace("\x1b[201~", ""))
+    } else {
+        text
+    };
+
+    write_to_pty(pty_handle, data.as_bytes())
 }
 
 pub fn resize_pty(pty_handle: &PtyHandle, cols: u16, rows: u16) -> Result<()> {
-    let master = pty_handle.master.lock();
-    master
-        .resize(PtySize {
-            rows,
-            cols,
-            pixel_width: 0,
-            pixel_height: 0,
-        })
-        .map_err(|e| Error::TerminalError(e.to_string()))?;
-    Ok(())
+    let size = PtySize {
+        rows,
+        cols,
+        pixel_width: 0,
+        pixel_height: 0,
+    };
+    send_pty_command(pty_handle, |reply| PtyCommand::Resize(size, reply))
 }
@@ -0,0 +1,231 @@
+use portable_pty::{MasterPty, PtySize};
+use std::io::{Read, Write};
+use std::sync::mpsc;
+use std::time::Duration;
+use tokio::sync::{oneshot, watch};
+
+use crate::error::{Error, Result};
+use super::pty::OutputPump;
+use super::types::PtyHandle;
+
+#[cfg(unix)]
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+
+/// Size of each read from a PTY
+const READ_BUFFER_SIZE: usize = 4096;
+
+/// How long a write waits for room in the PTY before checking again
+const WRITE_RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How often a paused blocking reader checks whether it may continue
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A request to the I/O thread that owns a PTY's master side
+pub enum PtyCommand {
+    Write(Vec<u8>, mpsc::Sender<std::result::Result<(), String>>),
+    Resize(PtySize, mpsc::Sender<std::result::Result<(), String>>),
+}
+
+/// Start the thread that owns the master side of a PTY and applies writes and
+/// resizes one at a time, so they can never race each other.
+/// The thread exits (closing the master) once the PtyHandle is dropped.
+pub fn spawn_pty_io(
+    master: Box<dyn MasterPty + Send>,
+    mut writer: Box<dyn Write + Send>,
+) -> mpsc::Sender<PtyCommand> {
+    let (commands, receiver) = mpsc::channel::<PtyCommand>();
+
+    std::thread::spawn(move || {
+        for command in receiver {
+            match command {
+                PtyCommand::Write(data, reply) => {
+                    let result = write_all(writer.as_mut(), &data);
+                    let _ = reply.send(result.map_err(|e| e.to_string()));
+                }
+                PtyCommand::Resize(size, reply) => {
+                    let _ = reply.send(master.resize(size).map_err(|e| e.to_string()));
+                }
+            }
+        }
+    });
+
+    commands
+}
+
+/// Send a command to a PTY's I/O thread and wait for its result
+pub fn send_pty_command(
+    pty_handle: &PtyHandle,
+    command: impl FnOnce(mpsc::Sender<std::result::Result<(), String>>) -> PtyCommand,
+) -> Result<()> {
+    let (reply, result) = mpsc::channel();
+    pty_handle
+        .commands
+        .send(command(reply))
+        .map_err(|_| Error::TerminalError("PTY is closed".into()))?;
+
+    result
+        .recv()
+        .map_err(|_| Error::TerminalError("PTY is closed".into()))?
+        .map_err(Error::TerminalError)
+}
+
+/// Write all of `data`, waiting out a full PTY input queue
+/// (the master is non-blocking when its output is read asynchronously)
+fn write_all(writer: &mut (dyn Write + Send), mut data: &[u8]) -> std::io::Result<()> {
+    while !data.is_empty() {
+        match writer.write(data) {
+            Ok(0) => return Err(std::io::ErrorKind::WriteZero.into()),
+            Ok(n) => data = &data[n..],
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                std::thread::sleep(WRITE_RETRY_INTERVAL);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    writer.flush()
+}
+
+/// Start reading a PTY's output into `pump`. On unix the master is polled from the
+/// async runtime, so idle PTYs cost no thread; elsewhere a blocking thread is used.
+/// Reading stops when the returned sender is dropped or the PTY closes.
+/// While `paused` is true the PTY is left undrained, so the child blocks once the
+/// kernel buffer fills rather than producing output nobody is reading.
+pub fn spawn_output_reader(
+    master: &(dyn MasterPty + Send),
+    pump: OutputPump,
+    paused: watch::Receiver<bool>,
+) -> Result<oneshot::Sender<()>> {
+    let (stop_tx, stop_rx) = oneshot::channel();
+
+    #[cfg(unix)]
+    if let Some(fd) = master.as_raw_fd() {
+        let fd = duplicate_nonblocking(fd).map_err(|e| Error::TerminalError(e.to_string()))?;
+        tauri::async_runtime::spawn(read_async(fd, pump, paused, stop_rx));
+        return Ok(stop_tx);
+    }
+
+    let reader = master
+        .try_clone_reader()
+        .map_err(|e| Error::TerminalError(e.to_string()))?;
+    std::thread::spawn(move || read_blocking(reader, pump, paused));
+    Ok(stop_tx)
+}
+
+/// Blocking read loop, used where the master can't be polled (it can't be stopped early)
+fn read_blocking(mut reader: Box<dyn Read + Send>, mut pump: OutputPump, paused: watch::Receiver<bool>) {
+    let mut buffer = [0u8; READ_BUFFER_SIZE];
+    loop {
+        // Stops waiting if the PtyHandle (and with it the sender) is dropped while paused
+        while *paused.borrow() && paused.has_changed().is_ok() {
+            std::thread::sleep(PAUSE_POLL_INTERVAL);
+        }
+
+        match reader.read(&mut buffer) {
+            Ok(0) => break, // EOF
+            Ok(n) => pump.feed(&buffer[..n]),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(_) => break,
+        }
+    }
+    pump.finish();
+}
+
+#[cfg(unix)]
+async fn read_async(
+    fd: OwnedFd,
+    mut pump: OutputPump,
+    mut paused: watch::Receiver<bool>,
+    mut stop: oneshot::Receiver<()>,
+) {
+    use tokio::io::unix::AsyncFd;
+
+    let async_fd = match AsyncFd::try_new(fd) {
+        Ok(async_fd) => async_fd,
+        Err(e) => {
+            let (fd, e) = e.into_parts();
+            // Not pollable here; read it on a blocking thread instead
+            eprintln!("[Ada] Falling back to a blocking PTY reader: {}", e);
+            if let Err(e) = set_nonblocking(fd.as_raw_fd(), false) {
+                eprintln!("[Ada] Failed to make PTY blocking again: {}", e);
+            }
+            let reader: Box<dyn Read + Send> = Box::new(std::fs::File::from(fd));
+            std::thread::spawn(move || read_blocking(reader, pump, paused));
+            return;
+        }
+    };
+
+    let mut buffer = [0u8; READ_BUFFER_SIZE];
+    loop {
+        if *paused.borrow() {
+            tokio::select! {
+                unpaused = paused.wait_for(|paused| !*paused) => {
+                    if unpaused.is_err() {
+                        break;
+                    }
+                }
+                _ = &mut stop => break,
+            }
+        }
+
+        let mut guard = tokio::select! {
+            guard = async_fd.readable() => match guard {
+                Ok(guard) => guard,
+                Err(_) => break,
+            },
+            // Resolves when the PtyHandle (and with it the sender) is dropped
+            _ = &mut stop => break,
+        };
+
+        match guard.try_io(|fd| read_fd(fd.get_ref().as_raw_fd(), &mut buffer)) {
+            Ok(Ok(0)) => break, // EOF
+            Ok(Ok(n)) => pump.feed(&buffer[..n]),
+            Ok(Err(e)) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            // EIO once the child side has closed
+            Ok(Err(_)) => break,
+            Err(_would_block) => continue,
+        }
+    }
+
+    pump.finish();
+}
+
+#[cfg(unix)]
+fn read_fd(fd: RawFd, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let n = unsafe { libc::read(fd, buffer.as_mut_ptr().cast(), buffer.len()) };
+    if n < 0 {
+        Err(std::io::Error::last_os_error())
+    } else {
+        Ok(n as usize)
+    }
+}
+
+/// Duplicate the master fd for reading and switch it to non-blocking mode.
+/// The flag is shared with the writer's fd, which `write_all` accounts for.
+#[cfg(unix)]
+fn duplicate_nonblocking(fd: RawFd) -> std::io::Result<OwnedFd> {
+    let duplicate = unsafe { libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, 0) };
+    if duplicate < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let duplicate = unsafe { OwnedFd::from_raw_fd(duplicate) };
+    set_nonblocking(duplicate.as_raw_fd(), true)?;
+    Ok(duplicate)
+}
+
+#[cfg(unix)]
+fn set_nonblocking(fd: RawFd, nonblocking: bool) -> std::io::Result<()> {
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+    if flags < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let flags = if nonblocking {
+        flags | libc::O_NONBLOCK
+    } else {
+        flags & !libc::O_NONBLOCK
+    };
+    if unsafe { libc::fcntl(fd, libc::F_SETFL, flags) } < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
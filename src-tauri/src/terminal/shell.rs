@@ -0,0 +1,260 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+
+/// How an agent command is launched inside its PTY
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(tag = "kind", content = "path", rename_all = "snake_case")]
+pub enum ShellSetting {
+    /// Exec the agent command directly, without a shell
+    #[default]
+    Direct,
+    /// Run the agent command through the user's login shell
+    Login,
+    /// Run the agent command through a specific shell executable
+    Custom(PathBuf),
+}
+
+/// The command-line dialect a shell speaks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellKind {
+    /// sh, bash, zsh, fish and friends
+    Posix,
+    /// Windows PowerShell or PowerShell 7 (pwsh)
+    PowerShell,
+    /// cmd.exe
+    Cmd,
+}
+
+impl ShellKind {
+    /// Tell the dialect from the shell executable's name
+    pub fn from_path(path: &Path) -> Self {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        match name.as_str() {
+            "pwsh" | "powershell" => Self::PowerShell,
+            "cmd" => Self::Cmd,
+            _ => Self::Posix,
+        }
+    }
+}
+
+/// A shell that has been detected on this machine
+#[derive(Debug, Clone)]
+pub struct ShellConfig {
+    pub path: PathBuf,
+}
+
+impl ShellConfig {
+    /// Detect the user's login shell: the first usable entry of `candidates`
+    pub fn detect() -> Option<Self> {
+        Self::candidates().into_iter().next().map(|path| Self { path })
+    }
+
+    /// Usable shells in order of preference: $SHELL, then zsh, bash and sh
+    #[cfg(not(windows))]
+    pub fn candidates() -> Vec<PathBuf> {
+        let from_env = std::env::var_os("SHELL").map(PathBuf::from);
+        let mut candidates: Vec<PathBuf> = Vec::new();
+
+        for path in from_env
+            .into_iter()
+            .chain(["/bin/zsh", "/bin/bash", "/bin/sh"].iter().map(PathBuf::from))
+        {
+            if let Ok(path) = validate_shell(&path) {
+                if !candidates.contains(&path) {
+                    candidates.push(path);
+                }
+            }
+        }
+        candidates
+    }
+
+    /// Usable shells on Windows: PowerShell 7, then Windows PowerShell, then %COMSPEC%
+    #[cfg(windows)]
+    pub fn candidates() -> Vec<PathBuf> {
+        let powershell = ["pwsh.exe", "powershell.exe"]
+            .iter()
+            .filter_map(|name| which::which(name).ok());
+        let comspec = std::env::var_os("COMSPEC")
+            .map(PathBuf::from)
+            .filter(|path| path.is_file())
+            .or_else(|| which::which("cmd.exe").ok());
+
+        let mut candidates: Vec<PathBuf> = Vec::new();
+        for path in powershell.chain(comspec) {
+            if !candidates.contains(&path) {
+                candidates.push(path);
+            }
+        }
+        candidates
+    }
+}
+
+/// The shell an agent will be launched through
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedShell {
+    /// None to exec the agent directly
+    pub path: Option<PathBuf>,
+    /// Set when the requested shell was unusable and a fallback was picked instead
+    pub warning: Option<String>,
+}
+
+/// Resolve which shell (if any) to launch an agent through.
+/// The project setting takes precedence over the client setting; with neither
+/// the command is exec'd directly.
+pub fn resolve_shell(
+    project_setting: Option<&ShellSetting>,
+    client_setting: Option<&ShellSetting>,
+) -> Result<ResolvedShell> {
+    let setting = project_setting.or(client_setting).cloned().unwrap_or_default();
+
+    match setting {
+        ShellSetting::Direct => Ok(ResolvedShell::default()),
+        ShellSetting::Login => ShellConfig::detect()
+            .map(|shell| ResolvedShell { path: Some(shell.path), warning: None })
+            .ok_or_else(|| Error::TerminalError("Could not detect a login shell".into())),
+        ShellSetting::Custom(path) => resolve_shell_path(&path),
+    }
+}
+
+/// Use `path` as the shell if it is usable, otherwise fall back to the login shell chain
+/// (override → $SHELL → zsh → bash → sh) with a warning explaining the substitution
+pub fn resolve_shell_path(path: &Path) -> Result<ResolvedShell> {
+    let error = match validate_shell(path) {
+        Ok(path) => return Ok(ResolvedShell { path: Some(path), warning: None }),
+        Err(e) => e,
+    };
+
+    let fallback = fallback_shells(path).into_iter().next().ok_or(error)?;
+    let warning = format!(
+        "Shell {} not found; using {} instead",
+        path.display(),
+        fallback.display()
+    );
+    eprintln!("[Ada] {}", warning);
+
+    Ok(ResolvedShell { path: Some(fallback), warning: Some(warning) })
+}
+
+/// Shells to try, in order, when `failed` can't be used
+pub fn fallback_shells(failed: &Path) -> Vec<PathBuf> {
+    ShellConfig::candidates()
+        .into_iter()
+        .filter(|path| path != failed)
+        .collect()
+}
+
+/// Verify a shell exists and can be executed before we try to spawn it,
+/// resolving bare names via PATH
+pub fn validate_shell(path: &Path) -> Result<PathBuf> {
+    if path.is_absolute() {
+        if is_executable(path) {
+            return Ok(path.to_path_buf());
+        }
+    } else if let Ok(resolved) = which::which(path) {
+        return Ok(resolved);
+    }
+
+    Err(Error::TerminalError(format!("Shell not found: {}", path.display())))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::metadata(path)
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Format a program and its arguments as a single command line in the given shell's dialect
+pub fn format_command_line(kind: ShellKind, program: &Path, args: &[String]) -> String {
+    let quote = match kind {
+        ShellKind::Posix => quote_arg,
+        ShellKind::PowerShell => quote_powershell_arg,
+        ShellKind::Cmd => quote_cmd_arg,
+    };
+
+    let line = std::iter::once(program.to_string_lossy().to_string())
+        .chain(args.iter().cloned())
+        .map(|arg| quote(&arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    match kind {
+        // A quoted program name is just a string to PowerShell without the call operator
+        ShellKind::PowerShell => format!("& {}", line),
+        ShellKind::Posix | ShellKind::Cmd => line,
+    }
+}
+
+/// Quote an argument for POSIX shells, leaving simple words untouched
+fn quote_arg(arg: &str) -> String {
+    let is_simple = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+
+    if is_simple {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// Quote an argument for PowerShell. Single-quoted strings are literal; `'` is escaped by doubling.
+/// `,` is quoted too, as PowerShell reads a bare `a,b` as an array.
+fn quote_powershell_arg(arg: &str) -> String {
+    let is_simple = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./\\:+=".contains(c));
+
+    if is_simple {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "''"))
+    }
+}
+
+/// Quote an argument for cmd.exe. Inside double quotes `&|<>^()` lose their meaning;
+/// embedded quotes are doubled, which the MSVC runtime reads back as a literal `"`.
+/// `%` is not escaped, so callers must keep the line out of a second expansion pass.
+fn quote_cmd_arg(arg: &str) -> String {
+    let is_simple = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./\\:+@".contains(c));
+
+    if is_simple {
+        arg.to_string()
+    } else {
+        format!("\"{}\"", arg.replace('"', "\"\""))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_powershell_args_that_need_it() {
+        assert_eq!(quote_powershell_arg("--model=opus"), "--model=opus");
+        assert_eq!(quote_powershell_arg("C:\\work\\ada"), "C:\\work\\ada");
+        assert_eq!(quote_powershell_arg(""), "''");
+        assert_eq!(quote_powershell_arg("a,b"), "'a,b'");
+        assert_eq!(quote_powershell_arg("fix the bug"), "'fix the bug'");
+        assert_eq!(quote_powershell_arg("it's $HOME"), "'it''s $HOME'");
+        assert_eq!(quote_powershell_arg("a;b|c"), "'a;b|c'");
+    }
+}
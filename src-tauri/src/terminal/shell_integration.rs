@@ -0,0 +1,161 @@
+use std::path::Path;
+
+use crate::clients::ClientConfig;
+
+/// zsh reads its startup files from $ZDOTDIR; ours load the user's from their real location
+const ZSHENV: &str = r#"# Ada shell integration: load the user's own .zshenv
+__ada_zdotdir="$ZDOTDIR"
+ZDOTDIR="${ADA_USER_ZDOTDIR:-$HOME}"
+[[ -f "$ZDOTDIR/.zshenv" ]] && source "$ZDOTDIR/.zshenv"
+# The user's .zshenv may move ZDOTDIR; their other startup files are read from there then
+ADA_USER_ZDOTDIR="${ZDOTDIR:-$HOME}"
+ZDOTDIR="$__ada_zdotdir"
+"#;
+
+const ZPROFILE: &str = r#"# Ada shell integration: load the user's own .zprofile
+ZDOTDIR="${ADA_USER_ZDOTDIR:-$HOME}"
+[[ -f "$ZDOTDIR/.zprofile" ]] && source "$ZDOTDIR/.zprofile"
+ZDOTDIR="$__ada_zdotdir"
+"#;
+
+const ZSHRC: &str = r#"# Ada shell integration: load the user's own .zshrc, then mark command boundaries (OSC 133)
+ZDOTDIR="${ADA_USER_ZDOTDIR:-$HOME}"
+[[ -f "$ZDOTDIR/.zshrc" ]] && source "$ZDOTDIR/.zshrc"
+# zsh reads any remaining startup files (.zlogin) from the user's directory
+unset __ada_zdotdir ADA_USER_ZDOTDIR
+
+__ada_running=
+__ada_precmd() {
+  local code=$?
+  [[ -n $__ada_running ]] && printf '\e]133;D;%s\a' "$code"
+  __ada_running=
+  printf '\e]133;A\a'
+}
+__ada_preexec() {
+  __ada_running=1
+  __ada_command_line "$1"
+  printf '\e]133;C\a'
+}
+# Report the command line about to run (OSC 133;E), escaping what would end or split it
+__ada_command_line() {
+  local line=$1
+  line=${line//\\/\\\\}
+  line=${line//;/\\x3b}
+  line=${line//$'\n'/\\x0a}
+  line=${line//$'\a'/\\x07}
+  line=${line//$'\e'/\\x1b}
+  printf '\e]133;E;%s\a' "$line"
+}
+# First, so $? is still the command's exit status
+precmd_functions=(__ada_precmd $precmd_functions)
+preexec_functions+=(__ada_preexec)
+PS1="$PS1%{"$'\e]133;B\a'"%}"
+"#;
+
+const BASHRC: &str = r#"# Ada shell integration: load the user's own .bashrc, then mark command boundaries (OSC 133)
+[ -f ~/.bashrc ] && . ~/.bashrc
+
+__ada_running=
+__ada_in_prompt=
+__ada_last_history=
+__ada_preexec() {
+  [ -n "$__ada_in_prompt" ] || [ -n "$__ada_running" ] && return
+  # The DEBUG trap also fires for PROMPT_COMMAND itself
+  [ "$BASH_COMMAND" = "__ada_precmd" ] && return
+  __ada_running=1
+  # The whole line from history, or just the first simple command when the line wasn't
+  # saved there (e.g. HISTCONTROL=ignorespace)
+  local line re='^ *[0-9]+\*? +(.*)$'
+  line=$(HISTTIMEFORMAT= builtin history 1)
+  if [ "$line" != "$__ada_last_history" ] && [[ $line =~ $re ]]; then
+    __ada_command_line "${BASH_REMATCH[1]}"
+  else
+    __ada_command_line "$BASH_COMMAND"
+  fi
+  printf '\e]133;C\a'
+}
+# Report the command line about to run (OSC 133;E), escaping what would end or split it
+__ada_command_line() {
+  local line=$1
+  line=${line//\\/\\\\}
+  line=${line//;/\\x3b}
+  line=${line//$'\n'/\\x0a}
+  line=${line//$'\a'/\\x07}
+  line=${line//$'\e'/\\x1b}
+  printf '\e]133;E;%s\a' "$line"
+}
+__ada_precmd() {
+  local code=$?
+  __ada_in_prompt=1
+  [ -n "$__ada_running" ] && printf '\e]133;D;%s\a' "$code"
+  __ada_running=
+  __ada_last_history=$(HISTTIMEFORMAT= builtin history 1)
+  printf '\e]133;A\a'
+}
+__ada_prompt_done() {
+  __ada_in_prompt=
+}
+# Newline-separated, since the user's PROMPT_COMMAND may already end in a semicolon
+PROMPT_COMMAND=$'__ada_precmd\n'"${PROMPT_COMMAND}"$'\n__ada_prompt_done'
+PS1="$PS1\[\e]133;B\a\]"
+# Run any DEBUG trap the user's .bashrc set (e.g. bash-preexec) after ours
+eval "__ada_user_trap=($(trap -p DEBUG))"
+__ada_user_trap=${__ada_user_trap[2]}
+trap '__ada_preexec; eval "$__ada_user_trap"' DEBUG
+"#;
+
+/// Load Ada's shell integration into a client that runs an interactive bash or zsh,
+/// so the terminal gets OSC 133 command boundaries. Other clients, and shells started
+/// with arguments of their own, are left alone.
+pub fn apply_shell_integration(client: &mut ClientConfig, dir: &Path) {
+    if !client.args.is_empty() {
+        return;
+    }
+
+    let program = Path::new(&client.command)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let installed = match program.as_str() {
+        "zsh" => install(
+            &dir.join("zsh"),
+            &[(".zshenv", ZSHENV), (".zprofile", ZPROFILE), (".zshrc", ZSHRC)],
+        ),
+        "bash" => install(dir, &[("ada.bashrc", BASHRC)]),
+        _ => return,
+    };
+    if let Err(e) = installed {
+        eprintln!("[Ada] Failed to install shell integration: {}", e);
+        return;
+    }
+
+    if program == "zsh" {
+        let user_zdotdir = client
+            .env
+            .get("ZDOTDIR")
+            .cloned()
+            .or_else(|| std::env::var("ZDOTDIR").ok());
+        if let Some(user_zdotdir) = user_zdotdir {
+            client.env.insert("ADA_USER_ZDOTDIR".into(), user_zdotdir);
+        }
+        client.env.insert("ZDOTDIR".into(), dir.join("zsh").to_string_lossy().to_string());
+    } else {
+        client.args = vec![
+            "--rcfile".into(),
+            dir.join("ada.bashrc").to_string_lossy().to_string(),
+        ];
+    }
+}
+
+/// Write the integration scripts, skipping any that are already up to date
+fn install(dir: &Path, files: &[(&str, &str)]) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    for (name, content) in files {
+        let path = dir.join(name);
+        if std::fs::read_to_string(&path).ok().as_deref() != Some(*content) {
+            std::fs::write(&path, content)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+use crate::clients::{ClientConfig, ClientType};
+use crate::error::{Error, Result};
+use super::escapes::strip_escapes;
+use super::pty::agent_path;
+
+/// How much of the end of a transcript is sent to the summarizer
+const MAX_TRANSCRIPT_CHARS: usize = 32 * 1024;
+
+/// How long the summarizer may take before it is killed
+const SUMMARY_TIMEOUT: Duration = Duration::from_secs(120);
+
+const SUMMARY_PROMPT: &str = "Below is the end of a terminal session in which a coding agent worked \
+on a task. Summarize it in a few sentences: what was asked, what the agent did, and how it ended \
+(finished, failed, or waiting for input). Mention any files changed and anything that needs a \
+human's attention. Reply with the summary only.";
+
+/// Plain text of the most recent output in `history`, for feeding to a summarizer
+pub fn recent_transcript(history: &[String]) -> String {
+    let text = strip_escapes(&history.concat());
+
+    match text.char_indices().rev().nth(MAX_TRANSCRIPT_CHARS.saturating_sub(1)) {
+        Some((start, _)) => text[start..].to_string(),
+        None => text,
+    }
+}
+
+/// Arguments that run a client non-interactively with its prompt read from stdin.
+/// Custom clients are expected to be configured that way already.
+pub fn summarizer_args(client: &ClientConfig) -> Vec<String> {
+    let args: &[&str] = match client.client_type {
+        ClientType::ClaudeCode => &["-p"],
+        ClientType::Codex => &["exec", "-"],
+        ClientType::OpenCode => &["run"],
+        // Aider only takes a message from a file; ask mode keeps it from editing anything
+        ClientType::Aider => &["--message-file", "/dev/stdin", "--chat-mode", "ask", "--yes-always", "--no-pretty"],
+        ClientType::Amp => &["--execute"],
+        ClientType::Goose => &["run", "--no-session", "--instructions", "-"],
+        // Runs non-interactively whenever stdin isn't a terminal
+        ClientType::QwenCode => &[],
+        // Copilot only takes its prompt as an argument, so like custom clients it needs
+        // arguments configured for reading stdin
+        ClientType::Copilot | ClientType::Custom => return client.args.clone(),
+    };
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+/// Run `client` with `args` as a one-shot summarizer over `transcript` and return its answer
+pub async fn summarize_transcript(
+    client: &ClientConfig,
+    args: &[String],
+    working_dir: &Path,
+    transcript: &str,
+) -> Result<String> {
+    let mut cmd = tokio::process::Command::new(client.get_command_path());
+    cmd.args(args)
+        .envs(&client.env)
+        .current_dir(working_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(home) = dirs::home_dir().filter(|_| cfg!(not(windows))) {
+        cmd.env("PATH", agent_path(&home));
+    }
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| Error::TerminalError(format!("Failed to start summarizer: {}", e)))?;
+
+    let input = format!("{}\n\n---\n{}", SUMMARY_PROMPT, transcript);
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| Error::TerminalError("Summarizer has no stdin".into()))?;
+
+    // Feed stdin while collecting output, so neither side can fill its pipe and stall
+    let write_input = async move {
+        let result = stdin.write_all(input.as_bytes()).await;
+        drop(stdin);
+        result
+    };
+    let (written, output) = tokio::time::timeout(
+        SUMMARY_TIMEOUT,
+        async { tokio::join!(write_input, child.wait_with_output()) },
+    )
+    .await
+    .map_err(|_| Error::TerminalError("Summarizer timed out".into()))?;
+
+    let output = output?;
+    if !output.status.success() {
+        return Err(Error::TerminalError(format!(
+            "Summarizer failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    // A summarizer that exits without reading all of its input is fine if it answered
+    if let Err(e) = written {
+        eprintln!("[Ada] Summarizer did not read its whole input: {}", e);
+    }
+
+    let summary = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if summary.is_empty() {
+        return Err(Error::TerminalError("Summarizer produced no output".into()));
+    }
+    Ok(summary)
+}
@@ -1,13 +1,36 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::{mpsc, Arc};
 use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Write;
 use chrono::{DateTime, Utc};
 use parking_lot::Mutex;
-use portable_pty::MasterPty;
+use portable_pty::Child;
+use tokio::sync::{oneshot, watch};
 
-/// Maximum number of output chunks to store per terminal
-const MAX_OUTPUT_HISTORY: usize = 1000;
+use crate::git::WorktreeChanges;
+use super::agent_status::AgentStatus;
+use super::escapes::{strip_escapes, ShellMark};
+use super::checkpoint::CheckpointConfig;
+use super::keep_alive::KeepAliveConfig;
+use super::pty_io::PtyCommand;
+
+/// Default amount of output kept in memory per terminal
+const DEFAULT_SCROLLBACK_BYTES: usize = 5 * 1024 * 1024;
+
+/// A transcript file is rotated once it grows past this
+const TRANSCRIPT_ROTATE_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Rotated transcript files kept per terminal; the oldest is dropped beyond this
+const MAX_ROTATED_TRANSCRIPTS: usize = 4;
+
+/// Maximum number of agent invocations kept in a terminal's audit log
+const MAX_INVOCATION_LOG: usize = 100;
+
+/// Maximum number of shell commands remembered per terminal
+const MAX_SHELL_COMMANDS: usize = 1000;
 
 /// Terminal mode determines how the terminal operates
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
@@ -30,7 +53,11 @@ pub struct Terminal {
     pub project_id: String,
     pub name: String,
     pub client_id: String,
+    /// Where the agent is launched (and relaunched on restart or resume)
     pub working_dir: PathBuf,
+    /// Directory the shell last reported via OSC 7, if it has moved since launching
+    #[serde(default)]
+    pub current_dir: Option<PathBuf>,
     pub branch: Option<String>,
     pub worktree_path: Option<PathBuf>,
     pub status: TerminalStatus,
@@ -44,45 +71,524 @@ pub struct Terminal {
     /// For Folder mode: the subfolder path relative to project
     #[serde(default)]
     pub folder_path: Option<PathBuf>,
+    /// Additional project root the terminal runs in (None = the project itself)
+    #[serde(default)]
+    pub root: Option<String>,
+    /// Shell the agent was launched through (None = exec'd directly), reused on restart
+    #[serde(default)]
+    pub shell: Option<PathBuf>,
+    /// Whether this terminal's process was cut short by Ada closing; cleared once its
+    /// scrollback has been loaded and marked as restored
+    #[serde(default)]
+    pub restored: bool,
+    /// Number of times the process has been respawned into this terminal
+    #[serde(default)]
+    pub respawn_count: u32,
+    /// Audit log of agent launches (only recorded when invocation logging is enabled)
+    #[serde(default)]
+    pub invocations: Vec<AgentInvocation>,
+    /// When the process last produced output
+    #[serde(default)]
+    pub last_activity: Option<DateTime<Utc>>,
+    /// Run group this terminal was launched as part of, if any
+    #[serde(default)]
+    pub group_id: Option<String>,
+    /// When the process last stopped (None while running)
+    #[serde(default)]
+    pub ended_at: Option<DateTime<Utc>>,
+    /// When the current (or last) process was launched
+    #[serde(default)]
+    pub started_at: Option<DateTime<Utc>>,
+    /// Seconds the agent ran across all of its previous launches (see `run_time_secs`)
+    #[serde(default)]
+    pub run_secs: u64,
+    /// Scrollback limit for this terminal (None = the app-wide setting)
+    #[serde(default)]
+    pub scrollback: Option<ScrollbackLimit>,
+    /// Resume this terminal when Ada starts, if `resume_terminals_on_start` is enabled
+    #[serde(default = "default_auto_resume")]
+    pub auto_resume: bool,
+    /// Keep-alive behavior for agents running against remote hosts
+    #[serde(default)]
+    pub keep_alive: Option<KeepAliveConfig>,
+    /// TERM, locale and color overrides on top of the client's
+    #[serde(default)]
+    pub terminal_env: Option<TerminalEnv>,
+    /// Model the agent runs (None = the project's override, or the agent's default)
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Launch profile of the client the agent runs with (see `ClientConfig::profiles`)
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Automatic checkpoints of the agent's work (None = off)
+    #[serde(default)]
+    pub checkpoints: Option<CheckpointConfig>,
+    /// Latest agent-written summary of the session (see `summarize_terminal`)
+    #[serde(default)]
+    pub summary: Option<TerminalSummary>,
+    /// Commands run in the terminal's shell, as delimited by shell integration marks
+    #[serde(default)]
+    pub commands: Vec<ShellCommand>,
+    /// PID of the running agent process (not persisted; gone once the process stops)
+    #[serde(skip)]
+    pub pid: Option<u32>,
+    /// What the running agent is doing, for agents whose output shows it (see `agent_status`)
+    #[serde(skip)]
+    pub agent_status: Option<AgentStatus>,
+}
+
+fn default_auto_resume() -> bool {
+    true
+}
+
+impl Terminal {
+    /// Checkout the terminal's agent works in, where its checkpoints are taken
+    pub fn checkpoint_dir(&self) -> &Path {
+        self.worktree_path.as_deref().unwrap_or(&self.working_dir)
+    }
+
+    /// Mark a newly launched process as starting
+    pub fn mark_started(&mut self, started_at: DateTime<Utc>) {
+        self.status = TerminalStatus::Starting;
+        self.current_dir = None;
+        self.agent_status = None;
+        self.started_at = Some(started_at);
+        self.ended_at = None;
+    }
+
+    /// Mark the process as stopped, keeping the original end time if it was already stopped
+    pub fn mark_stopped(&mut self, ended_at: DateTime<Utc>) {
+        if self.ended_at.is_none() {
+            self.run_secs += self.current_run_secs(ended_at);
+        }
+        self.status = TerminalStatus::Stopped;
+        self.ended_at.get_or_insert(ended_at);
+        self.pid = None;
+        self.agent_status = None;
+    }
+
+    /// Total seconds the agent has run, including the current launch up to `now`
+    pub fn run_time_secs(&self, now: DateTime<Utc>) -> u64 {
+        match self.ended_at {
+            Some(_) => self.run_secs,
+            None => self.run_secs + self.current_run_secs(now),
+        }
+    }
+
+    /// Terminals saved before launches were timed count from their creation
+    fn current_run_secs(&self, until: DateTime<Utc>) -> u64 {
+        let started_at = self.started_at.unwrap_or(self.created_at);
+        (until - started_at).num_seconds().max(0) as u64
+    }
+
+    /// Apply a shell integration mark found at `offset` in the output stream.
+    /// Returns the command it started or updated.
+    pub fn record_shell_mark(
+        &mut self,
+        mark: ShellMark,
+        offset: u64,
+        output: &TerminalOutputBuffer,
+    ) -> Option<&ShellCommand> {
+        let now = Utc::now();
+
+        if mark == ShellMark::PromptStart {
+            // A prompt redrawn without anything being run replaces the unused entry
+            if self.commands.last().is_some_and(|c| c.output_offset.is_none()) {
+                self.commands.pop();
+            }
+            if self.commands.len() >= MAX_SHELL_COMMANDS {
+                self.commands.remove(0);
+            }
+            self.commands.push(ShellCommand {
+                prompt_offset: offset,
+                input_offset: None,
+                output_offset: None,
+                end_offset: None,
+                command: None,
+                started_at: None,
+                finished_at: None,
+                exit_code: None,
+            });
+            return self.commands.last();
+        }
+
+        let command = self.commands.last_mut()?;
+        match mark {
+            ShellMark::PromptStart => {}
+            ShellMark::InputStart => command.input_offset = Some(offset),
+            ShellMark::CommandLine(line) => {
+                command.command = Some(line.trim().to_string()).filter(|line| !line.is_empty());
+                // Not a change anyone needs to hear about until the command starts
+                return None;
+            }
+            ShellMark::OutputStart => {
+                command.output_offset = Some(offset);
+                command.started_at = Some(now);
+                // Integrations that don't report the command line leave the echoed input,
+                // which line editing can garble
+                if command.command.is_none() {
+                    command.command = command
+                        .input_offset
+                        .and_then(|start| output.read_range(start, offset))
+                        .map(|line| strip_escapes(&line).trim().to_string())
+                        .filter(|line| !line.is_empty());
+                }
+            }
+            ShellMark::CommandFinished(exit_code) => {
+                // Shells report D for the empty line before the first prompt too
+                if command.output_offset.is_none() || command.end_offset.is_some() {
+                    return None;
+                }
+                command.end_offset = Some(offset);
+                command.finished_at = Some(now);
+                command.exit_code = exit_code;
+            }
+        }
+        Some(command)
+    }
+
+    pub fn record_invocation(&mut self, invocation: Option<AgentInvocation>) {
+        if let Some(invocation) = invocation {
+            if self.invocations.len() >= MAX_INVOCATION_LOG {
+                self.invocations.remove(0);
+            }
+            self.invocations.push(invocation);
+        }
+    }
+}
+
+/// A command run in a terminal's shell. Offsets are byte positions in the terminal's
+/// output stream; subtract the scrollback start to index into the history.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShellCommand {
+    /// Where the prompt for this command starts
+    pub prompt_offset: u64,
+    /// Where the typed command line starts (after the prompt)
+    pub input_offset: Option<u64>,
+    /// Where the command's output starts, once it has been run
+    pub output_offset: Option<u64>,
+    /// Where the command's output ends
+    pub end_offset: Option<u64>,
+    /// The command line as the shell reported it (OSC 133 `E`), or else as it echoed it
+    #[serde(default)]
+    pub command: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub exit_code: Option<i32>,
+}
+
+/// Emitted when a shell command in a terminal starts or finishes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalCommandEvent {
+    pub terminal_id: String,
+    pub command: ShellCommand,
+}
+
+/// A command that ran in a terminal's shell, for reviewing what an agent executed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandHistoryEntry {
+    /// None if the command line had already left the scrollback when it ran
+    pub command: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub exit_code: Option<i32>,
 }
 
-/// Stored terminal data for persistence
+impl From<&ShellCommand> for CommandHistoryEntry {
+    fn from(command: &ShellCommand) -> Self {
+        Self {
+            command: command.command.clone(),
+            started_at: command.started_at,
+            finished_at: command.finished_at,
+            exit_code: command.exit_code,
+        }
+    }
+}
+
+/// A terminal's shell commands with the offset its retained scrollback starts at
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalCommands {
+    pub scrollback_start: u64,
+    pub commands: Vec<ShellCommand>,
+}
+
+/// A single agent launch, as recorded for auditing
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TerminalData {
+pub struct AgentInvocation {
+    pub client_id: String,
+    /// Full argv of the agent command (program path first)
+    pub argv: Vec<String>,
+    /// Shell the command was run through, if any
+    pub shell: Option<String>,
+    pub working_dir: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Terminal type, locale and color environment for an agent.
+/// Set on a client and/or a terminal; the terminal's fields win, unset fields keep Ada's defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TerminalEnv {
+    /// `TERM` (default `xterm-256color`)
+    #[serde(default)]
+    pub term: Option<String>,
+    /// `LANG` (default: inherited, or `en_US.UTF-8` when Ada has no locale)
+    #[serde(default)]
+    pub lang: Option<String>,
+    /// `COLORTERM`, e.g. `truecolor`
+    #[serde(default)]
+    pub colorterm: Option<String>,
+    /// Some(true) sets `FORCE_COLOR`/`CLICOLOR_FORCE`, Some(false) sets `NO_COLOR`
+    #[serde(default)]
+    pub force_color: Option<bool>,
+}
+
+impl TerminalEnv {
+    /// Layer `overrides` on top of these settings
+    pub fn merged(&self, overrides: Option<&TerminalEnv>) -> TerminalEnv {
+        let Some(overrides) = overrides else {
+            return self.clone();
+        };
+        TerminalEnv {
+            term: overrides.term.clone().or_else(|| self.term.clone()),
+            lang: overrides.lang.clone().or_else(|| self.lang.clone()),
+            colorterm: overrides.colorterm.clone().or_else(|| self.colorterm.clone()),
+            force_color: overrides.force_color.or(self.force_color),
+        }
+    }
+
+    /// Environment variables these settings translate to
+    pub fn to_env(&self) -> Vec<(String, String)> {
+        let mut env = Vec::new();
+        if let Some(term) = &self.term {
+            env.push(("TERM".to_string(), term.clone()));
+        }
+        if let Some(lang) = &self.lang {
+            env.push(("LANG".to_string(), lang.clone()));
+        }
+        if let Some(colorterm) = &self.colorterm {
+            env.push(("COLORTERM".to_string(), colorterm.clone()));
+        }
+        match self.force_color {
+            Some(true) => {
+                env.push(("FORCE_COLOR".to_string(), "1".to_string()));
+                env.push(("CLICOLOR_FORCE".to_string(), "1".to_string()));
+            }
+            Some(false) => env.push(("NO_COLOR".to_string(), "1".to_string())),
+            None => {}
+        }
+        env
+    }
+}
+
+/// A summary of a terminal's session, written by the configured summary agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalSummary {
+    pub text: String,
+    /// Client that wrote the summary
+    pub client_id: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Stored terminal metadata, kept apart from the output history so startup can load
+/// every terminal without reading their scrollback
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalMeta {
     pub terminal: Terminal,
+}
+
+/// Stored output history of a terminal, read when its scrollback is first needed.
+/// Terminal files from before the history had a file of its own parse as this too.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalHistory {
     #[serde(default)]
     pub output_history: Vec<String>,
+    /// Output-stream offset of the start of `output_history`
+    #[serde(default)]
+    pub output_start: u64,
+}
+
+/// Format a dimmed, Ada-authored line for injection into a terminal's scrollback
+pub fn marker_line(message: &str) -> String {
+    format!("\r\n\x1b[2m[Ada] {}\x1b[0m\r\n", message)
+}
+
+/// Appends a terminal's evicted output to its transcript file (`<id>.log`), rotating it
+/// to `<id>.log.1` (and older ones up to `.MAX_ROTATED_TRANSCRIPTS`) once it gets too big
+pub struct TranscriptWriter {
+    path: PathBuf,
+    file: File,
+    bytes: u64,
+}
+
+impl TranscriptWriter {
+    pub fn open(path: PathBuf) -> std::io::Result<Self> {
+        let file = std::fs::OpenOptions::new().create(true).append(true).open(&path)?;
+        let bytes = file.metadata()?.len();
+        Ok(Self { path, file, bytes })
+    }
+
+    fn write(&mut self, data: &str) -> std::io::Result<()> {
+        if self.bytes > 0 && self.bytes + data.len() as u64 > TRANSCRIPT_ROTATE_BYTES {
+            self.rotate()?;
+        }
+        self.file.write_all(data.as_bytes())?;
+        self.bytes += data.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        for n in (1..MAX_ROTATED_TRANSCRIPTS).rev() {
+            let from = rotated_transcript(&self.path, n);
+            if from.exists() {
+                std::fs::rename(from, rotated_transcript(&self.path, n + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, rotated_transcript(&self.path, 1))?;
+        *self = Self::open(self.path.clone())?;
+        Ok(())
+    }
+
+    /// The transcript file at `path` and those rotated out of it, oldest first
+    pub fn files(path: &Path) -> Vec<PathBuf> {
+        (1..=MAX_ROTATED_TRANSCRIPTS)
+            .rev()
+            .map(|n| rotated_transcript(path, n))
+            .chain(std::iter::once(path.to_path_buf()))
+            .filter(|file| file.exists())
+            .collect()
+    }
+}
+
+fn rotated_transcript(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// How much output a terminal keeps
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScrollbackLimit {
+    /// Keep the most recent `max_bytes` of output; anything older is discarded
+    Bytes { max_bytes: usize },
+    /// Keep output past the memory limit: the most recent `max_bytes` in memory, with
+    /// older output rotated out into the terminal's transcript files (see `TranscriptWriter`)
+    Unlimited { max_bytes: usize },
+}
+
+impl Default for ScrollbackLimit {
+    fn default() -> Self {
+        ScrollbackLimit::Bytes { max_bytes: DEFAULT_SCROLLBACK_BYTES }
+    }
+}
+
+impl ScrollbackLimit {
+    pub fn max_bytes(&self) -> usize {
+        match self {
+            ScrollbackLimit::Bytes { max_bytes } | ScrollbackLimit::Unlimited { max_bytes } => *max_bytes,
+        }
+    }
 }
 
 /// In-memory terminal output buffer
 pub struct TerminalOutputBuffer {
-    pub buffer: Mutex<VecDeque<String>>,
+    buffer: Mutex<Scrollback>,
+}
+
+struct Scrollback {
+    chunks: VecDeque<String>,
+    bytes: usize,
+    max_bytes: usize,
+    /// Output-stream offset of the first retained byte (everything before was evicted)
+    start: u64,
+    /// Where evicted output goes in unlimited mode
+    transcript: Option<TranscriptWriter>,
 }
 
 impl TerminalOutputBuffer {
-    pub fn new() -> Self {
+    pub fn new(max_bytes: usize, transcript: Option<TranscriptWriter>) -> Self {
         Self {
-            buffer: Mutex::new(VecDeque::with_capacity(MAX_OUTPUT_HISTORY)),
+            buffer: Mutex::new(Scrollback {
+                chunks: VecDeque::new(),
+                bytes: 0,
+                max_bytes,
+                start: 0,
+                transcript,
+            }),
         }
     }
 
     pub fn append(&self, data: String) {
         let mut buffer = self.buffer.lock();
-        if buffer.len() >= MAX_OUTPUT_HISTORY {
-            buffer.pop_front();
+        buffer.bytes += data.len();
+        buffer.chunks.push_back(data);
+
+        // Always keep the newest chunk, even if it alone exceeds the limit
+        while buffer.bytes > buffer.max_bytes && buffer.chunks.len() > 1 {
+            let Some(evicted) = buffer.chunks.pop_front() else {
+                break;
+            };
+            buffer.bytes -= evicted.len();
+            buffer.start += evicted.len() as u64;
+            if let Some(transcript) = buffer.transcript.as_mut() {
+                if let Err(e) = transcript.write(&evicted) {
+                    eprintln!("[Ada] Failed to write terminal transcript: {}", e);
+                    buffer.transcript = None;
+                }
+            }
         }
-        buffer.push_back(data);
     }
 
     pub fn get_history(&self) -> Vec<String> {
-        self.buffer.lock().iter().cloned().collect()
+        self.buffer.lock().chunks.iter().cloned().collect()
     }
 
-    pub fn restore(&self, history: Vec<String>) {
-        let mut buffer = self.buffer.lock();
-        buffer.clear();
-        for item in history.into_iter().take(MAX_OUTPUT_HISTORY) {
-            buffer.push_back(item);
+    /// Output-stream offset of the oldest retained output
+    pub fn start(&self) -> u64 {
+        self.buffer.lock().start
+    }
+
+    /// Output-stream offset just past the newest output
+    pub fn end(&self) -> u64 {
+        let buffer = self.buffer.lock();
+        buffer.start + buffer.bytes as u64
+    }
+
+    /// Output between two stream offsets, or None if any of it is no longer retained
+    pub fn read_range(&self, start: u64, end: u64) -> Option<String> {
+        let buffer = self.buffer.lock();
+        if start < buffer.start || end < start || end > buffer.start + buffer.bytes as u64 {
+            return None;
+        }
+
+        let mut bytes = Vec::with_capacity((end - start) as usize);
+        let mut chunk_start = buffer.start;
+        for chunk in &buffer.chunks {
+            let chunk_end = chunk_start + chunk.len() as u64;
+            if chunk_end > start {
+                let from = start.saturating_sub(chunk_start) as usize;
+                let to = (end.min(chunk_end) - chunk_start) as usize;
+                bytes.extend_from_slice(&chunk.as_bytes()[from..to]);
+            }
+            if chunk_end >= end {
+                break;
+            }
+            chunk_start = chunk_end;
+        }
+
+        // Offsets can land inside a multi-byte character
+        Some(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    pub fn restore(&self, start: u64, history: Vec<String>) {
+        {
+            let mut buffer = self.buffer.lock();
+            buffer.chunks.clear();
+            buffer.bytes = 0;
+            buffer.start = start;
+        }
+        for item in history {
+            self.append(item);
         }
     }
 }
@@ -96,28 +602,64 @@ pub enum TerminalStatus {
     Error,
 }
 
-/// Handle to a running PTY - stored separately from Terminal for thread safety
+/// Handle to a running PTY - stored separately from Terminal for thread safety.
+/// The master side and its writer are owned by a per-PTY I/O thread, which
+/// serializes writes and resizes sent to it over `commands`.
 pub struct PtyHandle {
-    pub master: Arc<Mutex<Box<dyn MasterPty + Send>>>,
-    pub writer: Arc<Mutex<Box<dyn std::io::Write + Send>>>,
+    pub commands: mpsc::Sender<PtyCommand>,
+    pub child: Arc<Mutex<Box<dyn Child + Send + Sync>>>,
+    /// PID of the spawned child (also its process group ID, as it is a session leader)
+    pub pid: Option<u32>,
+    /// Becomes true once the process has produced its first output
+    pub ready: watch::Receiver<bool>,
+    /// Dropping this stops the output reader
+    pub _stop_reader: oneshot::Sender<()>,
+    /// While true the output reader stops draining the PTY
+    pub paused: watch::Sender<bool>,
+    /// Whether the program has enabled bracketed paste (`CSI ? 2004 h`)
+    pub bracketed_paste: Arc<AtomicBool>,
+    /// Set before Ada stops the process (see `kill_ptys`), so its exit is known not to be
+    /// the agent finishing on its own
+    pub stopping: Arc<AtomicBool>,
 }
 
-// PtyHandle is Send + Sync because we wrap everything in Arc<Mutex<>>
-unsafe impl Send for PtyHandle {}
-unsafe impl Sync for PtyHandle {}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateTerminalRequest {
     pub project_id: String,
     pub name: String,
     pub client_id: String,
-    /// Terminal mode
+    /// Terminal mode (None = the project's `default_terminal_mode`, or current branch)
     #[serde(default)]
-    pub mode: TerminalMode,
+    pub mode: Option<TerminalMode>,
     /// For Folder mode: path relative to project root
     pub folder_path: Option<String>,
-    /// For Worktree mode: branch to create/use worktree for
+    /// For Worktree mode: branch to create/use worktree for. A new branch starts at the
+    /// project's `default_base_branch` when it has one, HEAD otherwise.
     pub worktree_branch: Option<String>,
+    /// Name of an additional project root to run in; modes then apply to that root
+    /// instead of the project folder
+    #[serde(default)]
+    pub root: Option<String>,
+    /// Override the app-wide scrollback limit for this terminal
+    #[serde(default)]
+    pub scrollback: Option<ScrollbackLimit>,
+    /// Don't return until the terminal has produced its first output (or a timeout passes)
+    #[serde(default)]
+    pub wait_for_ready: bool,
+    /// Resume this terminal when Ada starts (defaults to true)
+    #[serde(default)]
+    pub auto_resume: Option<bool>,
+    #[serde(default)]
+    pub keep_alive: Option<KeepAliveConfig>,
+    /// TERM, locale and color overrides for this terminal
+    #[serde(default)]
+    pub terminal_env: Option<TerminalEnv>,
+    /// Model to run, e.g. "sonnet", over the project's override for the client
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Launch profile of the client, e.g. "plan" or "auto-approve"
+    #[serde(default)]
+    pub profile: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -127,6 +669,7 @@ pub struct TerminalInfo {
     pub name: String,
     pub client_id: String,
     pub working_dir: String,
+    pub current_dir: Option<String>,
     pub branch: Option<String>,
     pub worktree_path: Option<String>,
     pub status: TerminalStatus,
@@ -134,6 +677,28 @@ pub struct TerminalInfo {
     pub mode: TerminalMode,
     pub is_main: bool,
     pub folder_path: Option<String>,
+    pub root: Option<String>,
+    pub shell: Option<String>,
+    pub restored: bool,
+    pub respawn_count: u32,
+    pub last_activity: Option<DateTime<Utc>>,
+    /// Seconds elapsed since the last output, at the time this info was built
+    pub seconds_since_activity: Option<i64>,
+    pub group_id: Option<String>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub auto_resume: bool,
+    pub keep_alive: Option<KeepAliveConfig>,
+    pub terminal_env: Option<TerminalEnv>,
+    pub model: Option<String>,
+    pub profile: Option<String>,
+    pub checkpoints: Option<CheckpointConfig>,
+    pub summary: Option<TerminalSummary>,
+    pub pid: Option<u32>,
+    pub agent_status: Option<AgentStatus>,
+    /// Process group of the agent and its subprocesses (unix only; equal to `pid`)
+    pub pgid: Option<u32>,
+    /// Set by create/restart when the configured shell was unusable and a fallback took its place
+    pub shell_warning: Option<String>,
 }
 
 impl From<&Terminal> for TerminalInfo {
@@ -144,6 +709,7 @@ impl From<&Terminal> for TerminalInfo {
             name: terminal.name.clone(),
             client_id: terminal.client_id.clone(),
             working_dir: terminal.working_dir.to_string_lossy().to_string(),
+            current_dir: terminal.current_dir.as_ref().map(|p| p.to_string_lossy().to_string()),
             branch: terminal.branch.clone(),
             worktree_path: terminal.worktree_path.as_ref().map(|p| p.to_string_lossy().to_string()),
             status: terminal.status,
@@ -151,16 +717,117 @@ impl From<&Terminal> for TerminalInfo {
             mode: terminal.mode,
             is_main: terminal.is_main,
             folder_path: terminal.folder_path.as_ref().map(|p| p.to_string_lossy().to_string()),
+            root: terminal.root.clone(),
+            shell: terminal.shell.as_ref().map(|p| p.to_string_lossy().to_string()),
+            restored: terminal.restored,
+            respawn_count: terminal.respawn_count,
+            last_activity: terminal.last_activity,
+            seconds_since_activity: terminal
+                .last_activity
+                .map(|t| (Utc::now() - t).num_seconds().max(0)),
+            group_id: terminal.group_id.clone(),
+            ended_at: terminal.ended_at,
+            auto_resume: terminal.auto_resume,
+            keep_alive: terminal.keep_alive.clone(),
+            terminal_env: terminal.terminal_env.clone(),
+            model: terminal.model.clone(),
+            profile: terminal.profile.clone(),
+            checkpoints: terminal.checkpoints.clone(),
+            summary: terminal.summary.clone(),
+            pid: terminal.pid,
+            agent_status: terminal.agent_status,
+            pgid: terminal.pid.filter(|_| cfg!(unix)),
+            shell_warning: None,
         }
     }
 }
 
+/// A set of worktree terminals launched together from one request, so that
+/// several agents/branches can work the same prompt and be compared
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalGroup {
+    pub id: String,
+    pub project_id: String,
+    pub name: String,
+    /// Prompt sent to every member terminal on launch
+    pub prompt: Option<String>,
+    pub terminal_ids: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One worktree terminal to launch as part of a group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalGroupMember {
+    pub client_id: String,
+    pub worktree_branch: String,
+    /// Terminal name; defaults to the branch name
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateTerminalGroupRequest {
+    pub project_id: String,
+    pub name: String,
+    pub prompt: Option<String>,
+    pub members: Vec<TerminalGroupMember>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateTerminalGroupResult {
+    pub group: TerminalGroup,
+    pub terminals: Vec<TerminalInfo>,
+    /// Members that failed to launch, with the reason
+    pub errors: Vec<String>,
+}
+
+/// Status of a single group member, for comparing agent results side by side
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalGroupMemberStatus {
+    pub terminal: TerminalInfo,
+    /// Uncommitted changes in the member's worktree (None if they couldn't be read)
+    pub changes: Option<WorktreeChanges>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalGroupStatus {
+    pub group: TerminalGroup,
+    pub members: Vec<TerminalGroupMemberStatus>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TerminalOutput {
     pub terminal_id: String,
     pub data: String,
 }
 
+/// Liveness and resource usage of a terminal's agent process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalProcessInfo {
+    pub terminal_id: String,
+    pub pid: Option<u32>,
+    /// Process group of the agent and its subprocesses (unix only; equal to `pid`)
+    pub pgid: Option<u32>,
+    pub alive: bool,
+    /// Resident memory of the whole process group, in KiB
+    pub rss_kb: Option<u64>,
+    /// CPU usage of the whole process group, in percent of one core
+    pub cpu_percent: Option<f64>,
+}
+
+/// Emitted when reading a terminal's output is paused or resumed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalOutputPaused {
+    pub terminal_id: String,
+    pub paused: bool,
+}
+
+/// Emitted when a terminal's shell reports a new working directory (OSC 7)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalCwdChanged {
+    pub terminal_id: String,
+    pub working_dir: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResizeTerminalRequest {
     pub terminal_id: String,
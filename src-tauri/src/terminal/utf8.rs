@@ -0,0 +1,73 @@
+/// Incremental UTF-8 decoder for PTY output. A multi-byte character split across two
+/// reads is held back until the rest of it arrives instead of being replaced with U+FFFD.
+#[derive(Default)]
+pub struct Utf8Decoder {
+    /// Start of a character whose remaining bytes haven't been read yet (at most 3 bytes)
+    pending: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    /// Decode a chunk, replacing genuinely invalid bytes with U+FFFD
+    pub fn decode(&mut self, chunk: &[u8]) -> String {
+        let joined;
+        let mut input = if self.pending.is_empty() {
+            chunk
+        } else {
+            self.pending.extend_from_slice(chunk);
+            joined = std::mem::take(&mut self.pending);
+            joined.as_slice()
+        };
+
+        let mut output = String::with_capacity(input.len());
+        loop {
+            match std::str::from_utf8(input) {
+                Ok(valid) => {
+                    output.push_str(valid);
+                    break;
+                }
+                Err(e) => {
+                    let (valid, rest) = input.split_at(e.valid_up_to());
+                    // SAFETY: from_utf8 just validated this prefix
+                    output.push_str(unsafe { std::str::from_utf8_unchecked(valid) });
+
+                    match e.error_len() {
+                        Some(invalid_len) => {
+                            output.push(char::REPLACEMENT_CHARACTER);
+                            input = &rest[invalid_len..];
+                        }
+                        // Incomplete character at the end; finish it with the next chunk
+                        None => {
+                            self.pending = rest.to_vec();
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn holds_back_a_character_split_across_chunks() {
+        let bytes = "a€b".as_bytes();
+        let mut decoder = Utf8Decoder::default();
+        assert_eq!(decoder.decode(&bytes[..2]), "a");
+        assert_eq!(decoder.decode(&bytes[2..3]), "");
+        assert_eq!(decoder.decode(&bytes[3..]), "€b");
+    }
+
+    #[test]
+    fn replaces_invalid_bytes() {
+        let mut decoder = Utf8Decoder::default();
+        assert_eq!(decoder.decode(b"a\xffb"), "a\u{fffd}b");
+        // A lone continuation byte can't start a character, so it isn't held back
+        assert_eq!(decoder.decode(b"c\x80"), "c\u{fffd}");
+        assert_eq!(decoder.decode(b"d"), "d");
+    }
+}
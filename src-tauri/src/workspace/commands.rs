@@ -0,0 +1,188 @@
+use chrono::Utc;
+use tauri::State;
+
+use crate::error::{Error, Result};
+use crate::project::ProjectSummary;
+use crate::state::AppState;
+use crate::terminal::{create_main_terminal_internal, TerminalInfo};
+use super::{
+    CreateWorkspaceRequest, OpenWorkspaceResult, TerminalCounts, UpdateWorkspaceRequest, Workspace,
+    WorkspaceProjectStatus, WorkspaceStatus,
+};
+
+/// Check that every project exists, dropping duplicates but keeping the given order
+fn validate_project_ids(state: &AppState, project_ids: Vec<String>) -> Result<Vec<String>> {
+    let projects = state.projects.read();
+    let mut validated: Vec<String> = Vec::with_capacity(project_ids.len());
+    for project_id in project_ids {
+        if !projects.contains_key(&project_id) {
+            return Err(Error::ProjectNotFound(project_id));
+        }
+        if !validated.contains(&project_id) {
+            validated.push(project_id);
+        }
+    }
+    Ok(validated)
+}
+
+fn get_workspace(state: &AppState, workspace_id: &str) -> Result<Workspace> {
+    state
+        .workspaces
+        .read()
+        .get(workspace_id)
+        .cloned()
+        .ok_or_else(|| Error::InvalidRequest(format!("Workspace not found: {}", workspace_id)))
+}
+
+#[tauri::command]
+pub async fn create_workspace(
+    state: State<'_, AppState>,
+    request: CreateWorkspaceRequest,
+) -> Result<Workspace> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(Error::InvalidRequest("A workspace needs a name".into()));
+    }
+
+    let now = Utc::now();
+    let workspace = Workspace {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: name.to_string(),
+        project_ids: validate_project_ids(&state, request.project_ids)?,
+        created_at: now,
+        updated_at: now,
+    };
+
+    state.save_workspace(&workspace)?;
+    state.workspaces.write().insert(workspace.id.clone(), workspace.clone());
+
+    Ok(workspace)
+}
+
+#[tauri::command]
+pub async fn list_workspaces(state: State<'_, AppState>) -> Result<Vec<Workspace>> {
+    let mut workspaces: Vec<Workspace> = state.workspaces.read().values().cloned().collect();
+    workspaces.sort_by_key(|w| w.created_at);
+    Ok(workspaces)
+}
+
+/// Rename a workspace or replace its member projects
+#[tauri::command]
+pub async fn update_workspace(
+    state: State<'_, AppState>,
+    request: UpdateWorkspaceRequest,
+) -> Result<Workspace> {
+    let mut workspace = get_workspace(&state, &request.workspace_id)?;
+
+    if let Some(name) = request.name {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(Error::InvalidRequest("A workspace needs a name".into()));
+        }
+        workspace.name = name.to_string();
+    }
+    if let Some(project_ids) = request.project_ids {
+        workspace.project_ids = validate_project_ids(&state, project_ids)?;
+    }
+    workspace.updated_at = Utc::now();
+
+    state.save_workspace(&workspace)?;
+    state.workspaces.write().insert(workspace.id.clone(), workspace.clone());
+
+    Ok(workspace)
+}
+
+/// Delete a workspace. Its projects are left as they are.
+#[tauri::command]
+pub async fn delete_workspace(
+    state: State<'_, AppState>,
+    workspace_id: String,
+) -> Result<()> {
+    if state.workspaces.write().remove(&workspace_id).is_none() {
+        return Err(Error::InvalidRequest(format!("Workspace not found: {}", workspace_id)));
+    }
+    state.delete_workspace_file(&workspace_id)?;
+    Ok(())
+}
+
+/// Open every project in a workspace by starting (or returning) its main terminal, using
+/// each project's default agent or `client_id` for projects without one. Projects that
+/// can't be opened, such as archived ones, are reported in the result instead of failing
+/// the whole workspace.
+#[tauri::command]
+pub async fn open_workspace(
+    state: State<'_, AppState>,
+    workspace_id: String,
+    client_id: Option<String>,
+) -> Result<OpenWorkspaceResult> {
+    let workspace = get_workspace(&state, &workspace_id)?;
+    let mut main_terminals = Vec::new();
+    let mut errors = Vec::new();
+
+    for project_id in &workspace.project_ids {
+        let project = match state.projects.read().get(project_id) {
+            Some(project) => project.clone(),
+            None => {
+                errors.push(format!("{}: project not found", project_id));
+                continue;
+            }
+        };
+        if project.is_archived() {
+            errors.push(format!("{}: project is archived", project.name));
+            continue;
+        }
+
+        let Some(client_id) = project.settings.default_client.clone().or_else(|| client_id.clone()) else {
+            errors.push(format!("{}: no agent selected for this project", project.name));
+            continue;
+        };
+
+        match create_main_terminal_internal(&state, project_id, &client_id) {
+            Ok(info) => main_terminals.push(info),
+            Err(e) => {
+                eprintln!("[Ada] Failed to open project {} in workspace {}: {}", project.name, workspace.name, e);
+                errors.push(format!("{}: {}", project.name, e));
+            }
+        }
+    }
+
+    Ok(OpenWorkspaceResult { workspace, main_terminals, errors })
+}
+
+/// Terminal and agent status of every project in a workspace, with totals
+#[tauri::command]
+pub async fn get_workspace_status(
+    state: State<'_, AppState>,
+    workspace_id: String,
+) -> Result<WorkspaceStatus> {
+    let workspace = get_workspace(&state, &workspace_id)?;
+    let projects = state.projects.read();
+    let terminals = state.terminals.read();
+
+    let mut counts = TerminalCounts::default();
+    let mut project_statuses = Vec::new();
+
+    // Projects deleted since they were added drop out of the status
+    for project in workspace.project_ids.iter().filter_map(|id| projects.get(id)) {
+        let mut project_counts = TerminalCounts::default();
+        let mut project_terminals: Vec<TerminalInfo> = terminals
+            .values()
+            .filter(|t| t.project_id == project.id)
+            .map(TerminalInfo::from)
+            .collect();
+        project_terminals.sort_by_key(|t| t.created_at);
+
+        for terminal in &project_terminals {
+            project_counts.add(terminal.status);
+            counts.add(terminal.status);
+        }
+
+        project_statuses.push(WorkspaceProjectStatus {
+            project: ProjectSummary::from(project),
+            counts: project_counts,
+            terminals: project_terminals,
+        });
+    }
+
+    Ok(WorkspaceStatus { workspace, projects: project_statuses, counts })
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+use crate::project::ProjectSummary;
+use crate::terminal::{TerminalInfo, TerminalStatus};
+
+/// A named set of related projects (e.g. a frontend and its backend) that are opened
+/// and monitored together
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub id: String,
+    pub name: String,
+    pub project_ids: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateWorkspaceRequest {
+    pub name: String,
+    #[serde(default)]
+    pub project_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateWorkspaceRequest {
+    pub workspace_id: String,
+    pub name: Option<String>,
+    pub project_ids: Option<Vec<String>>,
+}
+
+/// What `open_workspace` set up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenWorkspaceResult {
+    pub workspace: Workspace,
+    /// Main terminal of each member project that could be opened
+    pub main_terminals: Vec<TerminalInfo>,
+    /// Member projects that couldn't be opened, with the reason
+    pub errors: Vec<String>,
+}
+
+/// Terminal counts for a project or a whole workspace
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TerminalCounts {
+    pub starting: usize,
+    pub running: usize,
+    pub stopped: usize,
+    pub error: usize,
+}
+
+impl TerminalCounts {
+    pub fn add(&mut self, status: TerminalStatus) {
+        match status {
+            TerminalStatus::Starting => self.starting += 1,
+            TerminalStatus::Running => self.running += 1,
+            TerminalStatus::Stopped => self.stopped += 1,
+            TerminalStatus::Error => self.error += 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceProjectStatus {
+    pub project: ProjectSummary,
+    pub counts: TerminalCounts,
+    pub terminals: Vec<TerminalInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceStatus {
+    pub workspace: Workspace,
+    pub projects: Vec<WorkspaceProjectStatus>,
+    /// Counts summed over every member project
+    pub counts: TerminalCounts,
+}